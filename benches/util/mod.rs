@@ -2,6 +2,7 @@ use bevy_math::{uvec2, UVec2};
 use image::{DynamicImage, GenericImageView, Rgba};
 use pixel_map::PixelMap;
 
+#[allow(dead_code)]
 pub fn create_checker_board(size: &UVec2) -> PixelMap {
     let mut pixel_map = PixelMap::new(size, false, 1);
     for x in 0..size.x {
@@ -12,6 +13,7 @@ pub fn create_checker_board(size: &UVec2) -> PixelMap {
     pixel_map
 }
 
+#[allow(dead_code)]
 pub fn load_image(pixel_map: &mut PixelMap<Rgba<u8>>, image: &DynamicImage) {
     let region_width = image.width();
     let region_height = image.height();
@@ -0,0 +1,199 @@
+use crate::{PNode, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashSet;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+// The square chunks of a map that contain active pixels.
+#[derive(Debug, Clone)]
+pub(crate) struct ActiveChunks {
+    chunk_size: u32,
+    chunks: FxHashSet<UVec2>,
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Begin tracking active pixels, being those that have recently changed, or neighbor a
+    /// pixel that has recently changed. Activity is tracked in square chunks of the given size,
+    /// such that any modification of pixel data activates the chunks overlapping the
+    /// modified pixels and their neighbors. See [PixelMap::visit_active].
+    ///
+    /// If activity is already tracked, all chunks are activated with the new chunk size.
+    ///
+    /// # Parameters
+    ///
+    /// - `chunk_size`: The width and height of a chunk, in pixels.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn enable_active_tracking(&mut self, chunk_size: u32) {
+        assert!(chunk_size > 0, "chunk_size must be non-zero");
+        let was_tracking = self.active.is_some();
+        self.active = Some(ActiveChunks {
+            chunk_size,
+            chunks: FxHashSet::default(),
+        });
+        if was_tracking {
+            self.activate(&self.map_rect());
+        }
+    }
+
+    /// Stop tracking active pixels, and discard any activity. See [PixelMap::enable_active_tracking].
+    #[inline]
+    pub fn disable_active_tracking(&mut self) {
+        self.active = None;
+    }
+
+    /// Activate the pixels within the given rectangle, and their neighbors, such as to wake
+    /// pixels whose update depends on something other than neighboring changes. Modifying
+    /// pixel data activates the modified pixels automatically.
+    /// Has no effect if activity is not tracked.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle of pixels to activate.
+    pub fn activate(&mut self, rect: &URect) {
+        let Some(active) = &mut self.active else {
+            return;
+        };
+        let step = self.pixel_size as u32;
+        let rect =
+            URect::from_corners(rect.min.saturating_sub(UVec2::splat(step)), rect.max + step)
+                .intersect(self.map_rect);
+        if rect.is_empty() {
+            return;
+        }
+        let min = rect.min / active.chunk_size;
+        let max = (rect.max - UVec2::ONE) / active.chunk_size;
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                active.chunks.insert(UVec2::new(x, y));
+            }
+        }
+    }
+
+    /// Determine if the given pixel is within an active chunk.
+    /// See [PixelMap::enable_active_tracking].
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// `true` if activity is tracked, and the chunk containing `point` is active.
+    #[inline]
+    #[must_use]
+    pub fn is_active(&self, point: UVec2) -> bool {
+        self.active
+            .as_ref()
+            .is_some_and(|active| active.chunks.contains(&(point / active.chunk_size)))
+    }
+
+    /// Visit the leaf nodes overlapping active chunks, and consume the activity, such that each
+    /// update of a falling-sand style simulation examines only the pixels that may move. Pixel data
+    /// modified in response to the visit activates chunks for the next visit.
+    /// See [PixelMap::enable_active_tracking].
+    ///
+    /// # Parameters
+    ///
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and an active area.
+    ///   Areas are visited bottom to top, and do not overlap.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed. Zero if activity is not tracked.
+    pub fn visit_active<F>(&mut self, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &URect),
+    {
+        let Some(active) = &mut self.active else {
+            return 0;
+        };
+        let chunk_size = active.chunk_size;
+        let mut chunks: Vec<UVec2> = active.chunks.drain().collect();
+        chunks.sort_unstable_by_key(|c| (c.y, c.x));
+
+        // Coalesce horizontal runs of chunks, to reduce descents
+        let mut traversed = 0;
+        let mut i = 0;
+        while i < chunks.len() {
+            let first = chunks[i];
+            let mut last = first;
+            i += 1;
+            while i < chunks.len() && chunks[i] == last + UVec2::X {
+                last = chunks[i];
+                i += 1;
+            }
+            let rect = URect::from_corners(first * chunk_size, (last + UVec2::ONE) * chunk_size);
+            traversed += self.visit_in_rect(&rect, &mut visitor);
+        }
+        traversed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    fn visited_area(pm: &mut PixelMap<bool, u32>) -> u32 {
+        let mut area = 0;
+        pm.visit_active(|_, r| area += r.width() * r.height());
+        area
+    }
+
+    #[test]
+    fn test_visit_active() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((5, 5), true);
+        assert_eq!(pm.visit_active(|_, _| panic!("not tracked")), 0);
+
+        pm.enable_active_tracking(4);
+        pm.set_pixel((5, 5), true);
+        assert!(!pm.is_active(UVec2::new(4, 7)));
+        pm.set_pixel((5, 5), false);
+        assert!(pm.is_active(UVec2::new(4, 7)));
+        assert!(!pm.is_active(UVec2::new(8, 5)));
+        assert_eq!(visited_area(&mut pm), 16);
+        assert_eq!(visited_area(&mut pm), 0);
+
+        // Neighbors of changed pixels are active, across chunk boundaries
+        pm.set_pixel((8, 8), true);
+        assert_eq!(visited_area(&mut pm), 4 * 16);
+        pm.draw_rect(&URect::new(0, 0, 1, 1), true);
+        assert!(pm.is_active(UVec2::ZERO));
+
+        pm.disable_active_tracking();
+        assert!(!pm.is_active(UVec2::ZERO));
+    }
+
+    #[test]
+    fn test_visit_active_falling() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.enable_active_tracking(4);
+        pm.set_pixel((2, 10), true);
+
+        // Drop the grain until it comes to rest on the bottom of the map
+        let mut steps = 0;
+        loop {
+            let mut moves = Vec::new();
+            pm.visit_active(|n, r| {
+                if *n.value() && r.min.y > 0 {
+                    moves.push(r.min);
+                }
+            });
+            if moves.is_empty() {
+                break;
+            }
+            for p in moves {
+                pm.set_pixel(p, false);
+                pm.set_pixel(p - UVec2::Y, true);
+            }
+            steps += 1;
+        }
+        assert_eq!(steps, 10);
+        assert_eq!(pm.get_pixel((2, 0)), Some(&true));
+    }
+}
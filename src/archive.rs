@@ -0,0 +1,277 @@
+use crate::{ArchivedPNode, ArchivedPixelMap, InclusiveRect, PixelMap, Region};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use rkyv::api::high::{HighDeserializer, HighSerializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor::Error;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+use std::fmt::Debug;
+
+impl<T, U> PixelMap<T, U>
+where
+    T: Copy
+        + PartialEq
+        + Archive
+        + for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Error>>,
+    U: Unsigned
+        + NumCast
+        + Copy
+        + Debug
+        + Archive
+        + for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Error>>,
+{
+    /// Serialize this [PixelMap] to bytes in its archived form, which may be accessed in place
+    /// with [ArchivedPixelMap::access], such as from a memory-mapped file, without
+    /// deserializing. Active pixel tracking is not archived.
+    pub fn to_archive_bytes(&self) -> Result<AlignedVec, Error> {
+        rkyv::to_bytes::<Error>(self)
+    }
+}
+
+impl<T, U> ArchivedPixelMap<T, U>
+where
+    T: Copy + PartialEq + Archive,
+    U: Unsigned + NumCast + Copy + Debug + Archive,
+    U::Archived: Copy + Into<U>,
+{
+    /// Access the archived form of a [PixelMap] in the given bytes, as produced by
+    /// [PixelMap::to_archive_bytes], without deserializing. The bytes are validated first,
+    /// which visits every node, but performs no allocation.
+    ///
+    /// # Returns
+    ///
+    /// If the bytes are not a valid archive of a [PixelMap] of the given types, an error is
+    /// returned.
+    pub fn access(bytes: &[u8]) -> Result<&Self, Error>
+    where
+        Self: for<'a> CheckBytes<HighValidator<'a, Error>>,
+    {
+        rkyv::access::<Self, Error>(bytes)
+    }
+
+    /// Deserialize the [PixelMap] of which this is the archived form.
+    pub fn to_pixel_map(&self) -> Result<PixelMap<T, U>, Error>
+    where
+        Self: Deserialize<PixelMap<T, U>, HighDeserializer<Error>>,
+    {
+        rkyv::deserialize::<PixelMap<T, U>, Error>(self)
+    }
+
+    /// Obtain the dimensions of the archived [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn map_size(&self) -> UVec2 {
+        self.map_rect().size()
+    }
+
+    /// Obtain the dimensions of the archived [PixelMap] as a rectangle.
+    #[inline]
+    #[must_use]
+    pub fn map_rect(&self) -> URect {
+        URect::from_corners(
+            UVec2::new(self.map_rect.min.x.into(), self.map_rect.min.y.into()),
+            UVec2::new(self.map_rect.max.x.into(), self.map_rect.max.y.into()),
+        )
+    }
+
+    /// Obtain the pixel size of the archived [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn pixel_size(&self) -> u8 {
+        self.pixel_size
+    }
+
+    /// Obtain the version of the archived [PixelMap], as per [PixelMap::version].
+    #[inline]
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.version.into()
+    }
+
+    /// Obtain the region that the quadtree root node of the archived [PixelMap] covers.
+    #[inline]
+    #[must_use]
+    pub fn region(&self) -> Region<U> {
+        self.root.region()
+    }
+
+    /// Obtain the archived root node.
+    #[inline]
+    #[must_use]
+    pub fn root(&self) -> &ArchivedPNode<T, U> {
+        &self.root
+    }
+
+    /// Get the archived value of the pixel at the given coordinates, as per
+    /// [PixelMap::get_pixel].
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to retrieve the associated value.
+    ///
+    /// # Returns
+    ///
+    /// If the coordinates are outside the [ArchivedPixelMap::map_rect], `None` is returned.
+    #[inline]
+    #[must_use]
+    pub fn get_pixel<P>(&self, point: P) -> Option<&T::Archived>
+    where
+        P: Into<UVec2>,
+    {
+        self.find_node(point).map(|node| node.value())
+    }
+
+    /// Get the archived leaf node that represents the pixel at the given coordinates, as per
+    /// [PixelMap::find_node].
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to retrieve the representing node.
+    ///
+    /// # Returns
+    ///
+    /// If the coordinates are outside the [ArchivedPixelMap::map_rect], `None` is returned.
+    #[inline]
+    #[must_use]
+    pub fn find_node<P>(&self, point: P) -> Option<&ArchivedPNode<T, U>>
+    where
+        P: Into<UVec2>,
+    {
+        let point = point.into();
+        if self.map_rect().contains_exclusive(point) {
+            Some(self.root.find_node(point))
+        } else {
+            None
+        }
+    }
+
+    /// Visit all archived leaf nodes, as per [PixelMap::visit].
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit<F>(&self, visitor: F) -> u32
+    where
+        F: FnMut(&ArchivedPNode<T, U>, &URect),
+    {
+        self.visit_in_rect(&self.map_rect(), visitor)
+    }
+
+    /// Visit all archived leaf nodes that overlap with the given rectangle, as per
+    /// [PixelMap::visit_in_rect].
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit_in_rect<F>(&self, rect: &URect, mut visitor: F) -> u32
+    where
+        F: FnMut(&ArchivedPNode<T, U>, &URect),
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return 0;
+        }
+        let mut traversed = 0u32;
+        self.root
+            .visit_leaves_in_rect(&rect, &mut visitor, &mut traversed);
+        traversed
+    }
+}
+
+// Archived forms of the `bevy_math` types stored by `PixelMap`.
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(remote = UVec2, archived = ArchivedUVec2)]
+pub(crate) struct UVec2Def {
+    x: u32,
+    y: u32,
+}
+
+impl From<UVec2Def> for UVec2 {
+    fn from(value: UVec2Def) -> Self {
+        UVec2::new(value.x, value.y)
+    }
+}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(remote = URect, archived = ArchivedURect)]
+pub(crate) struct URectDef {
+    #[rkyv(with = UVec2Def)]
+    min: UVec2,
+    #[rkyv(with = UVec2Def)]
+    max: UVec2,
+}
+
+impl From<URectDef> for URect {
+    fn from(value: URectDef) -> Self {
+        URect {
+            min: value.min,
+            max: value.max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_archive() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::new(20, 12), 0, 1);
+        pm.draw_rect(&URect::new(2, 2, 9, 7), 1);
+        pm.clear_dirty(true);
+        pm.set_pixel((15, 10), 2);
+
+        let bytes = pm.to_archive_bytes().unwrap();
+        let archived = ArchivedPixelMap::<u8, u32>::access(&bytes).unwrap();
+        assert_eq!(archived.map_rect(), pm.map_rect());
+        assert_eq!(archived.pixel_size(), 1);
+        assert_eq!(archived.version(), pm.version());
+        assert_eq!(archived.region(), *pm.region());
+        assert_eq!(archived.get_pixel((3, 3)), Some(&1));
+        assert_eq!(archived.get_pixel((15, 10)), Some(&2));
+        assert_eq!(archived.get_pixel((20, 0)), None);
+        let node = archived.find_node((15, 10)).unwrap();
+        assert!(node.is_leaf());
+        assert!(node.dirty());
+        assert_eq!(node.region(), *pm.find_node((15, 10)).unwrap().region());
+
+        let mut visited = Vec::new();
+        pm.visit(|n, r| visited.push((*n.value(), *r)));
+        let mut archived_visited = Vec::new();
+        let traversed = archived.visit(|n, r| archived_visited.push((*n.value(), *r)));
+        assert_eq!(archived_visited, visited);
+        assert_eq!(traversed, pm.visit(|_, _| {}));
+
+        let loaded = archived.to_pixel_map().unwrap();
+        assert!(loaded == pm);
+        assert_eq!(loaded.version(), pm.version());
+
+        // Invalid bytes are rejected
+        assert!(ArchivedPixelMap::<u8, u32>::access(&bytes[..bytes.len() - 4]).is_err());
+    }
+
+    #[test]
+    fn test_archive_compressed() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.enable_compression(1);
+        pm.set_pixel((1, 1), 1);
+        pm.set_pixel((9, 9), 2);
+        pm.clear_dirty(true);
+        assert_eq!(pm.compress_cold(pm.version() + 1), 2);
+
+        // Compressed subtrees are archived inflated, and compressed again when loaded
+        let bytes = pm.to_archive_bytes().unwrap();
+        let archived = ArchivedPixelMap::<u8, u32>::access(&bytes).unwrap();
+        assert_eq!(archived.get_pixel((1, 1)), Some(&1));
+        assert_eq!(archived.get_pixel((9, 9)), Some(&2));
+        assert_eq!(archived.visit(|_, _| {}), pm.visit(|_, _| {}));
+
+        let loaded = archived.to_pixel_map().unwrap();
+        assert_eq!(loaded.compressed_count(), 2);
+        assert!(loaded == pm);
+    }
+}
@@ -0,0 +1,249 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{PNode, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashSet;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A dense, coarse occupancy bitmask over a [PixelMap], as created by
+/// [PixelMap::broadphase_grid], holding one bit per square cell that is set where any pixel
+/// of the cell matches a predicate. Suited to the broadphase of a physics engine, which can
+/// reject most candidate pairs by testing a few bits rather than traversing the quadtree.
+///
+/// Bits are packed in row-major order from the `(0,0)` cell, such that the bit of cell
+/// `(x, y)` is bit `i % 64` of word `i / 64`, where `i = y * size.x + x`.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    cell_size: u32,
+    size: UVec2,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    // Create a grid of cleared bits covering the given map size.
+    fn new(map_size: UVec2, cell_size: u32) -> Self {
+        let size = (map_size + cell_size - 1) / cell_size;
+        let bits = size.x as usize * size.y as usize;
+        Self {
+            cell_size,
+            size,
+            words: vec![0; bits.div_ceil(64)],
+        }
+    }
+
+    /// The size of an edge, in pixels, of a single square cell. Cells are aligned with the
+    /// `(0,0)` point of the map.
+    #[inline]
+    #[must_use]
+    pub fn cell_size(&self) -> u32 {
+        self.cell_size
+    }
+
+    /// The number of cells on each axis.
+    #[inline]
+    #[must_use]
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    /// The packed bits of the grid, as described by [BitGrid].
+    #[inline]
+    #[must_use]
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Determine if the cell at the given cell coordinate is occupied. Cells outside of the
+    /// grid are not occupied.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, cell: UVec2) -> bool {
+        if cell.x >= self.size.x || cell.y >= self.size.y {
+            return false;
+        }
+        let i = self.index(cell);
+        self.words[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    /// The number of occupied cells.
+    #[inline]
+    #[must_use]
+    pub fn count_occupied(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// Determine if any cell overlapping the given rectangle, in pixels, is occupied, such as
+    /// to test the bounding box of a physics body.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle, in pixels, to test.
+    #[must_use]
+    pub fn any_in_rect(&self, rect: &URect) -> bool {
+        if rect.is_empty() {
+            return false;
+        }
+        let min = rect.min / self.cell_size;
+        let max = ((rect.max - 1) / self.cell_size).min(self.size.saturating_sub(UVec2::ONE));
+        if min.x >= self.size.x || min.y >= self.size.y {
+            return false;
+        }
+        (min.y..=max.y).any(|y| (min.x..=max.x).any(|x| self.get(UVec2::new(x, y))))
+    }
+
+    #[inline]
+    fn index(&self, cell: UVec2) -> usize {
+        cell.y as usize * self.size.x as usize + cell.x as usize
+    }
+
+    #[inline]
+    fn set(&mut self, cell: UVec2, occupied: bool) {
+        let i = self.index(cell);
+        if occupied {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    // The rectangle, in pixels, of the given cell, cropped to the given map rectangle.
+    #[inline]
+    fn cell_rect(&self, cell: UVec2, map_rect: &URect) -> URect {
+        URect::from_corners(cell * self.cell_size, (cell + 1) * self.cell_size).intersect(*map_rect)
+    }
+
+    // The range of cells overlapping the given non-empty rectangle, in pixels.
+    #[inline]
+    fn cells_in_rect(&self, rect: &URect) -> (UVec2, UVec2) {
+        (rect.min / self.cell_size, (rect.max - 1) / self.cell_size)
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Create a [BitGrid] over this [PixelMap], having one bit per square cell that is set
+    /// where any leaf node overlapping the cell matches the predicate, in a single traversal.
+    /// Keep the grid up to date with [PixelMap::update_broadphase] as the map is modified.
+    ///
+    /// # Parameters
+    ///
+    /// - `cell_size`: The size of an edge, in pixels, of a single square cell. Cells are
+    ///   aligned with the `(0,0)` point of the map.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle as parameters. This rectangle represents the intersection of the node's
+    ///   region and the [PixelMap::map_rect]. It returns `true` if the node is occupied, or
+    ///   `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// If `cell_size` is zero.
+    #[must_use]
+    pub fn broadphase_grid<F>(&self, cell_size: u32, mut predicate: F) -> BitGrid
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        assert!(cell_size > 0, "cell_size must be > 0");
+        let mut grid = BitGrid::new(self.map_size(), cell_size);
+        self.visit(|node, rect| {
+            if predicate(node, rect) {
+                let (min, max) = grid.cells_in_rect(rect);
+                for y in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        grid.set(UVec2::new(x, y), true);
+                    }
+                }
+            }
+        });
+        grid
+    }
+
+    /// Update the cells of the given [BitGrid] that overlap dirty leaf nodes of this
+    /// [PixelMap], such that the grid reflects all modifications since it was created by
+    /// [PixelMap::broadphase_grid]. Call this before clearing dirty state with
+    /// [PixelMap::clear_dirty], for each map modification.
+    ///
+    /// # Parameters
+    ///
+    /// - `grid`: The [BitGrid] to update, as created from this [PixelMap].
+    /// - `predicate`: A closure that determines whether a leaf node is occupied, as per
+    ///   [PixelMap::broadphase_grid].
+    ///
+    /// # Returns
+    ///
+    /// The number of cells whose occupancy changed.
+    ///
+    /// # Panics
+    ///
+    /// If the dimensions of `grid` do not match those of this [PixelMap].
+    pub fn update_broadphase<F>(&self, grid: &mut BitGrid, mut predicate: F) -> usize
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        assert_eq!(
+            grid.size,
+            (self.map_size() + grid.cell_size - 1) / grid.cell_size,
+            "grid dimensions must match the pixel map"
+        );
+        if !self.dirty() {
+            return 0;
+        }
+
+        let mut dirty: FxHashSet<UVec2> = FxHashSet::default();
+        self.visit_dirty(|_, rect| {
+            let (min, max) = grid.cells_in_rect(rect);
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    dirty.insert(UVec2::new(x, y));
+                }
+            }
+        });
+
+        let map_rect = self.map_rect();
+        let mut changed = 0;
+        for cell in dirty {
+            let rect = grid.cell_rect(cell, &map_rect);
+            let occupied = self.any_in_rect(&rect, &mut predicate) == Some(true);
+            if grid.get(cell) != occupied {
+                grid.set(cell, occupied);
+                changed += 1;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_broadphase_grid() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::new(40, 24), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 16, 8), true);
+        pm.set_pixel((35, 20), true);
+
+        let mut grid = pm.broadphase_grid(8, |n, _| *n.value());
+        assert_eq!(grid.size(), UVec2::new(5, 3));
+        assert_eq!(grid.count_occupied(), 3);
+        assert!(grid.get(UVec2::new(0, 0)));
+        assert!(grid.get(UVec2::new(1, 0)));
+        assert!(grid.get(UVec2::new(4, 2)));
+        assert!(!grid.get(UVec2::new(2, 0)));
+        assert!(!grid.get(UVec2::new(9, 9)));
+        assert!(grid.any_in_rect(&URect::new(30, 18, 50, 50)));
+        assert!(!grid.any_in_rect(&URect::new(17, 9, 31, 15)));
+        assert!(!grid.any_in_rect(&URect::new(60, 60, 70, 70)));
+        pm.clear_dirty(true);
+
+        // Only dirty cells are updated
+        pm.set_pixel((35, 20), false);
+        pm.set_pixel((20, 12), true);
+        assert_eq!(pm.update_broadphase(&mut grid, |n, _| *n.value()), 2);
+        assert_eq!(grid, pm.broadphase_grid(8, |n, _| *n.value()));
+        pm.clear_dirty(true);
+        assert_eq!(pm.update_broadphase(&mut grid, |n, _| *n.value()), 0);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::{CellFill, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+
+// The node budget of a map, and the version at which each square chunk was last modified.
+#[derive(Debug, Clone)]
+pub(crate) struct NodeBudget {
+    max_nodes: usize,
+    chunk_size: u32,
+    modified: FxHashMap<UVec2, u64>,
+}
+
+/// A subtree coarsened to a single leaf node by [PixelMap::enforce_node_budget].
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoarsenedNode<T> {
+    /// The region of the subtree.
+    pub rect: URect,
+
+    /// The value of the leaf node replacing the subtree.
+    pub value: T,
+
+    /// The rectangles and values of the leaf nodes that were replaced.
+    pub replaced: Vec<(URect, T)>,
+}
+
+/// A reducer for [PixelMap::enforce_node_budget], selecting the value covering the greatest
+/// area of the given leaf nodes. Ties are resolved in favor of the first value.
+///
+/// # Panics
+///
+/// If `leaves` is empty.
+#[must_use]
+pub fn dominant_value<T: Copy + PartialEq>(leaves: &[(URect, T)]) -> T {
+    let area = |value: &T| -> u64 {
+        leaves
+            .iter()
+            .filter(|(_, v)| v == value)
+            .map(|(r, _)| r.width() as u64 * r.height() as u64)
+            .sum()
+    };
+    let mut best = leaves.first().expect("leaves must not be empty").1;
+    let mut best_area = area(&best);
+    for (_, value) in leaves {
+        let value_area = area(value);
+        if value_area > best_area {
+            best = *value;
+            best_area = value_area;
+        }
+    }
+    best
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Limit the number of nodes of this [PixelMap], such that [PixelMap::enforce_node_budget]
+    /// coarsens the least recently modified subtrees when the limit is exceeded. Modification
+    /// is tracked in square chunks of the given size.
+    ///
+    /// The budget is not enforced by drawing, which may exceed it, as the choice of reducer
+    /// and the moment at which detail is lost are left to the caller, such as once per frame
+    /// after all edits. See [PixelMap::enforce_node_budget].
+    ///
+    /// If a budget is already enabled, its modification history is discarded.
+    ///
+    /// # Parameters
+    ///
+    /// - `max_nodes`: The maximum number of nodes, as per [crate::Stats::node_count].
+    /// - `chunk_size`: The width and height of a chunk, in pixels.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn enable_node_budget(&mut self, max_nodes: usize, chunk_size: u32) {
+        assert!(chunk_size > 0, "chunk_size must be non-zero");
+        self.budget = Some(NodeBudget {
+            max_nodes,
+            chunk_size,
+            modified: FxHashMap::default(),
+        });
+    }
+
+    /// Remove the node budget, and discard the modification history.
+    /// See [PixelMap::enable_node_budget].
+    #[inline]
+    pub fn disable_node_budget(&mut self) {
+        self.budget = None;
+    }
+
+    /// Obtain the maximum number of nodes, if a budget is enabled.
+    /// See [PixelMap::enable_node_budget].
+    #[inline]
+    #[must_use]
+    pub fn node_budget(&self) -> Option<usize> {
+        self.budget.as_ref().map(|budget| budget.max_nodes)
+    }
+
+    /// If the number of nodes exceeds the budget, coarsen subtrees of leaf nodes into single
+    /// leaf nodes, least recently modified first, until it doesn't, such as to degrade
+    /// accumulated detail gracefully rather than exhausting memory. Call this periodically,
+    /// such as once per frame, as the budget is not enforced by drawing. The whole tree is
+    /// traversed once, after which each coarsening takes time proportional to the depth of
+    /// the tree. Coarsened nodes are dirty, but are not recorded as modified.
+    /// Has no effect if no budget is enabled. See [PixelMap::enable_node_budget].
+    ///
+    /// # Parameters
+    ///
+    /// - `reducer`: A closure that takes the rectangles and values of the leaf nodes of a
+    ///   subtree, and returns the value of the leaf node replacing them, such as
+    ///   [dominant_value].
+    ///
+    /// # Returns
+    ///
+    /// The coarsened subtrees, in order of coarsening. Empty if the budget isn't exceeded.
+    pub fn enforce_node_budget<F>(&mut self, mut reducer: F) -> Vec<CoarsenedNode<T>>
+    where
+        F: FnMut(&[(URect, T)]) -> T,
+    {
+        let mut coarsened = Vec::new();
+        let Some(budget) = self.budget.take() else {
+            return coarsened;
+        };
+
+        // Collect the parents of leaf nodes, counting all nodes, in a single traversal
+        let mut candidates = BinaryHeap::new();
+        let mut node_count = 0;
+        self.root.visit_nodes_in_rect(
+            &self.region().into(),
+            &mut |node, _| {
+                node_count += 1;
+                if node.is_leaf_parent() {
+                    candidates.push(Candidate::new(&budget, node.region().as_urect()));
+                }
+                CellFill::Full
+            },
+            &mut 0,
+        );
+
+        while node_count > budget.max_nodes {
+            let Some(Candidate { rect, .. }) = candidates.pop() else {
+                break;
+            };
+            let mut replaced = Vec::with_capacity(4);
+            self.visit_in_rect(&rect, |node, sub_rect| {
+                replaced.push((*sub_rect, *node.value()))
+            });
+            if replaced.is_empty() {
+                // Outside the map, so there is nothing to lose
+                replaced.push((rect, *self.root.find_node(rect.min).value()));
+            }
+            let value = reducer(&replaced);
+
+            // Draw the whole region, which may extend beyond the map, to replace the subtree
+            // with a leaf node. The budget is taken, so coarsening is not recorded as a
+            // modification.
+            if self
+                .root
+                .draw_rect(&rect, self.pixel_size, value, true, &mut self.pool, &mut 0)
+            {
+                self.touch(&rect.intersect(self.map_rect));
+            }
+            coarsened.push(CoarsenedNode {
+                rect,
+                value,
+                replaced,
+            });
+
+            // Coarsening removes the four children, and those of any ancestors merged in
+            // turn. The parent of the resulting leaf node may become a parent of leaf nodes.
+            let size = self.root.find_node(rect.min).region().size_as::<u32>();
+            node_count -= 4 * (size / rect.width()).ilog2() as usize + 4;
+            let mut parent = &self.root;
+            while !parent.is_leaf() && parent.region().size_as::<u32>() > size * 2 {
+                parent = &parent.children()[parent.region().quadrant_for_upoint(rect.min) as usize];
+            }
+            if parent.is_leaf_parent() {
+                candidates.push(Candidate::new(&budget, parent.region().as_urect()));
+            }
+        }
+
+        self.budget = Some(budget);
+        coarsened
+    }
+
+    // Record the modification of the given rectangle at the current version.
+    pub(crate) fn record_modification(&mut self, rect: &URect) {
+        let Some(budget) = &mut self.budget else {
+            return;
+        };
+        let rect = rect.intersect(self.map_rect);
+        if rect.is_empty() {
+            return;
+        }
+        let min = rect.min / budget.chunk_size;
+        let max = (rect.max - UVec2::ONE) / budget.chunk_size;
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                budget.modified.insert(UVec2::new(x, y), self.version);
+            }
+        }
+    }
+}
+
+// A parent of leaf nodes that may be coarsened, ordered such that the least recently modified
+// is the greatest, then by position for determinism.
+#[derive(PartialEq, Eq)]
+struct Candidate {
+    modified: u64,
+    rect: URect,
+}
+
+impl Candidate {
+    fn new(budget: &NodeBudget, rect: URect) -> Self {
+        Self {
+            modified: budget.last_modified(&rect),
+            rect,
+        }
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let key = |c: &Self| Reverse((c.modified, c.rect.min.y, c.rect.min.x));
+        key(self).cmp(&key(other))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl NodeBudget {
+    // The latest version at which any chunk overlapping the given rectangle was modified,
+    // being zero if never.
+    fn last_modified(&self, rect: &URect) -> u64 {
+        let min = rect.min / self.chunk_size;
+        let max = (rect.max - UVec2::ONE) / self.chunk_size;
+        let chunks = (max - min + 1).as_u64vec2().element_product();
+        if chunks > self.modified.len() as u64 {
+            self.modified
+                .iter()
+                .filter(|(chunk, _)| chunk.cmpge(min).all() && chunk.cmple(max).all())
+                .map(|(_, version)| *version)
+                .max()
+                .unwrap_or(0)
+        } else {
+            let mut last = 0;
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    if let Some(version) = self.modified.get(&UVec2::new(x, y)) {
+                        last = last.max(*version);
+                    }
+                }
+            }
+            last
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_enforce_node_budget() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        assert!(pm.enforce_node_budget(dominant_value).is_empty());
+
+        pm.enable_node_budget(24, 4);
+        assert_eq!(pm.node_budget(), Some(24));
+
+        // Old detail in the bottom left, and recent detail in the top right
+        pm.draw_rect(&URect::new(0, 0, 3, 3), 1);
+        pm.set_pixel((15, 15), 2);
+        pm.set_pixel((13, 14), 3);
+        let before = pm.stats().node_count;
+        assert!(before > 24);
+
+        let coarsened = pm.enforce_node_budget(dominant_value);
+        assert!(!coarsened.is_empty());
+        assert!(pm.stats().node_count <= 24);
+        assert!(before - pm.stats().node_count >= coarsened.len() * 4);
+
+        // The recent detail survives, and the old detail is coarsened
+        assert_eq!(pm.get_pixel((15, 15)), Some(&2));
+        assert_eq!(pm.get_pixel((13, 14)), Some(&3));
+        let first = &coarsened[0];
+        assert!(URect::new(0, 0, 8, 8).contains(first.rect.center()));
+        assert_eq!(first.replaced.len(), 4);
+        assert!(first.replaced.iter().any(|(_, v)| *v == first.value));
+
+        // Already within budget
+        assert!(pm.enforce_node_budget(dominant_value).is_empty());
+        pm.disable_node_budget();
+        assert_eq!(pm.node_budget(), None);
+    }
+
+    #[test]
+    fn test_enforce_node_budget_frees_nodes() {
+        let noise = |pm: &mut PixelMap<u8, u32>| {
+            for i in 0..64u32 {
+                pm.set_pixel(((i * 7) % 32, (i * 13) % 32), (i % 3) as u8 + 1);
+            }
+        };
+
+        // Coarsened nodes are freed, not retained
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(32), 0, 1);
+        pm.enable_node_budget(64, 8);
+        noise(&mut pm);
+        let before = pm.stats().node_count;
+        assert!(!pm.enforce_node_budget(dominant_value).is_empty());
+        assert!(pm.stats().node_count <= 64);
+        assert!(pm.stats().node_count + pm.pooled_node_count() < before);
+        assert_eq!(pm.pooled_node_count(), 0);
+
+        // And retained only up to the pool's capacity, if enabled
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(32), 0, 1);
+        pm.enable_pool(4);
+        pm.enable_node_budget(64, 8);
+        noise(&mut pm);
+        assert!(!pm.enforce_node_budget(dominant_value).is_empty());
+        assert!(pm.stats().node_count + pm.pooled_node_count() <= 64 + 16);
+    }
+
+    #[test]
+    fn test_enforce_node_budget_cascade() {
+        // Coarsening proceeds up to the root, whose region spans more chunks than fit a u32
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(1 << 16), 0, 1);
+        pm.enable_node_budget(0, 1);
+        pm.set_pixel((5, 9), 1);
+        assert_eq!(pm.stats().node_count, 65);
+
+        let mut next = 1;
+        let coarsened = pm.enforce_node_budget(|_| {
+            next += 1;
+            next
+        });
+        assert_eq!(coarsened.len(), 16);
+        assert_eq!(coarsened.last().unwrap().rect, pm.map_rect());
+        assert_eq!(pm.stats().node_count, 1);
+        assert_eq!(pm.get_pixel((5, 9)), Some(&17));
+    }
+
+    #[test]
+    fn test_dominant_value() {
+        let leaves = [
+            (URect::new(0, 0, 1, 1), 1),
+            (URect::new(1, 0, 2, 1), 2),
+            (URect::new(0, 1, 2, 2), 2),
+        ];
+        assert_eq!(dominant_value(&leaves), 2);
+        assert_eq!(dominant_value(&leaves[..2]), 1);
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A flag by which a long-running operation, such as
+/// [PixelMap::pathfind_a_star_grid_cancellable], may be aborted from another thread, such as
+/// when its result is no longer needed because the player moved or a new edit came in.
+///
+/// Clones of a [CancelToken] share the same flag, so one clone may be given to a worker
+/// thread while another is retained to cancel the work.
+///
+/// [PixelMap::pathfind_a_star_grid_cancellable]: crate::PixelMap::pathfind_a_star_grid_cancellable
+#[derive(Debug, Default, Clone)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a new [CancelToken] that is not cancelled.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation of any operations observing this token, or its clones.
+    #[inline]
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Determine if cancellation has been requested.
+    #[inline]
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    // Return an error if cancellation has been requested.
+    #[inline]
+    pub(crate) fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<Arc<AtomicBool>> for CancelToken {
+    /// Create a [CancelToken] observing the given flag, which cancels when set to `true`.
+    fn from(flag: Arc<AtomicBool>) -> Self {
+        Self(flag)
+    }
+}
+
+/// The error of an operation aborted by its [CancelToken].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl Display for Cancelled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "operation cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[cfg(test)]
+mod test {
+    use crate::pathfinding::euclidean_heuristic;
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_cancel_token() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), true, 1);
+        pm.draw_rect(&URect::new(20, 0, 22, 60), false);
+        let other = pm.clone();
+
+        let token = CancelToken::new();
+        let pathfind = |token: &CancelToken| {
+            pm.pathfind_a_star_grid_cancellable(
+                &pm.map_rect(),
+                4,
+                UVec2::new(10, 10),
+                UVec2::new(40, 10),
+                euclidean_heuristic,
+                |n, _| *n.value(),
+                token,
+            )
+        };
+        assert!(matches!(pathfind(&token), Ok(Some(_))));
+        let contour = pm
+            .contour_cancellable(&pm.map_rect(), |n, _| *n.value(), &token)
+            .unwrap();
+        assert_eq!(
+            contour.len(),
+            pm.contour(&pm.map_rect(), |n, _| *n.value()).len()
+        );
+
+        // A clone cancels the original
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(pathfind(&token).unwrap_err(), Cancelled);
+        assert!(pm
+            .pathfind_to_any_cancellable(
+                &pm.map_rect(),
+                4,
+                UVec2::new(10, 10),
+                &[UVec2::new(40, 10)],
+                euclidean_heuristic,
+                |n, _| *n.value(),
+                &token,
+            )
+            .is_err());
+        assert!(pm
+            .contour_cancellable(&pm.map_rect(), |n, _| *n.value(), &token)
+            .is_err());
+
+        // A cancelled combination leaves the map unchanged
+        pm.clear_dirty(true);
+        let version = pm.version();
+        let result = pm.combine_cancellable(&other, (1, 0), |a, b| *a && *b, &token);
+        assert_eq!(result, Err(Cancelled));
+        assert_eq!(pm.version(), version);
+        assert!(!pm.dirty());
+
+        let token = CancelToken::default();
+        assert!(pm
+            .combine_cancellable(&other, (1, 0), |a, b| *a && *b, &token)
+            .is_ok());
+        assert_eq!(pm.get_pixel((19, 0)), Some(&false));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::upload_plan::merge_rects;
+use crate::PixelMap;
+use bevy_math::URect;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+// Dirty rectangles drained from a map, awaiting emission by drain_dirty_coalesced, and the
+// frame at which each was last extended.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CoalescedDirty {
+    frame: u64,
+    pending: Vec<(URect, u64)>,
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Drain the dirty leaf nodes of this [PixelMap], as per [PixelMap::drain_dirty], into
+    /// pending rectangles, and obtain those that have not changed for the given number of
+    /// frames. Call this once per frame, such as to upload changes to a texture, so that
+    /// rapid repeated edits to the same area, such as by a brush stroke, produce one upload
+    /// rather than one per frame.
+    ///
+    /// Dirty rectangles that overlap or touch a pending rectangle extend it to their
+    /// bounding rectangle, so a pending rectangle may include pixels that are not dirty.
+    ///
+    /// # Parameters
+    ///
+    /// - `min_age_frames`: The number of frames, being calls to this method, for which a
+    ///   pending rectangle must remain unchanged before it is returned. When zero, all
+    ///   pending rectangles are returned.
+    ///
+    /// # Returns
+    ///
+    /// The rectangles that have not changed for `min_age_frames` frames, which are no longer
+    /// pending.
+    pub fn drain_dirty_coalesced(&mut self, min_age_frames: u32) -> Vec<URect> {
+        let mut dirty = Vec::new();
+        let map_rect = self.map_rect;
+        self.drain_dirty(|node| {
+            let rect = node.region().intersect(&map_rect);
+            if !rect.is_empty() {
+                dirty.push(rect);
+            }
+        });
+
+        let coalesced = self.coalesced.get_or_insert_with(Default::default);
+        coalesced.frame += 1;
+        let frame = coalesced.frame;
+        for mut rect in merge_rects(dirty) {
+            // Absorb every pending rectangle touching the growing rectangle
+            while let Some(i) = coalesced
+                .pending
+                .iter()
+                .position(|(p, _)| touches(p, &rect))
+            {
+                rect = rect.union(coalesced.pending.swap_remove(i).0);
+            }
+            coalesced.pending.push((rect, frame));
+        }
+
+        let mut ready = Vec::new();
+        coalesced.pending.retain(|(rect, modified)| {
+            if frame - modified >= min_age_frames as u64 {
+                ready.push(*rect);
+                false
+            } else {
+                true
+            }
+        });
+        ready.sort_unstable_by_key(|r| (r.min.y, r.min.x));
+        ready
+    }
+}
+
+// Determine if two rectangles overlap, or share any part of an edge or corner.
+#[inline]
+fn touches(a: &URect, b: &URect) -> bool {
+    a.min.cmple(b.max).all() && b.min.cmple(a.max).all()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_drain_dirty_coalesced() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(32), 0, 1);
+        pm.clear_dirty(true);
+
+        // A brush stroke over three frames
+        for x in 2..5 {
+            pm.draw_rect(&URect::new(x, 2, x + 2, 4), 1);
+            assert!(pm.drain_dirty_coalesced(2).is_empty());
+        }
+        // An unrelated edit
+        pm.set_pixel((20, 20), 2);
+        assert!(pm.drain_dirty_coalesced(2).is_empty());
+
+        // The stroke is emitted once, as a single rectangle
+        assert_eq!(pm.drain_dirty_coalesced(2), vec![URect::new(2, 2, 6, 4)]);
+        assert!(!pm.dirty());
+        assert_eq!(
+            pm.drain_dirty_coalesced(2),
+            vec![URect::new(20, 20, 21, 21)]
+        );
+        assert!(pm.drain_dirty_coalesced(2).is_empty());
+
+        // Flushed immediately
+        pm.set_pixel((0, 0), 3);
+        assert_eq!(pm.drain_dirty_coalesced(0), vec![URect::new(0, 0, 1, 1)]);
+    }
+}
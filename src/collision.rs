@@ -0,0 +1,510 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{contour_polygons, PNode, PixelMap};
+use bevy_math::{Rect, URect, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// The outcome of moving a rectangle through a [PixelMap], as per [PixelMap::sweep_aabb].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SweepResult {
+    /// The fraction of the movement completed before a collision, from `0.0` to `1.0`.
+    /// `1.0` if there is no collision.
+    pub toi: f32,
+
+    /// The outward normal of the collided surface, being a unit vector on either axis.
+    /// Zero if there is no collision.
+    pub normal: Vec2,
+
+    /// The position of the `min` point of the rectangle upon completing the movement,
+    /// or upon collision.
+    pub position: Vec2,
+}
+
+impl SweepResult {
+    #[inline]
+    #[must_use]
+    pub fn is_hit(&self) -> bool {
+        self.normal != Vec2::ZERO
+    }
+}
+
+/// A shape to separate from matching pixels, as per [PixelMap::resolve_overlap].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OverlapShape {
+    /// A rectangle, exclusive of its `max` point, in map coordinates.
+    Rect(Rect),
+
+    /// A circle, in map coordinates.
+    Circle { center: Vec2, radius: f32 },
+}
+
+impl OverlapShape {
+    fn aabb(&self) -> Rect {
+        match self {
+            OverlapShape::Rect(rect) => *rect,
+            OverlapShape::Circle { center, radius } => {
+                Rect::from_center_half_size(*center, Vec2::splat(*radius))
+            }
+        }
+    }
+
+    // Translations that would place the shape against an edge, or corner, of an obstacle.
+    // The minimum separating translation is among them.
+    fn candidates(&self, obstacles: &[Rect]) -> Vec<Vec2> {
+        let (lo, hi, origin) = match self {
+            OverlapShape::Rect(rect) => (rect.max, rect.min, Vec2::ZERO),
+            OverlapShape::Circle { center, radius } => {
+                (*center + *radius, *center - *radius, *center)
+            }
+        };
+        let mut xs = vec![0.0];
+        let mut ys = vec![0.0];
+        for o in obstacles {
+            xs.extend([o.min.x - lo.x, o.max.x - hi.x]);
+            ys.extend([o.min.y - lo.y, o.max.y - hi.y]);
+        }
+        let mut candidates: Vec<Vec2> = xs
+            .iter()
+            .flat_map(|x| ys.iter().map(|y| Vec2::new(*x, *y)))
+            .collect();
+        if let OverlapShape::Circle { radius, .. } = self {
+            for o in obstacles {
+                for corner in [
+                    o.min,
+                    Vec2::new(o.max.x, o.min.y),
+                    o.max,
+                    Vec2::new(o.min.x, o.max.y),
+                ] {
+                    let away = (origin - corner).normalize_or_zero();
+                    if away != Vec2::ZERO {
+                        candidates.push(corner + away * *radius - origin);
+                    }
+                }
+            }
+        }
+        candidates
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Estimate the normal of the surface formed by matching pixels near the given point, such as
+    /// a hit point obtained from [PixelMap::ray_cast_dda], for bouncing projectiles or orienting
+    /// particle effects. The normal points away from the area-weighted centroid of matching pixels
+    /// within a square window around the point. Leaf nodes are weighted as a whole, rather than
+    /// pixel by pixel. Pixels outside the [PixelMap::map_rect] do not match.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The point at which to estimate the normal, in map coordinates.
+    /// - `radius`: The distance from `point` to each edge of the window. Larger values smooth
+    ///   over small surface details. A radius of zero is treated as one.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the window.
+    ///   It returns `true` if the node is part of the surface, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The normalized surface normal, or zero if no pixels within the window match the predicate,
+    /// or they surround the point evenly.
+    #[must_use]
+    pub fn surface_normal<F>(&self, point: Vec2, radius: u32, mut predicate: F) -> Vec2
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let radius = radius.max(1) as f32;
+        let min = (point - radius).floor().max(Vec2::ZERO).as_uvec2();
+        let max = (point + radius).ceil().max(Vec2::ZERO).as_uvec2();
+        let window = URect::from_corners(min, max).intersect(self.map_rect());
+        if window.is_empty() {
+            return Vec2::ZERO;
+        }
+
+        let mut moment = Vec2::ZERO;
+        self.visit_in_rect(&window, |node, sub_rect| {
+            if predicate(node, sub_rect) {
+                let area = (sub_rect.width() * sub_rect.height()) as f32;
+                moment += (sub_rect.as_rect().center() - point) * area;
+            }
+        });
+        (-moment).normalize_or_zero()
+    }
+
+    /// Move a rectangle by the given displacement until it collides with matching pixels, such as
+    /// for moving a platformer character against the map. Only leaf nodes overlapping the area
+    /// swept by the rectangle are examined, and each is tested as a whole, so long movements
+    /// through open space are inexpensive. Pixels outside the [PixelMap::map_rect] do not match.
+    ///
+    /// Matching pixels that the rectangle already overlaps are ignored, such that it may move out
+    /// of them. A rectangle touching a matching pixel cannot move into it. If the rectangle
+    /// reaches a matching pixel at a corner, the collision is reported on the `y` axis, such
+    /// that a character lands on a ledge rather than catching on its side.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle to move, exclusive of its `max` point, in map coordinates.
+    /// - `delta`: The displacement of the rectangle.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the swept area.
+    ///   It returns `true` if the node is solid, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// A [SweepResult] describing the earliest collision, if any.
+    #[must_use]
+    pub fn sweep_aabb<F>(&self, rect: &Rect, delta: Vec2, mut predicate: F) -> SweepResult
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let mut result = SweepResult {
+            toi: 1.0,
+            normal: Vec2::ZERO,
+            position: rect.min + delta,
+        };
+        let moved = Rect::from_corners(rect.min + delta, rect.max + delta);
+        let swept = rect.union(moved);
+        let min = swept.min.floor().max(Vec2::ZERO).as_uvec2();
+        let max = swept.max.ceil().max(Vec2::ZERO).as_uvec2();
+        let area = URect::from_corners(min, max).intersect(self.map_rect());
+        if area.is_empty() || delta == Vec2::ZERO {
+            return result;
+        }
+
+        self.visit_in_rect(&area, |node, sub_rect| {
+            if !predicate(node, sub_rect) {
+                return;
+            }
+            if let Some((toi, normal)) = sweep_toi(rect, delta, &sub_rect.as_rect()) {
+                if toi < result.toi || (toi == result.toi && normal.y != 0.0) {
+                    result.toi = toi;
+                    result.normal = normal;
+                }
+            }
+        });
+        result.position = rect.min + delta * result.toi;
+        result
+    }
+
+    /// Find the shortest translation that separates a shape from all matching pixels, such as
+    /// to push a character out of the map after teleporting or spawning. Pixels outside the
+    /// [PixelMap::map_rect] do not match, so a separating translation always exists.
+    /// The search widens progressively, examining matching leaf nodes as a whole.
+    ///
+    /// For a rectangle, the translation is exact. For a circle, it is exact against a single
+    /// obstacle, but may be longer than necessary where the circle would rest between the
+    /// corners of multiple obstacles.
+    ///
+    /// # Parameters
+    ///
+    /// - `shape`: The [OverlapShape] to separate.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the area searched.
+    ///   It returns `true` if the node is solid, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The minimum translation vector, or `None` if the shape does not overlap any matching pixels.
+    #[must_use]
+    pub fn resolve_overlap<F>(&self, shape: &OverlapShape, mut predicate: F) -> Option<Vec2>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        if !self.shape_overlaps(shape, Vec2::ZERO, &mut predicate) {
+            return None;
+        }
+        let aabb = shape.aabb();
+        let limit = (self.map_size().as_vec2() + aabb.size()).max_element();
+        let mut reach = aabb.size().max_element().max(1.0);
+        loop {
+            // Any translation within reach moves the shape within the window
+            let window = covered_pixels(&aabb.inflate(reach)).intersect(self.map_rect());
+            let mut obstacles = Vec::new();
+            self.visit_in_rect(&window, |node, sub_rect| {
+                if predicate(node, sub_rect) {
+                    obstacles.push(sub_rect.as_rect());
+                }
+            });
+
+            let exhaustive = reach >= limit;
+            let mut candidates = shape.candidates(&obstacles);
+            candidates.sort_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+            for candidate in candidates {
+                if !exhaustive && candidate.length() > reach {
+                    break;
+                }
+                if !self.shape_overlaps(shape, candidate, &mut predicate) {
+                    return Some(candidate);
+                }
+            }
+            if exhaustive {
+                return None;
+            }
+            reach *= 2.0;
+        }
+    }
+
+    /// Obtain polyline chains suitable for physics chain colliders, that outline the shapes
+    /// determined by the given `predicate`. Shapes are contoured as per [PixelMap::contour],
+    /// grouped with their holes as per [contour_polygons], simplified, and divided into chains
+    /// of a bounded number of vertices.
+    ///
+    /// Exterior rings wind counter-clockwise, and holes wind clockwise, with the `y` axis
+    /// pointing up, such that solid space is always to the left of a chain. The chains of a
+    /// ring are contiguous, each beginning at the last vertex of the previous chain, and the
+    /// last vertex of a ring repeats its first.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be contoured.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter.
+    ///   It returns `true` if the node is solid, or `false` otherwise.
+    /// - `simplify_epsilon`: The tolerance with which each ring is simplified, as per
+    ///   [crate::IsoLine::simplify]. Zero preserves all points.
+    /// - `max_vertices_per_chain`: The greatest number of vertices in a single chain.
+    ///
+    /// # Panics
+    ///
+    /// If `max_vertices_per_chain` is less than two.
+    #[must_use]
+    pub fn collision_chains<F>(
+        &self,
+        rect: &URect,
+        predicate: F,
+        simplify_epsilon: f32,
+        max_vertices_per_chain: usize,
+    ) -> Vec<Vec<Vec2>>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        assert!(
+            max_vertices_per_chain >= 2,
+            "max_vertices_per_chain must be >= 2"
+        );
+        let lines = self.contour(rect, predicate);
+        let rings = contour_polygons(&lines)
+            .into_iter()
+            .flat_map(|polygon| std::iter::once(polygon.exterior).chain(polygon.holes))
+            .chain(lines.into_iter().filter(|l| !l.is_closed()));
+
+        let mut chains = Vec::new();
+        for ring in rings {
+            let ring = if simplify_epsilon > 0.0 {
+                ring.simplify(simplify_epsilon)
+            } else {
+                ring
+            };
+            let points: Vec<Vec2> = ring.points.iter().map(|p| p.as_vec2()).collect();
+            let mut start = 0;
+            while start + 1 < points.len() {
+                let end = (start + max_vertices_per_chain).min(points.len());
+                chains.push(points[start..end].to_vec());
+                start = end - 1;
+            }
+        }
+        chains
+    }
+
+    // Determine if the shape, moved by the given translation, overlaps any matching pixels.
+    fn shape_overlaps<F>(&self, shape: &OverlapShape, translation: Vec2, predicate: &mut F) -> bool
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        match shape {
+            OverlapShape::Rect(rect) => {
+                let moved = Rect::from_corners(rect.min + translation, rect.max + translation);
+                self.any_in_rect(&covered_pixels(&moved), |node, sub_rect| {
+                    predicate(node, sub_rect)
+                })
+                .unwrap_or(false)
+            }
+            OverlapShape::Circle { center, radius } => {
+                let center = *center + translation;
+                let aabb = Rect::from_center_half_size(center, Vec2::splat(*radius));
+                self.any_in_rect(&covered_pixels(&aabb), |node, sub_rect| {
+                    let rect = sub_rect.as_rect();
+                    let nearest = center.clamp(rect.min, rect.max);
+                    nearest.distance(center) < radius - OVERLAP_EPSILON && predicate(node, sub_rect)
+                })
+                .unwrap_or(false)
+            }
+        }
+    }
+}
+
+// Overlaps smaller than this are disregarded, to tolerate rounding of translated coordinates.
+const OVERLAP_EPSILON: f32 = 1e-4;
+
+// The pixels overlapped by the given rectangle, exclusive of its max point.
+fn covered_pixels(rect: &Rect) -> URect {
+    URect {
+        min: (rect.min + OVERLAP_EPSILON)
+            .floor()
+            .max(Vec2::ZERO)
+            .as_uvec2(),
+        max: (rect.max - OVERLAP_EPSILON)
+            .ceil()
+            .max(Vec2::ZERO)
+            .as_uvec2(),
+    }
+}
+
+// The time of impact, and normal, of a rectangle moving by `delta` into a stationary obstacle.
+fn sweep_toi(rect: &Rect, delta: Vec2, obstacle: &Rect) -> Option<(f32, Vec2)> {
+    let mut entry = [f32::NEG_INFINITY; 2];
+    let mut exit = [f32::INFINITY; 2];
+    for axis in 0..2 {
+        let d = delta[axis];
+        if d > 0.0 {
+            entry[axis] = (obstacle.min[axis] - rect.max[axis]) / d;
+            exit[axis] = (obstacle.max[axis] - rect.min[axis]) / d;
+        } else if d < 0.0 {
+            entry[axis] = (obstacle.max[axis] - rect.min[axis]) / d;
+            exit[axis] = (obstacle.min[axis] - rect.max[axis]) / d;
+        } else if rect.max[axis] <= obstacle.min[axis] || rect.min[axis] >= obstacle.max[axis] {
+            return None;
+        }
+    }
+    let toi = entry[0].max(entry[1]);
+    if !(0.0..1.0).contains(&toi) || toi >= exit[0].min(exit[1]) {
+        return None;
+    }
+    // Prefer the y axis at corners
+    let axis = if entry[1] >= entry[0] { 1 } else { 0 };
+    let mut normal = Vec2::ZERO;
+    normal[axis] = -delta[axis].signum();
+    Some((toi, normal))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{vec2, Rect, URect, UVec2, Vec2};
+
+    #[test]
+    fn test_surface_normal() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(10, 0, 12, 16), true);
+        let normal = pm.surface_normal(vec2(10.0, 8.5), 3, |n, _| *n.value());
+        assert_eq!(normal, vec2(-1.0, 0.0));
+
+        // A diagonal slope
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        for x in 0..16 {
+            pm.draw_rect(&URect::new(x, 0, x + 1, 16 - x), true);
+        }
+        let normal = pm.surface_normal(vec2(8.0, 8.0), 4, |n, _| *n.value());
+        assert!(normal.abs_diff_eq(Vec2::ONE.normalize(), 1e-3), "{normal}");
+
+        assert_eq!(
+            pm.surface_normal(vec2(15.0, 15.0), 0, |_, _| false),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_sweep_aabb() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 64, 4), true);
+        pm.draw_rect(&URect::new(40, 4, 44, 20), true);
+        let solid = |n: &PNode<bool, u32>, _: &URect| *n.value();
+
+        // Falling onto the floor
+        let player = Rect::new(10.0, 20.0, 12.0, 24.0);
+        let result = pm.sweep_aabb(&player, vec2(0.0, -30.0), solid);
+        assert!(result.is_hit());
+        assert_eq!(result.normal, Vec2::Y);
+        assert_eq!(result.position, vec2(10.0, 4.0));
+        assert_eq!(result.toi, 16.0 / 30.0);
+
+        // Walking along the floor into the wall
+        let player = Rect::new(10.0, 4.0, 12.0, 8.0);
+        let result = pm.sweep_aabb(&player, vec2(40.0, 0.0), solid);
+        assert_eq!(result.normal, -Vec2::X);
+        assert_eq!(result.position, vec2(38.0, 4.0));
+
+        // Touching the wall blocks further movement, but not movement away
+        let player = Rect::new(38.0, 4.0, 40.0, 8.0);
+        assert_eq!(pm.sweep_aabb(&player, Vec2::X, solid).toi, 0.0);
+        assert!(!pm.sweep_aabb(&player, -Vec2::X, solid).is_hit());
+
+        // Landing on the corner of the wall
+        let player = Rect::new(32.0, 24.0, 36.0, 28.0);
+        let result = pm.sweep_aabb(&player, vec2(8.0, -8.0), solid);
+        assert_eq!(result.normal, Vec2::Y);
+        assert_eq!(result.position, vec2(36.0, 20.0));
+
+        // Open space
+        let player = Rect::new(10.0, 30.0, 12.0, 34.0);
+        let result = pm.sweep_aabb(&player, vec2(20.0, 10.0), solid);
+        assert!(!result.is_hit());
+        assert_eq!(result.toi, 1.0);
+        assert_eq!(result.position, vec2(30.0, 40.0));
+    }
+
+    #[test]
+    fn test_resolve_overlap() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 64, 4), true);
+        pm.draw_rect(&URect::new(40, 4, 44, 20), true);
+        let solid = |n: &PNode<bool, u32>, _: &URect| *n.value();
+
+        // Embedded in the floor
+        let shape = OverlapShape::Rect(Rect::new(10.0, 2.5, 12.0, 6.5));
+        assert_eq!(pm.resolve_overlap(&shape, solid), Some(vec2(0.0, 1.5)));
+
+        // Embedded in the wall, nearer its left side
+        let shape = OverlapShape::Rect(Rect::new(39.0, 10.0, 41.0, 12.0));
+        assert_eq!(pm.resolve_overlap(&shape, solid), Some(vec2(-1.0, 0.0)));
+
+        // Deep in the floor, beneath the wall, escaping beyond the map
+        let shape = OverlapShape::Rect(Rect::new(41.0, 1.0, 43.0, 3.0));
+        assert_eq!(pm.resolve_overlap(&shape, solid), Some(vec2(0.0, -3.0)));
+
+        // Beneath the wall, escaping to its side
+        let shape = OverlapShape::Rect(Rect::new(41.0, 3.0, 43.0, 5.0));
+        assert_eq!(pm.resolve_overlap(&shape, solid), Some(vec2(-3.0, 1.0)));
+
+        let shape = OverlapShape::Rect(Rect::new(10.0, 4.0, 12.0, 8.0));
+        assert_eq!(pm.resolve_overlap(&shape, solid), None);
+
+        // A circle against the corner of the wall
+        let shape = OverlapShape::Circle {
+            center: vec2(45.0, 21.0),
+            radius: 2.0,
+        };
+        let mtv = pm.resolve_overlap(&shape, solid).unwrap();
+        assert!(mtv.abs_diff_eq(Vec2::ONE.normalize() * (2.0 - 2.0f32.sqrt()), 1e-4));
+    }
+
+    #[test]
+    fn test_collision_chains() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(2, 2, 12, 12), true);
+        pm.draw_rect(&URect::new(5, 5, 8, 8), false);
+
+        let chains = pm.collision_chains(&pm.map_rect(), |n, _| *n.value(), 0.5, 64);
+        assert_eq!(chains.len(), 2);
+        // The exterior is simplified to its corners and starting point, counter-clockwise,
+        // then the clockwise hole
+        assert!(chains.iter().all(|c| c.first() == c.last()));
+        assert_eq!(chains[0].len(), 6);
+        assert_eq!(chains[1].len(), 5);
+        let area = |chain: &Vec<Vec2>| -> f32 {
+            chain.windows(2).map(|w| w[0].perp_dot(w[1])).sum::<f32>() / 2.0
+        };
+        assert_eq!(area(&chains[0]), 100.0);
+        assert_eq!(area(&chains[1]), -9.0);
+
+        // Chains are divided, and remain contiguous
+        let chains = pm.collision_chains(&pm.map_rect(), |n, _| *n.value(), 0.5, 3);
+        assert_eq!(chains.len(), 5);
+        assert!(chains.iter().all(|c| c.len() <= 3));
+        assert_eq!(chains[0].last(), chains[1].first());
+        assert_eq!(chains[1].last(), chains[2].first());
+        assert_eq!(chains[2].last(), chains[0].first());
+    }
+}
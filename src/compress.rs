@@ -0,0 +1,431 @@
+use crate::{Children, PNode, PixelMap, Region};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+// The depth of the subtrees of a map that may be compressed, and the version at which each
+// was last modified, keyed by the position of the subtree in units of its size.
+#[derive(Debug, Clone)]
+pub(crate) struct Compression {
+    depth: u32,
+    modified: FxHashMap<UVec2, u64>,
+}
+
+impl Compression {
+    // The width and height of the subtrees at the compression depth of the given root node.
+    fn subtree_size<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug>(
+        &self,
+        root: &PNode<T, U>,
+    ) -> u32 {
+        root.region()
+            .size_as::<u32>()
+            .checked_shr(self.depth)
+            .unwrap_or(0)
+            .max(1)
+    }
+}
+
+// The children of a node, stored compactly as per [PixelMap::compress_cold].
+#[derive(Debug, Clone)]
+pub(crate) struct CompressedNodes<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug>(
+    Box<Encoding<T, U>>,
+);
+
+// The shape of a subtree below its root node in pre-order, as a bit per node that is set for
+// branch nodes, and the values of its leaf nodes in the same order, as runs of indices into
+// a palette of distinct values. Reading the subtree inflates it into a cache.
+#[derive(Debug, Clone)]
+struct Encoding<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> {
+    region: Region<U>,
+    shape: Vec<u8>,
+    palette: Vec<T>,
+    runs: Vec<(u32, u32)>,
+    inflated: OnceLock<Children<T, U>>,
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> CompressedNodes<T, U> {
+    // Compress the given children of a node of the given region.
+    pub(crate) fn new(region: Region<U>, children: &Children<T, U>) -> Self {
+        let mut encoding = Encoding {
+            region,
+            shape: Vec::new(),
+            palette: Vec::new(),
+            runs: Vec::new(),
+            inflated: OnceLock::new(),
+        };
+        let mut bits = 0;
+        for child in children.iter() {
+            encoding.encode(child, &mut bits);
+        }
+        encoding.shape.shrink_to_fit();
+        encoding.palette.shrink_to_fit();
+        encoding.runs.shrink_to_fit();
+        Self(Box::new(encoding))
+    }
+
+    // Obtain the children, inflating them into the cache if not already.
+    #[inline]
+    pub(crate) fn children(&self) -> &Children<T, U> {
+        self.0.inflated.get_or_init(|| self.0.inflate())
+    }
+
+    // Take the children, inflating them if not already cached.
+    #[inline]
+    pub(crate) fn take_children(&mut self) -> Children<T, U> {
+        self.0.inflated.take().unwrap_or_else(|| self.0.inflate())
+    }
+
+    // Discard the inflated children, if any.
+    #[inline]
+    pub(crate) fn discard_inflated(&mut self) {
+        self.0.inflated.take();
+    }
+
+    // The value of the first leaf node.
+    #[inline]
+    pub(crate) fn first_value(&self) -> T {
+        self.0.palette[self.0.runs[0].0 as usize]
+    }
+
+    // Determine if any leaf node has a value other than the given value.
+    #[inline]
+    pub(crate) fn differs_from(&self, value: &T) -> bool {
+        self.0.palette.iter().any(|v| v != value)
+    }
+}
+
+// The encoding determines the subtree, so the cache is disregarded.
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PartialEq
+    for CompressedNodes<T, U>
+{
+    fn eq(&self, other: &Self) -> bool {
+        let (a, b) = (&self.0, &other.0);
+        a.region == b.region && a.shape == b.shape && a.palette == b.palette && a.runs == b.runs
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> Encoding<T, U> {
+    fn encode(&mut self, node: &PNode<T, U>, bits: &mut usize) {
+        if bits.is_multiple_of(8) {
+            self.shape.push(0);
+        }
+        if !node.is_leaf() {
+            self.shape[*bits / 8] |= 1 << (*bits % 8);
+        }
+        *bits += 1;
+
+        if node.is_leaf() {
+            let value = *node.value();
+            match self.runs.last_mut() {
+                Some((index, len)) if self.palette[*index as usize] == value => *len += 1,
+                _ => {
+                    let index = match self.palette.iter().position(|v| *v == value) {
+                        Some(index) => index,
+                        None => {
+                            self.palette.push(value);
+                            self.palette.len() - 1
+                        }
+                    };
+                    self.runs.push((index as u32, 1));
+                }
+            }
+        } else {
+            for child in node.children().iter() {
+                self.encode(child, bits);
+            }
+        }
+    }
+
+    fn inflate(&self) -> Children<T, U> {
+        let mut shape = (0..).map(|i| self.shape[i / 8] & (1 << (i % 8)) != 0);
+        let mut values = self.runs.iter().flat_map(|(index, len)| {
+            std::iter::repeat_n(self.palette[*index as usize], *len as usize)
+        });
+        inflate_children(&self.region, &mut shape, &mut values)
+    }
+}
+
+fn inflate_children<T, U, S, V>(region: &Region<U>, shape: &mut S, values: &mut V) -> Children<T, U>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    S: Iterator<Item = bool>,
+    V: Iterator<Item = T>,
+{
+    let (x, y, half_size) = (region.x(), region.y(), region.half_size());
+    let regions = [
+        Region::new(x, y, half_size),
+        Region::new(x + half_size, y, half_size),
+        Region::new(x + half_size, y + half_size, half_size),
+        Region::new(x, y + half_size, half_size),
+    ];
+    Box::new(regions.map(|region| {
+        if shape.next().unwrap() {
+            let children = inflate_children(&region, shape, values);
+            PNode::new_branch(region, children)
+        } else {
+            PNode::new(region, values.next().unwrap(), false)
+        }
+    }))
+}
+
+// Compressed nodes are persisted in their compressed form, but as their children, such that
+// they may be accessed in place when archived.
+
+#[cfg(feature = "serialize")]
+impl<T, U> serde::Serialize for CompressedNodes<T, U>
+where
+    T: Copy + PartialEq + serde::Serialize,
+    U: Unsigned + NumCast + Copy + Debug + serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.inflated.get() {
+            Some(children) => children.serialize(serializer),
+            None => self.0.inflate().serialize(serializer),
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl<'de, T, U> serde::Deserialize<'de> for CompressedNodes<T, U>
+where
+    T: Copy + PartialEq + serde::Deserialize<'de>,
+    U: Unsigned + NumCast + Copy + Debug + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let children = Children::<T, U>::deserialize(deserializer)?;
+        Ok(Self::new(parent_region(&children), &children))
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T, U> rkyv::Archive for CompressedNodes<T, U>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    Children<T, U>: rkyv::Archive,
+{
+    type Archived = rkyv::Archived<Children<T, U>>;
+    type Resolver = rkyv::Resolver<Children<T, U>>;
+
+    fn resolve(&self, resolver: Self::Resolver, out: rkyv::Place<Self::Archived>) {
+        self.children().resolve(resolver, out);
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T, U, S> rkyv::Serialize<S> for CompressedNodes<T, U>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    Children<T, U>: rkyv::Serialize<S>,
+    S: rkyv::rancor::Fallible + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        self.children().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl<T, U, D> rkyv::Deserialize<CompressedNodes<T, U>, D>
+    for rkyv::boxed::ArchivedBox<[crate::ArchivedPNode<T, U>; 4]>
+where
+    T: Copy + PartialEq + rkyv::Archive,
+    U: Unsigned + NumCast + Copy + Debug + rkyv::Archive,
+    Self: rkyv::Deserialize<Children<T, U>, D>,
+    D: rkyv::rancor::Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<CompressedNodes<T, U>, D::Error> {
+        let children: Children<T, U> = rkyv::Deserialize::deserialize(self, deserializer)?;
+        Ok(CompressedNodes::new(parent_region(&children), &children))
+    }
+}
+
+// The region of the parent of the given children.
+#[cfg(any(feature = "serialize", feature = "rkyv"))]
+fn parent_region<T, U>(children: &Children<T, U>) -> Region<U>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    let region = children[0].region();
+    Region::new(region.x(), region.y(), region.size() + region.size())
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Enable the compression of subtrees of this [PixelMap] at the given depth below the
+    /// root node, such that [PixelMap::compress_cold] may store those that are rarely modified
+    /// in a compact form. This suits very large, mostly static worlds, where noisy regions
+    /// that are never revisited would otherwise be fully expanded in memory.
+    ///
+    /// The version at which each subtree was last modified is tracked from now on, including
+    /// drawing within [PixelMap::without_dirty]. If compression is already enabled, its
+    /// modification history is discarded, but compressed subtrees remain compressed.
+    ///
+    /// # Parameters
+    ///
+    /// - `depth`: The depth of the subtrees to compress, where the root node is at depth
+    ///   zero. Each subtree covers `1 / 4^depth` of the root node's region.
+    pub fn enable_compression(&mut self, depth: u32) {
+        self.compression = Some(Compression {
+            depth,
+            modified: FxHashMap::default(),
+        });
+    }
+
+    /// Inflate all compressed subtrees, and discard the modification history.
+    /// See [PixelMap::enable_compression].
+    pub fn disable_compression(&mut self) {
+        self.root.inflate();
+        self.compression = None;
+    }
+
+    /// Obtain the depth of the subtrees to compress, if compression is enabled.
+    /// See [PixelMap::enable_compression].
+    #[inline]
+    #[must_use]
+    pub fn compression_depth(&self) -> Option<u32> {
+        self.compression
+            .as_ref()
+            .map(|compression| compression.depth)
+    }
+
+    /// Compress the subtrees at the depth given to [PixelMap::enable_compression] that were
+    /// last modified before the given version, and are not dirty. Subtrees that were not
+    /// modified since compression was enabled are compressed regardless of the version.
+    /// Has no effect if compression is not enabled.
+    ///
+    /// A compressed subtree stores its shape as a bit per node, and the values of its leaf
+    /// nodes as runs of indices into a palette of its distinct values, in place of its nodes.
+    /// It behaves as before for all queries and drawing, though at a cost:
+    ///
+    /// - Drawing to a compressed subtree inflates it, and it remains so until compressed
+    ///   again.
+    /// - Reading a compressed subtree, such as by visiting its leaf nodes, inflates a copy of
+    ///   its nodes, which is kept until the subtree is drawn to, or this is called again.
+    ///   So, call this periodically to reclaim memory of subtrees read since.
+    ///
+    /// # Parameters
+    ///
+    /// - `older_than_version`: Subtrees last modified at this [PixelMap::version] or later
+    ///   are not compressed.
+    ///
+    /// # Returns
+    ///
+    /// The number of subtrees compressed, excluding those that already were.
+    pub fn compress_cold(&mut self, older_than_version: u64) -> usize {
+        let Some(compression) = &self.compression else {
+            return 0;
+        };
+        let size = compression.subtree_size(&self.root);
+        self.root.compress_cold(compression.depth, &mut |region| {
+            compression
+                .modified
+                .get(&(region.point() / size))
+                .is_none_or(|version| *version < older_than_version)
+        })
+    }
+
+    /// Count the compressed subtrees of this [PixelMap]. See [PixelMap::compress_cold].
+    #[must_use]
+    pub fn compressed_count(&self) -> usize {
+        self.root.compressed_count()
+    }
+
+    // Record the modification of the given rectangle at the current version.
+    pub(crate) fn record_compression(&mut self, rect: &URect) {
+        let Some(compression) = &mut self.compression else {
+            return;
+        };
+        let rect = rect.intersect(self.map_rect);
+        if rect.is_empty() {
+            return;
+        }
+        let size = compression.subtree_size(&self.root);
+        let min = rect.min / size;
+        let max = (rect.max - UVec2::ONE) / size;
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                compression.modified.insert(UVec2::new(x, y), self.version);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    fn noisy_map() -> PixelMap<u8, u16> {
+        let mut pm = PixelMap::new(&UVec2::splat(64), 0, 1);
+        for y in 0..64u32 {
+            for x in 0..64u32 {
+                pm.set_pixel((x, y), ((x * 7 + y * 13) % 5) as u8);
+            }
+        }
+        pm.clear_dirty(true);
+        pm
+    }
+
+    #[test]
+    fn test_compress_cold() {
+        let mut pm = noisy_map();
+        assert_eq!(pm.compress_cold(u64::MAX), 0);
+        pm.enable_compression(2);
+        assert_eq!(pm.compression_depth(), Some(2));
+
+        // One subtree is dirty, and another was modified recently
+        pm.set_pixel((33, 1), 9);
+        let cold = pm.version() + 1;
+        pm.without_dirty(|pm| pm.set_pixel((1, 1), 9));
+        let expected = pm.clone();
+
+        assert_eq!(pm.compress_cold(cold), 14);
+        assert_eq!(pm.compressed_count(), 14);
+        assert_eq!(pm.compress_cold(cold), 0);
+        let stats = pm.stats();
+        assert_eq!(stats, expected.stats());
+        assert!(pm == expected);
+        assert_eq!(pm.get_pixel((40, 50)), expected.get_pixel((40, 50)));
+
+        // Reading leaves subtrees compressed, while drawing inflates them
+        let mut pixels = Vec::new();
+        pm.visit(|node, rect| pixels.push((*rect, *node.value())));
+        let mut expected_pixels = Vec::new();
+        expected.visit(|node, rect| expected_pixels.push((*rect, *node.value())));
+        assert_eq!(pixels, expected_pixels);
+        assert_eq!(pm.compressed_count(), 14);
+
+        pm.draw_rect(&URect::new(40, 40, 48, 48), 3);
+        assert_eq!(pm.compressed_count(), 13);
+        assert!(pm.dirty());
+        pm.clear_dirty(true);
+        let mut expected = expected.clone();
+        expected.draw_rect(&URect::new(40, 40, 48, 48), 3);
+        expected.clear_dirty(true);
+        assert!(pm == expected);
+
+        pm.disable_compression();
+        assert_eq!(pm.compressed_count(), 0);
+        assert!(pm == expected);
+    }
+
+    #[test]
+    fn test_compress_cold_uniform() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(64), 0, 1);
+        pm.enable_compression(1);
+        pm.draw_rect(&URect::new(0, 0, 40, 40), 1);
+        pm.clear_dirty(true);
+
+        // Leaf nodes at the compression depth are left as they are
+        assert_eq!(pm.compress_cold(pm.version() + 1), 3);
+        assert_eq!(pm.stats(), {
+            let mut expected = PixelMap::<u8, u16>::new(&UVec2::splat(64), 0, 1);
+            expected.draw_rect(&URect::new(0, 0, 40, 40), 1);
+            expected.stats()
+        });
+    }
+}
@@ -0,0 +1,171 @@
+use crate::PixelMap;
+use bevy_math::{Rect, URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// Anti-aliased drawing, where each pixel is blended with the fraction of its area covered by
+/// a shape, rather than being set outright, such as for soft brushes or field-of-view falloff
+/// in maps of scalar values.
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Draw an anti-aliased circle.
+    ///
+    /// # Parameters
+    ///
+    /// - `center`: The center of the circle.
+    /// - `radius`: The radius of the circle.
+    /// - `blend`: A closure that takes the current value of a pixel, and the coverage of the
+    ///   pixel by the circle, in the range `0..=1`, and returns the new value of the pixel.
+    ///   It is not called for pixels that are not covered.
+    ///
+    /// # Returns
+    ///
+    /// If the circle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false`
+    /// is returned.
+    pub fn draw_circle_aa<F>(&mut self, center: Vec2, radius: f32, blend: F) -> bool
+    where
+        F: FnMut(&T, f32) -> T,
+    {
+        let bounds = Rect::from_center_half_size(center, Vec2::splat(radius));
+        self.draw_coverage(bounds, |p| p.distance(center) - radius, blend)
+    }
+
+    /// Draw an anti-aliased line of the given width, having round ends.
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: The start point of the line.
+    /// - `end`: The end point of the line.
+    /// - `width`: The width of the line.
+    /// - `blend`: A closure that takes the current value of a pixel, and the coverage of the
+    ///   pixel by the line, in the range `0..=1`, and returns the new value of the pixel.
+    ///   It is not called for pixels that are not covered.
+    ///
+    /// # Returns
+    ///
+    /// If the line overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false`
+    /// is returned.
+    pub fn draw_line_aa<F>(&mut self, start: Vec2, end: Vec2, width: f32, blend: F) -> bool
+    where
+        F: FnMut(&T, f32) -> T,
+    {
+        let half_width = width / 2.0;
+        let bounds = Rect::from_corners(start, end).inflate(half_width);
+        let direction = end - start;
+        let length_squared = direction.length_squared();
+        let distance = move |p: Vec2| {
+            let t = if length_squared > 0.0 {
+                ((p - start).dot(direction) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            p.distance(start + direction * t) - half_width
+        };
+        self.draw_coverage(bounds, distance, blend)
+    }
+
+    // Blend pixels within the given bounds with their coverage by the shape described by the
+    // given signed distance function, being negative inside the shape. Coverage is
+    // approximated from the distance at the center of each pixel. Leaf nodes that are
+    // entirely covered, or entirely uncovered, are drawn or skipped wholesale.
+    fn draw_coverage<S, F>(&mut self, bounds: Rect, distance: S, mut blend: F) -> bool
+    where
+        S: Fn(Vec2) -> f32,
+        F: FnMut(&T, f32) -> T,
+    {
+        let bounds = bounds.inflate(0.5);
+        let bounds = URect::from_corners(
+            bounds.min.floor().max(Vec2::ZERO).as_uvec2(),
+            bounds.max.ceil().max(Vec2::ZERO).as_uvec2(),
+        )
+        .intersect(self.map_rect());
+        if bounds.is_empty() {
+            return false;
+        }
+
+        let coverage = |p: Vec2| (0.5 - distance(p)).clamp(0.0, 1.0);
+        let step = self.pixel_size as u32;
+        let half = step as f32 / 2.0;
+        let mut updates: Vec<(URect, T)> = Vec::new();
+        self.visit_in_rect(&bounds, |node, sub_rect| {
+            let value = node.value();
+            let rect = sub_rect.as_rect();
+            let d = distance(rect.center());
+            let half_diagonal = rect.half_size().length();
+            if d - half_diagonal >= 0.5 {
+                return;
+            }
+            if d + half_diagonal <= -0.5 {
+                let new_value = blend(value, 1.0);
+                if new_value != *value {
+                    updates.push((*sub_rect, new_value));
+                }
+                return;
+            }
+            for y in (sub_rect.min.y..sub_rect.max.y).step_by(step as usize) {
+                for x in (sub_rect.min.x..sub_rect.max.x).step_by(step as usize) {
+                    let point = UVec2::new(x, y);
+                    let c = coverage(point.as_vec2() + half);
+                    if c > 0.0 {
+                        let new_value = blend(value, c);
+                        if new_value != *value {
+                            updates.push((URect::from_corners(point, point + step), new_value));
+                        }
+                    }
+                }
+            }
+        });
+
+        for (rect, value) in updates {
+            self.draw_rect(&rect, value);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{UVec2, Vec2};
+
+    #[test]
+    fn test_draw_circle_aa() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(32), 0, 1);
+        let to_u8 = |v: &u8, c: f32| (*v).max((c * 255.0).round() as u8);
+        assert!(pm.draw_circle_aa(Vec2::splat(16.0), 8.0, to_u8));
+        assert_eq!(pm.get_pixel((16, 16)), Some(&255));
+        assert_eq!(pm.get_pixel((0, 0)), Some(&0));
+
+        // Graded along the edge
+        let edge: Vec<u8> = (20..27).map(|x| *pm.get_pixel((x, 16)).unwrap()).collect();
+        assert_eq!(edge[0], 255);
+        assert_eq!(*edge.last().unwrap(), 0);
+        assert!(edge.iter().any(|v| *v > 0 && *v < 255));
+        assert!(edge.windows(2).all(|w| w[0] >= w[1]));
+
+        assert!(!pm.draw_circle_aa(Vec2::splat(60.0), 4.0, to_u8));
+    }
+
+    #[test]
+    fn test_draw_line_aa() {
+        let mut pm: PixelMap<f32, u32> = PixelMap::new(&UVec2::splat(32), 0.0, 1);
+        let (start, end) = (Vec2::new(4.0, 4.0), Vec2::new(28.0, 20.0));
+        assert!(pm.draw_line_aa(start, end, 3.0, |v, c| v + c));
+        assert_eq!(pm.get_pixel((16, 12)), Some(&1.0));
+        assert_eq!(pm.get_pixel((16, 20)), Some(&0.0));
+        assert_eq!(pm.get_pixel((30, 30)), Some(&0.0));
+
+        let mut total = 0.0;
+        let mut partial = false;
+        pm.visit(|n, r| {
+            let v = *n.value();
+            total += v * (r.width() * r.height()) as f32;
+            partial |= v > 0.0 && v < 1.0;
+        });
+        assert!(partial);
+
+        // Approximately the area of the line, with round ends
+        let length = start.distance(end);
+        let area = length * 3.0 + std::f32::consts::PI * 1.5 * 1.5;
+        assert!((total - area).abs() < area * 0.1, "{total} {area}");
+    }
+}
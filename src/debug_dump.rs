@@ -0,0 +1,249 @@
+use crate::{CellFill, PNode, PixelMap};
+use bevy_math::URect;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+use std::io::Write;
+
+/// Styling applied to the output of [PixelMap::debug_svg].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugSvgStyle {
+    /// The number of SVG units per pixel.
+    pub scale: f32,
+
+    /// The stroke color of leaf node boundaries.
+    pub stroke: String,
+
+    /// The stroke width of node boundaries, in SVG units.
+    pub stroke_width: f32,
+
+    /// The stroke color of the boundaries of dirty nodes, both leaf and branch.
+    pub dirty_stroke: String,
+}
+
+impl Default for DebugSvgStyle {
+    fn default() -> Self {
+        Self {
+            scale: 8.0,
+            stroke: "#808080".to_string(),
+            stroke_width: 1.0,
+            dirty_stroke: "#ff0000".to_string(),
+        }
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Write an SVG document visualizing the quadtree structure of this [PixelMap]. Each leaf node
+    /// is drawn as a rectangle filled by the color obtained from `fill`, outlined by the node boundary.
+    /// Dirty nodes, including branch nodes, are then outlined in [DebugSvgStyle::dirty_stroke],
+    /// such that dirty propagation can be examined. The `y` axis is flipped so that the origin
+    /// of the map appears at the bottom-left.
+    ///
+    /// # Parameters
+    ///
+    /// - `writer`: The destination of the SVG document.
+    /// - `style`: The styling to apply.
+    /// - `fill`: A closure that takes a reference to a leaf node, and returns an SVG color.
+    pub fn debug_svg<W, F>(
+        &self,
+        writer: &mut W,
+        style: &DebugSvgStyle,
+        mut fill: F,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&PNode<T, U>) -> String,
+    {
+        let map_rect = self.map_rect();
+        let width = map_rect.width() as f32 * style.scale;
+        let height = map_rect.height() as f32 * style.scale;
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )?;
+
+        let mut result = Ok(());
+        self.visit(|node, sub_rect| {
+            if result.is_ok() {
+                result = write_svg_rect(
+                    writer,
+                    style,
+                    &map_rect,
+                    sub_rect,
+                    &fill(node),
+                    &style.stroke,
+                );
+            }
+        });
+        result?;
+
+        let mut result = Ok(());
+        self.visit_nodes_in_rect(&map_rect, |node, sub_rect| {
+            if !node.dirty() {
+                // Clean nodes never have dirty children
+                return CellFill::Empty;
+            }
+            if result.is_ok() {
+                result = write_svg_rect(
+                    writer,
+                    style,
+                    &map_rect,
+                    sub_rect,
+                    "none",
+                    &style.dirty_stroke,
+                );
+            }
+            CellFill::Full
+        });
+        result?;
+
+        writeln!(writer, "</svg>")
+    }
+
+    /// Render the pixels within the given rectangle as text, for inspecting the quadtree structure
+    /// of small regions of this [PixelMap]. Each pixel is rendered as the character obtained from
+    /// `glyph` for its leaf node. Boundaries between leaf nodes are drawn with `|`, `-` and `+`.
+    /// Rows are ordered top to bottom, such that the origin of the map appears at the bottom-left.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle of pixels to render.
+    /// - `glyph`: A closure that takes a reference to a leaf node, and returns the character
+    ///   with which to render its pixels. For example, this may distinguish dirty nodes.
+    ///
+    /// # Returns
+    ///
+    /// The rendered text, or an empty string if `rect` does not overlap this [PixelMap].
+    #[must_use]
+    pub fn debug_ascii<F>(&self, rect: &URect, mut glyph: F) -> String
+    where
+        F: FnMut(&PNode<T, U>) -> char,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return String::new();
+        }
+        let width = rect.width() as usize;
+        let height = rect.height() as usize;
+
+        // Identify the leaf node, and glyph, of each pixel
+        let mut leaves: Vec<(usize, char)> = vec![(0, ' '); width * height];
+        let mut leaf_index = 0;
+        self.visit_in_rect(&rect, |node, sub_rect| {
+            let c = glyph(node);
+            for y in sub_rect.min.y..sub_rect.max.y {
+                for x in sub_rect.min.x..sub_rect.max.x {
+                    let i = (y - rect.min.y) as usize * width + (x - rect.min.x) as usize;
+                    leaves[i] = (leaf_index, c);
+                }
+            }
+            leaf_index += 1;
+        });
+        let leaf_at = |x: usize, y: usize| leaves[y * width + x].0;
+
+        let mut out = String::with_capacity((width * 2 + 2) * (height * 2));
+        for y in (0..height).rev() {
+            for x in 0..width {
+                out.push(leaves[y * width + x].1);
+                if x + 1 < width {
+                    out.push(if leaf_at(x, y) != leaf_at(x + 1, y) {
+                        '|'
+                    } else {
+                        ' '
+                    });
+                }
+            }
+            push_line(&mut out);
+            if y == 0 {
+                break;
+            }
+            for x in 0..width {
+                let split_v = leaf_at(x, y) != leaf_at(x, y - 1);
+                out.push(if split_v { '-' } else { ' ' });
+                if x + 1 < width {
+                    let split_corner = split_v
+                        || leaf_at(x + 1, y) != leaf_at(x + 1, y - 1)
+                        || leaf_at(x, y) != leaf_at(x + 1, y)
+                        || leaf_at(x, y - 1) != leaf_at(x + 1, y - 1);
+                    out.push(if split_corner { '+' } else { ' ' });
+                }
+            }
+            push_line(&mut out);
+        }
+        out
+    }
+}
+
+// Terminate the current line of `out`, trimming trailing whitespace.
+fn push_line(out: &mut String) {
+    out.truncate(out.trim_end_matches(' ').len());
+    out.push('\n');
+}
+
+fn write_svg_rect<W: Write>(
+    writer: &mut W,
+    style: &DebugSvgStyle,
+    map_rect: &URect,
+    rect: &URect,
+    fill: &str,
+    stroke: &str,
+) -> std::io::Result<()> {
+    let x = rect.min.x as f32 * style.scale;
+    let y = (map_rect.max.y - rect.max.y) as f32 * style.scale;
+    let w = rect.width() as f32 * style.scale;
+    let h = rect.height() as f32 * style.scale;
+    writeln!(
+        writer,
+        r#"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="{fill}" stroke="{stroke}" stroke-width="{}"/>"#,
+        style.stroke_width
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_debug_ascii() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(4), false, 1);
+        pm.set_pixel((0, 0), true);
+        pm.clear_dirty(true);
+        pm.set_pixel((3, 3), true);
+        let text = pm.debug_ascii(&pm.map_rect(), |n| match (*n.value(), n.dirty()) {
+            (true, true) => 'X',
+            (true, false) => 'x',
+            (false, true) => 'O',
+            (false, false) => 'o',
+        });
+        let expected = "\
+o o|o|X
+   +-+-
+o o|o|o
+-+-+-+-
+o|o|o o
+-+-+
+x|o|o o
+";
+        assert_eq!(text, expected);
+        assert_eq!(pm.debug_ascii(&URect::new(8, 8, 9, 9), |_| 'o'), "");
+    }
+
+    #[test]
+    fn test_debug_svg() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(4), false, 1);
+        pm.clear_dirty(true);
+        pm.set_pixel((0, 0), true);
+        let mut out = Vec::new();
+        pm.debug_svg(&mut out, &DebugSvgStyle::default(), |n| {
+            if *n.value() { "black" } else { "white" }.to_string()
+        })
+        .unwrap();
+        let svg = String::from_utf8(out).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        // 7 leaves, then the dirty root, bottom-left branch and leaf
+        assert_eq!(svg.matches("<rect ").count(), 7 + 3);
+        assert_eq!(svg.matches(r##"stroke="#ff0000""##).count(), 3);
+        assert!(svg.contains(r#"<rect x="0" y="24" width="8" height="8" fill="black""#));
+    }
+}
@@ -1,7 +1,8 @@
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
 
-use bevy_math::{uvec2, IVec2, UVec2};
+use bevy_math::{uvec2, IVec2, UVec2, Vec2};
+use std::f32::consts::{FRAC_PI_4, PI};
 
 pub const NORTH: IVec2 = IVec2 { x: 0, y: 1 };
 pub const NORTH_EAST: IVec2 = IVec2 { x: 1, y: 1 };
@@ -65,6 +66,69 @@ impl Direction {
         Self::ALL.iter().copied()
     }
 
+    /// Iterate all directions clockwise, beginning with the given direction.
+    #[inline]
+    pub fn iter_from(start: Direction) -> impl Iterator<Item = Direction> {
+        let start = start.index();
+        (0..8).map(move |i| Self::ALL[(start + i) % 8])
+    }
+
+    /// Returns the direction nearest to the given vector, by angle. Vectors exactly between
+    /// two directions are rounded away from zero angle.
+    /// `None` if the vector is zero, or not finite.
+    #[inline]
+    #[must_use]
+    pub fn from_vec2(v: Vec2) -> Option<Direction> {
+        if v == Vec2::ZERO || !v.is_finite() {
+            return None;
+        }
+        // Octants counter-clockwise from east, being the reverse order of ALL, from north
+        let octant = (v.to_angle() / FRAC_PI_4).round() as i32;
+        Some(Self::ALL[(2 - octant).rem_euclid(8) as usize])
+    }
+
+    /// Returns the angle of this direction in radians, in the range `(-PI, PI]`, measured
+    /// counter-clockwise from [Direction::East], as per [Vec2::to_angle].
+    #[inline]
+    #[must_use]
+    pub fn to_angle(&self) -> f32 {
+        let octant = (2 - self.index() as i32).rem_euclid(8);
+        if octant > 4 {
+            (octant - 8) as f32 * FRAC_PI_4
+        } else if octant == 4 {
+            PI
+        } else {
+            octant as f32 * FRAC_PI_4
+        }
+    }
+
+    /// Returns the next direction clockwise, 45 degrees from this one.
+    #[inline]
+    #[must_use]
+    pub fn rotate_cw(&self) -> Direction {
+        Self::ALL[(self.index() + 1) % 8]
+    }
+
+    /// Returns the next direction counter-clockwise, 45 degrees from this one.
+    #[inline]
+    #[must_use]
+    pub fn rotate_ccw(&self) -> Direction {
+        Self::ALL[(self.index() + 7) % 8]
+    }
+
+    /// Returns the direction opposite this one.
+    #[inline]
+    #[must_use]
+    pub fn opposite(&self) -> Direction {
+        Self::ALL[(self.index() + 4) % 8]
+    }
+
+    // The position of this direction in ALL.
+    #[inline]
+    fn index(&self) -> usize {
+        self.as_bit().trailing_zeros() as usize
+    }
+
     /// Returns the unit vector for this direction.
     #[inline]
     #[must_use]
@@ -115,6 +179,24 @@ impl Direction {
         )
     }
 
+    /// Returns the bit representing this direction in a neighborhood mask, according to the
+    /// clockwise order of [Direction::ALL], starting with [Direction::North] as the lowest bit.
+    /// See [crate::PixelMap::neighborhood_mask].
+    #[inline]
+    #[must_use]
+    pub fn as_bit(&self) -> u8 {
+        match self {
+            Direction::North => 1 << 0,
+            Direction::NorthEast => 1 << 1,
+            Direction::East => 1 << 2,
+            Direction::SouthEast => 1 << 3,
+            Direction::South => 1 << 4,
+            Direction::SouthWest => 1 << 5,
+            Direction::West => 1 << 6,
+            Direction::NorthWest => 1 << 7,
+        }
+    }
+
     /// Returns true if this direction is diagonal (NE, NW, SE, SW).
     #[inline]
     #[must_use]
@@ -128,3 +210,48 @@ impl Direction {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_math::vec2;
+
+    #[test]
+    fn test_angles() {
+        for d in Direction::iter() {
+            assert_eq!(Direction::from_vec2(d.unit().as_vec2()), Some(d));
+            assert_eq!(
+                Direction::from_vec2(Vec2::from_angle(d.to_angle())),
+                Some(d)
+            );
+            assert!(
+                (Vec2::from_angle(d.to_angle()) - d.unit().as_vec2().normalize()).length() < 1e-6
+            );
+        }
+        assert_eq!(Direction::West.to_angle(), PI);
+        assert_eq!(Direction::from_vec2(vec2(10.0, 3.0)), Some(Direction::East));
+        assert_eq!(
+            Direction::from_vec2(vec2(-1.0, -0.9)),
+            Some(Direction::SouthWest)
+        );
+        assert_eq!(Direction::from_vec2(Vec2::ZERO), None);
+        assert_eq!(Direction::from_vec2(vec2(f32::NAN, 1.0)), None);
+    }
+
+    #[test]
+    fn test_rotation() {
+        assert_eq!(Direction::North.rotate_cw(), Direction::NorthEast);
+        assert_eq!(Direction::North.rotate_ccw(), Direction::NorthWest);
+        assert_eq!(Direction::NorthWest.rotate_cw(), Direction::North);
+        for d in Direction::iter() {
+            assert_eq!(d.rotate_cw().rotate_ccw(), d);
+            assert_eq!(d.opposite().unit(), -d.unit());
+        }
+        let from_west: Vec<Direction> = Direction::iter_from(Direction::West).collect();
+        assert_eq!(
+            from_west[..3],
+            [Direction::West, Direction::NorthWest, Direction::North]
+        );
+        assert_eq!(from_west.len(), 8);
+    }
+}
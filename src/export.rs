@@ -0,0 +1,287 @@
+use crate::{contour_polygons, IsoLine, PixelMap};
+use bevy_math::{IVec2, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+use std::io::Write;
+
+/// Styling applied to the output of [PixelMap::write_contours_svg].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContourSvgStyle {
+    /// The number of SVG units per pixel.
+    pub scale: f32,
+
+    /// The fill color of polygons.
+    pub fill: String,
+
+    /// The stroke color of all lines.
+    pub stroke: String,
+
+    /// The stroke width of all lines, in SVG units.
+    pub stroke_width: f32,
+}
+
+impl Default for ContourSvgStyle {
+    fn default() -> Self {
+        Self {
+            scale: 8.0,
+            fill: "#c0c0c0".to_string(),
+            stroke: "#000000".to_string(),
+            stroke_width: 1.0,
+        }
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Write an SVG document of the given contour lines, such as those produced by
+    /// [PixelMap::contour], for inspection in standard vector graphics tools. Closed lines are
+    /// grouped into polygons with holes, as per [contour_polygons], and each polygon is written
+    /// as a filled path. Lines that are not closed are written as unfilled paths. The `y` axis
+    /// is flipped so that the origin of the map appears at the bottom-left.
+    ///
+    /// # Parameters
+    ///
+    /// - `writer`: The destination of the SVG document.
+    /// - `lines`: The contour lines to write.
+    /// - `tolerance`: The epsilon with which each line is simplified, as per [IsoLine::simplify].
+    ///   Zero preserves all points.
+    /// - `style`: The styling to apply.
+    pub fn write_contours_svg<W>(
+        &self,
+        writer: &mut W,
+        lines: &[IsoLine],
+        tolerance: f32,
+        style: &ContourSvgStyle,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let map_rect = self.map_rect();
+        let width = map_rect.width() as f32 * style.scale;
+        let height = map_rect.height() as f32 * style.scale;
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+        )?;
+
+        let to_svg = |p: &IVec2| {
+            let x = p.x as f32 * style.scale;
+            let y = (map_rect.max.y as i32 - p.y) as f32 * style.scale;
+            format!("{x} {y}")
+        };
+        let path_data = |line: &IsoLine| {
+            let line = simplified(line, tolerance);
+            let mut d = String::new();
+            for (i, p) in line.points.iter().enumerate() {
+                d.push(if i == 0 { 'M' } else { 'L' });
+                d.push_str(&to_svg(p));
+            }
+            if line.is_closed() {
+                d.push('Z');
+            }
+            d
+        };
+
+        for polygon in contour_polygons(lines) {
+            let mut d = path_data(&polygon.exterior);
+            for hole in &polygon.holes {
+                d.push_str(&path_data(hole));
+            }
+            writeln!(
+                writer,
+                r#"<path d="{d}" fill="{}" fill-rule="evenodd" stroke="{}" stroke-width="{}"/>"#,
+                style.fill, style.stroke, style.stroke_width
+            )?;
+        }
+        for line in lines.iter().filter(|l| !l.is_closed()) {
+            writeln!(
+                writer,
+                r#"<path d="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                path_data(line),
+                style.stroke,
+                style.stroke_width
+            )?;
+        }
+
+        writeln!(writer, "</svg>")
+    }
+
+    /// Write a Wavefront OBJ model of the given triangle mesh, such as one produced by
+    /// [PixelMap::non_uniform_quad_mesh], for inspection in standard 3D modelling tools.
+    /// Vertices lie on the `z = 0` plane, in map coordinates. Each vertex has a texture
+    /// coordinate relative to the [PixelMap::map_rect], such that a texture of the whole
+    /// map may be applied.
+    ///
+    /// # Parameters
+    ///
+    /// - `writer`: The destination of the OBJ model.
+    /// - `vertices`: The vertex points of the mesh.
+    /// - `indices`: The vertex indices of each triangle of the mesh, in counter-clockwise winding.
+    pub fn export_mesh_obj<W>(
+        &self,
+        writer: &mut W,
+        vertices: &[UVec2],
+        indices: &[[u32; 3]],
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let size = self.map_size().max(UVec2::ONE).as_vec2();
+        writeln!(writer, "# pixel_map mesh")?;
+        for v in vertices {
+            writeln!(writer, "v {} {} 0", v.x, v.y)?;
+        }
+        for v in vertices {
+            let uv = v.as_vec2() / size;
+            writeln!(writer, "vt {} {}", uv.x, uv.y)?;
+        }
+        writeln!(writer, "vn 0 0 1")?;
+        for [a, b, c] in indices {
+            // Indices are one-based
+            let (a, b, c) = (a + 1, b + 1, c + 1);
+            writeln!(writer, "f {a}/{a}/1 {b}/{b}/1 {c}/{c}/1")?;
+        }
+        Ok(())
+    }
+}
+
+/// Write a GeoJSON `FeatureCollection` of the given contour lines, such as those produced by
+/// [PixelMap::contour], for inspection in standard GIS tools. Closed lines are grouped into
+/// polygons with holes, as per [contour_polygons], and each polygon is written as a `Polygon`
+/// feature. Lines that are not closed are written as `LineString` features. Coordinates are
+/// those of the map, with the `y` axis pointing up.
+///
+/// # Parameters
+///
+/// - `writer`: The destination of the GeoJSON document.
+/// - `lines`: The contour lines to write.
+/// - `tolerance`: The epsilon with which each line is simplified, as per [IsoLine::simplify].
+///   Zero preserves all points.
+pub fn write_contours_geojson<W>(
+    writer: &mut W,
+    lines: &[IsoLine],
+    tolerance: f32,
+) -> std::io::Result<()>
+where
+    W: Write,
+{
+    let coordinates = |line: &IsoLine| {
+        let points: Vec<String> = simplified(line, tolerance)
+            .points
+            .iter()
+            .map(|p| format!("[{},{}]", p.x, p.y))
+            .collect();
+        format!("[{}]", points.join(","))
+    };
+
+    let mut features = Vec::new();
+    for polygon in contour_polygons(lines) {
+        let rings: Vec<String> = std::iter::once(&polygon.exterior)
+            .chain(&polygon.holes)
+            .map(coordinates)
+            .collect();
+        features.push(format!(
+            r#"{{"type":"Feature","properties":{{}},"geometry":{{"type":"Polygon","coordinates":[{}]}}}}"#,
+            rings.join(",")
+        ));
+    }
+    for line in lines.iter().filter(|l| !l.is_closed()) {
+        features.push(format!(
+            r#"{{"type":"Feature","properties":{{}},"geometry":{{"type":"LineString","coordinates":{}}}}}"#,
+            coordinates(line)
+        ));
+    }
+
+    writeln!(
+        writer,
+        r#"{{"type":"FeatureCollection","features":[{}]}}"#,
+        features.join(",")
+    )
+}
+
+fn simplified(line: &IsoLine, tolerance: f32) -> IsoLine {
+    if tolerance > 0.0 {
+        line.simplify(tolerance)
+    } else {
+        line.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{ivec2, URect, UVec2};
+
+    fn map_with_hole() -> PixelMap<bool, u32> {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(8), false, 1);
+        pm.draw_rect(&URect::new(1, 1, 7, 7), true);
+        pm.draw_rect(&URect::new(3, 3, 5, 5), false);
+        pm
+    }
+
+    #[test]
+    fn test_write_contours_svg() {
+        let pm = map_with_hole();
+        let lines = pm.contour(&pm.map_rect(), |n, _| *n.value());
+        let style = ContourSvgStyle {
+            scale: 1.0,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        pm.write_contours_svg(&mut out, &lines, 0.5, &style)
+            .unwrap();
+        let svg = String::from_utf8(out).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        // A single polygon, with the hole as a second subpath
+        assert_eq!(svg.matches("<path").count(), 1);
+        assert_eq!(svg.matches('M').count(), 2);
+        assert!(svg.contains("evenodd"));
+        // The exterior is simplified to its corners, flipped on the y axis
+        assert_eq!(svg.matches('L').count(), 8);
+        assert!(svg.contains("1 7"));
+        assert!(svg.contains("7 1"));
+    }
+
+    #[test]
+    fn test_export_mesh_obj() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(8), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 4, 4), true);
+        let (vertices, indices) = pm.non_uniform_quad_mesh(&pm.map_rect(), |n, _| *n.value(), 4);
+        let mut out = Vec::new();
+        pm.export_mesh_obj(&mut out, &vertices, &indices).unwrap();
+        let obj = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = obj.lines().collect();
+        assert_eq!(lines.iter().filter(|l| l.starts_with("v ")).count(), 4);
+        assert_eq!(lines.iter().filter(|l| l.starts_with("vt ")).count(), 4);
+        assert_eq!(lines.iter().filter(|l| l.starts_with("f ")).count(), 2);
+        assert!(lines.contains(&"v 4 4 0"));
+        assert!(lines.contains(&"vt 0.5 0.5"));
+        assert!(lines.contains(&"f 1/1/1 2/2/1 3/3/1"));
+    }
+
+    #[test]
+    fn test_write_contours_geojson() {
+        let pm = map_with_hole();
+        let lines = pm.contour(&pm.map_rect(), |n, _| *n.value());
+        let mut out = Vec::new();
+        write_contours_geojson(&mut out, &lines, 0.5).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert_eq!(
+            json.trim_end(),
+            concat!(
+                r#"{"type":"FeatureCollection","features":[{"type":"Feature","properties":{},"#,
+                r#""geometry":{"type":"Polygon","coordinates":[[[1,7],[1,1],[7,1],[7,7],[1,7]],"#,
+                r#"[[3,5],[5,5],[5,3],[3,3],[3,5]]]}}]}"#
+            )
+        );
+
+        let open = IsoLine {
+            points: vec![ivec2(0, 0), ivec2(2, 0)],
+        };
+        let mut out = Vec::new();
+        write_contours_geojson(&mut out, &[open], 0.0).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains(r#""type":"LineString","coordinates":[[0,0],[2,0]]"#));
+    }
+}
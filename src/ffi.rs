@@ -0,0 +1,369 @@
+//! A C ABI over opaque [PixelMapHandle]s of `u32` pixel values, for driving a [PixelMap] from
+//! other languages, such as a C++ engine, or JavaScript by way of `wasm32-unknown-unknown`.
+//!
+//! The functions are exported unmangled, so they are linked into any `cdylib` or `staticlib`
+//! crate depending on this crate with the `ffi` feature. Coordinates are those of the map,
+//! having the origin at the bottom left, and rectangles exclude their maximum point.
+//! Functions accept null handles, which they ignore, so as to never panic across the ABI.
+
+use crate::{ICapsule, ICircle, ILine, PixelMap};
+use bevy_math::{URect, UVec2};
+
+/// An opaque handle to a [PixelMap] of `u32` pixel values, created by [pixel_map_new], and
+/// destroyed by [pixel_map_free].
+#[derive(Debug)]
+pub struct PixelMapHandle {
+    map: PixelMap<u32, u32>,
+}
+
+impl PixelMapHandle {
+    /// Obtain the wrapped [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn map(&self) -> &PixelMap<u32, u32> {
+        &self.map
+    }
+
+    /// Obtain the wrapped [PixelMap], mutably.
+    #[inline]
+    #[must_use]
+    pub fn map_mut(&mut self) -> &mut PixelMap<u32, u32> {
+        &mut self.map
+    }
+}
+
+/// Create a new map, as per [PixelMap::new].
+///
+/// # Returns
+///
+/// A handle to the map, to be destroyed with [pixel_map_free]. Null if a dimension is zero
+/// or too large, `pixel_size` is not a power of two, or a dimension is not a multiple of
+/// `pixel_size`.
+#[no_mangle]
+pub extern "C" fn pixel_map_new(
+    width: u32,
+    height: u32,
+    value: u32,
+    pixel_size: u8,
+) -> *mut PixelMapHandle {
+    let valid_size =
+        |size: u32| (1..=1 << 31).contains(&size) && size.is_multiple_of(pixel_size as u32);
+    if !pixel_size.is_power_of_two() || !valid_size(width) || !valid_size(height) {
+        return std::ptr::null_mut();
+    }
+    let map = PixelMap::new(&UVec2::new(width, height), value, pixel_size);
+    Box::into_raw(Box::new(PixelMapHandle { map }))
+}
+
+/// Destroy a map created by [pixel_map_new].
+///
+/// # Safety
+///
+/// `map` must be null, or a handle returned by [pixel_map_new] that has not been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_free(map: *mut PixelMapHandle) {
+    if !map.is_null() {
+        drop(Box::from_raw(map));
+    }
+}
+
+/// Obtain the width of a map.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_width(map: *const PixelMapHandle) -> u32 {
+    map.as_ref().map_or(0, |h| h.map.map_size().x)
+}
+
+/// Obtain the height of a map.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_height(map: *const PixelMapHandle) -> u32 {
+    map.as_ref().map_or(0, |h| h.map.map_size().y)
+}
+
+/// Obtain the value of a pixel, as per [PixelMap::get_pixel].
+///
+/// # Returns
+///
+/// If the pixel is within the map, `true` is returned, and its value is written to `value`.
+/// Otherwise, `false` is returned.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new]. `value` must be null,
+/// or valid for writing a `u32`.
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_get_pixel(
+    map: *const PixelMapHandle,
+    x: u32,
+    y: u32,
+    value: *mut u32,
+) -> bool {
+    let Some(handle) = map.as_ref() else {
+        return false;
+    };
+    let point = UVec2::new(x, y);
+    if value.is_null() || point.cmpge(handle.map.map_size()).any() {
+        return false;
+    }
+    match handle.map.get_pixel(point) {
+        Some(v) => {
+            *value = *v;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Set the value of a pixel, as per [PixelMap::set_pixel].
+///
+/// # Returns
+///
+/// If the pixel is within the map, `true` is returned. Otherwise, `false` is returned.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_set_pixel(
+    map: *mut PixelMapHandle,
+    x: u32,
+    y: u32,
+    value: u32,
+) -> bool {
+    let Some(handle) = map.as_mut() else {
+        return false;
+    };
+    let point = UVec2::new(x, y);
+    if point.cmpge(handle.map.map_size()).any() {
+        return false;
+    }
+    handle.map.set_pixel(point, value)
+}
+
+/// Set the value of all pixels of a map, as per [PixelMap::clear].
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_clear(map: *mut PixelMapHandle, value: u32) {
+    if let Some(handle) = map.as_mut() {
+        handle.map.clear(value);
+    }
+}
+
+/// Set the value of the pixels within a rectangle, from `(min_x, min_y)` up to, but not
+/// including, `(max_x, max_y)`, as per [PixelMap::draw_rect].
+///
+/// # Returns
+///
+/// If the rectangle overlaps the map, `true` is returned. Otherwise, `false` is returned.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_draw_rect(
+    map: *mut PixelMapHandle,
+    min_x: u32,
+    min_y: u32,
+    max_x: u32,
+    max_y: u32,
+    value: u32,
+) -> bool {
+    let Some(handle) = map.as_mut() else {
+        return false;
+    };
+    let rect = URect::new(min_x, min_y, max_x, max_y);
+    !rect.is_empty() && handle.map.draw_rect(&rect, value)
+}
+
+/// Set the value of the pixels within a circle, as per [PixelMap::draw_circle].
+///
+/// # Returns
+///
+/// If the circle overlaps the map, `true` is returned. Otherwise, `false` is returned, as it
+/// is when the bounding box of the circle exceeds the range of `i32` coordinates.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_draw_circle(
+    map: *mut PixelMapHandle,
+    x: i32,
+    y: i32,
+    radius: u32,
+    value: u32,
+) -> bool {
+    let Some(handle) = map.as_mut() else {
+        return false;
+    };
+    if !shape_in_range(&[(x, y)], radius) {
+        return false;
+    }
+    handle.map.draw_circle(&ICircle::new((x, y), radius), value)
+}
+
+/// Set the value of the pixels along a line, widened by the given radius with round ends,
+/// as per [PixelMap::draw_capsule]. A radius of zero draws a line one pixel wide.
+///
+/// # Returns
+///
+/// If the line overlaps the map, `true` is returned. Otherwise, `false` is returned, as it is
+/// when the bounding box of the widened line exceeds the range of `i32` coordinates.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new].
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_draw_line(
+    map: *mut PixelMapHandle,
+    start_x: i32,
+    start_y: i32,
+    end_x: i32,
+    end_y: i32,
+    radius: u32,
+    value: u32,
+) -> bool {
+    let Some(handle) = map.as_mut() else {
+        return false;
+    };
+    if !shape_in_range(&[(start_x, start_y), (end_x, end_y)], radius) {
+        return false;
+    }
+    let line = ILine::new((start_x, start_y), (end_x, end_y));
+    handle.map.draw_capsule(&ICapsule::new(line, radius), value)
+}
+
+/// Write the value of every pixel of a map to a buffer, in rows from the bottom of the map,
+/// such as for upload to a texture.
+///
+/// # Returns
+///
+/// If `len` is at least the width multiplied by the height of the map, `true` is returned.
+/// Otherwise, `false` is returned, and the buffer is not written.
+///
+/// # Safety
+///
+/// `map` must be null, or a live handle returned by [pixel_map_new]. `buffer` must be null,
+/// or valid for writing `len` values.
+#[no_mangle]
+pub unsafe extern "C" fn pixel_map_fill_buffer(
+    map: *const PixelMapHandle,
+    buffer: *mut u32,
+    len: usize,
+) -> bool {
+    let Some(handle) = map.as_ref() else {
+        return false;
+    };
+    let size = handle.map.map_size();
+    let width = size.x as usize;
+    let count = width.checked_mul(size.y as usize);
+    if buffer.is_null() || count.is_none_or(|count| len < count) {
+        return false;
+    }
+    let buffer = std::slice::from_raw_parts_mut(buffer, len);
+    handle.map.visit(|node, sub_rect| {
+        for y in sub_rect.min.y..sub_rect.max.y {
+            let row = y as usize * width;
+            buffer[row + sub_rect.min.x as usize..row + sub_rect.max.x as usize]
+                .fill(*node.value());
+        }
+    });
+    true
+}
+
+// Whether the bounding box of the given points, widened by `radius`, its exclusive `max`
+// point and its size, are within the range of `i32`, such that shapes about the points can
+// be drawn without overflow.
+#[must_use]
+fn shape_in_range(points: &[(i32, i32)], radius: u32) -> bool {
+    let r = radius as i64;
+    let range = |axis: &dyn Fn(&(i32, i32)) -> i32| {
+        let min = points.iter().map(axis).min().unwrap_or_default() as i64 - r;
+        let max = points.iter().map(axis).max().unwrap_or_default() as i64 + r;
+        min >= i32::MIN as i64 && max < i32::MAX as i64 && max - min < i32::MAX as i64
+    };
+    range(&|p| p.0) && range(&|p| p.1)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ffi::*;
+
+    #[test]
+    fn test_ffi() {
+        unsafe {
+            assert!(pixel_map_new(0, 4, 0, 1).is_null());
+            assert!(pixel_map_new(6, 4, 0, 4).is_null());
+            assert!(pixel_map_new(6, 4, 0, 3).is_null());
+
+            let map = pixel_map_new(6, 4, 7, 1);
+            assert!(!map.is_null());
+            assert_eq!(pixel_map_width(map), 6);
+            assert_eq!(pixel_map_height(map), 4);
+
+            assert!(pixel_map_set_pixel(map, 5, 3, 1));
+            assert!(!pixel_map_set_pixel(map, 6, 3, 1));
+            assert!(pixel_map_draw_rect(map, 0, 0, 2, 2, 2));
+            assert!(!pixel_map_draw_rect(map, 2, 2, 2, 2, 2));
+            assert!(pixel_map_draw_line(map, 0, 3, 1, 3, 0, 3));
+            assert!(!pixel_map_draw_circle(map, -20, -20, 2, 4));
+            assert!(!pixel_map_draw_circle(map, i32::MAX, 0, 10, 4));
+            assert!(!pixel_map_draw_circle(map, 0, 0, u32::MAX, 4));
+            assert!(!pixel_map_draw_line(map, i32::MIN, 0, i32::MAX, 0, 2, 4));
+            assert!(!pixel_map_draw_line(map, 0, 0, 1, 0, u32::MAX, 4));
+            assert!(!pixel_map_draw_line(
+                map,
+                i32::MAX,
+                i32::MIN,
+                i32::MAX,
+                i32::MIN,
+                0,
+                4
+            ));
+
+            let mut value = 0;
+            assert!(pixel_map_get_pixel(map, 5, 3, &mut value));
+            assert_eq!(value, 1);
+            assert!(!pixel_map_get_pixel(map, 6, 0, &mut value));
+
+            let mut buffer = [0u32; 24];
+            assert!(!pixel_map_fill_buffer(map, buffer.as_mut_ptr(), 23));
+            assert!(pixel_map_fill_buffer(map, buffer.as_mut_ptr(), 24));
+            assert_eq!(
+                buffer,
+                [
+                    2, 2, 7, 7, 7, 7, //
+                    2, 2, 7, 7, 7, 7, //
+                    7, 7, 7, 7, 7, 7, //
+                    3, 3, 7, 7, 7, 1, //
+                ]
+            );
+
+            pixel_map_clear(map, 0);
+            assert!(pixel_map_get_pixel(map, 5, 3, &mut value));
+            assert_eq!(value, 0);
+
+            // The largest shapes within range are drawn, clipped to the map
+            assert!(pixel_map_draw_line(map, -(1 << 29), 0, 1 << 29, 3, 1000, 5));
+            assert!(pixel_map_get_pixel(map, 5, 3, &mut value));
+            assert_eq!(value, 5);
+            assert!(pixel_map_draw_circle(map, 0, 0, i32::MAX as u32 / 2, 6));
+            assert!(pixel_map_get_pixel(map, 5, 3, &mut value));
+            assert_eq!(value, 6);
+
+            pixel_map_free(map);
+            pixel_map_free(std::ptr::null_mut());
+            assert_eq!(pixel_map_width(std::ptr::null()), 0);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::PixelMap;
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+
+/// The format version of a [PixelMapFile] written by this version of the crate. Files of
+/// older formats are upgraded on load.
+pub const PIXEL_MAP_FILE_FORMAT: u32 = 1;
+
+/// A schema-stable save format of a [PixelMap]. Rather than persisting the internal node
+/// structure, as the [Serialize] implementation of [PixelMap] does, leaf nodes are stored
+/// as rectangles of uniform value, behind a header describing the map. So, a file remains
+/// loadable as the internal representation of [PixelMap] evolves between crate versions, and
+/// as the pixel values of an application evolve between its own versions, by way of the
+/// migrations of a [PixelMapSchema].
+///
+/// The file is independent of the coordinate type `U` of the [PixelMap] that it was created
+/// from, and may be loaded into a [PixelMap] of any coordinate type large enough for its
+/// dimensions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PixelMapFile<T> {
+    /// A description of the map.
+    pub header: PixelMapFileHeader,
+
+    /// The pixel data of the map.
+    pub body: PixelMapFileBody<T>,
+}
+
+/// The header of a [PixelMapFile].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PixelMapFileHeader {
+    /// The format version of the file, as per [PIXEL_MAP_FILE_FORMAT].
+    pub format: u32,
+
+    /// An application-defined tag identifying the type of pixel values, such as `"terrain"`,
+    /// as per [PixelMapSchema::new].
+    pub value_type: String,
+
+    /// The application-defined version of the pixel values, as per [PixelMapSchema::new].
+    pub version: u32,
+
+    /// The dimensions of the map, as per [PixelMap::map_size].
+    pub map_size: UVec2,
+
+    /// The pixel size of the map, as per [PixelMap::pixel_size].
+    pub pixel_size: u8,
+}
+
+/// The pixel data of a [PixelMapFile], having a variant per format version, such that the
+/// data of older formats remains readable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PixelMapFileBody<T> {
+    /// Format version `1`: the rectangles of leaf nodes, in visitation order, and their
+    /// values.
+    V1(Vec<(URect, T)>),
+}
+
+/// A description of the pixel values expected when loading a [PixelMapFile], along with the
+/// migrations that upgrade the values of files written by older versions of an application.
+pub struct PixelMapSchema<T> {
+    value_type: String,
+    version: u32,
+    migrations: BTreeMap<u32, Migration<T>>,
+}
+
+type Migration<T> = Box<dyn Fn(&mut PixelMapFile<T>)>;
+
+/// An error loading a [PixelMapFile].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PixelMapFileError {
+    /// The file was written in a format newer than [PIXEL_MAP_FILE_FORMAT].
+    UnsupportedFormat(u32),
+
+    /// The value type of the file differs from that of the [PixelMapSchema].
+    ValueTypeMismatch { expected: String, found: String },
+
+    /// The file was written by a newer version than that of the [PixelMapSchema].
+    UnsupportedVersion(u32),
+
+    /// No migration is registered from the given version.
+    MissingMigration(u32),
+
+    /// The dimensions of the file can't be represented by the coordinate type of the
+    /// [PixelMap], or its pixel size is not a power of two that divides them.
+    InvalidDimensions(UVec2),
+}
+
+impl Display for PixelMapFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedFormat(format) => {
+                write!(f, "unsupported pixel map file format: {format}")
+            }
+            Self::ValueTypeMismatch { expected, found } => {
+                write!(
+                    f,
+                    "expected pixel map value type {expected:?}, found {found:?}"
+                )
+            }
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported pixel map file version: {version}")
+            }
+            Self::MissingMigration(version) => {
+                write!(f, "no pixel map migration from version {version}")
+            }
+            Self::InvalidDimensions(size) => {
+                write!(f, "invalid pixel map dimensions: {size}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PixelMapFileError {}
+
+impl<T> PixelMapSchema<T> {
+    /// Create a new [PixelMapSchema].
+    ///
+    /// # Parameters
+    ///
+    /// - `value_type`: A tag identifying the type of pixel values, which is stored in files,
+    ///   and must match on load.
+    /// - `version`: The current version of the pixel values. Files of older versions are
+    ///   upgraded by migrations.
+    #[must_use]
+    pub fn new(value_type: impl Into<String>, version: u32) -> Self {
+        Self {
+            value_type: value_type.into(),
+            version,
+            migrations: BTreeMap::new(),
+        }
+    }
+
+    /// Register a migration that upgrades a file from the given version to the next.
+    /// Migrations are applied in turn, from the version of a loaded file to the version of
+    /// this schema.
+    ///
+    /// # Parameters
+    ///
+    /// - `from_version`: The version of files that the migration applies to.
+    /// - `migration`: A closure that takes a mutable reference to the file, and modifies its
+    ///   values, or any of its header other than the version, which is updated afterward.
+    #[must_use]
+    pub fn with_migration<F>(mut self, from_version: u32, migration: F) -> Self
+    where
+        F: Fn(&mut PixelMapFile<T>) + 'static,
+    {
+        self.migrations.insert(from_version, Box::new(migration));
+        self
+    }
+
+    /// Obtain the value type tag of this schema.
+    #[inline]
+    #[must_use]
+    pub fn value_type(&self) -> &str {
+        &self.value_type
+    }
+
+    /// Obtain the current version of this schema.
+    #[inline]
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl<T> Debug for PixelMapSchema<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PixelMapSchema")
+            .field("value_type", &self.value_type)
+            .field("version", &self.version)
+            .field("migrations", &self.migrations.keys())
+            .finish()
+    }
+}
+
+impl<T: Copy + PartialEq> PixelMapFile<T> {
+    /// Create a [PixelMapFile] of the given [PixelMap], in the current format, for
+    /// serialization.
+    ///
+    /// # Parameters
+    ///
+    /// - `pixel_map`: The map to save.
+    /// - `schema`: The schema whose value type and version are stored in the header.
+    #[must_use]
+    pub fn new<U>(pixel_map: &PixelMap<T, U>, schema: &PixelMapSchema<T>) -> Self
+    where
+        U: Unsigned + NumCast + Copy + Debug,
+    {
+        let mut leaves = Vec::new();
+        pixel_map.visit(|node, sub_rect| leaves.push((*sub_rect, *node.value())));
+        Self {
+            header: PixelMapFileHeader {
+                format: PIXEL_MAP_FILE_FORMAT,
+                value_type: schema.value_type.clone(),
+                version: schema.version,
+                map_size: pixel_map.map_size(),
+                pixel_size: pixel_map.pixel_size(),
+            },
+            body: PixelMapFileBody::V1(leaves),
+        }
+    }
+
+    /// Upgrade this file to the current format and the version of the given schema, applying
+    /// migrations as necessary, and create the [PixelMap] that it describes. All nodes of the
+    /// map are clean.
+    ///
+    /// # Parameters
+    ///
+    /// - `schema`: The schema that the file must conform to once migrated.
+    /// - `value`: The value of pixels not covered by the file, which is not the case for
+    ///   files created by [PixelMapFile::new], but may be of migrated files.
+    ///
+    /// # Returns
+    ///
+    /// An error if the file has an unknown format, a differing value type, a version that
+    /// cannot be migrated to that of the schema, or invalid dimensions.
+    pub fn into_pixel_map<U>(
+        mut self,
+        schema: &PixelMapSchema<T>,
+        value: T,
+    ) -> Result<PixelMap<T, U>, PixelMapFileError>
+    where
+        U: Unsigned + NumCast + Copy + Debug,
+    {
+        self.upgrade(schema)?;
+
+        // Validate the header as per the requirements of PixelMap::new, rather than panic
+        let header = &self.header;
+        let pixel_size = header.pixel_size as u32;
+        let representable = header
+            .map_size
+            .max_element()
+            .checked_next_power_of_two()
+            .and_then(num_traits::cast::<u32, U>)
+            .is_some();
+        if !representable
+            || !header.pixel_size.is_power_of_two()
+            || !header.map_size.x.is_multiple_of(pixel_size)
+            || !header.map_size.y.is_multiple_of(pixel_size)
+        {
+            return Err(PixelMapFileError::InvalidDimensions(header.map_size));
+        }
+        let mut pixel_map = PixelMap::new(&header.map_size, value, header.pixel_size);
+        match self.body {
+            PixelMapFileBody::V1(leaves) => {
+                for (rect, value) in leaves {
+                    pixel_map.draw_rect(&rect, value);
+                }
+            }
+        }
+        pixel_map.clear_dirty(true);
+        Ok(pixel_map)
+    }
+
+    /// Upgrade this file to the current format and the version of the given schema, applying
+    /// migrations as necessary.
+    ///
+    /// # Returns
+    ///
+    /// An error if the file has an unknown format, a differing value type, a version that
+    /// cannot be migrated to that of the schema, or invalid dimensions.
+    pub fn upgrade(&mut self, schema: &PixelMapSchema<T>) -> Result<(), PixelMapFileError> {
+        // Upgrades between formats belong here, as formats are added
+        if self.header.format > PIXEL_MAP_FILE_FORMAT {
+            return Err(PixelMapFileError::UnsupportedFormat(self.header.format));
+        }
+        self.header.format = PIXEL_MAP_FILE_FORMAT;
+
+        if self.header.value_type != schema.value_type {
+            return Err(PixelMapFileError::ValueTypeMismatch {
+                expected: schema.value_type.clone(),
+                found: self.header.value_type.clone(),
+            });
+        }
+        if self.header.version > schema.version {
+            return Err(PixelMapFileError::UnsupportedVersion(self.header.version));
+        }
+        while self.header.version < schema.version {
+            let version = self.header.version;
+            let migration = schema
+                .migrations
+                .get(&version)
+                .ok_or(PixelMapFileError::MissingMigration(version))?;
+            migration(self);
+            self.header.version = version + 1;
+        }
+        Ok(())
+    }
+
+    /// Apply the given function to the value of each rectangle of pixels in this file,
+    /// such as within a migration.
+    pub fn map_values<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> T,
+    {
+        match &mut self.body {
+            PixelMapFileBody::V1(leaves) => {
+                for (_, value) in leaves.iter_mut() {
+                    *value = f(value);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_pixel_map_file() {
+        let mut pm: PixelMap<u8, u16> = PixelMap::new(&UVec2::new(20, 12), 0, 1);
+        pm.draw_rect(&URect::new(2, 2, 9, 7), 1);
+        pm.set_pixel((15, 10), 2);
+
+        // Saved by version 1 of an application
+        let schema_v1 = PixelMapSchema::new("terrain", 1);
+        let ron = ron::to_string(&PixelMapFile::new(&pm, &schema_v1)).unwrap();
+        let file: PixelMapFile<u8> = ron::from_str(&ron).unwrap();
+        assert_eq!(file.header.format, PIXEL_MAP_FILE_FORMAT);
+        assert_eq!(file.header.map_size, pm.map_size());
+
+        // Loaded into a map of another coordinate type
+        let loaded: PixelMap<u8, u32> = file.clone().into_pixel_map(&schema_v1, 0).unwrap();
+        assert_eq!(loaded.map_size(), pm.map_size());
+        pm.visit(|n, r| {
+            loaded.visit_in_rect(r, |m, _| assert_eq!(m.value(), n.value()));
+        });
+        assert!(!loaded.dirty());
+
+        // Loaded by version 3, where values were doubled, then offset
+        let schema_v3 = PixelMapSchema::new("terrain", 3)
+            .with_migration(1, |file| file.map_values(|v| v * 2))
+            .with_migration(2, |file| file.map_values(|v| v + 1));
+        let loaded: PixelMap<u8, u16> = file.clone().into_pixel_map(&schema_v3, 0).unwrap();
+        assert_eq!(loaded.get_pixel((0, 0)), Some(&1));
+        assert_eq!(loaded.get_pixel((3, 3)), Some(&3));
+        assert_eq!(loaded.get_pixel((15, 10)), Some(&5));
+
+        // Errors
+        let schema = PixelMapSchema::new("terrain", 3).with_migration(1, |_| {});
+        assert_eq!(
+            file.clone().into_pixel_map::<u16>(&schema, 0).unwrap_err(),
+            PixelMapFileError::MissingMigration(2)
+        );
+        assert_eq!(
+            file.clone()
+                .into_pixel_map::<u16>(&PixelMapSchema::new("water", 1), 0)
+                .unwrap_err(),
+            PixelMapFileError::ValueTypeMismatch {
+                expected: "water".into(),
+                found: "terrain".into()
+            }
+        );
+        let mut newer = file.clone();
+        newer.header.format = PIXEL_MAP_FILE_FORMAT + 1;
+        assert_eq!(
+            newer.into_pixel_map::<u16>(&schema_v1, 0).unwrap_err(),
+            PixelMapFileError::UnsupportedFormat(PIXEL_MAP_FILE_FORMAT + 1)
+        );
+        let mut big = file.clone();
+        big.header.map_size = UVec2::new(300, 10);
+        assert_eq!(
+            big.into_pixel_map::<u8>(&schema_v1, 0).unwrap_err(),
+            PixelMapFileError::InvalidDimensions(UVec2::new(300, 10))
+        );
+        for (map_size, pixel_size) in [
+            (UVec2::new(u32::MAX, 10), 1),
+            (UVec2::new(20, 12), 0),
+            (UVec2::new(20, 12), 3),
+            (UVec2::new(20, 12), 8),
+        ] {
+            let mut invalid = file.clone();
+            invalid.header.map_size = map_size;
+            invalid.header.pixel_size = pixel_size;
+            assert_eq!(
+                invalid.into_pixel_map::<u32>(&schema_v1, 0).unwrap_err(),
+                PixelMapFileError::InvalidDimensions(map_size)
+            );
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{FromF32, PixelMap, ToF32};
+use bevy_math::{URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// The axis along which a linear gradient varies, as per [PixelMap::draw_rect_gradient].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GradientAxis {
+    /// The gradient varies from left to right.
+    X,
+
+    /// The gradient varies from bottom to top.
+    Y,
+}
+
+impl<T, U> PixelMap<T, U>
+where
+    T: Copy + PartialEq + ToF32 + FromF32,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    /// Fill the given rectangle with a linear gradient between two values, such as to
+    /// initialize a falloff map. Each pixel is assigned the value interpolated at its center,
+    /// converted by [FromF32]. Regions over which the converted value is constant, as is
+    /// common for integer values, are drawn as a whole, rather than pixel by pixel.
+    ///
+    /// The rectangle is expanded to a multiple of the [PixelMap::pixel_size], and cropped to
+    /// the [PixelMap::map_rect]. The gradient spans the expanded rectangle before cropping.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle to fill.
+    /// - `from`: The value at the minimum edge of the rectangle along `axis`.
+    /// - `to`: The value at the maximum edge of the rectangle along `axis`.
+    /// - `axis`: The axis along which the gradient varies.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_rect_gradient(&mut self, rect: &URect, from: T, to: T, axis: GradientAxis) -> bool {
+        let rect = self.align_to_pixels(rect);
+        if rect.is_empty() {
+            return false;
+        }
+        let (min, extent) = match axis {
+            GradientAxis::X => (rect.min.x as f32, rect.width() as f32),
+            GradientAxis::Y => (rect.min.y as f32, rect.height() as f32),
+        };
+        let half = self.pixel_size as f32 / 2.;
+        let (from, to) = (from.to_f32(), to.to_f32());
+        self.draw_graded(&rect, &mut |sub_rect| {
+            let (lo, hi) = match axis {
+                GradientAxis::X => (sub_rect.min.x, sub_rect.max.x),
+                GradientAxis::Y => (sub_rect.min.y, sub_rect.max.y),
+            };
+            let t = |c: f32| (c - min) / extent;
+            let a = T::from_f32(lerp(from, to, t(lo as f32 + half)));
+            let b = T::from_f32(lerp(from, to, t(hi as f32 - half)));
+            (a == b).then_some(a)
+        });
+        true
+    }
+
+    /// Fill the given rectangle with a radial gradient between two values, such as to
+    /// initialize the light falloff around a point. Each pixel is assigned the value
+    /// interpolated by the distance of its center from `center`, converted by [FromF32].
+    /// Regions over which the converted value is constant, such as those beyond `radius`,
+    /// are drawn as a whole, rather than pixel by pixel.
+    ///
+    /// The rectangle is expanded to a multiple of the [PixelMap::pixel_size], and cropped to
+    /// the [PixelMap::map_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle to fill.
+    /// - `center`: The center of the gradient.
+    /// - `radius`: The distance from `center` at which the gradient reaches `to`.
+    /// - `from`: The value at `center`.
+    /// - `to`: The value at, and beyond, `radius`.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_radial_gradient(
+        &mut self,
+        rect: &URect,
+        center: Vec2,
+        radius: f32,
+        from: T,
+        to: T,
+    ) -> bool {
+        let rect = self.align_to_pixels(rect);
+        if rect.is_empty() {
+            return false;
+        }
+        let half = self.pixel_size as f32 / 2.;
+        let (from_f32, to_f32) = (from.to_f32(), to.to_f32());
+        let value = |distance: f32| {
+            if distance >= radius {
+                to
+            } else {
+                T::from_f32(lerp(from_f32, to_f32, distance / radius))
+            }
+        };
+        self.draw_graded(&rect, &mut |sub_rect| {
+            // The distance is monotonic between the nearest and farthest pixel centers
+            let min = sub_rect.min.as_vec2() + half;
+            let max = sub_rect.max.as_vec2() - half;
+            let nearest = center.clamp(min, max).distance(center);
+            let farthest = (center - min).abs().max((center - max).abs()).length();
+            let a = value(nearest);
+            let b = value(farthest);
+            (a == b).then_some(a)
+        });
+        true
+    }
+
+    // Expand the given rectangle to a multiple of the pixel size, cropped to the map.
+    fn align_to_pixels(&self, rect: &URect) -> URect {
+        let pixel_size = self.pixel_size as u32;
+        URect::from_corners(
+            rect.min / pixel_size * pixel_size,
+            (rect.max + pixel_size - 1) / pixel_size * pixel_size,
+        )
+        .intersect(self.map_rect)
+    }
+
+    // Draw the given rectangle by recursively dividing the regions of the quadtree, until
+    // `uniform` returns the single value of the intersection of a region and the rectangle.
+    // A region of a single pixel must be uniform.
+    fn draw_graded<F>(&mut self, rect: &URect, uniform: &mut F)
+    where
+        F: FnMut(&URect) -> Option<T>,
+    {
+        let mut stack = vec![self.region().as_urect()];
+        while let Some(region) = stack.pop() {
+            let sub_rect = region.intersect(*rect);
+            if sub_rect.is_empty() {
+                continue;
+            }
+            match uniform(&sub_rect) {
+                Some(value) => {
+                    self.draw_rect(&sub_rect, value);
+                }
+                None => {
+                    let half = region.size() / 2;
+                    for offset in [UVec2::ZERO, UVec2::X, UVec2::Y, UVec2::ONE] {
+                        let min = region.min + offset * half;
+                        stack.push(URect::from_corners(min, min + half));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t.clamp(0., 1.)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{vec2, URect, UVec2};
+
+    #[test]
+    fn test_draw_rect_gradient() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(16), 0, 1);
+        assert!(pm.draw_rect_gradient(&URect::new(0, 4, 16, 8), 0, 3, GradientAxis::X));
+        for x in 0..16 {
+            let expected = ((x as f32 + 0.5) / 16. * 3.).round() as u8;
+            assert_eq!(pm.get_pixel((x, 5)), Some(&expected), "x: {x}");
+        }
+        assert_eq!(pm.get_pixel((15, 8)), Some(&0));
+        // Columns of equal value are drawn as a whole
+        assert!(pm.stats().leaf_count < 16 * 4);
+
+        let mut pm = PixelMap::<f32, u16>::new(&UVec2::splat(8), 0., 2);
+        assert!(pm.draw_rect_gradient(&pm.map_rect(), 1., 0., GradientAxis::Y));
+        assert_eq!(pm.get_pixel((3, 0)), Some(&0.875));
+        assert_eq!(pm.get_pixel((3, 7)), Some(&0.125));
+
+        assert!(!pm.draw_rect_gradient(&URect::new(8, 8, 9, 9), 1., 0., GradientAxis::Y));
+    }
+
+    #[test]
+    fn test_draw_radial_gradient() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(64), 0, 1);
+        let center = vec2(20., 20.);
+        assert!(pm.draw_radial_gradient(&pm.map_rect(), center, 10., 200, 0));
+        for y in 0..64 {
+            for x in 0..64 {
+                let d = vec2(x as f32 + 0.5, y as f32 + 0.5).distance(center);
+                let expected = if d >= 10. {
+                    0
+                } else {
+                    (200. * (1. - d / 10.)).round() as u8
+                };
+                assert_eq!(pm.get_pixel((x, y)), Some(&expected), "({x}, {y})");
+            }
+        }
+        // Beyond the radius, the map is drawn in large uniform regions
+        assert!(pm.stats().leaf_count < 30 * 30);
+    }
+}
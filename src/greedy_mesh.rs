@@ -0,0 +1,218 @@
+use crate::{urect_points, PNode, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// A triangle mesh, as per [PixelMap::meshes_by_value].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshData {
+    /// The unique vertex points of the mesh.
+    pub vertices: Vec<UVec2>,
+
+    /// The vertex indices of each triangle of the mesh, in counter-clockwise winding.
+    pub indices: Vec<[u32; 3]>,
+}
+
+impl MeshData {
+    // Triangulate each rectangle as a quad, sharing coincident vertices.
+    fn from_rects(rects: &[URect]) -> Self {
+        let mut vertex_map: FxHashMap<UVec2, u32> = FxHashMap::default();
+        let mut mesh = MeshData {
+            vertices: Vec::with_capacity(rects.len() * 4),
+            indices: Vec::with_capacity(rects.len() * 2),
+        };
+        for rect in rects {
+            let i = urect_points(rect).map(|v| {
+                *vertex_map.entry(v).or_insert_with(|| {
+                    mesh.vertices.push(v);
+                    mesh.vertices.len() as u32 - 1
+                })
+            });
+            mesh.indices.push([i[0], i[1], i[2]]);
+            mesh.indices.push([i[0], i[2], i[3]]);
+        }
+        mesh
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Obtain rectangles that cover the leaf nodes accepted by the given `predicate`, merged
+    /// across node boundaries by greedy meshing. Leaf node regions are fragmented by the
+    /// structure of the quadtree, so this typically produces several times fewer rectangles
+    /// than there are matching leaf nodes, such as for rendering or colliders.
+    ///
+    /// Rectangles are grown greedily from the bottom-left, first along the `x` axis and then
+    /// along the `y` axis, so they are maximal in that order but not necessarily the fewest
+    /// possible.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Non-overlapping rectangles, within the given `rect`, ordered by their bottom-left point
+    /// in row-major order.
+    #[must_use]
+    pub fn maximal_rects<F>(&self, rect: &URect, mut predicate: F) -> Vec<URect>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let mut rects = Vec::new();
+        self.visit_in_rect(rect, |node, sub_rect| {
+            if predicate(node, sub_rect) {
+                rects.push(*sub_rect);
+            }
+        });
+        greedy_rects(&rects)
+    }
+}
+
+impl<T, U> PixelMap<T, U>
+where
+    T: Copy + PartialEq + Eq + Hash,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    /// Generate a triangle mesh for each distinct value of the leaf nodes within the given
+    /// rectangle, such as one per material or tile type, in a single traversal. Leaf nodes are
+    /// merged into maximal rectangles per value, as per [PixelMap::maximal_rects], and each
+    /// rectangle is triangulated as a quad.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    ///
+    /// # Returns
+    ///
+    /// A [MeshData] for each value present within the given `rect`.
+    #[must_use]
+    pub fn meshes_by_value(&self, rect: &URect) -> FxHashMap<T, MeshData> {
+        let mut rects: FxHashMap<T, Vec<URect>> = FxHashMap::default();
+        self.visit_in_rect(rect, |node, sub_rect| {
+            rects.entry(*node.value()).or_default().push(*sub_rect);
+        });
+        rects
+            .into_iter()
+            .map(|(value, rects)| (value, MeshData::from_rects(&greedy_rects(&rects))))
+            .collect()
+    }
+}
+
+// Merge non-overlapping rectangles into maximal rectangles, by greedy meshing over a grid
+// compressed to the distinct edges of the given rectangles.
+pub(crate) fn greedy_rects(rects: &[URect]) -> Vec<URect> {
+    if rects.len() < 2 {
+        return rects.to_vec();
+    }
+
+    let edges = |f: fn(&URect) -> [u32; 2]| {
+        let mut edges: Vec<u32> = rects.iter().flat_map(f).collect();
+        edges.sort_unstable();
+        edges.dedup();
+        edges
+    };
+    let xs = edges(|r| [r.min.x, r.max.x]);
+    let ys = edges(|r| [r.min.y, r.max.y]);
+    let index = |edges: &[u32], v: u32| edges.binary_search(&v).unwrap(); // Cannot fail
+
+    let columns = xs.len() - 1;
+    let rows = ys.len() - 1;
+    let mut covered = vec![false; columns * rows];
+    for r in rects {
+        for y in index(&ys, r.min.y)..index(&ys, r.max.y) {
+            for x in index(&xs, r.min.x)..index(&xs, r.max.x) {
+                covered[y * columns + x] = true;
+            }
+        }
+    }
+
+    let mut merged = Vec::new();
+    for y in 0..rows {
+        let mut x = 0;
+        while x < columns {
+            if !covered[y * columns + x] {
+                x += 1;
+                continue;
+            }
+            let mut width = 1;
+            while x + width < columns && covered[y * columns + x + width] {
+                width += 1;
+            }
+            let mut height = 1;
+            while y + height < rows && (x..x + width).all(|x| covered[(y + height) * columns + x]) {
+                height += 1;
+            }
+            for yy in y..y + height {
+                covered[yy * columns + x..yy * columns + x + width].fill(false);
+            }
+            merged.push(URect::new(xs[x], ys[y], xs[x + width], ys[y + height]));
+            x += width;
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_maximal_rects() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(1, 0, 13, 5), true);
+        let mut leaves = 0;
+        pm.visit(|n, _| leaves += *n.value() as u32);
+        assert!(leaves > 1);
+
+        let rects = pm.maximal_rects(&pm.map_rect(), |n, _| *n.value());
+        assert_eq!(rects, vec![URect::new(1, 0, 13, 5)]);
+
+        // An L-shape, clipped to the given rectangle
+        pm.draw_rect(&URect::new(1, 5, 4, 11), true);
+        let rects = pm.maximal_rects(&URect::new(0, 0, 16, 8), |n, _| *n.value());
+        assert_eq!(rects, vec![URect::new(1, 0, 13, 5), URect::new(1, 5, 4, 8)]);
+
+        let rects = pm.maximal_rects(&pm.map_rect(), |n, _| !*n.value());
+        let area: u32 = rects.iter().map(|r| r.width() * r.height()).sum();
+        assert_eq!(area, 256 - 60 - 18);
+        assert!(rects.len() < 8);
+    }
+
+    #[test]
+    fn test_meshes_by_value() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 16, 4), 1);
+        pm.draw_rect(&URect::new(4, 8, 6, 10), 2);
+
+        let meshes = pm.meshes_by_value(&pm.map_rect());
+        assert_eq!(meshes.len(), 3);
+
+        let floor = &meshes[&1];
+        assert_eq!(floor.indices.len(), 2);
+        assert_eq!(
+            floor.vertices,
+            vec![
+                UVec2::new(0, 0),
+                UVec2::new(16, 0),
+                UVec2::new(16, 4),
+                UVec2::new(0, 4)
+            ]
+        );
+        assert_eq!(floor.indices, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(meshes[&2].indices.len(), 2);
+
+        // The remaining area, with vertices shared between quads
+        let empty = &meshes[&0];
+        assert_eq!(empty.indices.len(), 8);
+        assert!(empty.vertices.len() < empty.indices.len() * 3);
+
+        let meshes = pm.meshes_by_value(&URect::new(0, 0, 8, 2));
+        assert_eq!(meshes.len(), 1);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::PixelMap;
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+use std::ops::ControlFlow;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Create a new [PixelMap] from a buffer of pixel values, such as the decoded pixels of
+    /// an image. Horizontal runs of equal values are drawn as a whole, rather than pixel by
+    /// pixel. All nodes of the new map are dirty.
+    ///
+    /// # Parameters
+    ///
+    /// - `dimensions`: The size of the new [PixelMap].
+    /// - `buffer`: The pixel values, in row-major order, where row `y` starts at index
+    ///   `y * dimensions.x`. Where `pixel_size` is greater than `1`, only the value at the
+    ///   bottom-left of each pixel is read.
+    /// - `pixel_size`: The pixel size of the new [PixelMap]. Must be a power of two.
+    ///
+    /// # Panics
+    ///
+    /// If the length of `buffer` differs from the area of `dimensions`, if `dimensions` is
+    /// zero on either axis, or as per [PixelMap::new].
+    #[must_use]
+    pub fn from_buffer(dimensions: &UVec2, buffer: &[T], pixel_size: u8) -> Self {
+        let result = Self::from_buffer_with_progress(dimensions, buffer, pixel_size, |_| {
+            ControlFlow::Continue(())
+        });
+        result.unwrap()
+    }
+
+    /// Create a new [PixelMap] from a buffer of pixel values, as per [PixelMap::from_buffer],
+    /// reporting progress after each row of pixels, such as to update a progress bar while
+    /// importing a large image. The import may be cancelled by the progress callback.
+    ///
+    /// # Parameters
+    ///
+    /// - `dimensions`: The size of the new [PixelMap].
+    /// - `buffer`: The pixel values, as per [PixelMap::from_buffer].
+    /// - `pixel_size`: The pixel size of the new [PixelMap]. Must be a power of two.
+    /// - `progress`: A closure that takes the fraction of the buffer imported so far, from
+    ///   `0.0` to `1.0`. It returns [ControlFlow::Break] to cancel the import, or
+    ///   [ControlFlow::Continue] to proceed.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the import was cancelled. Otherwise, `Some` of the new [PixelMap].
+    ///
+    /// # Panics
+    ///
+    /// If the length of `buffer` differs from the area of `dimensions`, if `dimensions` is
+    /// zero on either axis, or as per [PixelMap::new].
+    pub fn from_buffer_with_progress<F>(
+        dimensions: &UVec2,
+        buffer: &[T],
+        pixel_size: u8,
+        mut progress: F,
+    ) -> Option<Self>
+    where
+        F: FnMut(f32) -> ControlFlow<()>,
+    {
+        let width = dimensions.x as usize;
+        assert_eq!(
+            buffer.len(),
+            width * dimensions.y as usize,
+            "buffer length must match the area of dimensions"
+        );
+        let first = buffer.first().expect("dimensions must be non-zero");
+        let mut pixel_map = Self::new(dimensions, *first, pixel_size);
+        let step = pixel_size as u32;
+
+        for y in (0..dimensions.y).step_by(step as usize) {
+            let row = &buffer[y as usize * width..(y as usize + 1) * width];
+
+            // Draw runs of equal values, skipping those of the initial value
+            let mut x = 0;
+            while x < dimensions.x {
+                let value = row[x as usize];
+                let start = x;
+                x += step;
+                while x < dimensions.x && row[x as usize] == value {
+                    x += step;
+                }
+                if value != *first {
+                    pixel_map.draw_rect(&URect::new(start, y, x, y + step), value);
+                }
+            }
+
+            if progress((y + step) as f32 / dimensions.y as f32).is_break() {
+                return None;
+            }
+        }
+        Some(pixel_map)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::UVec2;
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn test_from_buffer() {
+        #[rustfmt::skip]
+        let buffer = [
+            0, 0, 1, 1,
+            0, 2, 2, 1,
+            0, 0, 0, 0,
+            3, 0, 0, 0,
+        ];
+        let pm = PixelMap::<u8, u16>::from_buffer(&UVec2::splat(4), &buffer, 1);
+        for (i, value) in buffer.iter().enumerate() {
+            assert_eq!(pm.get_pixel((i as u32 % 4, i as u32 / 4)), Some(value));
+        }
+        assert!(pm.dirty());
+
+        let pm = PixelMap::<u8, u16>::from_buffer(&UVec2::splat(4), &buffer, 2);
+        assert_eq!(pm.get_pixel((3, 1)), Some(&1));
+        assert_eq!(pm.get_pixel((0, 3)), Some(&0));
+    }
+
+    #[test]
+    fn test_from_buffer_with_progress() {
+        let buffer = vec![1u8; 16 * 8];
+        let mut fractions = Vec::new();
+        let pm = PixelMap::<u8, u16>::from_buffer_with_progress(
+            &UVec2::new(16, 8),
+            &buffer,
+            2,
+            |fraction| {
+                fractions.push(fraction);
+                ControlFlow::Continue(())
+            },
+        );
+        assert!(pm.is_some());
+        assert_eq!(fractions, vec![0.25, 0.5, 0.75, 1.0]);
+
+        let pm = PixelMap::<u8, u16>::from_buffer_with_progress(
+            &UVec2::new(16, 8),
+            &buffer,
+            1,
+            |fraction| {
+                if fraction < 0.5 {
+                    ControlFlow::Continue(())
+                } else {
+                    ControlFlow::Break(())
+                }
+            },
+        );
+        assert!(pm.is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_buffer_mismatched() {
+        let _ = PixelMap::<u8, u16>::from_buffer(&UVec2::splat(4), &[0; 8], 1);
+    }
+}
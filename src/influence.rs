@@ -0,0 +1,160 @@
+use crate::{ICircle, NeighborOrientation, PixelMap};
+use bevy_math::{uvec2, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// Operations that treat a [PixelMap] of `f32` values as an influence map, such as those used
+/// by game AI to represent the spread of threat or control.
+impl<U: Unsigned + NumCast + Copy + Debug> PixelMap<f32, U> {
+    /// Add influence to the pixels within the given radius of a point, diminishing linearly
+    /// with distance from the point.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The center of the splat.
+    /// - `radius`: The radius of the splat. Pixels at this distance receive a small fraction
+    ///   of `strength`.
+    /// - `strength`: The influence added at the center of the splat.
+    ///
+    /// # Returns
+    ///
+    /// If the splat overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn splat(&mut self, point: UVec2, radius: u32, strength: f32) -> bool {
+        let circle = ICircle::new(point.as_ivec2(), radius);
+        let extent = (radius + 1) as f32;
+        let mut changed = false;
+        for p in circle.unsigned_pixels() {
+            if let Some(value) = self.get_pixel(p).copied() {
+                let falloff = 1. - p.as_vec2().distance(point.as_vec2()) / extent;
+                changed |= self.set_pixel(p, value + strength * falloff);
+            }
+        }
+        changed
+    }
+
+    /// Spread influence to neighboring pixels, such that each pixel takes the greater of its own
+    /// value, and the values of its four neighbors multiplied by `decay`. This is repeated for
+    /// the given number of iterations, spreading influence by one pixel per iteration.
+    ///
+    /// Only pixels on the boundaries of leaf nodes can change in an iteration, as the neighbors
+    /// of interior pixels share their value. So, large uniform leaf nodes are not examined
+    /// pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `decay`: The factor applied to influence as it spreads to a neighboring pixel,
+    ///   typically in the range `0..1`.
+    /// - `iterations`: The number of times to spread influence.
+    pub fn propagate(&mut self, decay: f32, iterations: u32) {
+        let map_rect = self.map_rect();
+        let step = self.pixel_size() as u32;
+        for _ in 0..iterations {
+            // The greatest influence arriving at each boundary pixel that exceeds its current value
+            let mut updates: FxHashMap<UVec2, f32> = FxHashMap::default();
+            let mut offer = |point: UVec2, current: f32, value: f32| {
+                if value > current {
+                    let best = updates.entry(point).or_insert(value);
+                    *best = best.max(value);
+                }
+            };
+
+            self.root.visit_neighbor_pairs_face(
+                &map_rect,
+                &mut |or, a, a_rect, b, b_rect| {
+                    // Leaf nodes of the root region outside the map hold no influence
+                    if a_rect.is_empty() || b_rect.is_empty() {
+                        return;
+                    }
+                    let (a_value, b_value) = (*a.value(), *b.value());
+                    match or {
+                        NeighborOrientation::Horizontal => {
+                            let min_y = a_rect.min.y.max(b_rect.min.y);
+                            let max_y = a_rect.max.y.min(b_rect.max.y);
+                            for y in (min_y..max_y).step_by(step as usize) {
+                                offer(uvec2(b_rect.min.x, y), b_value, a_value * decay);
+                                offer(uvec2(a_rect.max.x - step, y), a_value, b_value * decay);
+                            }
+                        }
+                        NeighborOrientation::Vertical => {
+                            let min_x = a_rect.min.x.max(b_rect.min.x);
+                            let max_x = a_rect.max.x.min(b_rect.max.x);
+                            for x in (min_x..max_x).step_by(step as usize) {
+                                offer(uvec2(x, b_rect.min.y), b_value, a_value * decay);
+                                offer(uvec2(x, a_rect.max.y - step), a_value, b_value * decay);
+                            }
+                        }
+                    }
+                },
+                &mut 0,
+            );
+
+            if updates.is_empty() {
+                break;
+            }
+            for (point, value) in updates {
+                self.set_pixel(point, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::UVec2;
+
+    #[test]
+    fn test_splat() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(16), 0., 1);
+        assert!(pm.splat(UVec2::new(8, 8), 3, 4.));
+        assert_eq!(pm.get_pixel((8, 8)), Some(&4.));
+        assert_eq!(pm.get_pixel((10, 8)), Some(&2.));
+        assert_eq!(pm.get_pixel((12, 8)), Some(&0.));
+
+        // Additive
+        pm.splat(UVec2::new(8, 8), 3, 4.);
+        assert_eq!(pm.get_pixel((8, 8)), Some(&8.));
+    }
+
+    #[test]
+    fn test_propagate() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(16), 0., 1);
+        pm.set_pixel((5, 5), 8.);
+        pm.propagate(0.5, 2);
+
+        for y in 0..16 {
+            for x in 0..16 {
+                let d = (x as i32 - 5).abs() + (y as i32 - 5).abs();
+                let expected = match d {
+                    0 => 8.,
+                    1 => 4.,
+                    2 => 2.,
+                    _ => 0.,
+                };
+                assert_eq!(pm.get_pixel((x, y)), Some(&expected), "({}, {})", x, y);
+            }
+        }
+
+        // Influence never decreases
+        pm.propagate(0.5, 1);
+        assert_eq!(pm.get_pixel((5, 5)), Some(&8.));
+        assert_eq!(pm.get_pixel((5, 8)), Some(&1.));
+    }
+
+    #[test]
+    fn test_propagate_non_square() {
+        // Pixels outside the map retain the initial value, but must not spread it
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::new(20, 12), 1., 1);
+        pm.draw_rect(&pm.map_rect(), 0.);
+        pm.propagate(0.9, 4);
+        let mut total = 0.;
+        pm.visit(|n, r| total += *n.value() * (r.width() * r.height()) as f32);
+        assert_eq!(total, 0.);
+
+        pm.set_pixel((19, 11), 8.);
+        pm.propagate(0.5, 1);
+        assert_eq!(pm.get_pixel((18, 11)), Some(&4.));
+        assert_eq!(pm.get_pixel((19, 10)), Some(&4.));
+    }
+}
@@ -1,7 +1,6 @@
 use crate::{distance_to_line, ILine};
-use bevy_math::IVec2;
-use fxhash::{FxBuildHasher, FxHasher};
-use num_traits::Zero;
+use bevy_math::{IVec2, Vec2};
+use fxhash::{FxBuildHasher, FxHashMap, FxHashSet, FxHasher};
 use std::collections::HashMap;
 use std::hash::BuildHasherDefault;
 
@@ -27,13 +26,18 @@ impl FragmentAccumulator {
         }
     }
 
+    /// Take the accumulated lines, leaving this accumulator empty with its allocations
+    /// retained for reuse.
     #[inline]
-    pub(super) fn result(self) -> Vec<IsoLine> {
+    pub(super) fn result(&mut self) -> Vec<IsoLine> {
         assert_eq!(self.by_start.len(), self.fragments.len());
         assert_eq!(self.by_end.len(), self.fragments.len());
+        self.next_key = 0;
+        self.by_start.clear();
+        self.by_end.clear();
         self.fragments
-            .into_values()
-            .map(|frag| IsoLine { points: frag })
+            .drain()
+            .map(|(_, frag)| IsoLine { points: frag })
             .collect()
     }
 
@@ -132,6 +136,231 @@ impl IsoLine {
         let points = ramer_douglas_peucker(&self.points, epsilon);
         IsoLine { points }
     }
+
+    /// Calculate the signed area enclosed by this [IsoLine], by the shoelace formula.
+    /// The area is positive when the points wind counter-clockwise, with the `y` axis
+    /// pointing up, and negative when they wind clockwise. Zero if the line is not closed.
+    #[must_use]
+    pub fn signed_area(&self) -> f32 {
+        if !self.is_closed() {
+            return 0.0;
+        }
+        let twice_area: i64 = self
+            .points
+            .windows(2)
+            .map(|w| w[0].x as i64 * w[1].y as i64 - w[1].x as i64 * w[0].y as i64)
+            .sum();
+        twice_area as f32 / 2.0
+    }
+
+    /// Determine if the given point is enclosed by this [IsoLine], by the even-odd rule.
+    /// Always `false` if the line is not closed.
+    #[must_use]
+    pub fn encloses(&self, point: Vec2) -> bool {
+        if !self.is_closed() {
+            return false;
+        }
+        let mut inside = false;
+        for w in self.points.windows(2) {
+            let a = w[0].as_vec2();
+            let b = w[1].as_vec2();
+            if (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+            {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    // Reverse the winding of this line, if necessary, so that its signed area has the given sign.
+    fn wind(mut self, counter_clockwise: bool) -> IsoLine {
+        if (self.signed_area() > 0.0) != counter_clockwise {
+            self.points.reverse();
+        }
+        self
+    }
+}
+
+/// A polygon formed from closed [IsoLine]s, being an exterior ring and any holes within it.
+/// The exterior ring winds counter-clockwise, and holes wind clockwise, with the `y` axis
+/// pointing up.
+#[derive(Clone, Debug, Default)]
+pub struct ContourPolygon {
+    pub exterior: IsoLine,
+    pub holes: Vec<IsoLine>,
+}
+
+/// Group closed [IsoLine]s, such as those produced by [crate::PixelMap::contour], into polygons
+/// with holes. A line enclosed by an odd number of other lines is a hole of the innermost line
+/// enclosing it. Islands within holes form polygons of their own.
+///
+/// # Parameters
+///
+/// - `lines`: The lines to group. They must not cross each other. Lines that are not
+///   closed are ignored.
+///
+/// # Returns
+///
+/// The polygons, ordered as per the first occurrence of their exterior ring in `lines`.
+#[must_use]
+pub fn contour_polygons(lines: &[IsoLine]) -> Vec<ContourPolygon> {
+    let rings: Vec<&IsoLine> = lines
+        .iter()
+        .filter(|l| l.len() > 3 && l.is_closed())
+        .collect();
+
+    // Lines do not cross, so the midpoint of any segment is inside the same rings as the whole
+    let parents: Vec<Vec<usize>> = rings
+        .iter()
+        .enumerate()
+        .map(|(i, ring)| {
+            let probe = (ring.points[0] + ring.points[1]).as_vec2() / 2.0;
+            (0..rings.len())
+                .filter(|j| *j != i && rings[*j].encloses(probe))
+                .collect()
+        })
+        .collect();
+
+    let mut polygons = Vec::new();
+    let mut polygon_of_ring: Vec<Option<usize>> = vec![None; rings.len()];
+    for (i, ring) in rings.iter().enumerate() {
+        if parents[i].len().is_multiple_of(2) {
+            polygon_of_ring[i] = Some(polygons.len());
+            polygons.push(ContourPolygon {
+                exterior: (*ring).clone().wind(true),
+                holes: Vec::new(),
+            });
+        }
+    }
+    for (i, ring) in rings.iter().enumerate() {
+        if !parents[i].len().is_multiple_of(2) {
+            // The innermost enclosing ring is the one enclosed by all the others
+            let depth = parents[i].len() - 1;
+            let parent = parents[i].iter().find(|j| parents[**j].len() == depth);
+            if let Some(polygon) = parent.and_then(|j| polygon_of_ring[*j]) {
+                polygons[polygon].holes.push((*ring).clone().wind(false));
+            }
+        }
+    }
+    polygons
+}
+
+/// Simplify a set of [IsoLine]s, such as the borders of adjacent regions, such that edges
+/// shared by more than one line are simplified identically in each. Applying
+/// [IsoLine::simplify] to each line independently can otherwise produce gaps and overlaps
+/// between lines that share a boundary.
+///
+/// Lines are divided at anchor points, being the ends of open lines, and points where lines
+/// begin or stop sharing edges, or where more than two lines meet. Anchor points are always
+/// retained, and the runs of points between them are simplified by Ramer-Douglas-Peucker in a
+/// consistent direction, regardless of the direction of each line.
+///
+/// # Parameters
+///
+/// - `lines`: The lines to simplify.
+/// - `epsilon`: The tolerance with which to simplify, as per [IsoLine::simplify].
+///
+/// # Returns
+///
+/// The simplified lines, in the order given. Closed lines that share no edges may begin at a
+/// different point than given.
+#[must_use]
+pub fn simplify_shared(lines: &[IsoLine], epsilon: f32) -> Vec<IsoLine> {
+    let edge_key = |a: IVec2, b: IVec2| {
+        if (a.x, a.y) < (b.x, b.y) {
+            (a, b)
+        } else {
+            (b, a)
+        }
+    };
+
+    // The number of lines having each point, and each edge
+    let mut point_counts: FxHashMap<IVec2, u32> = FxHashMap::default();
+    let mut edge_counts: FxHashMap<(IVec2, IVec2), u32> = FxHashMap::default();
+    for line in lines {
+        let points: FxHashSet<IVec2> = line.points.iter().copied().collect();
+        for point in points {
+            *point_counts.entry(point).or_default() += 1;
+        }
+        let edges: FxHashSet<(IVec2, IVec2)> = line
+            .points
+            .windows(2)
+            .map(|w| edge_key(w[0], w[1]))
+            .collect();
+        for edge in edges {
+            *edge_counts.entry(edge).or_default() += 1;
+        }
+    }
+
+    lines
+        .iter()
+        .map(|line| {
+            if line.len() < 3 {
+                return line.clone();
+            }
+            let closed = line.is_closed();
+            let ring = if closed {
+                &line.points[..line.len() - 1]
+            } else {
+                &line.points[..]
+            };
+            let n = ring.len();
+            let is_anchor = |i: usize| {
+                if !closed && (i == 0 || i == n - 1) {
+                    return true;
+                }
+                let point = ring[i];
+                let prev = edge_counts[&edge_key(ring[(i + n - 1) % n], point)];
+                let next = edge_counts[&edge_key(point, ring[(i + 1) % n])];
+                point_counts[&point] > 1 && (prev != next || point_counts[&point] > prev.max(next))
+            };
+
+            // Rotate closed lines to begin at an anchor, or at their least point if none
+            let first = (0..n).find(|i| is_anchor(*i));
+            let (points, anchors): (Vec<IVec2>, Vec<usize>) = if closed {
+                let first = first
+                    .unwrap_or_else(|| (0..n).min_by_key(|i| (ring[*i].x, ring[*i].y)).unwrap());
+                let points: Vec<IVec2> = (0..=n).map(|i| ring[(first + i) % n]).collect();
+                let mut anchors: Vec<usize> =
+                    (0..n).filter(|i| is_anchor((first + i) % n)).collect();
+                if anchors.first() != Some(&0) {
+                    anchors.insert(0, 0);
+                }
+                anchors.push(n);
+                (points, anchors)
+            } else {
+                (ring.to_vec(), (0..n).filter(|i| is_anchor(*i)).collect())
+            };
+
+            let mut result = vec![points[0]];
+            for w in anchors.windows(2) {
+                let run = &points[w[0]..=w[1]];
+                result.extend_from_slice(&simplify_run(run, epsilon)[1..]);
+            }
+            IsoLine { points: result }
+        })
+        .collect()
+}
+
+// Simplify a run of points in a direction determined by the points alone, such that
+// the same run given in reverse produces the same simplified points.
+fn simplify_run(run: &[IVec2], epsilon: f32) -> Vec<IVec2> {
+    let key = |p: &IVec2| (p.x, p.y);
+    let (first, last) = if run.first() == run.last() && run.len() > 2 {
+        (&run[1], &run[run.len() - 2])
+    } else {
+        (&run[0], &run[run.len() - 1])
+    };
+    if key(first) <= key(last) {
+        ramer_douglas_peucker(run, epsilon)
+    } else {
+        let mut reversed = run.to_vec();
+        reversed.reverse();
+        let mut points = ramer_douglas_peucker(&reversed, epsilon);
+        points.reverse();
+        points
+    }
 }
 
 // Adapted from: https://git.sr.ht/~halzy/ramer_douglas_peucker
@@ -162,7 +391,6 @@ fn ramer_douglas_peucker(points: &[IVec2], epsilon: f32) -> Vec<IVec2> {
     let mut start: IVec2;
     let mut end: IVec2;
     let mut division_point: usize;
-    let mut should_keep_second_half: bool;
 
     while let Some(range) = ranges.pop() {
         range_start = *range.start();
@@ -174,16 +402,8 @@ fn ramer_douglas_peucker(points: &[IVec2], epsilon: f32) -> Vec<IVec2> {
         let (max_distance, max_index) = points[range_start + 1..range_end].iter().enumerate().fold(
             (0_f32, 0),
             move |(max_distance, max_index), (index, point)| {
-                let distance = {
-                    let mut d =
-                        distance_to_line(point.as_vec2(), &[start.as_vec2(), end.as_vec2()]);
-                    if d.is_zero() {
-                        let base = (point.x - start.x) as f32;
-                        let height = (point.y - start.y) as f32;
-                        d = base.hypot(height);
-                    }
-                    d
-                };
+                // The distance is to the segment, so collinear points beyond it are retained
+                let distance = distance_to_line(point.as_vec2(), &[start.as_vec2(), end.as_vec2()]);
 
                 if distance > max_distance {
                     // new max distance!
@@ -202,22 +422,9 @@ fn ramer_douglas_peucker(points: &[IVec2], epsilon: f32) -> Vec<IVec2> {
             // the space of the whole vector and not the range
             division_point = range_start + max_index;
 
-            // Process the second one first to maintain the stack
-            // The order of ranges and results are opposite, hence the awkwardness
-            should_keep_second_half = division_point - range_start > 2;
-            if should_keep_second_half {
-                ranges.push(division_point..=range_end);
-            }
-
-            if division_point - range_start > 2 {
-                ranges.push(range_start..=division_point);
-            } else {
-                results.push(points[division_point]);
-            }
-
-            if !should_keep_second_half {
-                results.push(end);
-            }
+            // Process the second one last to maintain the order of results
+            ranges.push(division_point..=range_end);
+            ranges.push(range_start..=division_point);
         } else {
             // Keep the end point for the results
             results.push(end);
@@ -226,3 +433,100 @@ fn ramer_douglas_peucker(points: &[IVec2], epsilon: f32) -> Vec<IVec2> {
 
     results
 }
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{ivec2, vec2, URect, UVec2};
+
+    #[test]
+    fn test_signed_area() {
+        let square = IsoLine {
+            points: vec![
+                ivec2(0, 0),
+                ivec2(2, 0),
+                ivec2(2, 2),
+                ivec2(0, 2),
+                ivec2(0, 0),
+            ],
+        };
+        assert_eq!(square.signed_area(), 4.0);
+        assert!(square.encloses(vec2(1.0, 1.0)));
+        assert!(!square.encloses(vec2(3.0, 1.0)));
+
+        let mut reversed = square.clone();
+        reversed.points.reverse();
+        assert_eq!(reversed.signed_area(), -4.0);
+
+        let open = IsoLine {
+            points: vec![ivec2(0, 0), ivec2(2, 0), ivec2(2, 2)],
+        };
+        assert_eq!(open.signed_area(), 0.0);
+    }
+
+    #[test]
+    fn test_contour_polygons() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(1, 1, 10, 10), true);
+        pm.draw_rect(&URect::new(3, 3, 8, 8), false);
+        pm.draw_rect(&URect::new(5, 5, 6, 6), true);
+        pm.draw_rect(&URect::new(12, 12, 14, 14), true);
+
+        let lines = pm.contour(&pm.map_rect(), |n, _| *n.value());
+        assert_eq!(lines.len(), 4);
+        let mut polygons = contour_polygons(&lines);
+        polygons.sort_by_key(|p| p.exterior.signed_area() as i32);
+        assert_eq!(polygons.len(), 3);
+        assert_eq!(polygons[0].exterior.signed_area(), 1.0);
+        assert!(polygons[0].holes.is_empty());
+        assert_eq!(polygons[1].exterior.signed_area(), 4.0);
+        assert_eq!(polygons[2].exterior.signed_area(), 81.0);
+        assert_eq!(polygons[2].holes.len(), 1);
+        assert_eq!(polygons[2].holes[0].signed_area(), -25.0);
+    }
+
+    #[test]
+    fn test_simplify_shared() {
+        // Two regions sharing a jagged border, given in opposite directions
+        let border = [
+            ivec2(4, 0),
+            ivec2(4, 2),
+            ivec2(5, 3),
+            ivec2(4, 4),
+            ivec2(3, 5),
+            ivec2(4, 6),
+            ivec2(4, 8),
+        ];
+        let mut left = border.to_vec();
+        left.extend([ivec2(0, 8), ivec2(0, 0), ivec2(4, 0)]);
+        let mut right: Vec<_> = border.iter().rev().copied().collect();
+        right.extend([ivec2(8, 0), ivec2(8, 8)]);
+        // Begin the right region at a different point
+        right.rotate_left(3);
+        right.push(right[0]);
+
+        let lines = [IsoLine { points: left }, IsoLine { points: right }];
+        for epsilon in [0.5, 0.9, 1.5] {
+            let simplified = simplify_shared(&lines, epsilon);
+            assert_eq!(simplified.len(), 2);
+            assert!(simplified.iter().all(|l| l.is_closed()));
+            let shared = |line: &IsoLine| {
+                let mut points: Vec<_> = line
+                    .points
+                    .iter()
+                    .filter(|p| (3..=5).contains(&p.x))
+                    .map(|p| (p.x, p.y))
+                    .collect();
+                points.sort();
+                points.dedup();
+                points
+            };
+            assert_eq!(shared(&simplified[0]), shared(&simplified[1]));
+            // The corners of each region are retained
+            assert!(simplified[0].points.contains(&ivec2(0, 0)));
+            assert!(simplified[1].points.contains(&ivec2(8, 8)));
+        }
+        let simplified = simplify_shared(&lines, 1.5);
+        assert_eq!(simplified[0].len(), 5);
+    }
+}
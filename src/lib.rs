@@ -8,22 +8,95 @@
 //! A type-generic value is stored for each pixel, but storage is optimized for regions of
 //! pixels having the same value (as per the function of a quadtree).
 
+mod active;
+#[cfg(feature = "rkyv")]
+mod archive;
+mod broadphase;
+mod budget;
+mod cancel;
+mod coalesce;
+mod collision;
+mod compress;
+mod coverage;
+mod debug_dump;
 mod direction;
+#[cfg(feature = "export")]
+mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "serialize")]
+mod file;
+mod gradient;
+mod greedy_mesh;
+mod import;
+mod influence;
 mod isocontour;
+mod linear_tree;
+mod map_ops;
 mod math;
+mod mesh_cache;
+mod metadata;
 mod nearest_neighbor;
+mod neighborhood;
 mod node_path;
+mod occupancy;
+mod outline;
+mod packed;
 pub mod pathfinding;
+mod pixel_iter;
 mod pixel_map;
+mod pixel_value;
 mod pnode;
+mod polygon_ops;
+mod pool;
+pub mod prelude;
+mod preview;
+#[cfg(feature = "python")]
+pub mod python;
 mod quadrant;
 mod ray_cast;
+mod reachability;
+pub mod rect_math;
 mod region;
+mod sampling;
+mod scratch;
+mod segmentation;
+#[cfg(feature = "serialize")]
+mod serialize;
 mod shapes;
+#[cfg(feature = "simulation")]
+mod simulation;
+mod stamp;
+mod step;
+mod subtree;
+mod traversal;
+#[cfg(feature = "traversal_stats")]
+mod traversal_stats;
+mod upload_plan;
+mod view;
+mod visitor;
+mod watch;
+mod world;
+mod wrap;
 
 pub use self::{
-    direction::*, isocontour::*, math::*, node_path::*, pixel_map::*, pnode::*, quadrant::*,
-    ray_cast::*, region::*, shapes::*,
+    broadphase::*, budget::*, cancel::*, collision::*, debug_dump::*, direction::*, gradient::*,
+    greedy_mesh::*, isocontour::*, linear_tree::*, map_ops::*, math::*, mesh_cache::*, metadata::*,
+    node_path::*, occupancy::*, packed::*, pixel_iter::*, pixel_map::*, pixel_value::*, pnode::*,
+    polygon_ops::*, preview::*, quadrant::*, ray_cast::*, rect_math::*, region::*, sampling::*,
+    scratch::*, shapes::*, stamp::*, traversal::*, upload_plan::*, view::*, visitor::*, watch::*,
+    world::*,
 };
 
+#[cfg(feature = "export")]
+pub use self::export::*;
+
+#[cfg(feature = "serialize")]
+pub use self::{file::*, serialize::*};
+
+#[cfg(feature = "traversal_stats")]
+pub use self::traversal_stats::*;
+
 pub use bevy_math;
+#[cfg(feature = "rkyv")]
+pub use rkyv;
@@ -0,0 +1,187 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{PNode, PixelMap};
+use bevy_math::UVec2;
+use num_traits::{NumCast, Unsigned};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+/// A node of a [LinearQuadtree], being four 32-bit words without pointers, such that a slice
+/// of nodes may be uploaded to a GPU buffer as-is.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct GpuNode {
+    /// The x coordinate of the bottom left of the region of the node.
+    pub x: u32,
+
+    /// The y coordinate of the bottom left of the region of the node.
+    pub y: u32,
+
+    /// The width and height of the region of the node.
+    pub size: u32,
+
+    /// For a branch node, the index of the first of its four children, which are consecutive
+    /// in [Quadrant](crate::Quadrant) order. For a leaf node, the index of its value. The upper
+    /// bits hold the [GpuNode::LEAF] and [GpuNode::DIRTY] flags.
+    pub data: u32,
+}
+
+impl GpuNode {
+    /// The flag of [GpuNode::data] denoting a leaf node.
+    pub const LEAF: u32 = 1 << 31;
+
+    /// The flag of [GpuNode::data] denoting a dirty node.
+    pub const DIRTY: u32 = 1 << 30;
+
+    /// The mask of [GpuNode::data] holding the child or value index.
+    pub const INDEX_MASK: u32 = Self::DIRTY - 1;
+
+    /// Determine if this is a leaf node.
+    #[inline]
+    #[must_use]
+    pub fn is_leaf(&self) -> bool {
+        self.data & Self::LEAF != 0
+    }
+
+    /// Determine if this node is dirty.
+    #[inline]
+    #[must_use]
+    pub fn is_dirty(&self) -> bool {
+        self.data & Self::DIRTY != 0
+    }
+
+    /// Obtain the index of the first child of this branch node, or the value of this leaf
+    /// node.
+    #[inline]
+    #[must_use]
+    pub fn index(&self) -> usize {
+        (self.data & Self::INDEX_MASK) as usize
+    }
+}
+
+/// A flat, pointer-free encoding of the quadtree of a [PixelMap], as produced by
+/// [PixelMap::to_linear_tree], suitable for upload to GPU buffers for the traversal of the
+/// tree by a shader, such as to raymarch or render huge maps without rasterizing them.
+///
+/// Nodes are in breadth-first order, with the root node first.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearQuadtree<T> {
+    /// The nodes of the tree.
+    pub nodes: Vec<GpuNode>,
+
+    /// The values of the leaf nodes, referenced by [GpuNode::index].
+    pub values: Vec<T>,
+}
+
+impl<T> LinearQuadtree<T> {
+    /// Obtain the value at the given point, by descending the tree as a shader would.
+    /// `None` if the point is outside the root node.
+    #[must_use]
+    pub fn find(&self, point: UVec2) -> Option<&T> {
+        let mut node = self.nodes.first()?;
+        let min = UVec2::new(node.x, node.y);
+        if point.cmplt(min).any() || point.cmpge(min + node.size).any() {
+            return None;
+        }
+        while !node.is_leaf() {
+            let half = node.size / 2;
+            let right = (point.x >= node.x + half) as usize;
+            let top = (point.y >= node.y + half) as usize;
+            // Quadrant order: bottom left, bottom right, top right, top left
+            let quadrant = [[0, 1], [3, 2]][top][right];
+            node = &self.nodes[node.index() + quadrant];
+        }
+        self.values.get(node.index())
+    }
+
+    /// Obtain the nodes as 32-bit words, four per node, such as for upload to a GPU buffer.
+    #[must_use]
+    pub fn node_words(&self) -> Vec<u32> {
+        self.nodes
+            .iter()
+            .flat_map(|n| [n.x, n.y, n.size, n.data])
+            .collect()
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Encode the quadtree of this [PixelMap] as a [LinearQuadtree], being flat arrays of
+    /// nodes and leaf values, without pointers. See [GpuNode].
+    ///
+    /// # Panics
+    ///
+    /// If the tree has more than [GpuNode::INDEX_MASK] nodes.
+    #[must_use]
+    pub fn to_linear_tree(&self) -> LinearQuadtree<T> {
+        let mut nodes = Vec::new();
+        let mut values = Vec::new();
+        let mut queue: VecDeque<&PNode<T, U>> = VecDeque::from([&self.root]);
+        // The index of the next node to be enqueued, being the first child of the next branch
+        let mut next = 1usize;
+        while let Some(node) = queue.pop_front() {
+            let rect = node.region().as_urect();
+            let (index, mut flags) = if node.is_leaf() {
+                values.push(*node.value());
+                (values.len() - 1, GpuNode::LEAF)
+            } else {
+                queue.extend(node.children().iter());
+                next += 4;
+                (next - 4, 0)
+            };
+            assert!(
+                index <= GpuNode::INDEX_MASK as usize,
+                "too many nodes for a linear tree"
+            );
+            if node.dirty() {
+                flags |= GpuNode::DIRTY;
+            }
+            nodes.push(GpuNode {
+                x: rect.min.x,
+                y: rect.min.y,
+                size: rect.width(),
+                data: index as u32 | flags,
+            });
+        }
+        LinearQuadtree { nodes, values }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_to_linear_tree() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 0, 1);
+        pm.draw_rect(&URect::new(4, 4, 8, 8), 1);
+        pm.set_pixel((1, 2), 2);
+        pm.clear_dirty(true);
+        pm.set_pixel((6, 1), 3);
+
+        let tree = pm.to_linear_tree();
+        assert_eq!(tree.nodes.len(), pm.stats().node_count);
+        assert_eq!(tree.values.len(), pm.stats().leaf_count);
+        assert_eq!(tree.node_words().len(), tree.nodes.len() * 4);
+
+        let root = tree.nodes[0];
+        assert!(!root.is_leaf());
+        assert!(root.is_dirty());
+        assert_eq!((root.x, root.y, root.size, root.index()), (0, 0, 8, 1));
+        let top_right = tree.nodes[root.index() + Quadrant::TopRight as usize];
+        assert!(top_right.is_leaf());
+        assert!(!top_right.is_dirty());
+        assert_eq!(tree.values[top_right.index()], 1);
+
+        for y in 0..8 {
+            for x in 0..8 {
+                let point = UVec2::new(x, y);
+                assert_eq!(tree.find(point), pm.get_pixel(point), "{:?}", point);
+            }
+        }
+        assert_eq!(tree.find(UVec2::new(8, 0)), None);
+    }
+}
@@ -0,0 +1,273 @@
+use crate::math::hash_u64;
+use crate::{ICircle, ITriangle, PixelMap};
+use bevy_math::{URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A shape drawn by [MapOp::Shape].
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapShape {
+    Rect(URect),
+    Circle(ICircle),
+    Triangle(ITriangle),
+}
+
+/// The manner in which [MapOp::Blend] combines values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    /// Take the blended value.
+    #[default]
+    Replace,
+
+    /// Add the blended value to the current value.
+    Add,
+
+    /// Multiply the current value by the blended value.
+    Multiply,
+
+    /// Take the lesser of the current and blended values.
+    Min,
+
+    /// Take the greater of the current and blended values.
+    Max,
+}
+
+impl BlendMode {
+    /// Combine the current value with the blended value.
+    #[inline]
+    #[must_use]
+    pub fn apply(&self, current: f32, value: f32) -> f32 {
+        match self {
+            BlendMode::Replace => value,
+            BlendMode::Add => current + value,
+            BlendMode::Multiply => current * value,
+            BlendMode::Min => current.min(value),
+            BlendMode::Max => current.max(value),
+        }
+    }
+}
+
+/// A single operation of a procedural generation pipeline, as per [PixelMap::apply_ops].
+/// Operations that nest a pipeline evaluate it over a map of the same dimensions, initially
+/// zero.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapOp {
+    /// Set every pixel to the value.
+    Fill(f32),
+
+    /// Set the pixels within the shape to the value.
+    Shape { shape: MapShape, value: f32 },
+
+    /// Add smooth value noise, in the range `0..amplitude`, that varies over lattice cells of
+    /// the given size, in pixels. The same seed produces the same noise.
+    Noise {
+        seed: u64,
+        cell_size: u32,
+        amplitude: f32,
+    },
+
+    /// Evaluate the nested pipeline, and combine its result with the current values.
+    Blend { ops: Vec<MapOp>, mode: BlendMode },
+
+    /// Replace each value with `below` if it is less than `level`, or `above` otherwise.
+    Threshold { level: f32, below: f32, above: f32 },
+
+    /// Apply the `ops` pipeline to the current values, but keep its result only where the
+    /// nested `mask` pipeline produces a positive value.
+    Mask { mask: Vec<MapOp>, ops: Vec<MapOp> },
+}
+
+/// Operations that treat a [PixelMap] of `f32` values as a field for procedural generation,
+/// such as heights or densities.
+impl<U: Unsigned + NumCast + Copy + Debug> PixelMap<f32, U> {
+    /// Apply a pipeline of operations, in order, describing procedural generation
+    /// declaratively. Operations other than [MapOp::Noise] are applied per leaf node, so
+    /// large uniform regions are not examined pixel by pixel. Nested pipelines are evaluated
+    /// into intermediate maps, which are otherwise not allocated.
+    ///
+    /// # Parameters
+    ///
+    /// - `ops`: The operations to apply.
+    pub fn apply_ops(&mut self, ops: &[MapOp]) {
+        for op in ops {
+            match op {
+                MapOp::Fill(value) => self.clear(*value),
+                MapOp::Shape { shape, value } => {
+                    match shape {
+                        MapShape::Rect(rect) => self.draw_rect(rect, *value),
+                        MapShape::Circle(circle) => self.draw_circle(circle, *value),
+                        MapShape::Triangle(triangle) => self.draw_triangle(triangle, *value),
+                    };
+                }
+                MapOp::Noise {
+                    seed,
+                    cell_size,
+                    amplitude,
+                } => self.add_noise(*seed, *cell_size, *amplitude),
+                MapOp::Blend { ops, mode } => {
+                    let other = self.evaluate(ops);
+                    self.combine(&other, (0, 0), |current, value| {
+                        mode.apply(*current, *value)
+                    });
+                }
+                MapOp::Threshold {
+                    level,
+                    below,
+                    above,
+                } => self.map_leaves(|value| if value < *level { *below } else { *above }),
+                MapOp::Mask { mask, ops } => {
+                    let mask = self.evaluate(mask);
+                    let mut result = self.clone();
+                    result.apply_ops(ops);
+                    let mut updates: Vec<(URect, f32)> = Vec::new();
+                    mask.visit(|node, sub_rect| {
+                        if *node.value() > 0.0 {
+                            result.visit_in_rect(sub_rect, |node, sub_rect| {
+                                updates.push((*sub_rect, *node.value()));
+                            });
+                        }
+                    });
+                    for (rect, value) in updates {
+                        self.draw_rect(&rect, value);
+                    }
+                }
+            }
+        }
+    }
+
+    // Evaluate a pipeline over a map of the same dimensions as this one, initially zero.
+    fn evaluate(&self, ops: &[MapOp]) -> Self {
+        let mut map = PixelMap::new(&self.map_size(), 0.0, self.pixel_size());
+        map.apply_ops(ops);
+        map
+    }
+
+    // Replace the value of each leaf node.
+    fn map_leaves<F>(&mut self, mut f: F)
+    where
+        F: FnMut(f32) -> f32,
+    {
+        let mut updates: Vec<(URect, f32)> = Vec::new();
+        self.visit(|node, sub_rect| {
+            let value = f(*node.value());
+            if value != *node.value() {
+                updates.push((*sub_rect, value));
+            }
+        });
+        for (rect, value) in updates {
+            self.draw_rect(&rect, value);
+        }
+    }
+
+    fn add_noise(&mut self, seed: u64, cell_size: u32, amplitude: f32) {
+        let cell_size = cell_size.max(1) as f32;
+        let step = self.pixel_size() as u32;
+        let half = step as f32 / 2.0;
+        let size = self.map_size();
+        let mut updates: Vec<(UVec2, f32)> = Vec::with_capacity((size.x * size.y) as usize);
+        for y in (0..size.y).step_by(step as usize) {
+            for x in (0..size.x).step_by(step as usize) {
+                let point = UVec2::new(x, y);
+                let p = (point.as_vec2() + half) / cell_size;
+                let current = self.get_pixel(point).copied().unwrap_or_default();
+                updates.push((point, current + amplitude * value_noise(seed, p)));
+            }
+        }
+        for (point, value) in updates {
+            self.set_pixel(point, value);
+        }
+    }
+}
+
+// Smoothly interpolated noise in the range `0..1`, having random values at integer
+// lattice points.
+fn value_noise(seed: u64, p: Vec2) -> f32 {
+    let cell = p.floor();
+    let t = p - cell;
+    let t = t * t * (Vec2::splat(3.0) - 2.0 * t);
+    let (x, y) = (cell.x as i64, cell.y as i64);
+    let a = lattice_value(seed, x, y);
+    let b = lattice_value(seed, x + 1, y);
+    let c = lattice_value(seed, x, y + 1);
+    let d = lattice_value(seed, x + 1, y + 1);
+    let bottom = a + (b - a) * t.x;
+    let top = c + (d - c) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+// A pseudo-random value in the range `0..1` for a lattice point.
+fn lattice_value(seed: u64, x: i64, y: i64) -> f32 {
+    (hash_u64(seed, x as u64, y as u64) >> 40) as f32 / (1u64 << 24) as f32
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_apply_ops() {
+        let mut pm: PixelMap<f32, u32> = PixelMap::new(&UVec2::splat(16), 0.0, 1);
+        pm.apply_ops(&[
+            MapOp::Fill(1.0),
+            MapOp::Shape {
+                shape: MapShape::Rect(URect::new(0, 0, 8, 16)),
+                value: 3.0,
+            },
+            MapOp::Blend {
+                ops: vec![MapOp::Shape {
+                    shape: MapShape::Circle(ICircle::new((12, 12), 2)),
+                    value: 2.0,
+                }],
+                mode: BlendMode::Add,
+            },
+        ]);
+        assert_eq!(pm.get_pixel((2, 2)), Some(&3.0));
+        assert_eq!(pm.get_pixel((12, 2)), Some(&1.0));
+        assert_eq!(pm.get_pixel((12, 12)), Some(&3.0));
+
+        pm.apply_ops(&[MapOp::Threshold {
+            level: 2.0,
+            below: 0.0,
+            above: 1.0,
+        }]);
+        assert_eq!(pm.get_pixel((2, 2)), Some(&1.0));
+        assert_eq!(pm.get_pixel((12, 2)), Some(&0.0));
+        assert_eq!(pm.get_pixel((12, 12)), Some(&1.0));
+
+        // Only the masked region is affected
+        pm.apply_ops(&[MapOp::Mask {
+            mask: vec![MapOp::Shape {
+                shape: MapShape::Rect(URect::new(0, 0, 4, 4)),
+                value: 1.0,
+            }],
+            ops: vec![MapOp::Fill(5.0)],
+        }]);
+        assert_eq!(pm.get_pixel((2, 2)), Some(&5.0));
+        assert_eq!(pm.get_pixel((5, 5)), Some(&1.0));
+        assert_eq!(pm.get_pixel((12, 2)), Some(&0.0));
+    }
+
+    #[test]
+    fn test_apply_ops_noise() {
+        let noise = |seed| {
+            let mut pm: PixelMap<f32, u32> = PixelMap::new(&UVec2::splat(16), 0.0, 1);
+            pm.apply_ops(&[MapOp::Noise {
+                seed,
+                cell_size: 4,
+                amplitude: 2.0,
+            }]);
+            pm
+        };
+        let a = noise(7);
+        assert!(a == noise(7));
+        assert!(a != noise(8));
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        a.visit(|n, _| {
+            min = min.min(*n.value());
+            max = max.max(*n.value());
+        });
+        assert!(min >= 0.0 && max < 2.0 && max > min);
+    }
+}
@@ -1,5 +1,4 @@
-use crate::{iline, ILine};
-use bevy_math::{ivec2, uvec2, vec2, IRect, IVec2, Rect, URect, UVec2, Vec2};
+use bevy_math::{UVec2, Vec2};
 
 /// Find the distance squared between two points.
 #[inline]
@@ -38,89 +37,29 @@ pub fn distance_to_line(p: Vec2, line: &[Vec2; 2]) -> f32 {
     distance_squared_to_line(p, line).sqrt()
 }
 
-/// Get the four points that make up the corners of the given `rect`.
+// A well-mixed pseudo-random value for the given seed and coordinates, by the SplitMix64
+// finalizer. Used where results must be reproducible without a random number generator.
 #[inline]
-#[must_use]
-pub fn rect_points(rect: &Rect) -> [Vec2; 4] {
-    [
-        rect.min,
-        rect.min + vec2(rect.width(), 0.),
-        rect.max,
-        rect.min + vec2(0., rect.height()),
-    ]
-}
-
-/// Get the four points that make up the corners of the given `rect`.
-#[inline]
-#[must_use]
-pub fn irect_points(rect: &IRect) -> [IVec2; 4] {
-    [
-        rect.min,
-        rect.min + ivec2(rect.width(), 0),
-        rect.max,
-        rect.min + ivec2(0, rect.height()),
-    ]
-}
-
-/// Get the four points that make up the corners of the given `rect`.
-#[inline]
-#[must_use]
-pub fn urect_points(rect: &URect) -> [UVec2; 4] {
-    [
-        rect.min,
-        rect.min + uvec2(rect.width(), 0),
-        rect.max,
-        rect.min + uvec2(0, rect.height()),
-    ]
+pub(crate) fn hash_u64(seed: u64, a: u64, b: u64) -> u64 {
+    let mut z =
+        seed ^ a.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ b.wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
 }
 
-/// Get the four lines that make up the edges of this rectangle.
+// The Morton (Z-order) code of the given point, interleaving the bits of x into the even
+// bits and those of y into the odd bits, such that points sorted by code are grouped by
+// quadtree node.
 #[inline]
-#[must_use]
-pub fn irect_edges(rect: &IRect) -> [ILine; 4] {
-    let min = rect.min;
-    let max = rect.max;
-    let width = rect.width();
-    let height = rect.height();
-    [
-        iline(min, min + ivec2(width, 0)),
-        iline(min + ivec2(width, 0), max),
-        iline(max, min + ivec2(0, height)),
-        iline(min + ivec2(0, height), min),
-    ]
-}
-
-/// Convert the given `IRect` into a `URect`, without wrapping negative values,
-/// effectively cropping the rectangle to the positive quadrant.
-#[inline]
-#[must_use]
-pub fn to_cropped_urect(rect: &IRect) -> URect {
-    URect::from_corners(
-        rect.min.max(IVec2::ZERO).as_uvec2(),
-        rect.max.max(IVec2::ZERO).as_uvec2(),
-    )
-}
-
-/// Subtract one from the maximum point of the given `rect`, allowing
-/// for exclusive handling with `contains`, for example.
-#[inline]
-#[must_use]
-pub fn exclusive_irect(rect: &IRect) -> IRect {
-    if rect.is_empty() {
-        return *rect;
-    }
-    let max = rect.max - IVec2::ONE;
-    IRect::from_corners(rect.min, max.max(rect.min))
-}
-
-/// Subtract one from the maximum point of the given `rect`, allowing
-/// for exclusive handling with `contains`, for example.
-#[inline]
-#[must_use]
-pub fn exclusive_urect(rect: &URect) -> URect {
-    if rect.is_empty() || rect.max.x == 0 || rect.max.y == 0 {
-        return *rect;
+pub(crate) fn morton_code(point: UVec2) -> u64 {
+    fn spread(v: u32) -> u64 {
+        let mut v = v as u64;
+        v = (v | (v << 16)) & 0x0000_FFFF_0000_FFFF;
+        v = (v | (v << 8)) & 0x00FF_00FF_00FF_00FF;
+        v = (v | (v << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+        v = (v | (v << 2)) & 0x3333_3333_3333_3333;
+        (v | (v << 1)) & 0x5555_5555_5555_5555
     }
-    let max = rect.max - UVec2::ONE;
-    URect::from_corners(rect.min, max.max(rect.min))
+    spread(point.x) | (spread(point.y) << 1)
 }
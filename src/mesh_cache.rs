@@ -0,0 +1,195 @@
+use crate::{PNode, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::{FxHashMap, FxHashSet};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A mesh of a single chunk of a [MeshCache], as per [PixelMap::non_uniform_quad_mesh].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MeshChunk {
+    /// The rectangle from which the mesh was generated.
+    pub rect: URect,
+
+    /// The unique vertex points of the mesh.
+    pub vertices: Vec<UVec2>,
+
+    /// The vertex indices of each triangle of the mesh, in counter-clockwise winding.
+    pub indices: Vec<[u32; 3]>,
+}
+
+/// A cache of meshes generated by [PixelMap::non_uniform_quad_mesh] over square chunks of a
+/// [PixelMap], such that modifications of the map only regenerate the meshes of chunks having
+/// dirty leaf nodes, rather than the whole mesh.
+#[derive(Debug, Clone)]
+pub struct MeshCache {
+    chunk_size: u32,
+    chunks: FxHashMap<UVec2, MeshChunk>,
+}
+
+impl MeshCache {
+    /// Create an empty [MeshCache].
+    ///
+    /// # Parameters
+    ///
+    /// - `chunk_size`: The size of an edge, in pixels, of a single square chunk. Chunks are
+    ///   aligned with the `(0,0)` point of the map.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    #[must_use]
+    pub fn new(chunk_size: u32) -> Self {
+        assert!(chunk_size > 0, "chunk_size must be > 0");
+        Self {
+            chunk_size,
+            chunks: FxHashMap::default(),
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /// The number of cached chunks.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Obtain the cached mesh of the chunk at the given chunk coordinate, if any.
+    #[inline]
+    #[must_use]
+    pub fn chunk(&self, coord: UVec2) -> Option<&MeshChunk> {
+        self.chunks.get(&coord)
+    }
+
+    /// Iterate the cached meshes, along with their chunk coordinate, in no particular order.
+    #[inline]
+    pub fn chunks(&self) -> impl Iterator<Item = (UVec2, &MeshChunk)> {
+        self.chunks.iter().map(|(coord, chunk)| (*coord, chunk))
+    }
+
+    /// Generate the meshes of chunks overlapping the given rectangle, where they are not yet
+    /// cached, or where they overlap dirty leaf nodes of the given [PixelMap]. Call this before
+    /// clearing dirty state with [PixelMap::clear_dirty], for each map modification.
+    ///
+    /// # Parameters
+    ///
+    /// - `pixel_map`: The [PixelMap] from which to generate meshes.
+    /// - `rect`: The rectangle in which contained or overlapping chunks will be updated.
+    ///   Chunks are clipped to this rectangle.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle, as per [PixelMap::non_uniform_quad_mesh].
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the chunks that were generated, in row-major order.
+    pub fn update<T, U, F>(
+        &mut self,
+        pixel_map: &PixelMap<T, U>,
+        rect: &URect,
+        mut predicate: F,
+    ) -> Vec<UVec2>
+    where
+        T: Copy + PartialEq,
+        U: Unsigned + NumCast + Copy + Debug,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let rect = rect.intersect(pixel_map.map_rect());
+        if rect.is_empty() {
+            return Vec::new();
+        }
+
+        let mut dirty: FxHashSet<UVec2> = FxHashSet::default();
+        if pixel_map.dirty() {
+            pixel_map.visit_dirty_in_rect(&rect, |_, sub_rect| {
+                let min = sub_rect.min / self.chunk_size;
+                let max = (sub_rect.max - 1) / self.chunk_size;
+                for y in min.y..=max.y {
+                    for x in min.x..=max.x {
+                        dirty.insert(UVec2::new(x, y));
+                    }
+                }
+            });
+        }
+
+        let min = rect.min / self.chunk_size;
+        let max = (rect.max - 1) / self.chunk_size;
+        let mut updated = Vec::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let coord = UVec2::new(x, y);
+                let chunk_rect =
+                    URect::from_corners(coord * self.chunk_size, (coord + 1) * self.chunk_size)
+                        .intersect(rect);
+                let cached = self.chunks.get(&coord).map(|c| c.rect);
+                if cached == Some(chunk_rect) && !dirty.contains(&coord) {
+                    continue;
+                }
+                let size_estimate = self.chunks.get(&coord).map_or(0, |c| c.indices.len());
+                let (vertices, indices) =
+                    pixel_map.non_uniform_quad_mesh(&chunk_rect, &mut predicate, size_estimate);
+                self.chunks.insert(
+                    coord,
+                    MeshChunk {
+                        rect: chunk_rect,
+                        vertices,
+                        indices,
+                    },
+                );
+                updated.push(coord);
+            }
+        }
+        updated
+    }
+
+    /// Remove all cached meshes.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_mesh_cache() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 8, 8), true);
+        let mut cache = MeshCache::new(16);
+
+        let updated = cache.update(&pm, &pm.map_rect(), |n, _| *n.value());
+        assert_eq!(updated.len(), 4);
+        assert_eq!(cache.chunk(UVec2::ZERO).unwrap().indices.len(), 2);
+        assert!(cache.chunk(UVec2::ONE).unwrap().indices.is_empty());
+        pm.clear_dirty(true);
+
+        // Nothing changed
+        let updated = cache.update(&pm, &pm.map_rect(), |n, _| *n.value());
+        assert!(updated.is_empty());
+
+        // Only the modified chunk is regenerated
+        pm.set_pixel((20, 3), true);
+        let updated = cache.update(&pm, &pm.map_rect(), |n, _| *n.value());
+        assert_eq!(updated, vec![UVec2::new(1, 0)]);
+        assert_eq!(cache.chunk(UVec2::new(1, 0)).unwrap().indices.len(), 2);
+        pm.clear_dirty(true);
+
+        // Chunks outside the rectangle are retained
+        let updated = cache.update(&pm, &URect::new(0, 0, 8, 8), |n, _| *n.value());
+        assert_eq!(updated, vec![UVec2::ZERO]);
+        assert_eq!(cache.len(), 4);
+    }
+}
@@ -0,0 +1,220 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{InclusiveRect, NodePath, PixelMap};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+/// A sparse store of metadata associated with individual pixels of a [PixelMap], such as the
+/// contents of a chest, or the state of a door. Entries are keyed by pixel coordinates rather
+/// than by node, so they remain consistent as the nodes of the map are subdivided and merged
+/// by edits, and may be found by the [NodePath] of the node now containing them with
+/// [PixelMetadata::metadata_in_node].
+///
+/// Entries are ordered by row, from the bottom of the map, then by column.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelMetadata<M> {
+    // Keyed by `(y, x)`, such that a row is a contiguous range
+    entries: BTreeMap<(u32, u32), M>,
+}
+
+impl<M> Default for PixelMetadata<M> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<M> PixelMetadata<M> {
+    /// Create a new, empty [PixelMetadata].
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Obtain the number of entries.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Determine if there are no entries.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Associate metadata with the pixel at the given coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The metadata previously associated with the pixel, if any.
+    #[inline]
+    pub fn insert(&mut self, point: UVec2, metadata: M) -> Option<M> {
+        self.entries.insert((point.y, point.x), metadata)
+    }
+
+    /// Obtain the metadata associated with the pixel at the given coordinates.
+    #[inline]
+    #[must_use]
+    pub fn get(&self, point: UVec2) -> Option<&M> {
+        self.entries.get(&(point.y, point.x))
+    }
+
+    /// Obtain the metadata associated with the pixel at the given coordinates, mutably.
+    #[inline]
+    #[must_use]
+    pub fn get_mut(&mut self, point: UVec2) -> Option<&mut M> {
+        self.entries.get_mut(&(point.y, point.x))
+    }
+
+    /// Remove the metadata associated with the pixel at the given coordinates.
+    ///
+    /// # Returns
+    ///
+    /// The removed metadata, if any.
+    #[inline]
+    pub fn remove(&mut self, point: UVec2) -> Option<M> {
+        self.entries.remove(&(point.y, point.x))
+    }
+
+    /// Remove all entries.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Obtain an iterator over all entries, and their pixel coordinates.
+    pub fn iter(&self) -> impl Iterator<Item = (UVec2, &M)> {
+        self.entries
+            .iter()
+            .map(|(&(y, x), metadata)| (UVec2::new(x, y), metadata))
+    }
+
+    /// Obtain an iterator over the entries of pixels within the given rectangle.
+    pub fn metadata_in_rect(&self, rect: &URect) -> impl Iterator<Item = (UVec2, &M)> {
+        let rect = *rect;
+        let range = if rect.is_empty() {
+            self.entries.range((0, 0)..(0, 0))
+        } else {
+            self.entries
+                .range((rect.min.y, rect.min.x)..(rect.max.y - 1, rect.max.x))
+        };
+        range
+            .filter(move |((_, x), _)| (rect.min.x..rect.max.x).contains(x))
+            .map(|(&(y, x), metadata)| (UVec2::new(x, y), metadata))
+    }
+
+    /// Obtain an iterator over the entries of pixels within the region of the node at the
+    /// given path of the given [PixelMap], such as that of a leaf node being visited.
+    pub fn metadata_in_node<T, U>(
+        &self,
+        pixel_map: &PixelMap<T, U>,
+        path: NodePath,
+    ) -> impl Iterator<Item = (UVec2, &M)>
+    where
+        T: Copy + PartialEq,
+        U: Unsigned + NumCast + Copy + Debug,
+    {
+        let rect = pixel_map
+            .root
+            .find_node_by_path(path)
+            .map(|node| node.region().intersect(&pixel_map.map_rect()))
+            .unwrap_or_default();
+        self.metadata_in_rect(&rect)
+    }
+
+    /// Retain only the entries for which the given predicate returns `true`.
+    pub fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(UVec2, &mut M) -> bool,
+    {
+        self.entries
+            .retain(|&(y, x), metadata| predicate(UVec2::new(x, y), metadata));
+    }
+
+    /// Retain only the entries of pixels within the given [PixelMap] for which the given
+    /// predicate returns `true`, given the current value of the pixel, such as to discard
+    /// the contents of chests that have since been destroyed.
+    ///
+    /// # Returns
+    ///
+    /// The removed entries, and their pixel coordinates.
+    pub fn retain_matching<T, U, F>(
+        &mut self,
+        pixel_map: &PixelMap<T, U>,
+        mut predicate: F,
+    ) -> Vec<(UVec2, M)>
+    where
+        T: Copy + PartialEq,
+        U: Unsigned + NumCast + Copy + Debug,
+        F: FnMut(UVec2, &M, &T) -> bool,
+    {
+        let mut removed = Vec::new();
+        let entries = std::mem::take(&mut self.entries);
+        for ((y, x), metadata) in entries {
+            let point = UVec2::new(x, y);
+            let keep = pixel_map.map_rect().contains_exclusive(point)
+                && pixel_map
+                    .get_pixel(point)
+                    .is_some_and(|value| predicate(point, &metadata, value));
+            if keep {
+                self.entries.insert((y, x), metadata);
+            } else {
+                removed.push((point, metadata));
+            }
+        }
+        removed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_pixel_metadata() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        let mut chests: PixelMetadata<&str> = PixelMetadata::new();
+        for (point, contents) in [((3, 3), "gold"), ((4, 3), "gems"), ((10, 12), "map")] {
+            pm.set_pixel(point, 1);
+            assert!(chests.insert(point.into(), contents).is_none());
+        }
+        assert_eq!(chests.len(), 3);
+        assert_eq!(chests.get(UVec2::new(4, 3)), Some(&"gems"));
+
+        let in_rect: Vec<_> = chests.metadata_in_rect(&URect::new(4, 0, 16, 16)).collect();
+        assert_eq!(
+            in_rect,
+            vec![(UVec2::new(4, 3), &"gems"), (UVec2::new(10, 12), &"map")]
+        );
+        assert_eq!(
+            chests.metadata_in_rect(&URect::new(0, 4, 16, 12)).count(),
+            0
+        );
+
+        // Consistent as nodes are merged and subdivided
+        pm.draw_rect(&URect::new(0, 0, 8, 8), 1);
+        let path = pm.get_path((0, 0)).unwrap();
+        let in_node: Vec<_> = chests.metadata_in_node(&pm, path).map(|(p, _)| p).collect();
+        assert_eq!(in_node, vec![UVec2::new(3, 3), UVec2::new(4, 3)]);
+        pm.set_pixel((4, 3), 0);
+        let path = pm.get_path((3, 3)).unwrap();
+        let in_node: Vec<_> = chests.metadata_in_node(&pm, path).map(|(p, _)| p).collect();
+        assert_eq!(in_node, vec![UVec2::new(3, 3)]);
+
+        // Discard the chests that were destroyed
+        let removed = chests.retain_matching(&pm, |_, _, v| *v == 1);
+        assert_eq!(removed, vec![(UVec2::new(4, 3), "gems")]);
+        assert_eq!(chests.len(), 2);
+        assert_eq!(chests.remove(UVec2::new(10, 12)), Some("map"));
+    }
+}
@@ -1,5 +1,5 @@
-use crate::{Direction, NeighborOrientation, PNode, PixelMap};
-use bevy_math::{uvec2, URect};
+use crate::{rect_outer_edge, Direction, NeighborOrientation, PNode, PixelMap};
+use bevy_math::URect;
 use num_traits::{NumCast, Unsigned};
 use std::fmt::Debug;
 
@@ -94,6 +94,82 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         );
     }
 
+    /// Visit neighboring nodes to the given node in all directions, as per
+    /// [PixelMap::visit_neighbors_with_boundary].
+    #[inline]
+    pub fn visit_all_neighbors_with_boundary<F, V, B>(
+        &self,
+        rect: &URect,
+        node_region: &URect,
+        mut predicate: F,
+        mut visitor: V,
+        mut on_boundary: B,
+    ) where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+        V: FnMut(&PNode<T, U>, &URect),
+        B: FnMut(Direction),
+    {
+        Direction::iter().for_each(|d| {
+            self.visit_neighbors_with_boundary(
+                rect,
+                node_region,
+                d,
+                &mut predicate,
+                &mut visitor,
+                &mut on_boundary,
+            )
+        });
+    }
+
+    /// Visit neighboring nodes to the given node, on the specified edge or corner, as per
+    /// [PixelMap::visit_neighbors], additionally reporting when that edge or corner lies
+    /// outside the map, such as to treat the edge of the map as a wall when auto-tiling.
+    /// Neighbors are only visited within the map.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `node_region`: The region represented by the node for which to visit neighbors.
+    /// - `direction`: The direction of the edge of the node for which to visit neighbors.
+    /// - `predicate`: As per [PixelMap::visit_neighbors].
+    /// - `visitor`: As per [PixelMap::visit_neighbors].
+    /// - `on_boundary`: A closure that takes the given `direction`, called instead of visiting
+    ///   any neighbors when the edge or corner in that direction lies outside the map.
+    pub fn visit_neighbors_with_boundary<F, V, B>(
+        &self,
+        rect: &URect,
+        node_region: &URect,
+        direction: Direction,
+        mut predicate: F,
+        mut visitor: V,
+        mut on_boundary: B,
+    ) where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+        V: FnMut(&PNode<T, U>, &URect),
+        B: FnMut(Direction),
+    {
+        let rect = rect.intersect(*node_region);
+        if rect.is_empty() {
+            return;
+        }
+
+        if is_map_boundary(&rect, &self.map_rect, direction) {
+            on_boundary(direction);
+            return;
+        }
+
+        let neighbor_rect = rect_outer_edge(&rect, direction).intersect(self.map_rect);
+        self.root.visit_leaves_in_rect(
+            &neighbor_rect,
+            &mut |node, sub_rect| {
+                if predicate(node, sub_rect) {
+                    visitor(node, sub_rect);
+                }
+            },
+            &mut 0,
+        );
+    }
+
     /// Visit all leaf nodes that intersect with the given `rect` that are neighbors.
     /// The `visitor` closure is called once for each unique pair of neighbor nodes.
     ///
@@ -114,47 +190,55 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     {
         let sub_rect = self.map_rect.intersect(*rect);
         if !sub_rect.is_empty() {
-            self.root.visit_neighbor_pairs_face(&sub_rect, visitor);
+            self.root
+                .visit_neighbor_pairs_face(&sub_rect, visitor, &mut 0);
+        }
+    }
+
+    /// Visit all leaf nodes that intersect with the given `rect` that are neighbors only by
+    /// a corner, without sharing an edge, such as for algorithms using 8-connectivity, in
+    /// conjunction with [PixelMap::visit_neighbor_pairs].
+    /// The `visitor` closure is called once for each unique pair of corner neighbor nodes.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `visitor`: A closure that takes:
+    ///   - The [Direction] of the top node from the bottom node, being either
+    ///     [Direction::NorthEast] or [Direction::NorthWest].
+    ///   - The bottom node.
+    ///   - The rectangle that is the effective intersection of the bottom node's region
+    ///     and the `rect` parameter supplied to this method.
+    ///   - The top node.
+    ///   - The rectangle that is the effective intersection of the top node's region
+    ///     and the `rect` parameter supplied to this method.
+    pub fn visit_neighbor_pairs_corner<F>(&self, rect: &URect, visitor: &mut F)
+    where
+        F: FnMut(Direction, &PNode<T, U>, &URect, &PNode<T, U>, &URect),
+    {
+        let sub_rect = self.map_rect.intersect(*rect);
+        if !sub_rect.is_empty() {
+            self.root.visit_neighbor_pairs_corner(&sub_rect, visitor);
         }
     }
 }
 
-/// Obtain a rect that encompasses the outer edge of the given `rect`, for the
-/// desired `direction`. The returned edge rect is 1 pixel "thick".
-/// The returned rect for diagonal edges (corners) is 1x1 pixel in size.
-#[inline]
-#[must_use]
-pub fn rect_outer_edge(rect: &URect, direction: Direction) -> URect {
+// Determine if the outer edge of the given `rect`, for the desired `direction`, lies outside
+// the given `map_rect`. A corner lies outside if either of its edges does.
+fn is_map_boundary(rect: &URect, map_rect: &URect, direction: Direction) -> bool {
+    let north = rect.max.y >= map_rect.max.y;
+    let east = rect.max.x >= map_rect.max.x;
+    let south = rect.min.y <= map_rect.min.y;
+    let west = rect.min.x <= map_rect.min.x;
     match direction {
-        Direction::North => URect::from_corners(
-            uvec2(rect.min.x, rect.max.y),
-            uvec2(rect.max.x, rect.max.y + 1),
-        ),
-        Direction::NorthEast => URect::from_corners(rect.max, rect.max + 1),
-        Direction::East => URect::from_corners(
-            uvec2(rect.max.x, rect.min.y),
-            uvec2(rect.max.x + 1, rect.max.y),
-        ),
-        Direction::SouthEast => URect::from_corners(
-            uvec2(rect.max.x, rect.min.y.saturating_sub(1)),
-            uvec2(rect.max.x + 1, rect.min.y),
-        ),
-        Direction::South => URect::from_corners(
-            uvec2(rect.min.x, rect.min.y.saturating_sub(1)),
-            uvec2(rect.max.x, rect.min.y),
-        ),
-        Direction::SouthWest => URect::from_corners(
-            uvec2(rect.min.x.saturating_sub(1), rect.min.y.saturating_sub(1)),
-            rect.min,
-        ),
-        Direction::West => URect::from_corners(
-            uvec2(rect.min.x.saturating_sub(1), rect.min.y),
-            uvec2(rect.min.x, rect.max.y),
-        ),
-        Direction::NorthWest => URect::from_corners(
-            uvec2(rect.min.x.saturating_sub(1), rect.max.y),
-            uvec2(rect.min.x, rect.max.y + 1),
-        ),
+        Direction::North => north,
+        Direction::NorthEast => north || east,
+        Direction::East => east,
+        Direction::SouthEast => south || east,
+        Direction::South => south,
+        Direction::SouthWest => south || west,
+        Direction::West => west,
+        Direction::NorthWest => north || west,
     }
 }
 
@@ -184,7 +268,7 @@ mod test {
             &pm.root.region().as_urect(),
             |n, _| *n.value(),
             |_n, _| {
-                assert!(false);
+                panic!("unexpected neighbor visit");
             },
         );
 
@@ -193,7 +277,7 @@ mod test {
             &pm.root.region().as_urect(),
             |n, _| !*n.value(),
             |_n, _| {
-                assert!(false);
+                panic!("unexpected neighbor visit");
             },
         );
     }
@@ -209,7 +293,7 @@ mod test {
             &n.region().as_urect(),
             |n, _| *n.value() != 0,
             |_n, _| {
-                assert!(false);
+                panic!("unexpected neighbor visit");
             },
         );
     }
@@ -365,4 +449,88 @@ mod test {
         );
         assert_eq!(visited, 90);
     }
+
+    #[test]
+    fn test_visit_neighbors_with_boundary() {
+        // Non-square, such that the root region extends beyond the top of the map
+        let mut pm = PixelMap::<u32, u16>::new(&uvec2(4, 2), 0, 1);
+        pm.set_pixel(uvec2(1, 0), 10);
+        pm.set_pixel(uvec2(2, 0), 20);
+
+        let corner = &pm.root.find_node(uvec2(0, 0)).region().as_urect();
+        let mut boundary = Vec::new();
+        let mut visited = Vec::new();
+        pm.visit_all_neighbors_with_boundary(
+            &pm.region().as_urect(),
+            corner,
+            |_, _| true,
+            |n, _| visited.push(*n.value()),
+            |d| boundary.push(d),
+        );
+        assert_eq!(
+            boundary,
+            vec![
+                Direction::SouthEast,
+                Direction::South,
+                Direction::SouthWest,
+                Direction::West,
+                Direction::NorthWest,
+            ]
+        );
+        assert_eq!(visited.len(), 3);
+        assert!(visited.contains(&10));
+
+        // The top of the map is a boundary, even though the root region extends beyond it
+        let top = &pm.root.find_node(uvec2(2, 1)).region().as_urect();
+        let mut boundary = Vec::new();
+        pm.visit_neighbors_with_boundary(
+            &pm.region().as_urect(),
+            top,
+            Direction::North,
+            |_, _| true,
+            |_, _| panic!("unexpected neighbor visit"),
+            |d| boundary.push(d),
+        );
+        assert_eq!(boundary, vec![Direction::North]);
+
+        let mut visited = Vec::new();
+        pm.visit_neighbors_with_boundary(
+            &pm.region().as_urect(),
+            top,
+            Direction::South,
+            |_, _| true,
+            |n, _| visited.push(*n.value()),
+            |_| panic!("unexpected boundary"),
+        );
+        assert_eq!(visited, vec![20]);
+    }
+
+    #[test]
+    fn test_visit_neighbor_pairs_corner() {
+        let mut pm = PixelMap::<u32, u16>::new(&UVec2::splat(4), 0, 1);
+        pm.set_pixel(uvec2(1, 1), 10);
+        pm.set_pixel(uvec2(2, 2), 20);
+        pm.set_pixel(uvec2(3, 0), 30);
+
+        let mut pairs = Vec::new();
+        pm.visit_neighbor_pairs_corner(&pm.region().as_urect(), &mut |d, b, _, t, _| {
+            pairs.push((d, b.region().as_urect().min, t.region().as_urect().min));
+        });
+
+        // Center of the root, between differently sized leaves
+        assert!(pairs.contains(&(Direction::NorthEast, uvec2(1, 1), uvec2(2, 2))));
+        assert!(pairs.contains(&(Direction::NorthWest, uvec2(2, 1), uvec2(0, 2))));
+        // Center of the bottom right quadrant
+        assert!(pairs.contains(&(Direction::NorthWest, uvec2(3, 0), uvec2(2, 1))));
+        assert!(pairs.contains(&(Direction::NorthEast, uvec2(2, 0), uvec2(3, 1))));
+        // Each branch yields two pairs
+        assert_eq!(pairs.len(), 2 * 4);
+
+        // Only within the given rectangle
+        let mut count = 0;
+        pm.visit_neighbor_pairs_corner(&URect::new(0, 0, 2, 2), &mut |_, _, _, _, _| {
+            count += 1;
+        });
+        assert_eq!(count, 2);
+    }
 }
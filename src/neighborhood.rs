@@ -0,0 +1,310 @@
+use crate::{Direction, InclusiveRect, NeighborOrientation, PNode, PixelMap};
+use bevy_math::{uvec2, URect, UVec2};
+use fxhash::FxHashSet;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Obtain a mask of which of the eight pixels surrounding the given point match the
+    /// predicate, as is used to select sprites for auto-tiling. Each direction is represented
+    /// by the bit given by [Direction::as_bit]. Pixels outside the [PixelMap::map_rect] do
+    /// not match.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel at the center of the neighborhood.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the node's region, cropped to the [PixelMap::map_rect].
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The neighborhood mask, or `None` if `point` is outside the [PixelMap::map_rect].
+    #[must_use]
+    pub fn neighborhood_mask<F>(&self, point: UVec2, mut predicate: F) -> Option<u8>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let map_rect = self.map_rect();
+        if !map_rect.contains_exclusive(point) {
+            return None;
+        }
+        let center = self.root.find_node(point);
+        let center_rect = center.region().intersect(&map_rect);
+        let center_match = predicate(center, &center_rect);
+
+        let mut mask = 0u8;
+        for d in Direction::iter() {
+            let p = point.as_ivec2() + d.unit();
+            if p.x < 0 || p.y < 0 || !map_rect.contains_exclusive(p.as_uvec2()) {
+                continue;
+            }
+            let p = p.as_uvec2();
+            let matched = if center.region().contains_upoint(p) {
+                center_match
+            } else {
+                let node = self.root.find_node(p);
+                predicate(node, &node.region().intersect(&map_rect))
+            };
+            if matched {
+                mask |= d.as_bit();
+            }
+        }
+        Some(mask)
+    }
+
+    /// Visit every pixel within the given rectangle, along with its neighborhood mask, as per
+    /// [PixelMap::neighborhood_mask]. The predicate is consulted once per leaf node, rather
+    /// than once per neighboring pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which pixels will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and `rect`, expanded by
+    ///   one pixel on each side. It returns `true` if the node matches the predicate, or `false` otherwise.
+    /// - `visitor`: A closure that takes the coordinates of a pixel, whether the pixel matches the
+    ///   predicate, and its neighborhood mask. Pixels are visited in row-major order.
+    pub fn visit_neighborhood_masks<F, V>(&self, rect: &URect, mut predicate: F, mut visitor: V)
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+        V: FnMut(UVec2, bool, u8),
+    {
+        let map_rect = self.map_rect();
+        let rect = rect.intersect(map_rect);
+        if rect.is_empty() {
+            return;
+        }
+
+        // Evaluate the predicate for the rect, and a border of one pixel around it
+        let outer = URect {
+            min: rect.min.saturating_sub(UVec2::ONE),
+            max: rect.max + UVec2::ONE,
+        }
+        .intersect(map_rect);
+        let width = outer.width() as usize;
+        let mut matches = vec![false; width * outer.height() as usize];
+        self.visit_in_rect(&outer, |node, sub_rect| {
+            if predicate(node, sub_rect) {
+                for y in sub_rect.min.y..sub_rect.max.y {
+                    let row = (y - outer.min.y) as usize * width;
+                    let start = row + (sub_rect.min.x - outer.min.x) as usize;
+                    let end = row + (sub_rect.max.x - outer.min.x) as usize;
+                    matches[start..end].fill(true);
+                }
+            }
+        });
+        let matches_at = |p: UVec2| -> bool {
+            outer.contains_exclusive(p)
+                && matches[(p.y - outer.min.y) as usize * width + (p.x - outer.min.x) as usize]
+        };
+
+        for y in rect.min.y..rect.max.y {
+            for x in rect.min.x..rect.max.x {
+                let point = uvec2(x, y);
+                let mut mask = 0u8;
+                for d in Direction::iter() {
+                    let p = point.as_ivec2() + d.unit();
+                    if p.x >= 0 && p.y >= 0 && matches_at(p.as_uvec2()) {
+                        mask |= d.as_bit();
+                    }
+                }
+                visitor(point, matches_at(point), mask);
+            }
+        }
+    }
+
+    /// Obtain the pixels within the given rectangle that match the predicate, and have at least
+    /// one of their four neighbors not matching. Neighbors outside the [PixelMap::map_rect] are
+    /// not considered. Only the edges shared by neighboring leaf nodes of differing predicate
+    /// results are examined, so uniform areas are not scanned pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which boundary pixels will be found.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and `rect`, expanded by
+    ///   one pixel on each side. It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// An iterator of the boundary pixel coordinates, in row-major order.
+    pub fn boundary_cells<F>(&self, rect: &URect, mut predicate: F) -> impl Iterator<Item = UVec2>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let map_rect = self.map_rect();
+        let rect = rect.intersect(map_rect);
+        let mut cells: FxHashSet<UVec2> = FxHashSet::default();
+        if !rect.is_empty() {
+            let outer = URect {
+                min: rect.min.saturating_sub(UVec2::ONE),
+                max: rect.max + UVec2::ONE,
+            }
+            .intersect(map_rect);
+            let unit = self.pixel_size() as u32;
+            let mut insert_strip = |strip: URect| {
+                for y in strip.min.y..strip.max.y {
+                    for x in strip.min.x..strip.max.x {
+                        if rect.contains_exclusive(uvec2(x, y)) {
+                            cells.insert(uvec2(x, y));
+                        }
+                    }
+                }
+            };
+
+            self.root.visit_neighbor_pairs_face(
+                &outer,
+                &mut |or, a, a_rect, b, b_rect| {
+                    let a_match = predicate(a, a_rect);
+                    if a_match == predicate(b, b_rect) {
+                        return;
+                    }
+                    let strip = match (or, a_match) {
+                        (NeighborOrientation::Horizontal, true) => URect::new(
+                            a_rect.max.x - unit,
+                            a_rect.min.y.max(b_rect.min.y),
+                            a_rect.max.x,
+                            a_rect.max.y.min(b_rect.max.y),
+                        ),
+                        (NeighborOrientation::Horizontal, false) => URect::new(
+                            b_rect.min.x,
+                            a_rect.min.y.max(b_rect.min.y),
+                            b_rect.min.x + unit,
+                            a_rect.max.y.min(b_rect.max.y),
+                        ),
+                        (NeighborOrientation::Vertical, true) => URect::new(
+                            a_rect.min.x.max(b_rect.min.x),
+                            a_rect.max.y - unit,
+                            a_rect.max.x.min(b_rect.max.x),
+                            a_rect.max.y,
+                        ),
+                        (NeighborOrientation::Vertical, false) => URect::new(
+                            a_rect.min.x.max(b_rect.min.x),
+                            b_rect.min.y,
+                            a_rect.max.x.min(b_rect.max.x),
+                            b_rect.min.y + unit,
+                        ),
+                    };
+                    insert_strip(strip);
+                },
+                &mut 0,
+            );
+        }
+
+        let mut cells: Vec<UVec2> = cells.into_iter().collect();
+        cells.sort_unstable_by_key(|p| (p.y, p.x));
+        cells.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_neighborhood_mask() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        pm.draw_rect(&URect::new(2, 2, 5, 5), true);
+
+        let mask = pm.neighborhood_mask(UVec2::new(3, 3), |n, _| *n.value());
+        assert_eq!(mask, Some(0xff));
+
+        // Bottom-left corner of the rect
+        let mask = pm.neighborhood_mask(UVec2::new(2, 2), |n, _| *n.value());
+        let expected =
+            Direction::North.as_bit() | Direction::NorthEast.as_bit() | Direction::East.as_bit();
+        assert_eq!(mask, Some(expected));
+
+        // Neighbors outside the map do not match
+        let mask = pm.neighborhood_mask(UVec2::new(0, 0), |n, _| !*n.value());
+        let expected =
+            Direction::North.as_bit() | Direction::NorthEast.as_bit() | Direction::East.as_bit();
+        assert_eq!(mask, Some(expected));
+        assert_eq!(pm.neighborhood_mask(UVec2::new(8, 0), |_, _| true), None);
+    }
+
+    #[test]
+    fn test_visit_neighborhood_masks() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(2, 2, 9, 5), true);
+        pm.set_pixel((12, 12), true);
+        pm.set_pixel((0, 15), true);
+
+        let rect = URect::new(0, 0, 16, 16);
+        let mut count = 0;
+        pm.visit_neighborhood_masks(
+            &rect,
+            |n, _| *n.value(),
+            |point, matched, mask| {
+                count += 1;
+                assert_eq!(matched, *pm.get_pixel(point).unwrap());
+                assert_eq!(
+                    Some(mask),
+                    pm.neighborhood_mask(point, |n, _| *n.value()),
+                    "point: {}",
+                    point
+                );
+            },
+        );
+        assert_eq!(count, 256);
+
+        let mut visited = Vec::new();
+        pm.visit_neighborhood_masks(
+            &URect::new(1, 1, 3, 2),
+            |n, _| *n.value(),
+            |p, _, mask| {
+                visited.push((p, mask));
+            },
+        );
+        assert_eq!(
+            visited,
+            vec![
+                (UVec2::new(1, 1), Direction::NorthEast.as_bit()),
+                (
+                    UVec2::new(2, 1),
+                    Direction::North.as_bit() | Direction::NorthEast.as_bit()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_boundary_cells() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.draw_circle(&ICircle::new((7, 6), 4), true);
+        pm.draw_rect(&URect::new(12, 0, 16, 16), true);
+
+        let rect = URect::new(0, 0, 16, 16);
+        let cells: Vec<UVec2> = pm.boundary_cells(&rect, |n, _| *n.value()).collect();
+
+        let mut expected = Vec::new();
+        for y in 0..16 {
+            for x in 0..16 {
+                let p = UVec2::new(x, y);
+                if !pm.get_pixel(p).unwrap() {
+                    continue;
+                }
+                let boundary = Direction::iter_cardinal().any(|d| {
+                    let n = p.as_ivec2() + d.unit();
+                    n.x >= 0
+                        && n.y >= 0
+                        && n.x < 16
+                        && n.y < 16
+                        && !pm.get_pixel(n.as_uvec2()).unwrap()
+                });
+                if boundary {
+                    expected.push(p);
+                }
+            }
+        }
+        assert_eq!(cells, expected);
+
+        // Boundaries along the edge of the rect consider pixels beyond it
+        let cells: Vec<UVec2> = pm
+            .boundary_cells(&URect::new(12, 0, 13, 2), |n, _| *n.value())
+            .collect();
+        assert_eq!(cells, vec![UVec2::new(12, 0), UVec2::new(12, 1)]);
+    }
+}
@@ -167,6 +167,39 @@ impl NodePath {
     }
 }
 
+/// A handle to a leaf node of a [crate::PixelMap], which does not borrow the map. It pairs
+/// the path to the node with the version of the map at the time the handle was obtained.
+/// It can be resolved by [crate::PixelMap::resolve] while the path still leads to a leaf
+/// node, being until that node is subdivided or merged.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NodeHandle {
+    path: NodePath,
+    version: u64,
+}
+
+impl NodeHandle {
+    #[inline]
+    #[must_use]
+    pub(crate) fn new(path: NodePath, version: u64) -> Self {
+        Self { path, version }
+    }
+
+    /// Get the path to the node.
+    #[inline]
+    #[must_use]
+    pub fn path(&self) -> NodePath {
+        self.path
+    }
+
+    /// Get the version of the [crate::PixelMap] at the time this handle was obtained.
+    #[inline]
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+}
+
 impl Deref for NodePath {
     type Target = u64;
 
@@ -0,0 +1,198 @@
+use crate::{PNode, PixelMap};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// The classification of a region of a [PixelMap] by [PixelMap::occupancy_summary].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OccupancyClass {
+    /// No leaf node in the region matches the predicate.
+    Empty,
+
+    /// Every leaf node in the region matches the predicate.
+    Full,
+
+    /// Some, but not all, leaf nodes in the region match the predicate.
+    Mixed,
+}
+
+impl OccupancyClass {
+    // Combine the classification of two disjoint regions.
+    #[inline]
+    fn union(self, other: OccupancyClass) -> OccupancyClass {
+        if self == other {
+            self
+        } else {
+            OccupancyClass::Mixed
+        }
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Classify each cell of a uniform grid over this [PixelMap], being the regions of the
+    /// nodes at the given depth, as [OccupancyClass::Empty], [OccupancyClass::Full] or
+    /// [OccupancyClass::Mixed], in a single traversal, such as to cull rendering chunks
+    /// against a camera frustum. This is equivalent to, but faster than, calling
+    /// [PixelMap::all_in_rect] and [PixelMap::any_in_rect] for each cell.
+    ///
+    /// Cells within leaf nodes larger than the cell size are classified by the value of the
+    /// leaf node. Cells are clipped to the [PixelMap::map_rect], and cells outside of it are
+    /// omitted.
+    ///
+    /// # Parameters
+    ///
+    /// - `depth`: The depth of the cells, where the root node has a depth of zero, such that
+    ///   the grid has `2^depth` cells on each side. This is clamped to the
+    ///   [PixelMap::depth_limit].
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle as parameters. This rectangle represents the intersection of the node's
+    ///   region and the cell. It returns `true` if the node is occupied, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// Each cell and its classification, sorted by row, then column.
+    #[must_use]
+    pub fn occupancy_summary<F>(&self, depth: u16, mut predicate: F) -> Vec<(URect, OccupancyClass)>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let cell_size = self.region().size_as::<u32>() >> depth.min(self.depth_limit());
+        let mut cells = Vec::new();
+        summarize(
+            &self.root,
+            cell_size,
+            &self.map_rect,
+            &mut predicate,
+            &mut cells,
+        );
+        cells.sort_unstable_by_key(|(r, _)| (r.min.y, r.min.x));
+        cells
+    }
+}
+
+// Append the cells within the given node to `cells`.
+fn summarize<T, U, F>(
+    node: &PNode<T, U>,
+    cell_size: u32,
+    map_rect: &URect,
+    predicate: &mut F,
+    cells: &mut Vec<(URect, OccupancyClass)>,
+) where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    F: FnMut(&PNode<T, U>, &URect) -> bool,
+{
+    let region = node.region().as_urect();
+    if region.intersect(*map_rect).is_empty() {
+        return;
+    }
+    if node.is_leaf() {
+        // Split the leaf into cells, each classified individually as the predicate may
+        // depend upon the rectangle
+        let mut y = region.min.y;
+        while y < region.max.y {
+            let mut x = region.min.x;
+            while x < region.max.x {
+                let cell = URect::from_corners(UVec2::new(x, y), UVec2::new(x, y) + cell_size)
+                    .intersect(*map_rect);
+                if !cell.is_empty() {
+                    cells.push((cell, classify_leaf(node, &cell, predicate)));
+                }
+                x += cell_size;
+            }
+            y += cell_size;
+        }
+    } else if node.region().size_as::<u32>() > cell_size {
+        for child in node.children().iter() {
+            summarize(child, cell_size, map_rect, predicate, cells);
+        }
+    } else {
+        let cell = region.intersect(*map_rect);
+        cells.push((cell, classify(node, &cell, predicate).unwrap()));
+    }
+}
+
+// Classify the leaf nodes of the given node that overlap the given rectangle, stopping upon
+// finding both occupied and unoccupied leaves. `None` if no leaf overlaps the rectangle.
+fn classify<T, U, F>(node: &PNode<T, U>, rect: &URect, predicate: &mut F) -> Option<OccupancyClass>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    F: FnMut(&PNode<T, U>, &URect) -> bool,
+{
+    let sub_rect = node.region().intersect(rect);
+    if sub_rect.is_empty() {
+        return None;
+    }
+    if node.is_leaf() {
+        return Some(classify_leaf(node, &sub_rect, predicate));
+    }
+    let mut class = None;
+    for child in node.children().iter() {
+        if let Some(child_class) = classify(child, rect, predicate) {
+            let combined = class.map_or(child_class, |c: OccupancyClass| c.union(child_class));
+            if combined == OccupancyClass::Mixed {
+                return Some(combined);
+            }
+            class = Some(combined);
+        }
+    }
+    class
+}
+
+#[inline]
+fn classify_leaf<T, U, F>(node: &PNode<T, U>, rect: &URect, predicate: &mut F) -> OccupancyClass
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    F: FnMut(&PNode<T, U>, &URect) -> bool,
+{
+    if predicate(node, rect) {
+        OccupancyClass::Full
+    } else {
+        OccupancyClass::Empty
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_occupancy_summary() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::new(16, 12), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 8, 8), 1);
+        pm.set_pixel((13, 2), 1);
+
+        let summary = pm.occupancy_summary(1, solid);
+        assert_eq!(
+            summary,
+            vec![
+                (URect::new(0, 0, 8, 8), OccupancyClass::Full),
+                (URect::new(8, 0, 16, 8), OccupancyClass::Mixed),
+                (URect::new(0, 8, 8, 12), OccupancyClass::Empty),
+                (URect::new(8, 8, 16, 12), OccupancyClass::Empty),
+            ]
+        );
+
+        // Cells smaller than leaf nodes, agreeing with per-cell queries
+        let summary = pm.occupancy_summary(3, solid);
+        assert_eq!(summary.len(), 8 * 6);
+        for (cell, class) in summary {
+            let all = pm.all_in_rect(&cell, solid) == Some(true);
+            let any = pm.any_in_rect(&cell, solid) == Some(true);
+            let expected = match (all, any) {
+                (true, _) => OccupancyClass::Full,
+                (false, true) => OccupancyClass::Mixed,
+                (false, false) => OccupancyClass::Empty,
+            };
+            assert_eq!(class, expected, "{:?}", cell);
+        }
+
+        assert_eq!(
+            pm.occupancy_summary(0, solid),
+            vec![(pm.map_rect(), OccupancyClass::Mixed)]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    InclusiveRect, PNode, PixelMap, EAST, NORTH, NORTH_EAST, NORTH_WEST, SOUTH, SOUTH_EAST,
+    SOUTH_WEST, WEST,
+};
+use bevy_math::{IVec2, URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+// The Moore neighborhood, in clockwise order.
+const MOORE: [IVec2; 8] = [
+    WEST, NORTH_WEST, NORTH, NORTH_EAST, EAST, SOUTH_EAST, SOUTH, SOUTH_WEST,
+];
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Trace the outer boundary of the connected shape containing the given seed pixel,
+    /// using Moore-neighbor tracing. Pixels are connected to any of their eight neighbors.
+    /// Unlike [PixelMap::contour], which produces line segments for all shapes in a rectangle,
+    /// this produces the ordered boundary pixels of a single shape.
+    ///
+    /// # Parameters
+    ///
+    /// - `seed`: The coordinates of any pixel within the shape.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the node's region, cropped to the [PixelMap::map_rect].
+    ///   It returns `true` if the node is part of the shape, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The boundary pixels of the shape, in clockwise order. Tracing starts from the pixel reached
+    /// by moving west from `seed` until the next pixel is outside the shape, so a `seed` east of a
+    /// hole in the shape yields the boundary of that hole. A pixel may appear more than once where
+    /// the boundary passes through it twice. Empty if `seed` is outside the [PixelMap::map_rect],
+    /// or does not match the predicate.
+    #[must_use]
+    pub fn trace_outline<F>(&self, seed: UVec2, mut predicate: F) -> Vec<UVec2>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let map_rect = self.map_rect();
+
+        // Reuse the result for the last leaf node, as neighboring pixels often share it
+        let mut last: Option<(&PNode<T, U>, bool)> = None;
+        let mut matches = |p: IVec2| -> bool {
+            if p.x < 0 || p.y < 0 || !map_rect.contains_exclusive(p.as_uvec2()) {
+                return false;
+            }
+            let p = p.as_uvec2();
+            if let Some((node, result)) = last {
+                if node.region().contains_upoint(p) {
+                    return result;
+                }
+            }
+            let node = self.root.find_node(p);
+            let result = predicate(node, &node.region().intersect(&map_rect));
+            last = Some((node, result));
+            result
+        };
+
+        let mut start = seed.as_ivec2();
+        if !matches(start) {
+            return vec![];
+        }
+        while matches(start + WEST) {
+            start += WEST;
+        }
+
+        let mut current = start;
+        let mut backtrack = start + WEST;
+        let mut outline = vec![start.as_uvec2()];
+        loop {
+            let from = MOORE
+                .iter()
+                .position(|d| current + *d == backtrack)
+                .unwrap();
+            let mut next = None;
+            for i in 1..=8 {
+                let p = current + MOORE[(from + i) % 8];
+                if matches(p) {
+                    next = Some((p, current + MOORE[(from + i - 1) % 8]));
+                    break;
+                }
+            }
+            let Some((p, b)) = next else {
+                // An isolated pixel
+                break;
+            };
+            if current == start && outline.len() > 1 && p.as_uvec2() == outline[1] {
+                // About to repeat the first move, so the boundary is closed
+                outline.pop();
+                break;
+            }
+            current = p;
+            backtrack = b;
+            outline.push(current.as_uvec2());
+        }
+        outline
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    fn uvecs(points: &[(u32, u32)]) -> Vec<UVec2> {
+        points.iter().map(|p| UVec2::new(p.0, p.1)).collect()
+    }
+
+    #[test]
+    fn test_trace_outline_rect() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        pm.draw_rect(&URect::new(2, 2, 5, 5), true);
+        let outline = pm.trace_outline(UVec2::new(3, 3), |n, _| *n.value());
+        assert_eq!(
+            outline,
+            uvecs(&[
+                (2, 3),
+                (2, 4),
+                (3, 4),
+                (4, 4),
+                (4, 3),
+                (4, 2),
+                (3, 2),
+                (2, 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_trace_outline_single_shape() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 2, 2), true);
+        pm.draw_rect(&URect::new(8, 8, 10, 10), true);
+
+        let outline = pm.trace_outline(UVec2::new(1, 1), |n, _| *n.value());
+        assert_eq!(outline, uvecs(&[(0, 1), (1, 1), (1, 0), (0, 0)]));
+
+        // Diagonally connected pixels
+        pm.set_pixel((2, 2), true);
+        let outline = pm.trace_outline(UVec2::new(0, 0), |n, _| *n.value());
+        assert_eq!(
+            outline,
+            uvecs(&[(0, 0), (0, 1), (1, 1), (2, 2), (1, 1), (1, 0)])
+        );
+    }
+
+    #[test]
+    fn test_trace_outline_degenerate() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        pm.set_pixel((4, 4), true);
+        assert_eq!(
+            pm.trace_outline(UVec2::new(4, 4), |n, _| *n.value()),
+            uvecs(&[(4, 4)])
+        );
+        assert!(pm
+            .trace_outline(UVec2::new(1, 1), |n, _| *n.value())
+            .is_empty());
+        assert!(pm.trace_outline(UVec2::new(9, 1), |_, _| true).is_empty());
+    }
+}
@@ -0,0 +1,318 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{InclusiveRect, PixelMap, Stats};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// The size of an edge, in pixels, of the square block of pixels packed into each leaf node
+/// of a [PackedBoolMap].
+pub const PACKED_BLOCK_SIZE: u32 = 8;
+
+/// A 2D grid of boolean pixels, storing each `8x8` block of pixels as a 64-bit mask in a
+/// single node of a [PixelMap]. Where a `PixelMap<bool>` would subdivide a noisy block down to
+/// unit pixels, needing up to 85 nodes, a [PackedBoolMap] needs one, while uniform regions
+/// larger than a block are merged as usual. This considerably reduces the node count and
+/// memory of dense, noisy boolean maps.
+///
+/// Bit `y * 8 + x` of a block's mask holds the pixel at `(x, y)` relative to the bottom-left
+/// of the block.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug)]
+pub struct PackedBoolMap<U: Unsigned + NumCast + Copy + Debug = u16> {
+    blocks: PixelMap<u64, U>,
+    map_rect: URect,
+}
+
+impl<U: Unsigned + NumCast + Copy + Debug> PackedBoolMap<U> {
+    /// Create a new [PackedBoolMap].
+    ///
+    /// # Parameters
+    ///
+    /// - `dimensions`: The size of this [PackedBoolMap].
+    /// - `value`: The initial value of all pixels in this [PackedBoolMap].
+    #[must_use]
+    pub fn new(dimensions: &UVec2, value: bool) -> Self {
+        let block_dimensions =
+            (*dimensions + PACKED_BLOCK_SIZE - 1) / PACKED_BLOCK_SIZE * PACKED_BLOCK_SIZE;
+        Self {
+            blocks: PixelMap::new(&block_dimensions, fill(value), PACKED_BLOCK_SIZE as u8),
+            map_rect: URect::from_corners(UVec2::ZERO, *dimensions),
+        }
+    }
+
+    /// Create a new [PackedBoolMap] having the same pixels as the given [PixelMap].
+    #[must_use]
+    pub fn from_pixel_map(pixel_map: &PixelMap<bool, U>) -> Self {
+        let mut packed = Self::new(&pixel_map.map_size(), false);
+        pixel_map.visit(|node, sub_rect| {
+            if *node.value() {
+                packed.draw_rect(sub_rect, true);
+            }
+        });
+        packed
+    }
+
+    /// Create a new [PixelMap] having the same pixels as this [PackedBoolMap], with a
+    /// `pixel_size` of `1`.
+    #[must_use]
+    pub fn to_pixel_map(&self) -> PixelMap<bool, U> {
+        let mut pixel_map = PixelMap::new(&self.map_size(), false, 1);
+        self.blocks.visit(|node, sub_rect| {
+            let sub_rect = sub_rect.intersect(self.map_rect);
+            match *node.value() {
+                0 => {}
+                u64::MAX => {
+                    pixel_map.draw_rect(&sub_rect, true);
+                }
+                mask => {
+                    for y in sub_rect.min.y..sub_rect.max.y {
+                        for x in sub_rect.min.x..sub_rect.max.x {
+                            if mask & (1 << bit_index(UVec2::new(x, y))) != 0 {
+                                pixel_map.set_pixel((x, y), true);
+                            }
+                        }
+                    }
+                }
+            }
+        });
+        pixel_map
+    }
+
+    /// Obtain the dimensions of this [PackedBoolMap].
+    #[inline]
+    #[must_use]
+    pub fn map_size(&self) -> UVec2 {
+        self.map_rect.size()
+    }
+
+    /// Obtain the dimensions of this [PackedBoolMap] as a rectangle.
+    #[inline]
+    #[must_use]
+    pub fn map_rect(&self) -> URect {
+        self.map_rect
+    }
+
+    /// Obtain the underlying [PixelMap] of 64-bit block masks, having a `pixel_size` of
+    /// [PACKED_BLOCK_SIZE]. Its dimensions are those of this [PackedBoolMap], rounded up to a
+    /// multiple of the block size, and bits of pixels outside of [PackedBoolMap::map_rect]
+    /// are unspecified.
+    #[inline]
+    #[must_use]
+    pub fn blocks(&self) -> &PixelMap<u64, U> {
+        &self.blocks
+    }
+
+    /// Obtain statistics of the underlying [PixelMap] of block masks.
+    #[inline]
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        self.blocks.stats()
+    }
+
+    /// Obtain the value of the pixel at the given coordinates.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to obtain the associated value.
+    ///
+    /// # Returns
+    ///
+    /// If the coordinates are outside the [PackedBoolMap::map_rect], `None` is returned.
+    /// Otherwise, `Some` of the value of the pixel is returned.
+    #[inline]
+    #[must_use]
+    pub fn get_pixel<P>(&self, point: P) -> Option<bool>
+    where
+        P: Into<UVec2>,
+    {
+        let point = point.into();
+        if !self.map_rect.contains_exclusive(point) {
+            return None;
+        }
+        let mask = *self.blocks.get_pixel(point)?;
+        Some(mask & (1 << bit_index(point)) != 0)
+    }
+
+    /// Set the value of the pixel at the given coordinates.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to set the associated value.
+    /// - `value`: The value to assign to the pixel.
+    ///
+    /// # Returns
+    ///
+    /// If the coordinates are outside the [PackedBoolMap::map_rect], `false` is returned.
+    /// Otherwise, `true` is returned.
+    #[inline]
+    pub fn set_pixel<P>(&mut self, point: P, value: bool) -> bool
+    where
+        P: Into<UVec2>,
+    {
+        let point = point.into();
+        if !self.map_rect.contains_exclusive(point) {
+            return false;
+        }
+        self.update_block(point, 1 << bit_index(point), value);
+        true
+    }
+
+    /// Set the value of the pixels within the given rectangle. Blocks entirely within the
+    /// rectangle are drawn wholesale, and only the blocks along its edges are masked.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given rectangle.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle overlaps the [PackedBoolMap::map_rect], `true` is returned.
+    /// Otherwise, `false` is returned.
+    pub fn draw_rect(&mut self, rect: &URect, value: bool) -> bool {
+        let mut rect = rect.intersect(self.map_rect);
+        if rect.is_empty() {
+            return false;
+        }
+
+        // Extend to the edge of the blocks where the rectangle reaches the edge of the map,
+        // such that edge blocks may be drawn wholesale, and merge with their neighbors
+        let block_max = self.blocks.map_rect().max;
+        if rect.max.x == self.map_rect.max.x {
+            rect.max.x = block_max.x;
+        }
+        if rect.max.y == self.map_rect.max.y {
+            rect.max.y = block_max.y;
+        }
+
+        // The blocks entirely within the rectangle, which may be empty
+        let inner = URect {
+            min: (rect.min + PACKED_BLOCK_SIZE - 1) / PACKED_BLOCK_SIZE * PACKED_BLOCK_SIZE,
+            max: rect.max / PACKED_BLOCK_SIZE * PACKED_BLOCK_SIZE,
+        };
+        let inner_blocks = inner.min.cmplt(inner.max).all();
+        if inner_blocks {
+            self.blocks.draw_rect(&inner, fill(value));
+        }
+
+        let min = rect.min / PACKED_BLOCK_SIZE;
+        let max = (rect.max - 1) / PACKED_BLOCK_SIZE;
+        for y in min.y..=max.y {
+            let mut x = min.x;
+            while x <= max.x {
+                let block = UVec2::new(x, y) * PACKED_BLOCK_SIZE;
+                if inner_blocks && inner.contains_exclusive(block) {
+                    // Skip to the blocks following the inner blocks of this row
+                    x = inner.max.x / PACKED_BLOCK_SIZE;
+                    continue;
+                }
+                let local = URect::from_corners(block, block + PACKED_BLOCK_SIZE).intersect(rect);
+                self.update_block(block, block_mask(&local), value);
+                x += 1;
+            }
+        }
+        true
+    }
+
+    /// Count the pixels that are `true`.
+    #[must_use]
+    pub fn count_ones(&self) -> u64 {
+        let mut count = 0u64;
+        self.blocks.visit(|node, sub_rect| {
+            let sub_rect = sub_rect.intersect(self.map_rect);
+            if sub_rect.is_empty() {
+                return;
+            }
+            count += match *node.value() {
+                0 => 0,
+                u64::MAX => sub_rect.width() as u64 * sub_rect.height() as u64,
+                mask => (mask & block_mask(&sub_rect)).count_ones() as u64,
+            };
+        });
+        count
+    }
+
+    // Set or clear the bits of the given mask, of the block containing the given point.
+    fn update_block(&mut self, point: UVec2, mask: u64, value: bool) {
+        let Some(&current) = self.blocks.get_pixel(point) else {
+            return;
+        };
+        let new = if value {
+            current | mask
+        } else {
+            current & !mask
+        };
+        if new != current {
+            self.blocks.set_pixel(point, new);
+        }
+    }
+}
+
+#[inline]
+fn fill(value: bool) -> u64 {
+    if value {
+        u64::MAX
+    } else {
+        0
+    }
+}
+
+#[inline]
+fn bit_index(point: UVec2) -> u32 {
+    (point.y % PACKED_BLOCK_SIZE) * PACKED_BLOCK_SIZE + point.x % PACKED_BLOCK_SIZE
+}
+
+// The mask of the bits of the given rectangle, which is contained by a single block.
+fn block_mask(rect: &URect) -> u64 {
+    let min = rect.min % PACKED_BLOCK_SIZE;
+    let width = rect.width();
+    let row = ((1u64 << width) - 1) << min.x;
+    (min.y..min.y + rect.height()).fold(0, |mask, y| mask | row << (y * PACKED_BLOCK_SIZE))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_packed_bool_map() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::new(60, 44), false, 1);
+        pm.scatter(&pm.map_rect(), 0.3, 5, true);
+        pm.draw_rect(&URect::new(3, 5, 40, 30), true);
+
+        let mut packed = PackedBoolMap::from_pixel_map(&pm);
+        assert!(packed.to_pixel_map() == pm);
+        assert!(packed.stats().node_count * 8 < pm.stats().node_count);
+        let mut ones = 0;
+        pm.visit(|n, r| {
+            if *n.value() {
+                ones += r.width() as u64 * r.height() as u64;
+            }
+        });
+        assert_eq!(packed.count_ones(), ones);
+
+        assert!(packed.set_pixel((59, 43), false));
+        assert_eq!(packed.get_pixel((59, 43)), Some(false));
+        assert!(packed.set_pixel((59, 43), true));
+        assert_eq!(packed.get_pixel((59, 43)), Some(true));
+        assert!(!packed.set_pixel((60, 43), true));
+        assert_eq!(packed.get_pixel((60, 43)), None);
+
+        // Drawing to the edge of the map merges the edge blocks
+        assert!(packed.draw_rect(&packed.map_rect(), true));
+        packed
+            .blocks()
+            .visit(|n, _| assert_eq!(*n.value(), u64::MAX));
+        assert_eq!(packed.count_ones(), 60 * 44);
+        assert!(packed.draw_rect(&URect::new(5, 5, 20, 13), false));
+        assert_eq!(packed.count_ones(), 60 * 44 - 15 * 8);
+        assert_eq!(packed.get_pixel((4, 5)), Some(true));
+        assert_eq!(packed.get_pixel((5, 5)), Some(false));
+        assert_eq!(packed.get_pixel((19, 12)), Some(false));
+        assert_eq!(packed.get_pixel((20, 12)), Some(true));
+        assert!(!packed.draw_rect(&URect::new(60, 0, 70, 10), false));
+    }
+}
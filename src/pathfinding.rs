@@ -1,5 +1,5 @@
 use crate::nearest_neighbor::cell_neighbor;
-use crate::{Direction, PNode, PixelMap};
+use crate::{CancelToken, Cancelled, Direction, PNode, PixelMap, QueryScratch};
 use bevy_math::{uvec2, URect, UVec2};
 use fxhash::FxHasher;
 use indexmap::map::Entry::{Occupied, Vacant};
@@ -10,6 +10,14 @@ use std::collections::BinaryHeap;
 use std::fmt::Debug;
 use std::hash::BuildHasherDefault;
 
+mod hpa;
+mod navmesh;
+mod path_cache;
+
+pub use hpa::*;
+pub use navmesh::*;
+pub use path_cache::*;
+
 // Adapted from: https://github.com/evenfurther/pathfinding/blob/main/src/directed/astar.rs
 // Released under a dual Apache 2.0 / MIT free software license.
 
@@ -27,6 +35,23 @@ pub struct PathfindAStarGridResult {
     pub considered_cells: u32,
 }
 
+/// Results from [PixelMap::pathfind_to_any].
+#[derive(Debug, Clone)]
+pub struct PathfindToAnyResult {
+    /// The index, into the given `goals`, of the goal point that was reached.
+    pub goal_index: usize,
+
+    /// A path of points from the desired `start` point to the reached goal point.
+    /// Never empty.
+    pub path: Vec<UVec2>,
+
+    /// The cost value of the path.
+    pub cost: u32,
+
+    /// The number of cells examined to find the shortest path.
+    pub considered_cells: u32,
+}
+
 type FxIndexMap<K, V> = IndexMap<K, V, BuildHasherDefault<FxHasher>>;
 
 impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
@@ -59,11 +84,11 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     ///   [euclidean_heuristic] is provided. But, a heuristic tuned specifically for your use case
     ///   can produce significant performance improvements.
     /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
-    ///    rectangle as parameters. This rectangle represents the intersection of the node's
-    ///    region and the `bounds` parameter supplied to this method. It returns `true` if the
-    ///    node matches the predicate, or `false` otherwise. The `predicate` function is consulted
-    ///    for every node the composes a cell (with short-circuit), and cell is considered to be
-    ///    navigable only if all nodes produce a `true` result.
+    ///   rectangle as parameters. This rectangle represents the intersection of the node's
+    ///   region and the `bounds` parameter supplied to this method. It returns `true` if the
+    ///   node matches the predicate, or `false` otherwise. The `predicate` function is consulted
+    ///   for every node the composes a cell (with short-circuit), and cell is considered to be
+    ///   navigable only if all nodes produce a `true` result.
     ///
     /// # Returns
     ///
@@ -83,8 +108,107 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         start: UVec2,
         goal: UVec2,
         heuristic: H,
-        mut predicate: F,
+        predicate: F,
+    ) -> Option<PathfindAStarGridResult>
+    where
+        H: Fn(&UVec2, &UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.pathfind_a_star_grid_with(
+            bounds,
+            cell_size,
+            start,
+            goal,
+            heuristic,
+            predicate,
+            &mut QueryScratch::new(),
+        )
+    }
+
+    /// As per [PixelMap::pathfind_a_star_grid], but reusing the allocations of the given
+    /// [QueryScratch] for the open set and visited cells of the search, such as when
+    /// pathfinding every frame.
+    ///
+    /// # Parameters
+    ///
+    /// As per [PixelMap::pathfind_a_star_grid], and:
+    ///
+    /// - `scratch`: The [QueryScratch] whose allocations to reuse.
+    #[allow(clippy::too_many_arguments)]
+    pub fn pathfind_a_star_grid_with<H, F>(
+        &self,
+        bounds: &URect,
+        cell_size: u32,
+        start: UVec2,
+        goal: UVec2,
+        heuristic: H,
+        predicate: F,
+        scratch: &mut QueryScratch,
     ) -> Option<PathfindAStarGridResult>
+    where
+        H: Fn(&UVec2, &UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.pathfind_a_star_grid_impl(
+            bounds, cell_size, start, goal, heuristic, predicate, scratch, None,
+        )
+        .ok()
+        .flatten()
+    }
+
+    /// As per [PixelMap::pathfind_a_star_grid], but aborting the search once the given
+    /// [CancelToken] is cancelled, such as when the path is no longer needed by the time a
+    /// worker thread would find it.
+    ///
+    /// # Parameters
+    ///
+    /// As per [PixelMap::pathfind_a_star_grid], and:
+    ///
+    /// - `cancel`: The [CancelToken] observed before examining each cell.
+    ///
+    /// # Returns
+    ///
+    /// [Cancelled] if the search was aborted. Otherwise, as per
+    /// [PixelMap::pathfind_a_star_grid].
+    #[allow(clippy::too_many_arguments)]
+    pub fn pathfind_a_star_grid_cancellable<H, F>(
+        &self,
+        bounds: &URect,
+        cell_size: u32,
+        start: UVec2,
+        goal: UVec2,
+        heuristic: H,
+        predicate: F,
+        cancel: &CancelToken,
+    ) -> Result<Option<PathfindAStarGridResult>, Cancelled>
+    where
+        H: Fn(&UVec2, &UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.pathfind_a_star_grid_impl(
+            bounds,
+            cell_size,
+            start,
+            goal,
+            heuristic,
+            predicate,
+            &mut QueryScratch::new(),
+            Some(cancel),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn pathfind_a_star_grid_impl<H, F>(
+        &self,
+        bounds: &URect,
+        cell_size: u32,
+        start: UVec2,
+        goal: UVec2,
+        heuristic: H,
+        mut predicate: F,
+        scratch: &mut QueryScratch,
+        cancel: Option<&CancelToken>,
+    ) -> Result<Option<PathfindAStarGridResult>, Cancelled>
     where
         H: Fn(&UVec2, &UVec2) -> u32,
         F: FnMut(&PNode<T, U>, &URect) -> bool,
@@ -92,25 +216,33 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         if cell_size < 1 {
             panic!("grid_size must be >= 1");
         }
-        let grid_half_size = cell_size / 2;
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "pathfind_a_star_grid",
+            ?start,
+            ?goal,
+            cell_size,
+            considered_cells = tracing::field::Empty
+        )
+        .entered();
 
         let bounds = bounds.intersect(self.map_rect());
         if bounds.is_empty() {
-            return None;
+            return Ok(None);
         }
 
         let start_node = self.root.find_node(start);
 
         // Special case: start or goal point is out of bounds
         if !bounds.contains(start) || !bounds.contains(goal) {
-            return None;
+            return Ok(None);
         }
 
         // Special case: start node does not match predicate
         {
             let sub_rect = bounds.intersect(start_node.region().as_urect());
             if !predicate(start_node, &sub_rect) {
-                return None;
+                return Ok(None);
             }
         }
 
@@ -119,28 +251,266 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
             let goal_node = self.root.find_node(goal);
             let sub_rect = bounds.intersect(goal_node.region().as_urect());
             if !predicate(goal_node, &sub_rect) {
-                return None;
+                return Ok(None);
             }
         }
 
         // Special case: start and goal are within one node -> draw straight line
         if start_node.region().contains_upoint(goal) {
             let path = vec![start, goal];
-            return Some(PathfindAStarGridResult {
+            return Ok(Some(PathfindAStarGridResult {
                 path,
                 cost: 0,
                 considered_cells: 1,
-            });
+            }));
+        }
+
+        let search = self.search_grid(
+            cell_size,
+            start,
+            &[goal],
+            |p| heuristic(p, &goal),
+            &mut predicate,
+            &mut scratch.grid,
+            cancel,
+        )?;
+
+        #[cfg(feature = "tracing")]
+        span.record("considered_cells", search.considered_cells);
+
+        let Some((_, mut path, cost)) = search.reached else {
+            return Ok(None);
+        };
+
+        // Replace first and last points (cell centres) with start and goal
+        *path.first_mut().unwrap() = start;
+        *path.last_mut().unwrap() = goal;
+
+        Ok(Some(PathfindAStarGridResult {
+            path,
+            cost,
+            considered_cells: search.considered_cells,
+        }))
+    }
+
+    /// Find the shortest path from the `start` point to the nearest reachable point of
+    /// `goals`, using a single A* search over a grid of cells, as per
+    /// [PixelMap::pathfind_a_star_grid]. This is considerably cheaper than searching for a path
+    /// to each goal in turn, such as when selecting the nearest of many candidate targets.
+    ///
+    /// # Parameters
+    ///
+    /// - `bounds`: The rectangle in which contained or overlapping cells will be considered.
+    /// - `cell_size`: The size of an edge, in pixels, of a single square cell in the grid to
+    ///   navigate, as per [PixelMap::pathfind_a_star_grid].
+    /// - `start`: The origin point of the potential path.
+    /// - `goals`: The candidate destination points of the potential path.
+    /// - `heuristic`: The `A*` algorithm heuristic function, which is evaluated against each
+    ///   candidate goal, taking the minimum.
+    /// - `predicate`: A closure that determines whether a leaf node is navigable, as per
+    ///   [PixelMap::pathfind_a_star_grid].
+    ///
+    /// # Returns
+    ///
+    /// `None` is returned under the same conditions as [PixelMap::pathfind_a_star_grid],
+    /// where candidate goals that are out of bounds, or whose nodes do not pass the
+    /// `predicate`, are ignored. Otherwise, `Some` of a [PathfindToAnyResult] is returned.
+    ///
+    /// # Panics
+    ///
+    /// If `cell_size` is zero.
+    pub fn pathfind_to_any<H, F>(
+        &self,
+        bounds: &URect,
+        cell_size: u32,
+        start: UVec2,
+        goals: &[UVec2],
+        heuristic: H,
+        predicate: F,
+    ) -> Option<PathfindToAnyResult>
+    where
+        H: Fn(&UVec2, &UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.pathfind_to_any_impl(bounds, cell_size, start, goals, heuristic, predicate, None)
+            .ok()
+            .flatten()
+    }
+
+    /// As per [PixelMap::pathfind_to_any], but aborting the search once the given
+    /// [CancelToken] is cancelled.
+    ///
+    /// # Parameters
+    ///
+    /// As per [PixelMap::pathfind_to_any], and:
+    ///
+    /// - `cancel`: The [CancelToken] observed before examining each cell.
+    ///
+    /// # Returns
+    ///
+    /// [Cancelled] if the search was aborted. Otherwise, as per [PixelMap::pathfind_to_any].
+    #[allow(clippy::too_many_arguments)]
+    pub fn pathfind_to_any_cancellable<H, F>(
+        &self,
+        bounds: &URect,
+        cell_size: u32,
+        start: UVec2,
+        goals: &[UVec2],
+        heuristic: H,
+        predicate: F,
+        cancel: &CancelToken,
+    ) -> Result<Option<PathfindToAnyResult>, Cancelled>
+    where
+        H: Fn(&UVec2, &UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.pathfind_to_any_impl(
+            bounds,
+            cell_size,
+            start,
+            goals,
+            heuristic,
+            predicate,
+            Some(cancel),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn pathfind_to_any_impl<H, F>(
+        &self,
+        bounds: &URect,
+        cell_size: u32,
+        start: UVec2,
+        goals: &[UVec2],
+        heuristic: H,
+        mut predicate: F,
+        cancel: Option<&CancelToken>,
+    ) -> Result<Option<PathfindToAnyResult>, Cancelled>
+    where
+        H: Fn(&UVec2, &UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        if cell_size < 1 {
+            panic!("grid_size must be >= 1");
+        }
+
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!(
+            "pathfind_to_any",
+            ?start,
+            goals = goals.len(),
+            cell_size,
+            considered_cells = tracing::field::Empty
+        )
+        .entered();
+
+        let bounds = bounds.intersect(self.map_rect());
+        if bounds.is_empty() || !bounds.contains(start) {
+            return Ok(None);
+        }
+
+        let start_node = self.root.find_node(start);
+        {
+            let sub_rect = bounds.intersect(start_node.region().as_urect());
+            if !predicate(start_node, &sub_rect) {
+                return Ok(None);
+            }
+        }
+
+        // Candidate goals that are in bounds, and whose nodes match the predicate,
+        // with their index into `goals`
+        let mut candidates: Vec<(usize, UVec2)> = Vec::with_capacity(goals.len());
+        for (i, goal) in goals.iter().enumerate() {
+            if !bounds.contains(*goal) {
+                continue;
+            }
+            let goal_node = self.root.find_node(*goal);
+            let sub_rect = bounds.intersect(goal_node.region().as_urect());
+            if predicate(goal_node, &sub_rect) {
+                candidates.push((i, *goal));
+            }
+        }
+
+        // Special case: a goal is within the start node -> draw straight line to the nearest
+        if let Some((goal_index, goal)) = candidates
+            .iter()
+            .filter(|(_, goal)| start_node.region().contains_upoint(*goal))
+            .min_by_key(|(_, goal)| heuristic(&start, goal))
+        {
+            return Ok(Some(PathfindToAnyResult {
+                goal_index: *goal_index,
+                path: vec![start, *goal],
+                cost: 0,
+                considered_cells: 1,
+            }));
         }
 
-        let mut to_see = BinaryHeap::with_capacity(512);
+        let points: Vec<UVec2> = candidates.iter().map(|(_, goal)| *goal).collect();
+        let search = self.search_grid(
+            cell_size,
+            start,
+            &points,
+            |p| {
+                points
+                    .iter()
+                    .map(|goal| heuristic(p, goal))
+                    .min()
+                    .unwrap_or(0)
+            },
+            &mut predicate,
+            &mut GridScratch::default(),
+            cancel,
+        )?;
+
+        #[cfg(feature = "tracing")]
+        span.record("considered_cells", search.considered_cells);
+
+        let Some((reached, mut path, cost)) = search.reached else {
+            return Ok(None);
+        };
+        let (goal_index, goal) = candidates[reached];
+
+        // Replace first and last points (cell centres) with start and goal
+        *path.first_mut().unwrap() = start;
+        *path.last_mut().unwrap() = goal;
+
+        Ok(Some(PathfindToAnyResult {
+            goal_index,
+            path,
+            cost,
+            considered_cells: search.considered_cells,
+        }))
+    }
+
+    /// Search a grid of cells, from the cell containing the `start` point, until reaching
+    /// a cell containing any of the `goals` points, or until cancelled.
+    #[allow(clippy::too_many_arguments)]
+    fn search_grid<H, F>(
+        &self,
+        cell_size: u32,
+        start: UVec2,
+        goals: &[UVec2],
+        heuristic: H,
+        predicate: &mut F,
+        scratch: &mut GridScratch,
+        cancel: Option<&CancelToken>,
+    ) -> Result<GridSearch, Cancelled>
+    where
+        H: Fn(&UVec2) -> u32,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let grid_half_size = cell_size / 2;
+
+        let GridScratch { to_see, parents } = scratch;
+        to_see.clear();
+        to_see.reserve(512);
         to_see.push(SmallestCostHolder {
             estimated_cost: 0,
             cost: 0,
             index: 0,
         });
 
-        let mut parents: FxIndexMap<UVec2, (u32, u32)> = FxIndexMap::default();
+        parents.clear();
         let start_cell = cell_for_point(start, cell_size);
         parents.insert(start_cell.min, (u32::MAX, 0));
 
@@ -149,28 +519,24 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         let mut last_successful_direction: Direction = Direction::North;
 
         while let Some(SmallestCostHolder { cost, index, .. }) = to_see.pop() {
+            if let Some(cancel) = cancel {
+                cancel.check()?;
+            }
+
             let cell = {
                 let (cell_min, &(_, c)) = parents.get_index(index as usize).unwrap(); // Cannot fail
                 let cell = URect::from_corners(*cell_min, *cell_min + cell_size);
 
                 // Are we done?
-                if cell.contains(goal) {
+                if let Some(reached) = goals.iter().position(|goal| cell.contains(*goal)) {
                     let path = reverse_path(parents, index);
 
                     // Map path points to cell centres
-                    let mut path: Vec<UVec2> =
+                    let path: Vec<UVec2> =
                         path.into_iter().map(|min| min + grid_half_size).collect();
 
-                    // Replace first point (cell centre) with start
-                    *path.get_mut(0).unwrap() = start;
-
-                    // Replace last point (cell centre) with goal
-                    let len = path.len();
-                    *path.get_mut(len - 1).unwrap() = goal;
-
-                    return Some(PathfindAStarGridResult {
-                        path,
-                        cost,
+                    return Ok(GridSearch {
+                        reached: Some((reached, path, cost)),
                         considered_cells,
                     });
                 }
@@ -193,7 +559,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
                         return;
                     }
 
-                    match self.root.all_leaves_in_rect(&cell, &mut predicate) {
+                    match self.root.all_leaves_in_rect(&cell, &mut *predicate) {
                         Some(pass) => {
                             if !pass {
                                 return;
@@ -209,13 +575,13 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
 
                     match parents.entry(neighbor_cell.min) {
                         Vacant(e) => {
-                            h = heuristic(&(*e.key() + grid_half_size), &goal);
+                            h = heuristic(&(*e.key() + grid_half_size));
                             i = e.index() as u32;
                             e.insert((index, new_cost));
                         }
                         Occupied(mut e) => {
                             if e.get().1 > new_cost {
-                                h = heuristic(&(*e.key() + grid_half_size), &goal);
+                                h = heuristic(&(*e.key() + grid_half_size));
                                 i = e.index() as u32;
                                 e.insert((index, new_cost));
                             } else {
@@ -232,12 +598,35 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
                     });
                 });
         }
-        None
+
+        Ok(GridSearch {
+            reached: None,
+            considered_cells,
+        })
     }
 }
 
+/// The outcome of a grid search by `PixelMap::search_grid`.
+struct GridSearch {
+    /// The index of the goal reached, the path of cell centres to it, and the path cost.
+    reached: Option<(usize, Vec<UVec2>, u32)>,
+
+    /// The number of cells examined.
+    considered_cells: u32,
+}
+
+/// The allocations of a grid search by `PixelMap::search_grid`, retained by a [QueryScratch].
+#[derive(Default)]
+pub(crate) struct GridScratch {
+    /// The open set of cells to examine.
+    to_see: BinaryHeap<SmallestCostHolder<u32>>,
+
+    /// The examined cells, by their `min` point, with the index of their parent and their cost.
+    parents: FxIndexMap<UVec2, (u32, u32)>,
+}
+
 #[inline]
-fn reverse_path(parents: FxIndexMap<UVec2, (u32, u32)>, start: u32) -> Vec<UVec2> {
+fn reverse_path(parents: &FxIndexMap<UVec2, (u32, u32)>, start: u32) -> Vec<UVec2> {
     let mut i = start;
     let path = std::iter::from_fn(|| {
         parents.get_index(i as usize).map(|(node, value)| {
@@ -308,3 +697,62 @@ fn directions(last_success: Direction, direction_toggle: bool) -> [Direction; 8]
 
     all
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_pathfind_to_any() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), true, 1);
+        // A wall separating the start from the geometrically nearest goal
+        pm.draw_rect(&URect::new(20, 0, 22, 60), false);
+
+        let start = UVec2::new(10, 10);
+        let goals = [
+            UVec2::new(30, 10),
+            UVec2::new(10, 40),
+            UVec2::new(21, 30),   // Within the wall
+            UVec2::new(100, 100), // Out of bounds
+        ];
+        let result = pm
+            .pathfind_to_any(
+                &pm.map_rect(),
+                4,
+                start,
+                &goals,
+                euclidean_heuristic,
+                |n, _| *n.value(),
+            )
+            .unwrap();
+        assert_eq!(result.goal_index, 1);
+        assert_eq!(*result.path.first().unwrap(), start);
+        assert_eq!(*result.path.last().unwrap(), goals[1]);
+
+        // Without the nearer goal, the search goes around the wall
+        let result = pm
+            .pathfind_to_any(
+                &pm.map_rect(),
+                4,
+                start,
+                &goals[..1],
+                euclidean_heuristic,
+                |n, _| *n.value(),
+            )
+            .unwrap();
+        assert_eq!(result.goal_index, 0);
+        assert!(result.path.iter().any(|p| p.y >= 60));
+
+        let result = pm.pathfind_to_any(
+            &pm.map_rect(),
+            4,
+            start,
+            &goals[2..],
+            euclidean_heuristic,
+            |n, _| *n.value(),
+        );
+        assert!(result.is_none());
+    }
+}
@@ -0,0 +1,595 @@
+use crate::{InclusiveRect, PNode, PixelMap};
+use bevy_math::{uvec2, URect, UVec2};
+use fxhash::{FxHashMap, FxHashSet};
+use num_traits::{NumCast, Unsigned};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::Debug;
+
+/// A precomputed abstraction of a [PixelMap] for hierarchical pathfinding (HPA*), as built by
+/// [PixelMap::build_hpa_graph]. The map is divided into square clusters, and the navigable
+/// pixels along each border between clusters are grouped into entrances. A transition point
+/// at the middle of each entrance is linked to the other transition points of its cluster,
+/// by the length of the shortest path between them within the cluster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HpaGraph {
+    cluster_size: u32,
+    map_rect: URect,
+    columns: u32,
+    rows: u32,
+
+    // Transitions across the border with the east and north neighbor of each cluster,
+    // as pairs of the points on either side of the border.
+    east: Vec<Vec<(UVec2, UVec2)>>,
+    north: Vec<Vec<(UVec2, UVec2)>>,
+
+    // Path lengths between the transition points of each cluster.
+    intra: Vec<FxHashMap<UVec2, Vec<(UVec2, u32)>>>,
+}
+
+/// Results from [PixelMap::pathfind_hpa].
+#[derive(Debug, Clone)]
+pub struct PathfindHpaResult {
+    /// A path of orthogonally adjacent points from the `start` point to the `goal` point,
+    /// inclusive. Never empty.
+    pub path: Vec<UVec2>,
+
+    /// The number of steps in the path.
+    pub cost: u32,
+}
+
+impl HpaGraph {
+    /// The width and height of each cluster, in pixels.
+    #[inline]
+    #[must_use]
+    pub fn cluster_size(&self) -> u32 {
+        self.cluster_size
+    }
+
+    /// The number of transition points, over all clusters.
+    #[must_use]
+    pub fn transition_count(&self) -> usize {
+        self.intra.iter().map(|i| i.len()).sum()
+    }
+
+    fn cluster_of(&self, point: UVec2) -> usize {
+        let c = point / self.cluster_size;
+        (c.y * self.columns + c.x) as usize
+    }
+
+    fn cluster_rect(&self, cluster: usize) -> URect {
+        let c = uvec2(cluster as u32 % self.columns, cluster as u32 / self.columns);
+        let min = c * self.cluster_size;
+        URect::from_corners(min, min + self.cluster_size).intersect(self.map_rect)
+    }
+
+    fn west_of(&self, cluster: usize) -> Option<usize> {
+        (!(cluster as u32).is_multiple_of(self.columns)).then(|| cluster - 1)
+    }
+
+    fn south_of(&self, cluster: usize) -> Option<usize> {
+        (cluster as u32 >= self.columns).then(|| cluster - self.columns as usize)
+    }
+
+    fn east_of(&self, cluster: usize) -> Option<usize> {
+        (cluster as u32 % self.columns + 1 < self.columns).then(|| cluster + 1)
+    }
+
+    fn north_of(&self, cluster: usize) -> Option<usize> {
+        (cluster as u32 / self.columns + 1 < self.rows).then(|| cluster + self.columns as usize)
+    }
+
+    // The transition points within the given cluster.
+    fn transitions(&self, cluster: usize) -> Vec<UVec2> {
+        let mut points: Vec<UVec2> = self.east[cluster]
+            .iter()
+            .chain(&self.north[cluster])
+            .map(|(a, _)| *a)
+            .collect();
+        let west = self.west_of(cluster).map(|w| &self.east[w]);
+        let south = self.south_of(cluster).map(|s| &self.north[s]);
+        points.extend(west.into_iter().chain(south).flatten().map(|(_, b)| *b));
+        points.sort_unstable_by_key(|p| (p.y, p.x));
+        points.dedup();
+        points
+    }
+
+    // The transition points across a border from the given transition point.
+    fn links(&self, point: UVec2) -> Vec<UVec2> {
+        let cluster = self.cluster_of(point);
+        let outgoing = self.east[cluster]
+            .iter()
+            .chain(&self.north[cluster])
+            .filter(|(a, _)| *a == point)
+            .map(|(_, b)| *b);
+        let west = self.west_of(cluster).map(|w| &self.east[w]);
+        let south = self.south_of(cluster).map(|s| &self.north[s]);
+        let incoming = west
+            .into_iter()
+            .chain(south)
+            .flatten()
+            .filter(|(_, b)| *b == point)
+            .map(|(a, _)| *a);
+        outgoing.chain(incoming).collect()
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Build an [HpaGraph] over this [PixelMap], for finding long paths with
+    /// [PixelMap::pathfind_hpa]. Paths move between orthogonally adjacent pixels.
+    ///
+    /// # Parameters
+    ///
+    /// - `cluster_size`: The width and height of each cluster, in pixels. A power of two
+    ///   aligns clusters with quadtree nodes. Larger clusters make the graph smaller, but
+    ///   each cluster slower to search.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and a cluster.
+    ///   It returns `true` if the node is navigable, or `false` otherwise.
+    ///
+    /// # Panics
+    ///
+    /// If `cluster_size` is zero.
+    #[must_use]
+    pub fn build_hpa_graph<F>(&self, cluster_size: u32, mut predicate: F) -> HpaGraph
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        assert!(cluster_size > 0, "cluster_size must be non-zero");
+        let size = self.map_size();
+        let columns = size.x.div_ceil(cluster_size);
+        let rows = size.y.div_ceil(cluster_size);
+        let count = (columns * rows) as usize;
+        let mut graph = HpaGraph {
+            cluster_size,
+            map_rect: self.map_rect(),
+            columns,
+            rows,
+            east: vec![Vec::new(); count],
+            north: vec![Vec::new(); count],
+            intra: vec![FxHashMap::default(); count],
+        };
+        let all: Vec<usize> = (0..count).collect();
+        self.recache_clusters(&mut graph, &all, &mut predicate);
+        graph
+    }
+
+    /// Update an [HpaGraph] for the modifications made to this [PixelMap] since it was built,
+    /// as indicated by dirty leaf nodes. Only the clusters containing dirty nodes, and their
+    /// neighbors, are recomputed. Dirty state is retained; clear it with [PixelMap::clear_dirty]
+    /// once all dependents have been updated.
+    ///
+    /// # Parameters
+    ///
+    /// - `graph`: The graph to update, built from this [PixelMap].
+    /// - `predicate`: The predicate with which the graph was built.
+    ///
+    /// # Returns
+    ///
+    /// The number of clusters that contained dirty nodes.
+    ///
+    /// # Panics
+    ///
+    /// If `graph` was built from a [PixelMap] of different dimensions.
+    pub fn refresh_hpa_graph<F>(&self, graph: &mut HpaGraph, mut predicate: F) -> usize
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        assert_eq!(graph.map_rect, self.map_rect(), "graph must match the map");
+        let mut dirty: FxHashSet<usize> = FxHashSet::default();
+        self.visit_dirty(|_, sub_rect| {
+            let min = sub_rect.min / graph.cluster_size;
+            let max = (sub_rect.max - UVec2::ONE) / graph.cluster_size;
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    dirty.insert((y * graph.columns + x) as usize);
+                }
+            }
+        });
+        let mut clusters: Vec<usize> = dirty.iter().copied().collect();
+        clusters.sort_unstable();
+        self.recache_clusters(graph, &clusters, &mut predicate);
+        clusters.len()
+    }
+
+    /// Find a path from the `start` point to the `goal` point, by A* search over the transition
+    /// points of an [HpaGraph], refined into individual steps within each cluster crossed.
+    /// The path is near optimal, as clusters are crossed only at transition points.
+    ///
+    /// # Parameters
+    ///
+    /// - `graph`: The graph, built from this [PixelMap], and updated for any modifications.
+    /// - `start`: The origin point of the path.
+    /// - `goal`: The destination point of the path.
+    /// - `predicate`: The predicate with which the graph was built.
+    ///
+    /// # Returns
+    ///
+    /// `None` if either point is outside the [PixelMap::map_rect], or not navigable, or the
+    /// goal is unreachable. Otherwise, `Some` of a [PathfindHpaResult].
+    ///
+    /// # Panics
+    ///
+    /// If `graph` was built from a [PixelMap] of different dimensions.
+    pub fn pathfind_hpa<F>(
+        &self,
+        graph: &HpaGraph,
+        start: UVec2,
+        goal: UVec2,
+        mut predicate: F,
+    ) -> Option<PathfindHpaResult>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        assert_eq!(graph.map_rect, self.map_rect(), "graph must match the map");
+        if !self.map_rect().contains_exclusive(start) || !self.map_rect().contains_exclusive(goal) {
+            return None;
+        }
+        let start_cluster = graph.cluster_of(start);
+        let goal_cluster = graph.cluster_of(goal);
+        let mut clusters: FxHashMap<usize, (URect, Vec<bool>)> = FxHashMap::default();
+        let mut open_cluster = |cluster: usize| {
+            clusters
+                .entry(cluster)
+                .or_insert_with(|| {
+                    let rect = graph.cluster_rect(cluster);
+                    (rect, self.open_cells(&rect, &mut predicate))
+                })
+                .clone()
+        };
+
+        let (start_rect, start_open) = open_cluster(start_cluster);
+        let (goal_rect, goal_open) = open_cluster(goal_cluster);
+        if !start_open[cell_index(&start_rect, start)] || !goal_open[cell_index(&goal_rect, goal)] {
+            return None;
+        }
+
+        // Within a single cluster, a local path may suffice
+        let goal_distances = bfs_distances(&goal_open, &goal_rect, goal);
+        if start_cluster == goal_cluster {
+            if let Some(path) = descend(&goal_distances, &goal_rect, start) {
+                let cost = path.len() as u32 - 1;
+                return Some(PathfindHpaResult { path, cost });
+            }
+        }
+
+        // Temporarily link the start and goal points to the transitions of their clusters
+        let start_distances = bfs_distances(&start_open, &start_rect, start);
+        let from_start: Vec<(UVec2, u32)> = graph
+            .transitions(start_cluster)
+            .into_iter()
+            .filter_map(|t| {
+                let d = start_distances[cell_index(&start_rect, t)];
+                (d != u32::MAX).then_some((t, d))
+            })
+            .collect();
+        let to_goal: FxHashMap<UVec2, u32> = graph
+            .transitions(goal_cluster)
+            .into_iter()
+            .filter_map(|t| {
+                let d = goal_distances[cell_index(&goal_rect, t)];
+                (d != u32::MAX).then_some((t, d))
+            })
+            .collect();
+
+        let heuristic = |p: UVec2| p.x.abs_diff(goal.x) + p.y.abs_diff(goal.y);
+        let mut parents: FxHashMap<UVec2, (u32, UVec2)> = FxHashMap::default();
+        parents.insert(start, (0, start));
+        let mut to_see = BinaryHeap::new();
+        to_see.push(Reverse((heuristic(start), 0u32, start.y, start.x)));
+        let mut found = false;
+        while let Some(Reverse((_, cost, y, x))) = to_see.pop() {
+            let point = uvec2(x, y);
+            if point == goal {
+                found = true;
+                break;
+            }
+            if cost > parents[&point].0 {
+                continue;
+            }
+            let mut successors: Vec<(UVec2, u32)> = Vec::new();
+            if point == start {
+                successors.extend(&from_start);
+            }
+            if let Some(edges) = graph.intra[graph.cluster_of(point)].get(&point) {
+                successors.extend(edges);
+            }
+            successors.extend(graph.links(point).into_iter().map(|p| (p, 1)));
+            if let Some(d) = to_goal.get(&point) {
+                successors.push((goal, *d));
+            }
+            for (next, step) in successors {
+                let new_cost = cost + step;
+                if parents.get(&next).is_none_or(|(c, _)| new_cost < *c) {
+                    parents.insert(next, (new_cost, point));
+                    to_see.push(Reverse((
+                        new_cost + heuristic(next),
+                        new_cost,
+                        next.y,
+                        next.x,
+                    )));
+                }
+            }
+        }
+        if !found {
+            return None;
+        }
+
+        let mut waypoints = vec![goal];
+        let mut point = goal;
+        while point != start {
+            point = parents[&point].1;
+            waypoints.push(point);
+        }
+        waypoints.reverse();
+
+        // Refine each leg between waypoints into individual steps
+        let mut path = vec![start];
+        for leg in waypoints.windows(2) {
+            let (a, b) = (leg[0], leg[1]);
+            let cluster = graph.cluster_of(a);
+            if cluster != graph.cluster_of(b) {
+                path.push(b);
+                continue;
+            }
+            let (rect, open) = open_cluster(cluster);
+            let distances = bfs_distances(&open, &rect, b);
+            path.extend(descend(&distances, &rect, a)?.into_iter().skip(1));
+        }
+        let cost = path.len() as u32 - 1;
+        Some(PathfindHpaResult { path, cost })
+    }
+
+    // Recompute the borders of the given clusters, and the transition paths of the
+    // given clusters and their neighbors.
+    fn recache_clusters<F>(&self, graph: &mut HpaGraph, clusters: &[usize], predicate: &mut F)
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let mut affected: FxHashSet<usize> = FxHashSet::default();
+        for &cluster in clusters {
+            affected.insert(cluster);
+            let neighbors = [
+                graph.west_of(cluster),
+                graph.south_of(cluster),
+                graph.east_of(cluster),
+                graph.north_of(cluster),
+            ];
+            affected.extend(neighbors.into_iter().flatten());
+            for border in [Some(cluster), graph.west_of(cluster)]
+                .into_iter()
+                .flatten()
+            {
+                graph.east[border] = match graph.east_of(border) {
+                    Some(east) => self.entrances(graph, border, east, true, predicate),
+                    None => Vec::new(),
+                };
+            }
+            for border in [Some(cluster), graph.south_of(cluster)]
+                .into_iter()
+                .flatten()
+            {
+                graph.north[border] = match graph.north_of(border) {
+                    Some(north) => self.entrances(graph, border, north, false, predicate),
+                    None => Vec::new(),
+                };
+            }
+        }
+
+        for cluster in affected {
+            let rect = graph.cluster_rect(cluster);
+            let open = self.open_cells(&rect, predicate);
+            let transitions = graph.transitions(cluster);
+            let mut intra: FxHashMap<UVec2, Vec<(UVec2, u32)>> = FxHashMap::default();
+            for t in &transitions {
+                let distances = bfs_distances(&open, &rect, *t);
+                let edges = transitions
+                    .iter()
+                    .filter(|u| *u != t)
+                    .filter_map(|u| {
+                        let d = distances[cell_index(&rect, *u)];
+                        (d != u32::MAX).then_some((*u, d))
+                    })
+                    .collect();
+                intra.insert(*t, edges);
+            }
+            graph.intra[cluster] = intra;
+        }
+    }
+
+    // Find the transitions across the border between two adjacent clusters, at the middle
+    // of each run of navigable pixels on both sides.
+    fn entrances<F>(
+        &self,
+        graph: &HpaGraph,
+        a: usize,
+        b: usize,
+        horizontal: bool,
+        predicate: &mut F,
+    ) -> Vec<(UVec2, UVec2)>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let rect = graph.cluster_rect(a);
+        let (a_edge, b_edge) = if horizontal {
+            (
+                URect::new(rect.max.x - 1, rect.min.y, rect.max.x, rect.max.y),
+                URect::new(rect.max.x, rect.min.y, rect.max.x + 1, rect.max.y),
+            )
+        } else {
+            (
+                URect::new(rect.min.x, rect.max.y - 1, rect.max.x, rect.max.y),
+                URect::new(rect.min.x, rect.max.y, rect.max.x, rect.max.y + 1),
+            )
+        };
+        debug_assert!(graph.cluster_rect(b).contains(b_edge.min));
+        let a_open = self.open_cells(&a_edge, predicate);
+        let b_open = self.open_cells(&b_edge, predicate);
+
+        let mut transitions = Vec::new();
+        let mut run_start = None;
+        for i in 0..=a_open.len() {
+            let open = i < a_open.len() && a_open[i] && b_open[i];
+            match (open, run_start) {
+                (true, None) => run_start = Some(i),
+                (false, Some(s)) => {
+                    let middle = ((s + i - 1) / 2) as u32;
+                    transitions.push(if horizontal {
+                        (
+                            a_edge.min + UVec2::Y * middle,
+                            b_edge.min + UVec2::Y * middle,
+                        )
+                    } else {
+                        (
+                            a_edge.min + UVec2::X * middle,
+                            b_edge.min + UVec2::X * middle,
+                        )
+                    });
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+        transitions
+    }
+
+    // The navigability of each pixel of the given rectangle, in row-major order.
+    fn open_cells<F>(&self, rect: &URect, predicate: &mut F) -> Vec<bool>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let mut open = vec![false; (rect.width() * rect.height()) as usize];
+        self.visit_in_rect(rect, |node, sub_rect| {
+            if predicate(node, sub_rect) {
+                for y in sub_rect.min.y..sub_rect.max.y {
+                    for x in sub_rect.min.x..sub_rect.max.x {
+                        open[cell_index(rect, uvec2(x, y))] = true;
+                    }
+                }
+            }
+        });
+        open
+    }
+}
+
+#[inline]
+fn cell_index(rect: &URect, point: UVec2) -> usize {
+    ((point.y - rect.min.y) * rect.width() + (point.x - rect.min.x)) as usize
+}
+
+// Breadth-first search of the navigable pixels of a rectangle, obtaining the distance
+// of each pixel from the given point, or `u32::MAX` if unreachable.
+fn bfs_distances(open: &[bool], rect: &URect, from: UVec2) -> Vec<u32> {
+    let mut distances = vec![u32::MAX; open.len()];
+    distances[cell_index(rect, from)] = 0;
+    let mut queue = VecDeque::from([from]);
+    while let Some(p) = queue.pop_front() {
+        let d = distances[cell_index(rect, p)];
+        for n in orthogonal_neighbors(rect, p) {
+            let i = cell_index(rect, n);
+            if open[i] && distances[i] == u32::MAX {
+                distances[i] = d + 1;
+                queue.push_back(n);
+            }
+        }
+    }
+    distances
+}
+
+// Follow decreasing distances from the given point, to the origin of the distances.
+fn descend(distances: &[u32], rect: &URect, from: UVec2) -> Option<Vec<UVec2>> {
+    let mut d = distances[cell_index(rect, from)];
+    if d == u32::MAX {
+        return None;
+    }
+    let mut path = vec![from];
+    let mut p = from;
+    while d > 0 {
+        p = orthogonal_neighbors(rect, p).find(|n| distances[cell_index(rect, *n)] == d - 1)?;
+        path.push(p);
+        d -= 1;
+    }
+    Some(path)
+}
+
+fn orthogonal_neighbors(rect: &URect, p: UVec2) -> impl Iterator<Item = UVec2> {
+    let rect = *rect;
+    [
+        (p.x > rect.min.x).then(|| p - UVec2::X),
+        (p.x + 1 < rect.max.x).then(|| p + UVec2::X),
+        (p.y > rect.min.y).then(|| p - UVec2::Y),
+        (p.y + 1 < rect.max.y).then(|| p + UVec2::Y),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    fn assert_valid_path(pm: &PixelMap<bool, u32>, path: &[UVec2], start: UVec2, goal: UVec2) {
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+        for step in path.windows(2) {
+            let d = step[0].as_ivec2() - step[1].as_ivec2();
+            assert_eq!(d.abs().element_sum(), 1, "{:?}", step);
+        }
+        assert!(path.iter().all(|p| pm.get_pixel(*p) == Some(&true)));
+    }
+
+    #[test]
+    fn test_pathfind_hpa() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::new(64, 60), true, 1);
+        pm.draw_rect(&URect::new(30, 0, 34, 50), false);
+        let open = |n: &PNode<bool, u32>, _: &URect| *n.value();
+        let graph = pm.build_hpa_graph(16, open);
+        assert!(graph.transition_count() > 0);
+
+        let (start, goal) = (UVec2::new(2, 2), UVec2::new(62, 2));
+        let result = pm.pathfind_hpa(&graph, start, goal, open).unwrap();
+        assert_valid_path(&pm, &result.path, start, goal);
+        assert_eq!(result.cost as usize, result.path.len() - 1);
+        // Over the wall, within a few steps of optimal
+        assert!(result.cost >= 60 + 2 * 48);
+        assert!(result.cost <= 60 + 2 * 48 + 16);
+
+        // Within a cluster
+        let result = pm
+            .pathfind_hpa(&graph, start, UVec2::new(5, 6), open)
+            .unwrap();
+        assert_eq!(result.cost, 7);
+
+        assert!(pm
+            .pathfind_hpa(&graph, start, UVec2::new(31, 2), open)
+            .is_none());
+        assert!(pm
+            .pathfind_hpa(&graph, start, UVec2::new(64, 2), open)
+            .is_none());
+    }
+
+    #[test]
+    fn test_refresh_hpa_graph() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), true, 1);
+        pm.draw_rect(&URect::new(30, 0, 34, 50), false);
+        let open = |n: &PNode<bool, u32>, _: &URect| *n.value();
+        let (start, goal) = (UVec2::new(2, 2), UVec2::new(62, 2));
+        pm.clear_dirty(true);
+        let mut graph = pm.build_hpa_graph(16, open);
+        assert_eq!(pm.refresh_hpa_graph(&mut graph, open), 0);
+
+        // Seal the gap above the wall
+        pm.draw_rect(&URect::new(30, 50, 34, 64), false);
+        assert_eq!(pm.refresh_hpa_graph(&mut graph, open), 2);
+        pm.clear_dirty(true);
+        assert!(pm.pathfind_hpa(&graph, start, goal, open).is_none());
+
+        // Open a door through the wall
+        pm.draw_rect(&URect::new(30, 10, 34, 12), true);
+        pm.refresh_hpa_graph(&mut graph, open);
+        let result = pm.pathfind_hpa(&graph, start, goal, open).unwrap();
+        assert_valid_path(&pm, &result.path, start, goal);
+        assert_eq!(graph, pm.build_hpa_graph(16, open));
+    }
+}
@@ -0,0 +1,380 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::upload_plan::merge_rects;
+use crate::{PNode, PixelMap};
+use bevy_math::{URect, UVec2, Vec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+
+/// A navigation mesh of convex polygons covering the open space of a [PixelMap], as built by
+/// [PixelMap::build_navmesh]. Each polygon is an axis-aligned rectangle, and polygons sharing
+/// an edge are linked through a portal spanning the shared portion of the edge.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NavMesh {
+    polygons: Vec<URect>,
+    links: Vec<Vec<NavLink>>,
+}
+
+// A traversable link from one polygon to another, through a portal whose end points are
+// given on the left and right hand sides when travelling across it.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NavLink {
+    to: usize,
+    left: Vec2,
+    right: Vec2,
+}
+
+impl NavLink {
+    #[inline]
+    fn midpoint(&self) -> Vec2 {
+        (self.left + self.right) / 2.0
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Build a [NavMesh] over the open space within the given rectangle, for smooth
+    /// any-angle pathfinding without the cell size tuning of [PixelMap::pathfind_a_star_grid].
+    /// Open space is eroded by the agent radius, such that any point within the mesh is a
+    /// valid position for the center of an agent. Leaf nodes of the eroded space are merged
+    /// into larger rectangles, which form the polygons of the mesh.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle to cover. Its edges are treated as walls.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node is navigable, or `false` otherwise.
+    /// - `agent_radius`: The distance, in pixels, that an agent's center must keep from walls on
+    ///   each axis. That is, agents are treated as squares.
+    #[must_use]
+    pub fn build_navmesh<F>(&self, rect: &URect, mut predicate: F, agent_radius: u32) -> NavMesh
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let rect = rect.intersect(self.map_rect());
+        let inner = URect {
+            min: rect.min + agent_radius,
+            max: rect.max.saturating_sub(UVec2::splat(agent_radius)),
+        };
+        if rect.is_empty() || inner.is_empty() {
+            return NavMesh::default();
+        }
+
+        // Erode open space by inflating walls
+        let mut open: PixelMap<bool, U> = PixelMap::new(&self.map_size(), false, self.pixel_size);
+        open.draw_rect(&inner, true);
+        self.visit_in_rect(&rect, |node, sub_rect| {
+            if !predicate(node, sub_rect) {
+                let wall = URect {
+                    min: sub_rect.min.saturating_sub(UVec2::splat(agent_radius)),
+                    max: sub_rect.max + agent_radius,
+                };
+                open.draw_rect(&wall, false);
+            }
+        });
+
+        let mut polygons: Vec<URect> = Vec::new();
+        open.visit_in_rect(&inner, |node, sub_rect| {
+            if *node.value() {
+                polygons.push(*sub_rect);
+            }
+        });
+        let polygons = merge_rects(polygons);
+
+        // Link polygons whose edges touch, by indexing them by their min edges
+        let mut by_min_x: FxHashMap<u32, Vec<usize>> = FxHashMap::default();
+        let mut by_min_y: FxHashMap<u32, Vec<usize>> = FxHashMap::default();
+        for (i, p) in polygons.iter().enumerate() {
+            by_min_x.entry(p.min.x).or_default().push(i);
+            by_min_y.entry(p.min.y).or_default().push(i);
+        }
+        let mut links: Vec<Vec<NavLink>> = vec![Vec::new(); polygons.len()];
+        for (a, pa) in polygons.iter().enumerate() {
+            for b in by_min_x.get(&pa.max.x).into_iter().flatten() {
+                let pb = &polygons[*b];
+                let lo = pa.min.y.max(pb.min.y) as f32;
+                let hi = pa.max.y.min(pb.max.y) as f32;
+                if lo < hi {
+                    let x = pa.max.x as f32;
+                    let (top, bottom) = (Vec2::new(x, hi), Vec2::new(x, lo));
+                    links[a].push(NavLink {
+                        to: *b,
+                        left: top,
+                        right: bottom,
+                    });
+                    links[*b].push(NavLink {
+                        to: a,
+                        left: bottom,
+                        right: top,
+                    });
+                }
+            }
+            for b in by_min_y.get(&pa.max.y).into_iter().flatten() {
+                let pb = &polygons[*b];
+                let lo = pa.min.x.max(pb.min.x) as f32;
+                let hi = pa.max.x.min(pb.max.x) as f32;
+                if lo < hi {
+                    let y = pa.max.y as f32;
+                    let (west, east) = (Vec2::new(lo, y), Vec2::new(hi, y));
+                    links[a].push(NavLink {
+                        to: *b,
+                        left: west,
+                        right: east,
+                    });
+                    links[*b].push(NavLink {
+                        to: a,
+                        left: east,
+                        right: west,
+                    });
+                }
+            }
+        }
+
+        NavMesh { polygons, links }
+    }
+}
+
+impl NavMesh {
+    /// The polygons of the mesh, as rectangles in map coordinates.
+    #[inline]
+    #[must_use]
+    pub fn polygons(&self) -> &[URect] {
+        &self.polygons
+    }
+
+    /// Obtain the indices of the polygons linked to the polygon of the given index.
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        self.links
+            .get(index)
+            .into_iter()
+            .flatten()
+            .map(|link| link.to)
+    }
+
+    /// Find the index of the polygon containing the given point, including its edges.
+    /// Polygons are searched linearly.
+    #[must_use]
+    pub fn polygon_at(&self, point: Vec2) -> Option<usize> {
+        self.polygons
+            .iter()
+            .position(|p| p.as_rect().contains(point))
+    }
+
+    /// Find a path across the mesh, by A* search over polygons, followed by funnel
+    /// smoothing of the portals crossed, such that the path turns only at the corners
+    /// of walls.
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: The origin point of the path.
+    /// - `goal`: The destination point of the path.
+    ///
+    /// # Returns
+    ///
+    /// The points of the path, from `start` to `goal` inclusive, or `None` if either point
+    /// is outside the mesh, or the goal is unreachable.
+    #[must_use]
+    pub fn find_path(&self, start: Vec2, goal: Vec2) -> Option<Vec<Vec2>> {
+        let from = self.polygon_at(start)?;
+        let to = self.polygon_at(goal)?;
+
+        // Polygons are entered at the midpoint of a portal
+        let mut cost = vec![f32::INFINITY; self.polygons.len()];
+        let mut entry = vec![start; self.polygons.len()];
+        let mut came_from: Vec<Option<NavLink>> = vec![None; self.polygons.len()];
+        let mut to_see = BinaryHeap::new();
+        cost[from] = 0.0;
+        to_see.push(Candidate {
+            estimated_cost: start.distance(goal),
+            polygon: from,
+        });
+        while let Some(Candidate {
+            estimated_cost,
+            polygon,
+        }) = to_see.pop()
+        {
+            if polygon == to {
+                break;
+            }
+            if estimated_cost > cost[polygon] + entry[polygon].distance(goal) {
+                continue;
+            }
+            for link in &self.links[polygon] {
+                let point = link.midpoint();
+                let new_cost = cost[polygon] + entry[polygon].distance(point);
+                if new_cost < cost[link.to] {
+                    cost[link.to] = new_cost;
+                    entry[link.to] = point;
+                    came_from[link.to] = Some(NavLink {
+                        to: polygon,
+                        ..*link
+                    });
+                    to_see.push(Candidate {
+                        estimated_cost: new_cost + point.distance(goal),
+                        polygon: link.to,
+                    });
+                }
+            }
+        }
+        if from != to && came_from[to].is_none() {
+            return None;
+        }
+
+        // Collect the portals crossed, with the `to` of each being the polygon left behind
+        let mut portals: Vec<(Vec2, Vec2)> = vec![(goal, goal)];
+        let mut polygon = to;
+        while let Some(link) = came_from[polygon] {
+            portals.push((link.left, link.right));
+            polygon = link.to;
+        }
+        portals.push((start, start));
+        portals.reverse();
+        Some(funnel(&portals))
+    }
+}
+
+// Simple stupid funnel algorithm, as described by Mikko Mononen. The first and last
+// portals are the degenerate start and goal points.
+fn funnel(portals: &[(Vec2, Vec2)]) -> Vec<Vec2> {
+    // Positive when `c` is to the left of the line from `a` to `b`
+    let cross = |a: Vec2, b: Vec2, c: Vec2| (b - a).perp_dot(c - a);
+
+    let mut path = vec![portals[0].0];
+    let mut apex = portals[0].0;
+    let (mut left, mut right) = portals[0];
+    let (mut left_index, mut right_index) = (0, 0);
+    let mut i = 1;
+    while i < portals.len() {
+        let (new_left, new_right) = portals[i];
+
+        // Tighten the right side of the funnel
+        if cross(apex, right, new_right) >= 0.0 {
+            if apex == right || cross(apex, left, new_right) < 0.0 {
+                right = new_right;
+                right_index = i;
+            } else {
+                // The right side crosses the left, which becomes the new apex
+                path.push(left);
+                apex = left;
+                (left, right) = (apex, apex);
+                right_index = left_index;
+                i = left_index + 1;
+                continue;
+            }
+        }
+
+        // Tighten the left side of the funnel
+        if cross(apex, left, new_left) <= 0.0 {
+            if apex == left || cross(apex, right, new_left) > 0.0 {
+                left = new_left;
+                left_index = i;
+            } else {
+                // The left side crosses the right, which becomes the new apex
+                path.push(right);
+                apex = right;
+                (left, right) = (apex, apex);
+                left_index = right_index;
+                i = right_index + 1;
+                continue;
+            }
+        }
+        i += 1;
+    }
+
+    let goal = portals[portals.len() - 1].0;
+    if path.last() != Some(&goal) {
+        path.push(goal);
+    }
+    path
+}
+
+struct Candidate {
+    estimated_cost: f32,
+    polygon: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, for a min-heap
+        other.estimated_cost.total_cmp(&self.estimated_cost)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{vec2, URect, UVec2};
+
+    fn walled_map() -> PixelMap<bool, u32> {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), true, 1);
+        pm.draw_rect(&URect::new(14, 0, 18, 24), false);
+        pm
+    }
+
+    #[test]
+    fn test_build_navmesh() {
+        let pm = walled_map();
+        let mesh = pm.build_navmesh(&pm.map_rect(), |n, _| *n.value(), 1);
+        let area: u32 = mesh.polygons().iter().map(|p| p.width() * p.height()).sum();
+        // The map shrunk by the radius, less the inflated wall
+        assert_eq!(area, 30 * 30 - 6 * 24);
+        for (i, p) in mesh.polygons().iter().enumerate() {
+            assert!(mesh.neighbors(i).all(|n| n != i), "{p:?}");
+        }
+        assert!(mesh.polygon_at(vec2(16.0, 10.0)).is_none());
+        assert!(mesh.polygon_at(vec2(16.0, 28.0)).is_some());
+    }
+
+    #[test]
+    fn test_navmesh_find_path() {
+        let pm = walled_map();
+        let mesh = pm.build_navmesh(&pm.map_rect(), |n, _| *n.value(), 1);
+
+        // Around the top of the wall, turning above its inflated corners
+        let path = mesh.find_path(vec2(4.0, 4.0), vec2(28.0, 4.0)).unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[0], vec2(4.0, 4.0));
+        assert_eq!(path[3], vec2(28.0, 4.0));
+        assert!(path[1].x <= 13.0 && path[1].y == 25.0);
+        assert!(path[2].x >= 19.0 && path[2].y == 25.0);
+        for segment in path.windows(2) {
+            for i in 0..=100 {
+                // Clear of the wall, inflated by the agent radius
+                let p = segment[0].lerp(segment[1], i as f32 / 100.0);
+                assert!(p.x <= 13.001 || p.x >= 18.999 || p.y >= 24.999, "{p}");
+            }
+        }
+
+        // A straight line in open space
+        let path = mesh.find_path(vec2(4.0, 28.0), vec2(28.0, 27.0)).unwrap();
+        assert_eq!(path, vec![vec2(4.0, 28.0), vec2(28.0, 27.0)]);
+
+        assert!(mesh.find_path(vec2(16.0, 4.0), vec2(28.0, 4.0)).is_none());
+
+        // Sealed off
+        let mut pm = walled_map();
+        pm.draw_rect(&URect::new(14, 24, 18, 32), false);
+        let mesh = pm.build_navmesh(&pm.map_rect(), |n, _| *n.value(), 1);
+        assert!(mesh.find_path(vec2(4.0, 4.0), vec2(28.0, 4.0)).is_none());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::upload_plan::merge_rects;
+use crate::PixelMap;
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A cache of computed paths, keyed by their start and goal points, such as for many agents
+/// sharing routes. Each path is associated with a corridor, being its bounding rectangle
+/// expanded by a margin, and is invalidated when pixels within the corridor are modified.
+///
+/// A path remains valid while no pixel along it changes, but a shorter path may be opened
+/// by modifications nearby. The corridor margin controls how far away such modifications
+/// are considered.
+#[derive(Debug, Clone, Default)]
+pub struct PathCache {
+    paths: FxHashMap<(UVec2, UVec2), CachedPath>,
+}
+
+#[derive(Debug, Clone)]
+struct CachedPath {
+    path: Vec<UVec2>,
+    corridor: URect,
+}
+
+impl PathCache {
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of cached paths.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Obtain the cached path from the `start` point to the `goal` point, if any.
+    #[must_use]
+    pub fn get(&self, start: UVec2, goal: UVec2) -> Option<&[UVec2]> {
+        self.paths.get(&(start, goal)).map(|c| c.path.as_slice())
+    }
+
+    /// Cache a path from its first point to its last point, replacing any previously cached
+    /// path between them. Empty paths are not cached.
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: The points of the path, from start to goal.
+    /// - `margin`: The distance, in pixels, by which to expand the bounding rectangle of
+    ///   the path to form its corridor.
+    pub fn insert(&mut self, path: Vec<UVec2>, margin: u32) {
+        let (Some(start), Some(goal)) = (path.first().copied(), path.last().copied()) else {
+            return;
+        };
+        let min = path.iter().fold(start, |m, p| m.min(*p));
+        let max = path.iter().fold(start, |m, p| m.max(*p));
+        let corridor = URect {
+            min: min.saturating_sub(UVec2::splat(margin)),
+            max: max.saturating_add(UVec2::splat(margin + 1)),
+        };
+        self.paths
+            .insert((start, goal), CachedPath { path, corridor });
+    }
+
+    /// Obtain the cached path from the `start` point to the `goal` point, or compute and
+    /// cache it when absent. Paths that cannot be found are not cached.
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: The origin point of the path.
+    /// - `goal`: The destination point of the path.
+    /// - `margin`: The corridor margin, as per [PathCache::insert].
+    /// - `find_path`: A closure that computes the path, such as by
+    ///   [PixelMap::pathfind_a_star_grid].
+    pub fn get_or_insert_with<F>(
+        &mut self,
+        start: UVec2,
+        goal: UVec2,
+        margin: u32,
+        find_path: F,
+    ) -> Option<&[UVec2]>
+    where
+        F: FnOnce() -> Option<Vec<UVec2>>,
+    {
+        if !self.paths.contains_key(&(start, goal)) {
+            self.insert(find_path()?, margin);
+        }
+        self.get(start, goal)
+    }
+
+    /// Remove cached paths whose corridor overlaps the given rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The number of paths removed.
+    pub fn invalidate_rect(&mut self, rect: &URect) -> usize {
+        let count = self.paths.len();
+        self.paths
+            .retain(|_, c| c.corridor.intersect(*rect).is_empty());
+        count - self.paths.len()
+    }
+
+    /// Remove cached paths whose corridor overlaps dirty leaf nodes of the given [PixelMap].
+    /// Call this before clearing dirty state with [PixelMap::clear_dirty], for each map
+    /// modification.
+    ///
+    /// # Returns
+    ///
+    /// The number of paths removed.
+    pub fn invalidate_dirty<T, U>(&mut self, pixel_map: &PixelMap<T, U>) -> usize
+    where
+        T: Copy + PartialEq,
+        U: Unsigned + NumCast + Copy + Debug,
+    {
+        if self.paths.is_empty() || !pixel_map.dirty() {
+            return 0;
+        }
+        let mut dirty: Vec<URect> = Vec::new();
+        pixel_map.visit_dirty(|_, sub_rect| dirty.push(*sub_rect));
+        let dirty = merge_rects(dirty);
+
+        let count = self.paths.len();
+        self.paths.retain(|_, c| {
+            dirty
+                .iter()
+                .all(|rect| c.corridor.intersect(*rect).is_empty())
+        });
+        count - self.paths.len()
+    }
+
+    /// Remove all cached paths.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pathfinding::*;
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_path_cache() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), true, 1);
+        pm.clear_dirty(true);
+        let mut cache = PathCache::new();
+        let find = |pm: &PixelMap<bool, u32>, start: UVec2, goal: UVec2| {
+            pm.pathfind_a_star_grid(
+                &pm.map_rect(),
+                1,
+                start,
+                goal,
+                euclidean_heuristic,
+                |n, _| *n.value(),
+            )
+            .map(|r| r.path)
+        };
+
+        let (a, b) = (UVec2::new(2, 2), UVec2::new(10, 2));
+        let (c, d) = (UVec2::new(2, 50), UVec2::new(10, 50));
+        let mut computed = 0;
+        for _ in 0..3 {
+            for (start, goal) in [(a, b), (c, d)] {
+                let path = cache.get_or_insert_with(start, goal, 2, || {
+                    computed += 1;
+                    find(&pm, start, goal)
+                });
+                assert!(path.is_some());
+            }
+        }
+        assert_eq!(computed, 2);
+        assert_eq!(cache.len(), 2);
+
+        // Modifying pixels away from both corridors invalidates nothing
+        pm.set_pixel((40, 30), false);
+        assert_eq!(cache.invalidate_dirty(&pm), 0);
+        pm.clear_dirty(true);
+
+        // Modifying pixels within the margin of a corridor invalidates its path only
+        pm.set_pixel((6, 4), false);
+        assert_eq!(cache.invalidate_dirty(&pm), 1);
+        pm.clear_dirty(true);
+        assert!(cache.get(a, b).is_none());
+        assert!(cache.get(c, d).is_some());
+
+        assert_eq!(cache.invalidate_rect(&URect::new(0, 0, 64, 64)), 1);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{PNode, PixelMap, URectPixelIterator};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Obtain an iterator over every pixel within the given rectangle, along with its value.
+    /// Leaf nodes are expanded into pixel coordinates lazily, as iteration proceeds.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which pixels will be iterated.
+    ///
+    /// # Returns
+    ///
+    /// An iterator yielding pixel coordinates and values, ordered by leaf node in pre-order,
+    /// and row-major within each leaf node. If `rect` does not overlap the
+    /// [PixelMap::map_rect], the iterator yields nothing.
+    #[must_use]
+    pub fn iter_pixels(&self, rect: &URect) -> PixelMapIterator<'_, T, U> {
+        let rect = rect.intersect(self.map_rect());
+        let mut stack = Vec::with_capacity(32);
+        if !rect.is_empty() {
+            stack.push(&self.root);
+        }
+        PixelMapIterator {
+            rect,
+            stack,
+            current: None,
+        }
+    }
+}
+
+/// Iterates the pixels, and their values, of a [PixelMap] within a rectangle.
+/// See [PixelMap::iter_pixels].
+pub struct PixelMapIterator<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> {
+    rect: URect,
+    stack: Vec<&'a PNode<T, U>>,
+    current: Option<(&'a T, URectPixelIterator)>,
+}
+
+impl<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> Iterator
+    for PixelMapIterator<'a, T, U>
+{
+    type Item = (UVec2, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((value, pixels)) = &mut self.current {
+                if let Some(point) = pixels.next() {
+                    return Some((point, *value));
+                }
+                self.current = None;
+            }
+
+            let node = self.stack.pop()?;
+            let sub_rect = node.region().intersect(&self.rect);
+            if sub_rect.is_empty() {
+                continue;
+            }
+            if node.is_leaf() {
+                self.current = Some((node.value(), URectPixelIterator::new(sub_rect)));
+            } else {
+                // Reversed, such that the first quadrant is visited first
+                self.stack.extend(node.children().iter().rev());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_iter_pixels() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        pm.draw_rect(&URect::new(1, 1, 4, 3), true);
+        pm.set_pixel((6, 7), true);
+
+        let rect = URect::new(0, 0, 8, 8);
+        let mut expected: Vec<(UVec2, bool)> = Vec::new();
+        pm.visit_in_rect(&rect, |node, sub_rect| {
+            for p in URectPixelIterator::new(*sub_rect) {
+                expected.push((p, *node.value()));
+            }
+        });
+        let pixels: Vec<(UVec2, bool)> = pm.iter_pixels(&rect).map(|(p, v)| (p, *v)).collect();
+        assert_eq!(pixels, expected);
+        assert_eq!(pixels.len(), 64);
+        assert_eq!(pixels.iter().filter(|(_, v)| *v).count(), 3 * 2 + 1);
+
+        let pixels: Vec<UVec2> = pm
+            .iter_pixels(&URect::new(5, 6, 7, 9))
+            .filter(|(_, v)| **v)
+            .map(|(p, _)| p)
+            .collect();
+        assert_eq!(pixels, vec![UVec2::new(6, 7)]);
+
+        assert_eq!(pm.iter_pixels(&URect::new(9, 9, 12, 12)).count(), 0);
+    }
+}
@@ -2,19 +2,30 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    ICircle, ILine, IsoLine, PNode, RayCast, RayCastContext, RayCastQuery, RayCastResult, Region,
+    ICapsule, ICircle, ILine, IRing, IRoundedRect, ISector, ITriangle, IsoLine, PNode, RayCast,
+    RayCastContext, RayCastQuery, RayCastResult, Region,
 };
+use crate::active::ActiveChunks;
+use crate::budget::NodeBudget;
+use crate::coalesce::CoalescedDirty;
+use crate::compress::Compression;
 use crate::isocontour::FragmentAccumulator;
+use crate::math::{hash_u64, morton_code};
+use crate::pool::NodePool;
+use crate::watch::Watches;
 use crate::{
-    exclusive_urect, iline, to_cropped_urect, urect_points, CellFill, NeighborOrientation,
-    NodePath, RotatedIRect,
+    iline, inclusive_to_cropped_urect, to_cropped_urect, urect_points, CancelToken, Cancelled,
+    CellFill, InclusiveRect, MapTransform, NeighborOrientation, NodeHandle, NodePath, Quadrant,
+    QueryScratch, RasterShape, RotatedIRect, URectPixelIterator,
 };
-use bevy_math::{ivec2, IVec2, URect, UVec2};
+use bevy_math::{ivec2, IRect, IVec2, URect, UVec2};
 use fxhash::{FxBuildHasher, FxHasher};
 use num_traits::{NumCast, Unsigned, Zero};
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Formatter};
 use std::hash::{BuildHasher, BuildHasherDefault};
+#[cfg(feature = "tracing")]
+use tracing::field::Empty;
 
 /// A two-dimensional map of pixels implemented by an MX quadtree.
 /// The coordinate origin is at the bottom left.
@@ -25,11 +36,42 @@ use std::hash::{BuildHasher, BuildHasherDefault};
 ///   A more useful type could be a `Color`.
 /// - `U`: The unsigned integer type of the coordinates used to index the pixels, typically `u16` (default), or `u32`.
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-#[derive(Clone, PartialEq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
+#[derive(Clone)]
 pub struct PixelMap<T: Copy + PartialEq = bool, U: Unsigned + NumCast + Copy + Debug = u16> {
     pub(crate) root: PNode<T, U>,
+    #[cfg_attr(feature = "rkyv", rkyv(with = crate::archive::URectDef))]
     pub(crate) map_rect: URect,
     pub(crate) pixel_size: u8,
+    #[cfg_attr(feature = "serialize", serde(default))]
+    pub(crate) version: u64,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) clean: bool,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) active: Option<ActiveChunks>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) budget: Option<NodeBudget>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) compression: Option<Compression>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) coalesced: Option<CoalescedDirty>,
+    #[cfg_attr(feature = "serialize", serde(skip, default = "NodePool::default"))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) pool: NodePool<T, U>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) watches: Option<Watches>,
+    #[cfg_attr(feature = "serialize", serde(skip))]
+    #[cfg_attr(feature = "rkyv", rkyv(with = rkyv::with::Skip))]
+    pub(crate) transform: Option<MapTransform>,
 }
 
 impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
@@ -50,7 +92,8 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     #[must_use]
     pub fn new(dimensions: &UVec2, value: T, pixel_size: u8) -> Self {
         assert!(
-            dimensions.x % pixel_size as u32 == 0 && dimensions.y % pixel_size as u32 == 0,
+            dimensions.x.is_multiple_of(pixel_size as u32)
+                && dimensions.y.is_multiple_of(pixel_size as u32),
             "dimensions must be a multiple of pixel_size on each axis"
         );
         assert!(
@@ -65,6 +108,15 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
             root: PNode::new(region, value, true),
             map_rect: URect::from_corners(UVec2::ZERO, *dimensions),
             pixel_size,
+            version: 0,
+            clean: false,
+            active: None,
+            budget: None,
+            compression: None,
+            coalesced: None,
+            pool: NodePool::default(),
+            watches: None,
+            transform: None,
         }
     }
 
@@ -101,12 +153,88 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
 
     /// Discard any existing pixel data and set the root node's value to that provided.
     ///
+    /// The nodes of the discarded quadtree are freed, unless pooling is enabled, in which case
+    /// they are retained up to the pool's capacity for reuse by subsequent drawing. Either
+    /// way, clearing takes time proportional to the number of nodes discarded.
+    /// See [PixelMap::enable_pool].
+    ///
     /// # Parameters
     ///
     /// - `value`: The value to assign to the root node.
     #[inline]
     pub fn clear(&mut self, value: T) {
-        self.root.set_value(value);
+        let changed = self.root.differs_from(&value);
+        if changed {
+            self.touch(&self.map_rect());
+        }
+        self.root
+            .set_value_pooled(value, changed && !self.clean, &mut self.pool);
+    }
+
+    /// Merge sibling leaf nodes whose values are close enough, as determined by the given
+    /// `tolerance` closure, trading exactness for node count. Sibling leaf nodes are otherwise
+    /// only merged when their values are equal, so imported images or noise rarely compress.
+    ///
+    /// Merging proceeds bottom-up, so merged nodes may merge again with their siblings. The
+    /// merged node takes the value of its first (bottom-left) child, and each child is compared
+    /// with that value. Merged nodes are marked dirty.
+    ///
+    /// # Parameters
+    ///
+    /// - `tolerance`: A closure that takes the value of the first child of a node, and the
+    ///   value of another child, and returns `true` if they may be merged.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes that were merged.
+    pub fn decimate_with<F>(&mut self, tolerance: F) -> usize
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let merged = self.root.decimate_with(&tolerance);
+        if merged > 0 {
+            self.touch(&self.map_rect());
+        }
+        merged
+    }
+
+    /// Replace the value of each leaf node with the nearest entry of the given palette, such as
+    /// to posterize an imported image. Leaf nodes that become equal are merged, which can
+    /// dramatically reduce the node count of maps having many distinct values.
+    ///
+    /// # Parameters
+    ///
+    /// - `palette`: The values to which leaf node values are mapped.
+    /// - `metric`: A closure that takes a leaf node value, and a palette entry, and returns the
+    ///   distance between them. Of equally near palette entries, the first is taken.
+    ///
+    /// # Returns
+    ///
+    /// If any leaf node values were changed, `true` is returned. Otherwise, `false` is returned.
+    ///
+    /// # Panics
+    ///
+    /// If `palette` is empty.
+    pub fn quantize<F>(&mut self, palette: &[T], metric: F) -> bool
+    where
+        F: Fn(&T, &T) -> u32,
+    {
+        assert!(!palette.is_empty(), "palette must not be empty");
+        let mut updates: Vec<(URect, T)> = Vec::new();
+        self.visit(|node, sub_rect| {
+            let value = node.value();
+            let nearest = palette
+                .iter()
+                .min_by_key(|entry| metric(value, entry))
+                .unwrap(); // Cannot fail
+            if nearest != value {
+                updates.push((*sub_rect, *nearest));
+            }
+        });
+        for (rect, value) in &updates {
+            self.draw_rect(rect, *value);
+        }
+        !updates.is_empty()
     }
 
     /// Determine if this [PixelMap] is empty, which means that it has no pixel data.
@@ -115,11 +243,12 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         self.root.is_leaf()
     }
 
-    /// Determine if the given point is within the [PixelMap::map_size] bounds.
+    /// Determine if the given point is within the [PixelMap::map_size] bounds, which exclude
+    /// the maximum point.
     #[inline]
     #[must_use]
     pub fn contains(&self, point: UVec2) -> bool {
-        self.map_rect.contains(point)
+        self.map_rect.contains_exclusive(point)
     }
 
     /// Get the value of the pixel at the given coordinates. If the coordinates are outside the
@@ -142,6 +271,33 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         }
     }
 
+    /// Get the values of the pixels at many coordinates at once, such as the positions of
+    /// particles each frame. Rather than descending from the root node for each point, as
+    /// [PixelMap::get_pixel] does, the points are sorted by locality and each node is
+    /// traversed once for all of the points within it.
+    ///
+    /// # Parameters
+    ///
+    /// - `points`: The coordinates of the pixels for which to retrieve the associated values.
+    /// - `out`: A vector that is cleared, then filled with the value of each pixel, in the
+    ///   order of `points`, or `None` where a point is outside the [PixelMap::map_rect].
+    pub fn get_pixels(&self, points: &[UVec2], out: &mut Vec<Option<T>>) {
+        out.clear();
+        out.resize(points.len(), None);
+
+        let origin = self.root.region().point();
+        let mut sorted: Vec<(UVec2, usize)> = points
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| self.contains(**p))
+            .map(|(i, p)| (*p, i))
+            .collect();
+        sorted.sort_unstable_by_key(|(p, _)| morton_code(*p - origin));
+
+        self.root
+            .visit_sorted_points(&sorted, &mut |i, node| out[i] = Some(*node.value()));
+    }
+
     /// Get the node that represents the pixel at the given coordinates. If the coordinates
     /// are outside the region covered by this [PixelMap], None is returned.
     ///
@@ -186,7 +342,170 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         }
     }
 
-    /// Set the value of the pixel at the given coordinates.
+    /// Get the depth of the leaf node that stores the pixel at the given point, where the
+    /// root node has a depth of zero. Each level of depth halves the size of a node's region.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to retrieve the node depth.
+    ///
+    /// # Returns
+    ///
+    /// If the coordinates are outside the region covered by this [PixelMap], `None` is returned.
+    #[inline]
+    #[must_use]
+    pub fn depth_at<P>(&self, point: P) -> Option<u16>
+    where
+        P: Into<UVec2>,
+    {
+        self.find_node(point).map(|node| self.node_depth(node))
+    }
+
+    /// Get the depth of the deepest leaf node of this [PixelMap], where the root node has a
+    /// depth of zero. This traverses the quadtree. The depth is bound by
+    /// [PixelMap::depth_limit], which is reached when any leaf node is of unit pixel size.
+    #[must_use]
+    pub fn max_depth(&self) -> u16 {
+        let mut max_depth = 0;
+        self.root.visit_nodes_in_rect(
+            &self.region().into(),
+            &mut |node, _| {
+                if node.is_leaf() {
+                    max_depth = max_depth.max(self.node_depth(node));
+                }
+                CellFill::Full
+            },
+            &mut 0,
+        );
+        max_depth
+    }
+
+    /// Get the greatest depth that the quadtree of this [PixelMap] can subdivide to, being the
+    /// depth of a node having a region of [PixelMap::pixel_size].
+    #[inline]
+    #[must_use]
+    pub fn depth_limit(&self) -> u16 {
+        (self.region().size_as::<u32>() / self.pixel_size as u32).trailing_zeros() as u16
+    }
+
+    /// Subdivide the leaf nodes overlapping the given rectangle down to the given depth, without
+    /// changing any pixel values, such as to prepare the structure of a region before editing
+    /// its nodes independently, for example from multiple threads. Subdivided nodes are merged
+    /// again by subsequent modifications that pass through them.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which overlapping leaf nodes will be subdivided.
+    /// - `depth`: The depth to which to subdivide, where the root node has a depth of zero.
+    ///   This is clamped to the [PixelMap::depth_limit].
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes that were subdivided.
+    pub fn presubdivide_to(&mut self, rect: &URect, depth: u16) -> usize {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return 0;
+        }
+        let min_size = self.region().size_as::<u32>() >> depth.min(self.depth_limit());
+        let mut count = 0;
+        self.root
+            .presubdivide(&rect, min_size, &mut self.pool, &mut count);
+        count
+    }
+
+    // The depth of the given node, determined by its size relative to the root node.
+    #[inline]
+    fn node_depth(&self, node: &PNode<T, U>) -> u16 {
+        (self.region().size_as::<u32>() / node.region().size_as::<u32>()).trailing_zeros() as u16
+    }
+
+    /// Obtain the version of this [PixelMap], which is incremented by each method that
+    /// modifies pixel data. Marking nodes clean does not change the version.
+    #[inline]
+    #[must_use]
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Get a handle to the leaf node that stores the pixel at the given point. Unlike a node
+    /// reference, a handle does not borrow this [PixelMap], so it may be retained across
+    /// modifications, and cheaply revalidated with [PixelMap::resolve].
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to retrieve a node handle.
+    ///
+    /// # Returns
+    ///
+    /// If the coordinates are outside the [PixelMap::map_rect], `None` is returned.
+    #[inline]
+    #[must_use]
+    pub fn get_handle<P>(&self, point: P) -> Option<NodeHandle>
+    where
+        P: Into<UVec2>,
+    {
+        self.get_path(point)
+            .map(|path| NodeHandle::new(path, self.version))
+    }
+
+    /// Get handles to the leaf nodes that are contained by or overlap the given rectangle.
+    /// See [PixelMap::get_handle].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping leaf nodes will be collected.
+    #[must_use]
+    pub fn leaf_handles_in_rect(&self, rect: &URect) -> Vec<NodeHandle> {
+        let mut handles = Vec::new();
+        self.visit_in_rect(rect, |node, _| {
+            let (_, path) = self.root.node_path(node.region().point());
+            handles.push(NodeHandle::new(path, self.version));
+        });
+        handles
+    }
+
+    /// Resolve a handle obtained from this [PixelMap] to the node it refers to.
+    ///
+    /// # Parameters
+    ///
+    /// - `handle`: A handle obtained from [PixelMap::get_handle] or [PixelMap::leaf_handles_in_rect].
+    ///
+    /// # Returns
+    ///
+    /// The node and its region, cropped to the [PixelMap::map_rect]. If the leaf node has
+    /// since been subdivided, or merged with its siblings, `None` is returned, and a new
+    /// handle must be queried. Modifications elsewhere in this [PixelMap] do not affect
+    /// the handle, though the node's value may have changed if its region was drawn over,
+    /// such as when [PixelMap::version] differs from [NodeHandle::version].
+    #[inline]
+    #[must_use]
+    pub fn resolve(&self, handle: &NodeHandle) -> Option<(&PNode<T, U>, URect)> {
+        // The region of a node is determined by its path, so the handle remains valid while
+        // its path leads to a leaf node
+        let node = self.root.find_node_by_path(handle.path())?;
+        if !node.is_leaf() {
+            return None;
+        }
+        Some((node, node.region().intersect(&self.map_rect)))
+    }
+
+    // Advance the version, and activate the given rectangle, when modifying pixel data.
+    #[inline]
+    pub(crate) fn touch(&mut self, rect: &URect) {
+        self.version = self.version.wrapping_add(1);
+        self.record_compression(rect);
+        if self.clean {
+            return;
+        }
+        self.activate(rect);
+        self.record_modification(rect);
+        self.notify_watches(rect);
+    }
+
+    /// Set the value of the pixel at the given coordinates. Writing the value the pixel
+    /// already has does not mark any node dirty, nor affect the [PixelMap::version], active
+    /// chunks or [PixelMap::watch] subscriptions.
     ///
     /// # Parameters
     ///
@@ -203,13 +522,44 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     {
         let point = point.into();
         if self.contains(point) {
-            self.root.set_pixel(point, self.pixel_size, value);
+            if self
+                .root
+                .set_pixel(point, self.pixel_size, value, !self.clean, &mut self.pool)
+            {
+                self.touch(&URect::from_corners(point, point + UVec2::ONE));
+            }
             true
         } else {
             false
         }
     }
 
+    /// Set the value of the pixel at the given coordinates, as per [PixelMap::set_pixel], only
+    /// if its value differs, such that change feeds contain no spurious events. When the
+    /// value is unchanged, the [PixelMap::version], active chunks and
+    /// [PixelMap::watch] subscriptions are unaffected.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel for which to set the associated value.
+    /// - `value`: The value to assign to the pixel.
+    ///
+    /// # Returns
+    ///
+    /// If the value of the pixel changed, `true` is returned. Otherwise, including when the
+    /// coordinates are outside the [PixelMap::map_rect], `false` is returned.
+    #[inline]
+    pub fn set_pixel_if_changed<P>(&mut self, point: P, value: T) -> bool
+    where
+        P: Into<UVec2>,
+    {
+        let point = point.into();
+        match self.get_pixel(point) {
+            Some(current) if *current != value => self.set_pixel(point, value),
+            _ => false,
+        }
+    }
+
     /// Set the value of all pixel coordinates yielded by the given iterator.
     ///
     /// # Parameters
@@ -234,7 +584,102 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         changed
     }
 
-    /// Set the value of the pixels within the given rectangle.
+    /// Set the value of all pixel coordinates in the given point cloud. Points are bucketed
+    /// by quadrant before insertion, such that each node is descended into once, rather than
+    /// once per point as with [PixelMap::set_pixels]. Points outside the [PixelMap::map_rect]
+    /// are ignored.
+    ///
+    /// # Parameters
+    ///
+    /// - `points`: The pixel coordinates for which to set the associated value.
+    /// - `value`: The value to assign to the given pixels.
+    ///
+    /// # Returns
+    ///
+    /// If any of the coordinates are inside the [PixelMap::map_rect],
+    /// `true` is returned, `false` otherwise.
+    pub fn set_from_point_cloud<I>(&mut self, points: I, value: T) -> bool
+    where
+        I: IntoIterator<Item = IVec2>,
+    {
+        let max = self.map_rect.max.as_ivec2();
+        let mut points: Vec<UVec2> = points
+            .into_iter()
+            .filter(|p| p.cmpge(IVec2::ZERO).all() && p.cmplt(max).all())
+            .map(|p| p.as_uvec2())
+            .collect();
+        if points.is_empty() {
+            return false;
+        }
+        let changed = self.root.set_points(
+            &mut points,
+            self.pixel_size,
+            value,
+            !self.clean,
+            &mut self.pool,
+        );
+        if changed {
+            self.version = self.version.wrapping_add(1);
+            if !self.clean && (self.active.is_some() || self.watches.is_some()) {
+                for point in &points {
+                    let rect = URect::from_corners(*point, *point + UVec2::ONE);
+                    self.activate(&rect);
+                    self.notify_watches(&rect);
+                }
+            }
+        }
+        true
+    }
+
+    /// Set the value of pseudo-random pixels within the given rectangle, such as to scatter
+    /// decorations. Placement is deterministic for a given `seed`, being derived from a
+    /// counter-based hash rather than a random number generator. The gaps between placed pixels
+    /// are drawn directly, so the cost is proportional to the number of pixels placed rather
+    /// than the area of the rectangle, and placed pixels are inserted as per
+    /// [PixelMap::set_from_point_cloud].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which pixels will be placed.
+    /// - `density`: The probability, from `0.0` to `1.0`, that each pixel is placed.
+    /// - `seed`: The seed from which placement is derived.
+    /// - `value`: The value to assign to placed pixels.
+    ///
+    /// # Returns
+    ///
+    /// If any pixels were placed, `true` is returned. Otherwise, `false` is returned.
+    pub fn scatter(&mut self, rect: &URect, density: f32, seed: u64, value: T) -> bool {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() || density.is_nan() || density <= 0.0 {
+            return false;
+        }
+        if density >= 1.0 {
+            return self.draw_rect(&rect, value);
+        }
+
+        let step = self.pixel_size as u32;
+        let cells = (rect.size() + step - 1) / step;
+        let count = cells.x as u64 * cells.y as u64;
+        let ln_miss = (1.0 - density as f64).ln();
+        let mut points = Vec::with_capacity((count as f64 * density as f64) as usize + 1);
+        let mut i = 0u64;
+        for counter in 0.. {
+            // The number of cells skipped before the next placement is geometrically distributed
+            let u = (hash_u64(seed, counter, 0) >> 11) as f64 / (1u64 << 53) as f64;
+            i = i.saturating_add(((1.0 - u).ln() / ln_miss) as u64);
+            if i >= count {
+                break;
+            }
+            let cell = UVec2::new((i % cells.x as u64) as u32, (i / cells.x as u64) as u32);
+            points.push((rect.min + cell * step).as_ivec2());
+            i += 1;
+        }
+        self.set_from_point_cloud(points, value)
+    }
+
+    /// Set the value of the pixels within the given rectangle. Nodes whose pixels already
+    /// have the value are not marked dirty, and if no pixel changes, the [PixelMap::version],
+    /// active chunks and [PixelMap::watch] subscriptions are unaffected.
     ///
     /// # Parameters
     ///
@@ -246,15 +691,95 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     /// If the rectangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
     #[inline]
     pub fn draw_rect(&mut self, rect: &URect, value: T) -> bool {
+        #[cfg(feature = "tracing")]
+        let span = tracing::debug_span!("draw_rect", ?rect, traversed = Empty).entered();
+
         let rect = rect.intersect(self.map_rect());
         if rect.is_empty() {
             return false;
         }
-        self.root.draw_rect(&rect, self.pixel_size, value);
+        let mut traversed = 0u32;
+        if self.root.draw_rect(
+            &rect,
+            self.pixel_size,
+            value,
+            !self.clean,
+            &mut self.pool,
+            &mut traversed,
+        ) {
+            self.touch(&rect);
+        }
+
+        #[cfg(feature = "tracing")]
+        span.record("traversed", traversed);
         true
     }
 
-    /// Set the value of the pixels within the given rotated rectangle.
+    /// Set the value of the pixels within the given rectangle, as per [PixelMap::draw_rect],
+    /// only if any of their values differ, such that change feeds contain no spurious events.
+    /// When no value differs, the [PixelMap::version], active chunks and
+    /// [PixelMap::watch] subscriptions are unaffected.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given rectangle.
+    ///
+    /// # Returns
+    ///
+    /// If the value of any pixel changed, `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_rect_if_changed(&mut self, rect: &URect, value: T) -> bool {
+        match self.all_in_rect(rect, |node, _| *node.value() == value) {
+            Some(false) => self.draw_rect(rect, value),
+            _ => false,
+        }
+    }
+
+    /// Set the value of the pixels within the given rectangle, as per [PixelMap::draw_rect],
+    /// but without marking affected nodes as dirty, as per [PixelMap::without_dirty].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given rectangle.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_rect_clean(&mut self, rect: &URect, value: T) -> bool {
+        self.without_dirty(|pm| pm.draw_rect(rect, value))
+    }
+
+    /// Draw to this [PixelMap] within the given closure without marking affected nodes as
+    /// dirty, such as for bulk world generation that should not leave the whole tree to be
+    /// drained before the first frame. Nodes that were already dirty remain so.
+    ///
+    /// Pixels set by the `set_*` and `draw_*` methods within the closure are not reported as
+    /// changes: chunks are not activated, [PixelMap::watch] subscriptions are not notified,
+    /// and the modification is not recorded for [PixelMap::enforce_node_budget]. The
+    /// [PixelMap::version] is still advanced.
+    ///
+    /// # Parameters
+    ///
+    /// - `f`: A closure that draws to this [PixelMap].
+    ///
+    /// # Returns
+    ///
+    /// The result of `f`.
+    pub fn without_dirty<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&mut Self) -> R,
+    {
+        let clean = std::mem::replace(&mut self.clean, true);
+        let result = f(self);
+        self.clean = clean;
+        result
+    }
+
+    /// Set the value of the pixels within the given rotated rectangle. The rectangle is
+    /// scan-converted into horizontal spans of pixels, as per [RotatedIRect::spans], each
+    /// drawn as a rectangle, such that exactly the pixels of [RotatedIRect::pixels] are set.
     ///
     /// # Parameters
     ///
@@ -269,20 +794,113 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         if rrect.rotation.is_zero() {
             return self.draw_rect(&to_cropped_urect(&rrect.rect), value);
         }
-        let rect = rrect.aabb().intersect(self.map_rect().as_irect());
-        if rect.is_empty() {
+        self.draw_shape(rrect, value)
+    }
+
+    /// Set the value of the pixels along the inside edges of the given rectangle, such as to
+    /// draw a selection box. Each edge is drawn as a single rectangle, rather than pixel by
+    /// pixel. When the rectangle is no more than twice the `thickness` in width or height, it
+    /// is filled.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle whose outline will be set to the associated value.
+    /// - `thickness`: The thickness of the outline, in pixels, inward from the edges of `rect`.
+    /// - `value`: The value to assign to the pixels of the outline.
+    ///
+    /// # Returns
+    ///
+    /// If the outline overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_rect_outline(&mut self, rect: &URect, thickness: u32, value: T) -> bool {
+        if thickness == 0 || rect.is_empty() {
             return false;
         }
-        let inner_rect = to_cropped_urect(&rrect.inner_rect());
-        self.root.draw_rect(&inner_rect, self.pixel_size, value);
-        let inner_rect = exclusive_urect(&inner_rect);
-        for point in rrect.unsigned_pixels() {
-            if inner_rect.contains(point) {
-                continue;
+        if thickness.saturating_mul(2) >= rect.width().min(rect.height()) {
+            return self.draw_rect(rect, value);
+        }
+        let (min, max) = (rect.min, rect.max);
+        let edges = [
+            URect::new(min.x, min.y, max.x, min.y + thickness),
+            URect::new(min.x, max.y - thickness, max.x, max.y),
+            URect::new(
+                min.x,
+                min.y + thickness,
+                min.x + thickness,
+                max.y - thickness,
+            ),
+            URect::new(
+                max.x - thickness,
+                min.y + thickness,
+                max.x,
+                max.y - thickness,
+            ),
+        ];
+        let mut changed = false;
+        for edge in &edges {
+            changed |= self.draw_rect(edge, value);
+        }
+        changed
+    }
+
+    /// Set the value of the pixels along the inside edges of the given rotated rectangle, as
+    /// per [PixelMap::draw_rect_outline]. The outline is drawn as horizontal spans, being the
+    /// spans of the rotated rectangle less those of the rotated rectangle inset by the
+    /// `thickness`.
+    ///
+    /// # Parameters
+    ///
+    /// - `rrect`: The rotated rectangle whose outline will be set to the associated value.
+    /// - `thickness`: The thickness of the outline, in pixels, inward from the edges of `rrect`.
+    /// - `value`: The value to assign to the pixels of the outline.
+    ///
+    /// # Returns
+    ///
+    /// If the outline overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_rotated_rect_outline(
+        &mut self,
+        rrect: &RotatedIRect,
+        thickness: u32,
+        value: T,
+    ) -> bool {
+        if rrect.rotation.is_zero() {
+            return self.draw_rect_outline(&to_cropped_urect(&rrect.rect), thickness, value);
+        }
+        if thickness == 0 {
+            return false;
+        }
+        let rect = rrect.rect;
+        let inset = thickness.min(i32::MAX as u32) as i32;
+        if inset.saturating_mul(2) >= rect.width().min(rect.height()) {
+            return self.draw_rotated_rect(rrect, value);
+        }
+        let inner = RotatedIRect::new(
+            IRect::from_corners(rect.min + inset, rect.max - inset),
+            rrect.rotation,
+        );
+        let inner_spans = inner.spans();
+
+        // Rows of the outline are split into left and right spans where the inner rectangle
+        // is present, each drawn in order, such that consecutive rows coalesce
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut inner_spans = inner_spans.iter().peekable();
+        for span in rrect.spans() {
+            let y = span.start().y;
+            while inner_spans.next_if(|s| s.start().y < y).is_some() {}
+            match inner_spans.next_if(|s| s.start().y == y) {
+                Some(hole) => {
+                    if hole.start().x > span.start().x {
+                        left.push(iline(span.start(), ivec2(hole.start().x - 1, y)));
+                    }
+                    if hole.end().x < span.end().x {
+                        right.push(iline(ivec2(hole.end().x + 1, y), span.end()));
+                    }
+                }
+                None => left.push(span),
             }
-            self.set_pixel(point, value);
         }
-        true
+        let changed = self.draw_spans(&left, value);
+        self.draw_spans(&right, value) || changed
     }
 
     /// Set the value of the pixels within the given circle.
@@ -301,143 +919,615 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         let aabb = to_cropped_urect(&circle.aabb());
         let rect = aabb.intersect(self.map_rect());
         if rect.is_empty() {
-            return false;
+            return false;
+        }
+        if self.root.draw_circle(
+            circle,
+            &self.map_rect,
+            self.pixel_size,
+            value,
+            !self.clean,
+            &mut self.pool,
+        ) {
+            self.touch(&inclusive_to_cropped_urect(&circle.aabb()).intersect(self.map_rect()));
+        }
+        true
+    }
+
+    /// Set the value of the pixels within the given circle, coarsely rasterized at the given
+    /// depth, such as for a minimap layer that needn't be pixel-exact. Each square cell of the
+    /// size of a node at `max_depth` is set to the value if its center is within the circle,
+    /// so nodes are never subdivided beyond that depth. Each level of depth above the
+    /// [PixelMap::depth_limit] reduces the number of cells examined by a factor of four.
+    ///
+    /// # Parameters
+    ///
+    /// - `circle`: The circle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given circle.
+    /// - `max_depth`: The depth at which to rasterize, where the root node has a depth of zero.
+    ///   This is clamped to the [PixelMap::depth_limit], which produces the same result as
+    ///   [PixelMap::draw_circle].
+    ///
+    /// # Returns
+    ///
+    /// If the circle's aabb overlaps the [PixelMap::map_rect], `true` is returned. Otherwise,
+    /// `false` is returned.
+    pub fn draw_circle_lod(&mut self, circle: &ICircle, value: T, max_depth: u16) -> bool {
+        let aabb = inclusive_to_cropped_urect(&circle.aabb()).intersect(self.map_rect());
+        if aabb.is_empty() {
+            return false;
+        }
+        let cell_size = self.region().size_as::<u32>() >> max_depth.min(self.depth_limit());
+        let min = aabb.min / cell_size;
+        let max = (aabb.max - 1) / cell_size;
+
+        // Draw each row of cells as runs of contiguous cells
+        for y in min.y..=max.y {
+            let mut run: Option<u32> = None;
+            for x in min.x..=max.x + 1 {
+                let center = (UVec2::new(x, y) * cell_size + cell_size / 2).as_ivec2();
+                let inside = x <= max.x && circle.contains(center);
+                match (run, inside) {
+                    (None, true) => run = Some(x),
+                    (Some(start), false) => {
+                        let rect = URect::new(
+                            start * cell_size,
+                            y * cell_size,
+                            x * cell_size,
+                            (y + 1) * cell_size,
+                        );
+                        self.draw_rect(&rect, value);
+                        run = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+
+    /// Set the value of the pixels within the given capsule.
+    ///
+    /// # Parameters
+    ///
+    /// - `capsule`: The capsule in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given capsule.
+    ///
+    /// # Returns
+    ///
+    /// If the capsule overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_capsule(&mut self, capsule: &ICapsule, value: T) -> bool {
+        self.draw_shape(capsule, value)
+    }
+
+    /// Set the value of the pixels within the given ring, such as to telegraph an area of
+    /// effect around a point.
+    ///
+    /// # Parameters
+    ///
+    /// - `ring`: The ring in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given ring.
+    ///
+    /// # Returns
+    ///
+    /// If the ring overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_ring(&mut self, ring: &IRing, value: T) -> bool {
+        self.draw_shape(ring, value)
+    }
+
+    /// Set the value of the pixels within the given circular sector, such as to reveal a
+    /// cone of vision.
+    ///
+    /// # Parameters
+    ///
+    /// - `sector`: The sector in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given sector.
+    ///
+    /// # Returns
+    ///
+    /// If the sector overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_sector(&mut self, sector: &ISector, value: T) -> bool {
+        self.draw_shape(sector, value)
+    }
+
+    /// Set the value of the pixels within the given rounded rectangle.
+    ///
+    /// # Parameters
+    ///
+    /// - `rrect`: The rounded rectangle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given rounded rectangle.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_rounded_rect(&mut self, rrect: &IRoundedRect, value: T) -> bool {
+        self.draw_shape(rrect, value)
+    }
+
+    /// Set the value of the pixels within the given triangle.
+    ///
+    /// # Parameters
+    ///
+    /// - `triangle`: The triangle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given triangle.
+    ///
+    /// # Returns
+    ///
+    /// If the triangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_triangle(&mut self, triangle: &ITriangle, value: T) -> bool {
+        self.draw_shape(triangle, value)
+    }
+
+    /// Set the value of the pixels within the given shape.
+    ///
+    /// # Parameters
+    ///
+    /// - `shape`: The [RasterShape] in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given shape.
+    ///
+    /// # Returns
+    ///
+    /// If any pixels of the shape overlap the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_shape<S>(&mut self, shape: &S, value: T) -> bool
+    where
+        S: RasterShape,
+    {
+        if inclusive_to_cropped_urect(&shape.aabb())
+            .intersect(self.map_rect())
+            .is_empty()
+        {
+            return false;
+        }
+        let Some(bounds) = self.map_rect().to_inclusive() else {
+            return false;
+        };
+        self.draw_spans(&shape.spans_in(&bounds.as_irect()), value)
+    }
+
+    /// Set the value of the pixels within the given shape, as per [PixelMap::draw_shape],
+    /// only if any of their values differ, such that change feeds contain no spurious events.
+    /// When no value differs, the [PixelMap::version], active chunks and
+    /// [PixelMap::watch] subscriptions are unaffected.
+    ///
+    /// # Parameters
+    ///
+    /// - `shape`: The shape in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given shape.
+    ///
+    /// # Returns
+    ///
+    /// If the value of any pixel changed, `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_shape_if_changed<S>(&mut self, shape: &S, value: T) -> bool
+    where
+        S: RasterShape,
+    {
+        match self.all_in_shape(shape, |node, _| *node.value() == value) {
+            Some(false) => self.draw_shape(shape, value),
+            _ => false,
+        }
+    }
+
+    // Draw inclusive horizontal pixel spans, ordered bottom to top. Consecutive rows having
+    // identical spans are coalesced into a single rectangle.
+    pub(crate) fn draw_spans(&mut self, spans: &[ILine], value: T) -> bool {
+        let mut changed = false;
+        let mut i = 0;
+        while i < spans.len() {
+            let first = spans[i];
+            let mut last = first;
+            i += 1;
+            while i < spans.len() {
+                let next = spans[i];
+                if next.start().x != first.start().x
+                    || next.end().x != first.end().x
+                    || next.start().y != last.start().y + 1
+                {
+                    break;
+                }
+                last = next;
+                i += 1;
+            }
+            let rect = IRect::from_corners(first.start(), last.end());
+            changed |= self.draw_rect(&inclusive_to_cropped_urect(&rect), value);
+        }
+        changed
+    }
+
+    /// Visit all leaf nodes in this [PixelMap] in pre-order.
+    ///
+    /// # Parameters
+    ///
+    /// - `visitor`: A closure that takes a reference to a leaf node as its only parameter.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    #[inline]
+    pub fn visit<F>(&self, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &URect),
+    {
+        let mut traversed = 0u32;
+        self.root
+            .visit_leaves_in_rect(&self.map_rect(), &mut visitor, &mut traversed);
+        traversed
+    }
+
+    /// Visit all leaf nodes in this [PixelMap] that overlap with the given rectangle.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    #[inline]
+    pub fn visit_in_rect<F>(&self, rect: &URect, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &URect),
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return 0;
+        }
+        let mut traversed = 0u32;
+        self.root
+            .visit_leaves_in_rect(&rect, &mut visitor, &mut traversed);
+        traversed
+    }
+
+    /// Visit all nodes in this [PixelMap] that overlap with the given rectangle, controlling
+    /// navigation with the visitor return value.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `visitor`: A closure that takes a reference to a node, and a reference to a
+    ///   rectangle as parameters. This rectangle represents the intersection of the node's
+    ///   region and the `rect` parameter supplied to this method. It returns a [CellFill]
+    ///   that denotes which child nodes should be visited.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    #[inline]
+    pub fn visit_nodes_in_rect<F>(&self, rect: &URect, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> CellFill,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return 0;
+        }
+        let mut traversed = 0u32;
+        self.root
+            .visit_nodes_in_rect(&rect, &mut visitor, &mut traversed);
+        traversed
+    }
+
+    /// Visit this [PixelMap] in fixed-size square tiles, in row-major order from the origin,
+    /// reporting whether each tile is uniform. Tiles on the right and top edges of the map are
+    /// cropped to the [PixelMap::map_rect]. This is suitable for streaming texture tiles, or
+    /// baking tilemap chunks, where uniform tiles can be handled without examining their pixels.
+    ///
+    /// # Parameters
+    ///
+    /// - `tile_size`: The width and height of each tile. Must be `>= 1`.
+    /// - `visitor`: A closure that takes the rectangle of a tile, and `Some` value if every pixel
+    ///   in the tile has that value, or `None` if the tile has mixed values.
+    ///
+    /// # Panics
+    ///
+    /// If `tile_size` is zero.
+    pub fn visit_tiles<F>(&self, tile_size: u32, mut visitor: F)
+    where
+        F: FnMut(URect, Option<&T>),
+    {
+        assert!(tile_size > 0, "tile_size must be > 0");
+        let map_rect = self.map_rect();
+        let columns = map_rect.max.x.div_ceil(tile_size);
+        let rows = map_rect.max.y.div_ceil(tile_size);
+
+        // Classify all tiles in a single traversal, rather than querying each tile
+        let mut tiles = vec![TileValue::Unvisited; columns as usize * rows as usize];
+        classify_tiles(&self.root, &map_rect, tile_size, columns, &mut tiles);
+
+        for (i, tile_value) in tiles.iter().enumerate() {
+            let x = (i as u32 % columns) * tile_size;
+            let y = (i as u32 / columns) * tile_size;
+            let tile = URect::new(
+                x,
+                y,
+                x.saturating_add(tile_size),
+                y.saturating_add(tile_size),
+            )
+            .intersect(map_rect);
+            let value = match tile_value {
+                TileValue::Uniform(value) => Some(*value),
+                TileValue::Unvisited | TileValue::Mixed => None,
+            };
+            visitor(tile, value);
+        }
+    }
+
+    /// Reduce each fixed-size square tile of this [PixelMap] to a single value, such as a tile
+    /// identifier for a tilemap renderer. Tiles are laid out as per [PixelMap::visit_tiles].
+    /// The reducer is given the leaf nodes overlapping each tile, rather than individual pixels,
+    /// so a tile within a single leaf node is reduced from a single value.
+    ///
+    /// # Parameters
+    ///
+    /// - `tile_size`: The width and height of each tile. Must be `>= 1`.
+    /// - `reducer`: A closure that takes the rectangle of a tile, and the leaf nodes overlapping
+    ///   the tile, as pairs of the node's value, and the node's region cropped to the tile.
+    ///   It returns the reduced value of the tile, such as the value covering the greatest area.
+    ///
+    /// # Returns
+    ///
+    /// The reduced tile values in row-major order from the origin, such that the tile at column
+    /// `x` and row `y` is at index `y * columns + x`, where `columns` is the map width divided
+    /// by `tile_size`, rounded up.
+    ///
+    /// # Panics
+    ///
+    /// If `tile_size` is zero.
+    #[must_use]
+    pub fn to_tile_grid<R, F>(&self, tile_size: u32, mut reducer: F) -> Vec<R>
+    where
+        F: FnMut(&URect, &[(T, URect)]) -> R,
+    {
+        if tile_size < 1 {
+            panic!("tile_size must be >= 1");
+        }
+        let map_rect = self.map_rect();
+        let columns = map_rect.max.x.div_ceil(tile_size);
+        let rows = map_rect.max.y.div_ceil(tile_size);
+        let mut grid = Vec::with_capacity((columns * rows) as usize);
+        let mut leaves: Vec<(T, URect)> = Vec::new();
+        for y in (0..map_rect.max.y).step_by(tile_size as usize) {
+            for x in (0..map_rect.max.x).step_by(tile_size as usize) {
+                let tile = URect::new(x, y, x + tile_size, y + tile_size).intersect(map_rect);
+                leaves.clear();
+                self.root.visit_leaves_in_rect(
+                    &tile,
+                    &mut |node, sub_rect| leaves.push((*node.value(), *sub_rect)),
+                    &mut 0,
+                );
+                grid.push(reducer(&tile, &leaves));
+            }
+        }
+        grid
+    }
+
+    /// Determine if any of the leaf nodes within the bounds of the given rectangle match the predicate.
+    /// Node visitation short-circuits upon the first match.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// `Some(true)` if any of the leaf nodes within the bounds of `rect` match the
+    /// predicate. Or `Some(false)` if no nodes within `rect` match the predicate.
+    /// `None` if `rect` does not overlap the region covered by this [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn any_in_rect<F>(&self, rect: &URect, mut f: F) -> Option<bool>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return None;
+        }
+        self.root.any_leaves_in_rect(&rect, &mut f)
+    }
+
+    /// Determine if all the leaf nodes within the bounds of the given rectangle match the predicate.
+    /// Node visitation short-circuits upon the first match.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// `Some(true)` if all of the leaf nodes within the bounds of `rect` match the
+    /// predicate. Or `Some(false)` if none or some of the nodes within `rect` match the predicate.
+    /// `None` if `rect` does not overlap the region covered by this [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn all_in_rect<F>(&self, rect: &URect, mut f: F) -> Option<bool>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return None;
         }
-        // Implementation note: Despite the aabb check, this still allows drawing circle pixels
-        // beyond the map bounds, within the quadtree region space. Fix me.
-        self.root.draw_circle(circle, self.pixel_size, value);
-        true
+        self.root.all_leaves_in_rect(&rect, &mut f)
     }
 
-    /// Visit all leaf nodes in this [PixelMap] in pre-order.
+    /// Determine if any of the leaf nodes that overlap the given circle match the predicate.
+    /// Nodes are pruned by testing their region against the circle, rather than visiting
+    /// the circle's pixels. Node visitation short-circuits upon the first match.
     ///
     /// # Parameters
     ///
-    /// - `visitor`: A closure that takes a reference to a leaf node as its only parameter.
+    /// - `circle`: The circle in which contained or overlapping nodes will be visited.
+    /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the circle's aabb.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
     ///
     /// # Returns
     ///
-    /// The number of nodes traversed.
-    #[inline]
-    pub fn visit<F>(&self, mut visitor: F) -> u32
+    /// `Some(true)` if any of the leaf nodes overlapping the circle match the
+    /// predicate. Or `Some(false)` if no nodes overlapping the circle match the predicate.
+    /// `None` if the circle does not overlap the region covered by this [PixelMap].
+    #[must_use]
+    pub fn any_in_circle<F>(&self, circle: &ICircle, mut f: F) -> Option<bool>
     where
-        F: FnMut(&PNode<T, U>, &URect),
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
-        let mut traversed = 0u32;
+        let rect = inclusive_to_cropped_urect(&circle.aabb()).intersect(self.map_rect());
+        if rect.is_empty() {
+            return None;
+        }
+        let overlaps = |r: &URect| {
+            r.as_irect()
+                .to_inclusive()
+                .is_some_and(|r| circle.intersects_rect(&r))
+        };
         self.root
-            .visit_leaves_in_rect(&self.map_rect(), &mut visitor, &mut traversed);
-        traversed
+            .any_overlapping_leaves_in_rect(&rect, &overlaps, &mut f)
     }
 
-    /// Visit all leaf nodes in this [PixelMap] that overlap with the given rectangle.
+    /// Determine if all the leaf nodes that overlap the given circle match the predicate.
+    /// Nodes are pruned by testing their region against the circle, rather than visiting
+    /// the circle's pixels. Node visitation short-circuits upon the first mismatch.
     ///
     /// # Parameters
     ///
-    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
-    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
-    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    /// - `circle`: The circle in which contained or overlapping nodes will be visited.
+    /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the circle's aabb.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
     ///
     /// # Returns
     ///
-    /// The number of nodes traversed.
-    #[inline]
-    pub fn visit_in_rect<F>(&self, rect: &URect, mut visitor: F) -> u32
+    /// `Some(true)` if all of the leaf nodes overlapping the circle match the
+    /// predicate. Or `Some(false)` if none or some of the nodes overlapping the circle match the predicate.
+    /// `None` if the circle does not overlap the region covered by this [PixelMap].
+    #[must_use]
+    pub fn all_in_circle<F>(&self, circle: &ICircle, mut f: F) -> Option<bool>
     where
-        F: FnMut(&PNode<T, U>, &URect),
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
-        let rect = rect.intersect(self.map_rect());
+        let rect = inclusive_to_cropped_urect(&circle.aabb()).intersect(self.map_rect());
         if rect.is_empty() {
-            return 0;
+            return None;
         }
-        let mut traversed = 0u32;
+        let overlaps = |r: &URect| {
+            r.as_irect()
+                .to_inclusive()
+                .is_some_and(|r| circle.intersects_rect(&r))
+        };
         self.root
-            .visit_leaves_in_rect(&rect, &mut visitor, &mut traversed);
-        traversed
+            .all_overlapping_leaves_in_rect(&rect, &overlaps, &mut f)
     }
 
-    /// Visit all nodes in this [PixelMap] that overlap with the given rectangle, controlling
-    /// navigation with the visitor return value.
+    /// Determine if any of the leaf nodes that the given line passes through match the predicate.
+    /// See [ILine::passes_through_rect]. Nodes are pruned by testing their region against the line,
+    /// rather than visiting the line's pixels. Node visitation short-circuits upon the first match.
     ///
     /// # Parameters
     ///
-    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
-    /// - `visitor`: A closure that takes a reference to a node, and a reference to a
-    ///   rectangle as parameters. This rectangle represents the intersection of the node's
-    ///   region and the `rect` parameter supplied to this method. It returns a [CellFill]
-    ///   that denotes which child nodes should be visited.
+    /// - `line`: The line along which nodes will be visited.
+    /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the line's aabb.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
     ///
     /// # Returns
     ///
-    /// The number of nodes traversed.
-    #[inline]
-    pub fn visit_nodes_in_rect<F>(&self, rect: &URect, mut visitor: F) -> u32
+    /// `Some(true)` if any of the leaf nodes along the line match the
+    /// predicate. Or `Some(false)` if no nodes along the line match the predicate.
+    /// `None` if the line does not overlap the region covered by this [PixelMap].
+    #[must_use]
+    pub fn any_on_line<F>(&self, line: &ILine, mut f: F) -> Option<bool>
     where
-        F: FnMut(&PNode<T, U>, &URect) -> CellFill,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
-        let rect = rect.intersect(self.map_rect());
+        let rect = inclusive_to_cropped_urect(&line.aabb()).intersect(self.map_rect());
         if rect.is_empty() {
-            return 0;
+            return None;
         }
-        let mut traversed = 0u32;
+        let overlaps = |r: &URect| {
+            r.as_irect()
+                .to_inclusive()
+                .is_some_and(|r| line.passes_through_rect(&r))
+        };
         self.root
-            .visit_nodes_in_rect(&rect, &mut visitor, &mut traversed);
-        traversed
+            .any_overlapping_leaves_in_rect(&rect, &overlaps, &mut f)
     }
 
-    /// Determine if any of the leaf nodes within the bounds of the given rectangle match the predicate.
+    /// Determine if any of the leaf nodes that overlap the given shape match the predicate.
     /// Node visitation short-circuits upon the first match.
     ///
     /// # Parameters
     ///
-    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `shape`: The [RasterShape] in which contained or overlapping nodes will be visited.
     /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
-    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   This rectangle represents the intersection of the node's region and the shape's aabb.
     ///   It returns `true` if the node matches the predicate, or `false` otherwise.
     ///
     /// # Returns
     ///
-    /// `Some(true)` if any of the leaf nodes within the bounds of `rect` match the
-    /// predicate. Or `Some(false)` if no nodes within `rect` match the predicate.
-    /// `None` if `rect` does not overlap the region covered by this [PixelMap].
-    #[inline]
+    /// `Some(true)` if any of the leaf nodes overlapping the shape match the
+    /// predicate. Or `Some(false)` if no nodes overlapping the shape match the predicate.
+    /// `None` if the shape's aabb does not overlap the region covered by this [PixelMap].
     #[must_use]
-    pub fn any_in_rect<F>(&self, rect: &URect, mut f: F) -> Option<bool>
+    pub fn any_in_shape<S, F>(&self, shape: &S, mut f: F) -> Option<bool>
     where
+        S: RasterShape,
         F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
-        let rect = rect.intersect(self.map_rect());
+        let rect = inclusive_to_cropped_urect(&shape.aabb()).intersect(self.map_rect());
         if rect.is_empty() {
             return None;
         }
-        self.root.any_leaves_in_rect(&rect, &mut f)
+        let inner_rects = shape.inner_rects();
+        let spans = shape.spans_in(&rect.as_irect().to_inclusive()?);
+        self.root.any_leaves_in_rect(&rect, &mut |node, sub_rect| {
+            overlaps_shape(&inner_rects, &spans, sub_rect) && f(node, sub_rect)
+        })
     }
 
-    /// Determine if all the leaf nodes within the bounds of the given rectangle match the predicate.
-    /// Node visitation short-circuits upon the first match.
+    /// Determine if all the leaf nodes that overlap the given shape match the predicate.
+    /// Node visitation short-circuits upon the first mismatch.
     ///
     /// # Parameters
     ///
-    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `shape`: The [RasterShape] in which contained or overlapping nodes will be visited.
     /// - `f`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
-    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   This rectangle represents the intersection of the node's region and the shape's aabb.
     ///   It returns `true` if the node matches the predicate, or `false` otherwise.
     ///
     /// # Returns
     ///
-    /// `Some(true)` if all of the leaf nodes within the bounds of `rect` match the
-    /// predicate. Or `Some(false)` if none or some of the nodes within `rect` match the predicate.
-    /// `None` if `rect` does not overlap the region covered by this [PixelMap].
-    #[inline]
+    /// `Some(true)` if all of the leaf nodes overlapping the shape match the
+    /// predicate. Or `Some(false)` if none or some of the nodes overlapping the shape match the predicate.
+    /// `None` if the shape's aabb does not overlap the region covered by this [PixelMap].
     #[must_use]
-    pub fn all_in_rect<F>(&self, rect: &URect, mut f: F) -> Option<bool>
+    pub fn all_in_shape<S, F>(&self, shape: &S, mut f: F) -> Option<bool>
     where
+        S: RasterShape,
         F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
-        let rect = rect.intersect(self.map_rect());
+        let rect = inclusive_to_cropped_urect(&shape.aabb()).intersect(self.map_rect());
         if rect.is_empty() {
             return None;
         }
-        self.root.all_leaves_in_rect(&rect, &mut f)
+        let inner_rects = shape.inner_rects();
+        let spans = shape.spans_in(&rect.as_irect().to_inclusive()?);
+        self.root.all_leaves_in_rect(&rect, &mut |node, sub_rect| {
+            !overlaps_shape(&inner_rects, &spans, sub_rect) || f(node, sub_rect)
+        })
     }
 
     /// Visit all leaf nodes in this [PixelMap] that are marked as dirty. This is useful for examining
@@ -553,6 +1643,34 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         }
     }
 
+    /// Obtain the coordinates of every pixel, within the given rectangle, of the leaf nodes that match
+    /// the given predicate. Unlike [PixelMap::points], which yields node region corners, this
+    /// yields the position of each individual pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The pixel coordinates, ordered by leaf node, and row-major within each leaf node.
+    #[must_use]
+    pub fn matching_points<F>(&self, rect: &URect, mut predicate: F) -> Vec<UVec2>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let mut result = Vec::new();
+        self.visit_in_rect(rect, |node, sub_rect| {
+            if predicate(node, sub_rect) {
+                result.extend(URectPixelIterator::new(*sub_rect));
+            }
+        });
+        result
+    }
+
     /// Obtain the points of node region corners that overlap with the given rectangle, and match
     /// the given predicate. Calls #[Self::collect_points] internally, but takes a guess at a
     /// reasonable capacity for the resulting HashSet.
@@ -580,6 +1698,36 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         result
     }
 
+    /// As per [PixelMap::points], but collecting into the given [QueryScratch], reusing its
+    /// allocations, such as when called every frame.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `offset`: An offset to apply to returned points.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    /// - `scratch`: The [QueryScratch] whose allocations to reuse.
+    ///
+    /// # Returns
+    ///
+    /// The collected points, borrowed from the `scratch`, which are replaced by its next use.
+    pub fn points_with<'a, F>(
+        &self,
+        rect: &URect,
+        offset: IVec2,
+        predicate: F,
+        scratch: &'a mut QueryScratch,
+    ) -> &'a HashSet<IVec2, BuildHasherDefault<FxHasher>>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        scratch.points.clear();
+        self.collect_points(rect, offset, predicate, &mut scratch.points);
+        &scratch.points
+    }
+
     /// Collect the points of node region corners that overlap with the given rectangle, and match
     /// the given predicate.
     ///
@@ -650,6 +1798,111 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         }
     }
 
+    /// Find the first pixel along the given line segment, from its start, whose leaf node
+    /// matches the predicate. Unlike [PixelMap::ray_cast], the line may extend beyond the
+    /// [PixelMap::map_rect], including into negative coordinates, as it is first clipped
+    /// to the map. See [ILine::clip_to_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `line`: The line segment along which nodes will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node as its only parameter.
+    ///   It returns `true` if the node is intersected, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates of the first intersected pixel, or `None` if the line does not
+    /// overlap the [PixelMap::map_rect], or no node along it matches the predicate.
+    #[must_use]
+    pub fn intersect_segment<F>(&self, line: &ILine, mut predicate: F) -> Option<UVec2>
+    where
+        F: FnMut(&PNode<T, U>) -> bool,
+    {
+        let bounds = self.map_rect().to_inclusive()?.as_irect();
+        let line = line.clip_to_rect(&bounds)?;
+        let query = RayCastQuery::new(line);
+        self.ray_cast(query, |node| {
+            if predicate(node) {
+                RayCast::Hit
+            } else {
+                RayCast::Continue
+            }
+        })
+        .collision_point
+    }
+
+    /// Find the first pixel, in scanline order, whose leaf node matches the predicate. Scanline
+    /// order begins at the bottom-left of the rectangle, proceeding along the `x` axis, then
+    /// upward along the `y` axis.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a rectangle as parameters.
+    ///   This rectangle represents the intersection of the node's region and the `rect` parameter supplied to this method.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates and value of the first matching pixel, or `None` if no node within
+    /// the rectangle matches the predicate.
+    #[must_use]
+    pub fn find_first<F>(&self, rect: &URect, mut predicate: F) -> Option<(UVec2, &T)>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        // The first pixel of each matching rectangle is its min point
+        let mut first: Option<UVec2> = None;
+        self.visit_in_rect(rect, |node, sub_rect| {
+            let candidate = sub_rect.min;
+            if first.is_some_and(|f| (f.y, f.x) <= (candidate.y, candidate.x)) {
+                return;
+            }
+            if predicate(node, sub_rect) {
+                first = Some(candidate);
+            }
+        });
+        let first = first?;
+        Some((first, self.root.find_node(first).value()))
+    }
+
+    /// Find the first pixel along the given line segment, from its start, whose leaf node
+    /// matches the predicate. The line may extend beyond the [PixelMap::map_rect], as per
+    /// [PixelMap::intersect_segment]. The predicate is consulted once per leaf node crossed.
+    ///
+    /// # Parameters
+    ///
+    /// - `line`: The line segment along which nodes will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node as its only parameter.
+    ///   It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// The coordinates and value of the first matching pixel, or `None` if the line does not
+    /// overlap the [PixelMap::map_rect], or no node along it matches the predicate.
+    #[must_use]
+    pub fn find_first_along<F>(&self, line: &ILine, mut predicate: F) -> Option<(UVec2, &T)>
+    where
+        F: FnMut(&PNode<T, U>) -> bool,
+    {
+        let bounds = self.map_rect().to_inclusive()?.as_irect();
+        let line = line.clip_to_rect(&bounds)?;
+        // The region of the last leaf node that did not match
+        let mut last: Option<URect> = None;
+        for point in line.pixels() {
+            let point = point.as_uvec2();
+            if last.is_some_and(|region| region.contains_exclusive(point)) {
+                continue;
+            }
+            let node = self.root.find_node(point);
+            if predicate(node) {
+                return Some((point, node.value()));
+            }
+            last = Some(node.region().as_urect());
+        }
+        None
+    }
+
     /// Collect statistics by traversing the [PixelMap] quadtree.
     ///
     /// # Returns
@@ -657,9 +1910,55 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     /// A [Stats] struct that contains information about [PixelMap]'s current state.
     #[must_use]
     pub fn stats(&self) -> Stats {
+        self.subtree_stats(&self.root)
+    }
+
+    /// Obtain statistics of the subtree of the root node's child in the given quadrant.
+    /// Telemetry may sample one quadrant at a time, spreading the cost of traversal over
+    /// successive calls.
+    ///
+    /// # Returns
+    ///
+    /// A [Stats] struct describing the subtree, which is empty if the root node is a leaf.
+    #[must_use]
+    pub fn quadrant_stats(&self, quadrant: Quadrant) -> Stats {
+        if self.root.is_leaf() {
+            Stats::default()
+        } else {
+            self.subtree_stats(&self.root.children()[quadrant as usize])
+        }
+    }
+
+    /// Obtain a [StatsSnapshot] of this [PixelMap], being its [Stats] split by the quadrants
+    /// of the root node, along with its version. Like [PixelMap::stats], this traverses the
+    /// whole tree on the calling thread, which must have access to this [PixelMap]. The
+    /// snapshot is an owned value, such as to compare against later snapshots by version.
+    #[must_use]
+    pub fn stats_snapshot(&self) -> StatsSnapshot {
+        let quadrants = [
+            self.quadrant_stats(Quadrant::BottomLeft),
+            self.quadrant_stats(Quadrant::BottomRight),
+            self.quadrant_stats(Quadrant::TopRight),
+            self.quadrant_stats(Quadrant::TopLeft),
+        ];
+        let root = Stats {
+            node_count: 1,
+            leaf_count: self.root.is_leaf() as usize,
+            unit_count: (self.root.is_leaf() && self.root.region().is_unit(self.pixel_size))
+                as usize,
+        };
+        StatsSnapshot {
+            version: self.version,
+            map_size: self.map_size(),
+            total: quadrants.iter().fold(root, |total, stats| total + *stats),
+            quadrants,
+        }
+    }
+
+    fn subtree_stats(&self, node: &PNode<T, U>) -> Stats {
         let mut stats = Stats::default();
-        self.root.visit_nodes_in_rect(
-            &self.region().into(),
+        node.visit_nodes_in_rect(
+            &node.region().into(),
             &mut |node, _| {
                 stats.node_count += 1;
                 if node.is_leaf() {
@@ -719,7 +2018,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     /// # let mut other: PixelMap<Color, u16> = PixelMap::new(&UVec2::splat(128), Color::BLACK, 1);
     /// // Intersection (AND)
     /// pixel_map.combine(&other, (0, 0), |c1, c2| {
-    ///    if c1 == &Color::BLACK && c2 == &Color::BLACK {
+    ///   if c1 == &Color::BLACK && c2 == &Color::BLACK {
     ///       Color::BLACK
     ///   } else {
     ///      Color::WHITE
@@ -731,20 +2030,92 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
         P: Into<UVec2>,
         F: Fn(&T, &T) -> T,
     {
-        let offset = offset.into();
+        let _ = self.combine_impl(other, offset.into(), combiner, None);
+    }
+
+    /// As per [PixelMap::combine], but aborting once the given [CancelToken] is cancelled,
+    /// such as when combining on a worker thread while new edits come in. Cancellation is
+    /// observed while sampling the other [PixelMap], before this [PixelMap] is modified, so
+    /// an aborted combination leaves this [PixelMap] unchanged.
+    ///
+    /// # Parameters
+    ///
+    /// As per [PixelMap::combine], and:
+    ///
+    /// - `cancel`: The [CancelToken] observed before sampling each leaf node.
+    ///
+    /// # Returns
+    ///
+    /// [Cancelled] if the combination was aborted.
+    pub fn combine_cancellable<P, F>(
+        &mut self,
+        other: &Self,
+        offset: P,
+        combiner: F,
+        cancel: &CancelToken,
+    ) -> Result<(), Cancelled>
+    where
+        P: Into<UVec2>,
+        F: Fn(&T, &T) -> T,
+    {
+        self.combine_impl(other, offset.into(), combiner, Some(cancel))
+    }
+
+    fn combine_impl<F>(
+        &mut self,
+        other: &Self,
+        offset: UVec2,
+        combiner: F,
+        cancel: Option<&CancelToken>,
+    ) -> Result<(), Cancelled>
+    where
+        F: Fn(&T, &T) -> T,
+    {
+        let is_cancelled = || cancel.is_some_and(|c| c.is_cancelled());
+
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::debug_span!("combine", ?offset, traversed = Empty, updates = Empty).entered();
+
         let mut updates: Vec<(URect, T)> = Vec::new();
-        self.visit(|node, _| {
+        #[cfg(feature = "tracing")]
+        let mut other_traversed = 0;
+        let mut visitor = |node: &PNode<T, U>, _: &URect| {
+            if is_cancelled() {
+                return;
+            }
             let mut region_rect: URect = node.region().into();
             region_rect = URect::from_corners(region_rect.min + offset, region_rect.max + offset);
-            other.visit_in_rect(&region_rect, |other_node, sub_rect| {
+            let mut other_visitor = |other_node: &PNode<T, U>, sub_rect: &URect| {
                 let value = combiner(node.value(), other_node.value());
                 let sub_rect = URect::from_corners(sub_rect.min - offset, sub_rect.max - offset);
                 updates.push((sub_rect, value));
-            });
-        });
+            };
+            #[cfg(feature = "tracing")]
+            {
+                other_traversed += other.visit_in_rect(&region_rect, &mut other_visitor);
+            }
+            #[cfg(not(feature = "tracing"))]
+            other.visit_in_rect(&region_rect, &mut other_visitor);
+        };
+        #[cfg(feature = "tracing")]
+        let traversed = self.visit(&mut visitor);
+        #[cfg(not(feature = "tracing"))]
+        self.visit(&mut visitor);
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("traversed", traversed + other_traversed);
+            span.record("updates", updates.len());
+        }
+
+        if let Some(cancel) = cancel {
+            cancel.check()?;
+        }
         for (rect, value) in updates {
             self.draw_rect(&rect, value);
         }
+        Ok(())
     }
 
     /// Generate a quad mesh that contains a triangulated quad for each leaf node accepted by
@@ -832,8 +2203,8 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     ///
     /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
     /// - `predicate`: A closure that takes a reference to a leaf node,
-    ///    and a reference to the rectangle that is the effective intersection of the node's
-    ///    region and the `rect` parameter supplied to this method.
+    ///   and a reference to the rectangle that is the effective intersection of the node's
+    ///   region and the `rect` parameter supplied to this method.
     ///
     /// # Returns
     ///
@@ -841,30 +2212,120 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
     /// `predicate` closure. The number of segments returned are the minimum possible,
     /// in that one segment does not share continuity with any other segment.
     #[must_use]
-    pub fn contour<F>(&self, rect: &URect, mut predicate: F) -> Vec<IsoLine>
+    pub fn contour<F>(&self, rect: &URect, predicate: F) -> Vec<IsoLine>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.contour_into(rect, predicate, &mut FragmentAccumulator::new(256), None)
+            .unwrap_or_default()
+    }
+
+    /// As per [PixelMap::contour], but reusing the allocations of the given [QueryScratch]
+    /// for the accumulation of line segments, such as when called every frame.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node,
+    ///   and a reference to the rectangle that is the effective intersection of the node's
+    ///   region and the `rect` parameter supplied to this method.
+    /// - `scratch`: The [QueryScratch] whose allocations to reuse.
+    #[must_use]
+    pub fn contour_with<F>(
+        &self,
+        rect: &URect,
+        predicate: F,
+        scratch: &mut QueryScratch,
+    ) -> Vec<IsoLine>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.contour_into(rect, predicate, &mut scratch.fragments, None)
+            .unwrap_or_default()
+    }
+
+    /// As per [PixelMap::contour], but aborting once the given [CancelToken] is cancelled,
+    /// such as when the contour is no longer needed by the time a worker thread would
+    /// produce it. Once cancelled, the predicate is no longer consulted, nor are segments
+    /// accumulated, for the remaining nodes.
+    ///
+    /// # Parameters
+    ///
+    /// As per [PixelMap::contour], and:
+    ///
+    /// - `cancel`: The [CancelToken] observed before consulting the predicate of each node.
+    ///
+    /// # Returns
+    ///
+    /// [Cancelled] if contouring was aborted. Otherwise, as per [PixelMap::contour].
+    pub fn contour_cancellable<F>(
+        &self,
+        rect: &URect,
+        predicate: F,
+        cancel: &CancelToken,
+    ) -> Result<Vec<IsoLine>, Cancelled>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.contour_into(
+            rect,
+            predicate,
+            &mut FragmentAccumulator::new(256),
+            Some(cancel),
+        )
+    }
+
+    fn contour_into<F>(
+        &self,
+        rect: &URect,
+        mut predicate: F,
+        fragments: &mut FragmentAccumulator,
+        cancel: Option<&CancelToken>,
+    ) -> Result<Vec<IsoLine>, Cancelled>
     where
         F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::debug_span!("contour", ?rect, traversed = Empty, lines = Empty).entered();
+
         let sub_rect = self.map_rect.intersect(*rect);
         if sub_rect.is_empty() {
-            return vec![];
+            return Ok(vec![]);
         }
 
-        let mut fragments = FragmentAccumulator::new(256);
-        self.contour_segments(&sub_rect, &mut predicate, |seg| {
-            fragments.attach(*seg);
-        });
+        // Once cancelled, no node matches the predicate, so no further segments are produced
+        let is_cancelled = || cancel.is_some_and(|c| c.is_cancelled());
+        let predicate = |node: &PNode<T, U>, rect: &URect| !is_cancelled() && predicate(node, rect);
+
+        #[cfg(feature = "tracing")]
+        let traversed = self.contour_segments(&sub_rect, predicate, |seg| fragments.attach(*seg));
+        #[cfg(not(feature = "tracing"))]
+        self.contour_segments(&sub_rect, predicate, |seg| fragments.attach(*seg));
+        let result = fragments.result();
+        if let Some(cancel) = cancel {
+            cancel.check()?;
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            span.record("traversed", traversed);
+            span.record("lines", result.len());
+        }
 
-        fragments.result()
+        Ok(result)
     }
 
-    fn contour_segments<F, G>(&self, rect: &URect, mut predicate: F, mut seg_handler: G)
+    // Returns the number of nodes traversed.
+    fn contour_segments<F, G>(&self, rect: &URect, mut predicate: F, mut seg_handler: G) -> u32
     where
         F: FnMut(&PNode<T, U>, &URect) -> bool,
         G: FnMut(&ILine),
     {
-        self.root
-            .visit_neighbor_pairs_face(rect, &mut |or, a, a_rect, b, b_rect| {
+        let mut traversed = 0;
+        self.root.visit_neighbor_pairs_face(
+            rect,
+            &mut |or, a, a_rect, b, b_rect| {
                 match or {
                     NeighborOrientation::Horizontal => {
                         let (left, left_rect, right, right_rect) = (a, a_rect, b, b_rect);
@@ -929,7 +2390,20 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
                         }
                     }
                 }
-            });
+            },
+            &mut traversed,
+        );
+        traversed
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PartialEq for PixelMap<T, U> {
+    // The version and active chunks are excluded, as they reflect the history of a map
+    // rather than its pixels.
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root
+            && self.map_rect == other.map_rect
+            && self.pixel_size == other.pixel_size
     }
 }
 
@@ -944,7 +2418,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> Debug for PixelM
 /// Stores statistics about a [PixelMap].
 /// See [PixelMap::stats].
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-#[derive(Debug, Default, Eq, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
 pub struct Stats {
     /// The number of nodes in the quadtree.
     pub node_count: usize,
@@ -957,9 +2431,113 @@ pub struct Stats {
     pub unit_count: usize,
 }
 
+impl std::ops::Add for Stats {
+    type Output = Stats;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Stats {
+            node_count: self.node_count + rhs.node_count,
+            leaf_count: self.leaf_count + rhs.leaf_count,
+            unit_count: self.unit_count + rhs.unit_count,
+        }
+    }
+}
+
+/// A snapshot of the statistics of a [PixelMap], split by the quadrants of the root node.
+/// See [PixelMap::stats_snapshot].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct StatsSnapshot {
+    /// The [PixelMap::version] at the time of the snapshot.
+    pub version: u64,
+
+    /// The dimensions of the [PixelMap].
+    pub map_size: UVec2,
+
+    /// The statistics of the whole quadtree, as per [PixelMap::stats].
+    pub total: Stats,
+
+    /// The statistics of the subtree of each child of the root node, indexed by [Quadrant],
+    /// as per [PixelMap::quadrant_stats].
+    pub quadrants: [Stats; 4],
+}
+
+// The values of the leaf nodes overlapping a tile of [PixelMap::visit_tiles].
+#[derive(Clone, Copy)]
+enum TileValue<'a, T> {
+    Unvisited,
+    Uniform(&'a T),
+    Mixed,
+}
+
+// Merge the leaf nodes of the given node into the values of the tiles they overlap. A leaf
+// covering many tiles is merged into each in one step, and subtrees within a single tile that
+// is already mixed are skipped.
+fn classify_tiles<'a, T, U>(
+    node: &'a PNode<T, U>,
+    map_rect: &URect,
+    tile_size: u32,
+    columns: u32,
+    tiles: &mut [TileValue<'a, T>],
+) where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    let rect = node.region().intersect(map_rect);
+    if rect.is_empty() {
+        return;
+    }
+    let min = rect.min / tile_size;
+    let max = (rect.max - 1) / tile_size;
+    let index = |x: u32, y: u32| y as usize * columns as usize + x as usize;
+
+    if node.is_leaf() {
+        let value = node.value();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let tile = &mut tiles[index(x, y)];
+                *tile = match *tile {
+                    TileValue::Unvisited => TileValue::Uniform(value),
+                    TileValue::Uniform(v) if v == value => TileValue::Uniform(v),
+                    _ => TileValue::Mixed,
+                };
+            }
+        }
+    } else if min == max && matches!(tiles[index(min.x, min.y)], TileValue::Mixed) {
+        // The subtree can't change the value of the single tile it lies within
+    } else {
+        for child in node.children().iter() {
+            classify_tiles(child, map_rect, tile_size, columns, tiles);
+        }
+    }
+}
+
+// Determine if any pixel of the given `rect` is contained by the shape, consulting the
+// shape's inner rects before its spans, which are ordered bottom to top. Rectangles are
+// inclusive of their `max` points, so overlap by a single row or column.
+fn overlaps_shape(inner_rects: &[IRect], spans: &[ILine], rect: &URect) -> bool {
+    let Some(irect) = rect.as_irect().to_inclusive() else {
+        return false;
+    };
+    let overlaps = |min: IVec2, max: IVec2| {
+        min.cmple(max).all() && min.cmple(irect.max).all() && irect.min.cmple(max).all()
+    };
+    if inner_rects
+        .iter()
+        .any(|inner| overlaps(inner.min, inner.max))
+    {
+        return true;
+    }
+    let first = spans.partition_point(|span| span.start().y < irect.min.y);
+    spans[first..]
+        .iter()
+        .take_while(|span| span.start().y <= irect.max.y)
+        .any(|span| overlaps(span.start(), span.end()))
+}
+
 #[inline]
 #[must_use]
-fn next_pow2(mut n: u32) -> u32 {
+pub(crate) fn next_pow2(mut n: u32) -> u32 {
     if n <= 2 {
         return 2;
     }
@@ -977,9 +2555,9 @@ fn next_pow2(mut n: u32) -> u32 {
 
 #[cfg(test)]
 mod test {
-    use crate::pixel_map::next_pow2;
+    use crate::pixel_map::{next_pow2, overlaps_shape};
     use crate::*;
-    use bevy_math::{IVec2, URect, UVec2};
+    use bevy_math::{ivec2, IRect, IVec2, URect, UVec2, Vec2};
     use std::collections::HashSet;
 
     #[test]
@@ -1000,6 +2578,31 @@ mod test {
         assert!(pm.root.is_leaf());
     }
 
+    #[test]
+    fn test_get_pixels() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::new(24, 16), 0, 1);
+        pm.draw_rect(&URect::new(2, 3, 9, 12), 1);
+        pm.draw_circle(&ICircle::new((17, 7), 5), 2);
+        pm.set_pixel((23, 15), 3);
+
+        let mut points: Vec<UVec2> = (0..24)
+            .flat_map(|x| (0..16).map(move |y| UVec2::new((x * 7) % 24, y)))
+            .collect();
+        points.push(UVec2::new(24, 0));
+        points.push(UVec2::new(3, 4));
+
+        let mut out = vec![Some(9)];
+        pm.get_pixels(&points, &mut out);
+        assert_eq!(out.len(), points.len());
+        for (point, value) in points.iter().zip(&out) {
+            assert_eq!(value.as_ref(), pm.get_pixel(*point));
+        }
+        assert_eq!(out[out.len() - 2], None);
+
+        pm.get_pixels(&[], &mut out);
+        assert!(out.is_empty());
+    }
+
     #[test]
     fn test_draw_rect() {
         let map_size = 32;
@@ -1012,11 +2615,10 @@ mod test {
                 let rect = URect::new(0, 0, rect_width, rect_height);
                 pm.draw_rect(&rect, true);
 
-                let rect = exclusive_urect(&rect);
                 for y in 0..map_size {
                     for x in 0..map_size {
                         let p = (x, y).into();
-                        if rect.contains(p) {
+                        if rect.contains_exclusive(p) {
                             assert_eq!(
                                 pm.get_pixel(p),
                                 Some(&true),
@@ -1041,6 +2643,481 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_draw_capsule() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let capsule = ICapsule::new(iline((-4, 3), (20, 12)), 4);
+        assert!(pm.draw_capsule(&capsule, true));
+        for y in 0..32 {
+            for x in 0..32 {
+                let p = UVec2::new(x, y);
+                assert_eq!(
+                    pm.get_pixel(p),
+                    Some(&capsule.contains(p.as_ivec2())),
+                    "assert: {}",
+                    p
+                );
+            }
+        }
+
+        let capsule = ICapsule::new(iline((40, 40), (50, 50)), 4);
+        assert!(!pm.draw_capsule(&capsule, true));
+
+        // Only the rows of a long capsule within the map are examined
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(64), false, 1);
+        let capsule = ICapsule::new(iline((-30000, -30000), (30000, 30000)), 3);
+        assert!(pm.draw_capsule(&capsule, true));
+        assert_eq!(pm.get_pixel((10, 10)), Some(&true));
+        assert_eq!(pm.get_pixel((10, 15)), Some(&false));
+    }
+
+    // The name and unsigned fields of a span.
+    #[cfg(feature = "tracing")]
+    type CapturedSpan = (&'static str, Vec<(String, u64)>);
+
+    // Captures the name and unsigned fields of each span, recorded when created or later.
+    #[cfg(feature = "tracing")]
+    #[derive(Clone, Default)]
+    struct SpanCapture(std::sync::Arc<std::sync::Mutex<Vec<CapturedSpan>>>);
+
+    #[cfg(feature = "tracing")]
+    impl SpanCapture {
+        fn field(&self, span: &str, field: &str) -> Option<u64> {
+            let spans = self.0.lock().unwrap();
+            let (_, fields) = spans.iter().rev().find(|(name, _)| *name == span)?;
+            fields.iter().find(|(f, _)| f == field).map(|(_, v)| *v)
+        }
+
+        fn push_fields(&self, id: &tracing::span::Id, record: &tracing::span::Record) {
+            struct Fields<'a>(&'a mut Vec<(String, u64)>);
+            impl tracing::field::Visit for Fields<'_> {
+                fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+                    self.0.push((field.name().to_string(), value));
+                }
+                fn record_debug(&mut self, _: &tracing::field::Field, _: &dyn std::fmt::Debug) {}
+            }
+            let mut spans = self.0.lock().unwrap();
+            record.record(&mut Fields(&mut spans[id.into_u64() as usize - 1].1));
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for SpanCapture {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let id = {
+                let mut spans = self.0.lock().unwrap();
+                spans.push((attrs.metadata().name(), Vec::new()));
+                tracing::span::Id::from_u64(spans.len() as u64)
+            };
+            self.push_fields(&id, &tracing::span::Record::new(attrs.values()));
+            id
+        }
+
+        fn record(&self, id: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+            self.push_fields(id, values);
+        }
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+        fn event(&self, _: &tracing::Event<'_>) {}
+        fn enter(&self, _: &tracing::span::Id) {}
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_spans() {
+        let capture = SpanCapture::default();
+        tracing::subscriber::with_default(capture.clone(), || {
+            let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+            pm.draw_rect(&URect::new(0, 0, 32, 32), true);
+            assert_eq!(capture.field("draw_rect", "traversed"), Some(1));
+            pm.draw_rect(&URect::new(3, 3, 9, 9), false);
+            let traversed = capture.field("draw_rect", "traversed").unwrap();
+            assert!(traversed > 4);
+
+            let contour = pm.contour(&pm.map_rect(), |n, _| *n.value());
+            let nodes = pm.stats().node_count as u64;
+            assert_eq!(capture.field("contour", "traversed"), Some(nodes));
+            assert_eq!(
+                capture.field("contour", "lines"),
+                Some(contour.len() as u64)
+            );
+
+            let path = pm.pathfind_a_star_grid(
+                &pm.map_rect(),
+                1,
+                UVec2::new(1, 1),
+                UVec2::new(20, 20),
+                crate::pathfinding::euclidean_heuristic,
+                |n, _| *n.value(),
+            );
+            assert_eq!(
+                capture.field("pathfind_a_star_grid", "considered_cells"),
+                Some(path.unwrap().considered_cells as u64)
+            );
+
+            // Each leaf node is combined with the same leaf node of the clone
+            let other = pm.clone();
+            let leaves = pm.stats().leaf_count as u64;
+            pm.combine(&other, (0, 0), |a, b| *a && *b);
+            assert_eq!(capture.field("combine", "updates"), Some(leaves));
+            assert!(capture.field("combine", "traversed").unwrap() > nodes + leaves);
+        });
+    }
+
+    #[test]
+    fn test_no_op_writes_never_dirty() {
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(32), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 8, 8), 1);
+        pm.set_pixel((20, 20), 2);
+        pm.presubdivide_to(&URect::new(16, 0, 32, 16), 3);
+        pm.clear_dirty(true);
+        pm.enable_active_tracking(8);
+        let watch = pm.watch(&pm.map_rect());
+        let version = pm.version();
+
+        pm.set_pixel((3, 3), 1);
+        pm.set_pixel((20, 20), 2);
+        pm.draw_rect(&URect::new(0, 0, 8, 8), 1);
+        pm.draw_rect(&URect::new(16, 0, 32, 16), 0);
+        pm.draw_rect(&URect::new(2, 2, 6, 6), 1);
+        pm.draw_circle(&ICircle::new((4, 4), 3), 1);
+        pm.set_from_point_cloud([IVec2::new(1, 1), IVec2::new(7, 7)], 1);
+        pm.draw_shape(&ITriangle::new((0, 0), (7, 0), (0, 7)), 1);
+        assert!(!pm.dirty());
+        assert_eq!(pm.version(), version);
+        assert_eq!(pm.visit_active(|_, _| {}), 0);
+        assert!(pm.poll_changes(watch).is_empty());
+
+        pm.draw_rect(&URect::new(16, 0, 32, 16), 1);
+        assert!(pm.dirty());
+        assert!(pm.version() > version);
+        assert!(pm.visit_active(|_, _| {}) > 0);
+        assert!(!pm.poll_changes(watch).is_empty());
+    }
+
+    #[test]
+    fn test_if_changed() {
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(32), 0, 1);
+        pm.clear_dirty(true);
+        let watch = pm.watch(&pm.map_rect());
+        let version = pm.version();
+
+        assert!(!pm.set_pixel_if_changed((3, 3), 0));
+        assert!(!pm.set_pixel_if_changed((40, 3), 1));
+        assert!(!pm.draw_rect_if_changed(&URect::new(0, 0, 8, 8), 0));
+        assert!(!pm.draw_shape_if_changed(&ICircle::new((8, 8), 4), 0));
+        assert_eq!(pm.version(), version);
+        assert!(pm.poll_changes(watch).is_empty());
+        assert!(!pm.dirty());
+
+        assert!(pm.set_pixel_if_changed((3, 3), 1));
+        assert!(!pm.set_pixel_if_changed((3, 3), 1));
+        assert!(pm.draw_rect_if_changed(&URect::new(0, 0, 8, 8), 1));
+        assert!(!pm.draw_rect_if_changed(&URect::new(2, 2, 8, 8), 1));
+        assert!(pm.draw_shape_if_changed(&ICircle::new((8, 8), 4), 1));
+        assert!(!pm.draw_shape_if_changed(&ICircle::new((8, 8), 4), 1));
+        assert!(!pm.poll_changes(watch).is_empty());
+    }
+
+    #[test]
+    fn test_draw_ring_and_sector() {
+        let ring = IRing::new((14, 12), 5, 11);
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        assert!(pm.draw_ring(&ring, true));
+        for p in URectPixelIterator::new(pm.map_rect()) {
+            assert_eq!(
+                pm.get_pixel(p),
+                Some(&ring.contains(p.as_ivec2())),
+                "ring: {p}"
+            );
+        }
+
+        let sector = ISector::new((4, 28), 20, -1.2, 0.9);
+        pm.clear(false);
+        assert!(pm.draw_sector(&sector, true));
+        for p in URectPixelIterator::new(pm.map_rect()) {
+            assert_eq!(
+                pm.get_pixel(p),
+                Some(&sector.contains(p.as_ivec2())),
+                "sector: {p}"
+            );
+        }
+
+        assert!(!pm.draw_ring(&IRing::new((50, 50), 2, 4), true));
+        assert!(!pm.draw_sector(&ISector::new((50, 50), 4, 0.0, 1.0), true));
+
+        // Only the rows of a large sector within the map are examined
+        let sector = ISector::new((-100, 16), 6000, -0.1, 0.2);
+        pm.clear(false);
+        assert!(pm.draw_sector(&sector, true));
+        for p in URectPixelIterator::new(pm.map_rect()) {
+            assert_eq!(
+                pm.get_pixel(p),
+                Some(&sector.contains(p.as_ivec2())),
+                "large sector: {p}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_draw_rounded_rect() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let rrect = IRoundedRect::new(IRect::new(3, -2, 28, 20), 6);
+        assert!(pm.draw_rounded_rect(&rrect, true));
+        for y in 0..32 {
+            for x in 0..32 {
+                let p = UVec2::new(x, y);
+                assert_eq!(
+                    pm.get_pixel(p),
+                    Some(&rrect.contains(p.as_ivec2())),
+                    "assert: {}",
+                    p
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw_triangle() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let triangle = ITriangle::new((-5, 2), (30, 9), (11, 40));
+        assert!(pm.draw_triangle(&triangle, true));
+        for y in 0..32 {
+            for x in 0..32 {
+                let p = UVec2::new(x, y);
+                assert_eq!(
+                    pm.get_pixel(p),
+                    Some(&triangle.contains(p.as_ivec2())),
+                    "assert: {}",
+                    p
+                );
+            }
+        }
+
+        // Only the rows of a large triangle within the map are examined
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(64), false, 1);
+        let triangle = ITriangle::new((-40000, -40000), (40000, -40000), (0, 64));
+        assert!(pm.draw_triangle(&triangle, true));
+        assert_eq!(pm.get_pixel((0, 0)), Some(&true));
+        assert_eq!(pm.get_pixel((1, 63)), Some(&false));
+    }
+
+    #[test]
+    fn test_draw_shape() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let circle = ICircle::new((20, 12), 9);
+        assert!(pm.draw_shape(&circle, true));
+        for y in 0..32 {
+            for x in 0..32 {
+                let p = UVec2::new(x, y);
+                assert_eq!(
+                    pm.get_pixel(p),
+                    Some(&RasterShape::contains(&circle, p.as_ivec2())),
+                    "assert: {}",
+                    p
+                );
+            }
+        }
+        assert!(!pm.draw_shape(&IRect::new(-10, -10, -1, -1), true));
+    }
+
+    #[test]
+    fn test_any_in_shape() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        pm.set_pixel((20, 20), true);
+        let triangle = ITriangle::new((0, 0), (31, 0), (0, 31));
+        assert_eq!(pm.any_in_shape(&triangle, |n, _| *n.value()), Some(false));
+        let triangle = ITriangle::new((31, 31), (31, 0), (0, 31));
+        assert_eq!(pm.any_in_shape(&triangle, |n, _| *n.value()), Some(true));
+        let circle = ICircle::new((23, 23), 5);
+        assert_eq!(pm.any_in_shape(&circle, |n, _| *n.value()), Some(true));
+        let circle = ICircle::new((23, 23), 4);
+        assert_eq!(pm.any_in_shape(&circle, |n, _| *n.value()), Some(false));
+        let circle = ICircle::new((-10, -10), 3);
+        assert_eq!(pm.any_in_shape(&circle, |n, _| *n.value()), None);
+    }
+
+    #[test]
+    fn test_all_in_shape() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let rrect = IRoundedRect::new(IRect::new(4, 4, 20, 14), 4);
+        pm.draw_rounded_rect(&rrect, true);
+        assert_eq!(pm.all_in_shape(&rrect, |n, _| *n.value()), Some(true));
+        let rrect = IRoundedRect::new(IRect::new(4, 4, 20, 14), 3);
+        assert_eq!(pm.all_in_shape(&rrect, |n, _| *n.value()), Some(false));
+        let capsule = ICapsule::new(iline((8, 9), (16, 9)), 4);
+        assert_eq!(pm.all_in_shape(&capsule, |n, _| *n.value()), Some(true));
+        let capsule = ICapsule::new(iline((8, 9), (16, 9)), 6);
+        assert_eq!(pm.all_in_shape(&capsule, |n, _| *n.value()), Some(false));
+    }
+
+    #[test]
+    fn test_overlaps_shape() {
+        // The inner rect overlaps a single column of the rect, away from its min point
+        let inner_rects = [IRect::new(0, 2, 4, 10)];
+        assert!(overlaps_shape(&inner_rects, &[], &URect::new(4, 0, 8, 4)));
+        assert!(!overlaps_shape(&inner_rects, &[], &URect::new(5, 0, 8, 4)));
+        assert!(!overlaps_shape(&inner_rects, &[], &URect::new(0, 0, 4, 2)));
+
+        let spans = [iline((6, 1), (6, 1)), iline((9, 3), (12, 3))];
+        assert!(overlaps_shape(&[], &spans, &URect::new(4, 0, 8, 4)));
+        assert!(overlaps_shape(&[], &spans, &URect::new(12, 3, 14, 4)));
+        assert!(!overlaps_shape(&[], &spans, &URect::new(7, 0, 9, 4)));
+        assert!(!overlaps_shape(&[], &spans, &URect::new(0, 4, 32, 8)));
+    }
+
+    #[test]
+    fn test_all_in_rotated_rect() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let rrect = RotatedIRect::new(IRect::new(6, 8, 24, 18), 0.6);
+        pm.draw_shape(&rrect, true);
+        assert_eq!(pm.all_in_shape(&rrect, |n, _| *n.value()), Some(true));
+        let larger = RotatedIRect::new(IRect::new(5, 8, 24, 18), 0.6);
+        assert_eq!(pm.all_in_shape(&larger, |n, _| *n.value()), Some(false));
+    }
+
+    #[test]
+    fn test_any_in_circle() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        pm.set_pixel((20, 20), true);
+        let circle = ICircle::new((23, 23), 5);
+        assert_eq!(pm.any_in_circle(&circle, |n, _| *n.value()), Some(true));
+        let circle = ICircle::new((23, 23), 4);
+        assert_eq!(pm.any_in_circle(&circle, |n, _| *n.value()), Some(false));
+        let circle = ICircle::new((-10, -10), 3);
+        assert_eq!(pm.any_in_circle(&circle, |n, _| *n.value()), None);
+    }
+
+    #[test]
+    fn test_all_in_circle() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        let circle = ICircle::new((12, 14), 7);
+        pm.draw_circle(&circle, true);
+        let mut visited = 0;
+        let result = pm.all_in_circle(&circle, |n, _| {
+            visited += 1;
+            *n.value()
+        });
+        assert_eq!(result, Some(true));
+        let mut leaves = 0;
+        pm.visit_in_rect(&inclusive_to_cropped_urect(&circle.aabb()), |_, _| {
+            leaves += 1
+        });
+        assert!(visited < leaves);
+
+        let circle = ICircle::new((12, 14), 8);
+        assert_eq!(pm.all_in_circle(&circle, |n, _| *n.value()), Some(false));
+    }
+
+    #[test]
+    fn test_any_on_line() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        pm.set_pixel((12, 6), true);
+        let line = iline((0, 0), (31, 15));
+        assert_eq!(pm.any_on_line(&line, |n, _| *n.value()), Some(true));
+        let line = iline((0, 2), (31, 17));
+        assert_eq!(pm.any_on_line(&line, |n, _| *n.value()), Some(false));
+        let line = iline((-5, -5), (-1, 40));
+        assert_eq!(pm.any_on_line(&line, |n, _| *n.value()), None);
+    }
+
+    #[test]
+    fn test_set_from_point_cloud() {
+        let points = vec![
+            ivec2(3, 4),
+            ivec2(30, 1),
+            ivec2(-2, 5),
+            ivec2(17, 29),
+            ivec2(3, 4),
+            ivec2(40, 40),
+            ivec2(16, 16),
+        ];
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        assert!(pm.set_from_point_cloud(points.iter().copied(), true));
+
+        let mut expected = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        for p in &points {
+            if p.cmpge(IVec2::ZERO).all() {
+                expected.set_pixel(p.as_uvec2(), true);
+            }
+        }
+        assert_eq!(pm, expected);
+        assert_eq!(pm.stats(), expected.stats());
+
+        assert!(!pm.set_from_point_cloud(vec![ivec2(-1, 0), ivec2(32, 0)], true));
+    }
+
+    #[test]
+    fn test_matching_points() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(32), false, 1);
+        pm.draw_rect(&URect::new(4, 4, 8, 6), true);
+        pm.set_pixel((20, 21), true);
+        let points = pm.matching_points(&URect::new(0, 0, 32, 32), |n, _| *n.value());
+        let mut expected: Vec<UVec2> = URectPixelIterator::new(URect::new(4, 4, 8, 6)).collect();
+        expected.push(UVec2::new(20, 21));
+        let points: HashSet<UVec2> = points.into_iter().collect();
+        assert_eq!(points, expected.into_iter().collect::<HashSet<UVec2>>());
+
+        let points = pm.matching_points(&URect::new(5, 5, 6, 8), |n, _| *n.value());
+        assert_eq!(points, vec![UVec2::new(5, 5)]);
+    }
+
+    #[test]
+    fn test_visit_tiles() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::new(20, 12), false, 1);
+        pm.draw_rect(&URect::new(8, 0, 16, 8), true);
+        pm.set_pixel((3, 9), true);
+
+        let mut tiles = Vec::new();
+        pm.visit_tiles(8, |rect, value| tiles.push((rect, value.copied())));
+        assert_eq!(
+            tiles,
+            vec![
+                (URect::new(0, 0, 8, 8), Some(false)),
+                (URect::new(8, 0, 16, 8), Some(true)),
+                (URect::new(16, 0, 20, 8), Some(false)),
+                (URect::new(0, 8, 8, 12), None),
+                (URect::new(8, 8, 16, 12), Some(false)),
+                (URect::new(16, 8, 20, 12), Some(false)),
+            ]
+        );
+
+        let mut count = 0;
+        pm.visit_tiles(5, |rect, value| {
+            count += 1;
+            let expected = pm.all_in_rect(&rect, |n, _| *n.value()) == Some(true)
+                || pm.all_in_rect(&rect, |n, _| !*n.value()) == Some(true);
+            assert_eq!(value.is_some(), expected, "tile: {:?}", rect);
+        });
+        assert_eq!(count, 4 * 3);
+
+        // Agrees with per-tile queries where tiles straddle branch nodes
+        pm.draw_circle(&ICircle::new((9, 5), 4), true);
+        for tile_size in [1, 3, 7, 32] {
+            pm.visit_tiles(tile_size, |rect, value| {
+                let value = value.copied();
+                let expected = match pm.all_in_rect(&rect, |n, _| *n.value()) {
+                    Some(true) => Some(true),
+                    _ if pm.all_in_rect(&rect, |n, _| !*n.value()) == Some(true) => Some(false),
+                    _ => None,
+                };
+                assert_eq!(value, expected, "tile: {:?}", rect);
+            });
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_visit_tiles_zero_size() {
+        let pm = PixelMap::<bool, u32>::new(&UVec2::new(20, 12), false, 1);
+        pm.visit_tiles(0, |_, _| {});
+    }
+
     #[test]
     fn test_stats_with_root_node() {
         let pm = PixelMap::<bool, u32>::new(&UVec2::splat(2), false, 1);
@@ -1083,6 +3160,37 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_stats_snapshot() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(4), false, 1);
+        let snapshot = pm.stats_snapshot();
+        assert_eq!(snapshot.total, pm.stats());
+        assert_eq!(snapshot.quadrants, [Stats::default(); 4]);
+
+        pm.draw_rect(&URect::new(0, 0, 2, 2), true);
+        pm.set_pixel((0, 0), false);
+        let snapshot = pm.stats_snapshot();
+        assert_eq!(snapshot.version, pm.version());
+        assert_eq!(snapshot.map_size, UVec2::splat(4));
+        assert_eq!(snapshot.total, pm.stats());
+        assert_eq!(
+            snapshot.quadrants[Quadrant::BottomLeft as usize],
+            Stats {
+                node_count: 5,
+                leaf_count: 4,
+                unit_count: 4,
+            }
+        );
+        assert_eq!(
+            pm.quadrant_stats(Quadrant::TopRight),
+            Stats {
+                node_count: 1,
+                leaf_count: 1,
+                unit_count: 0,
+            }
+        );
+    }
+
     #[test]
     fn test_any_in_rect() {
         let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(2), false, 1);
@@ -1149,6 +3257,401 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_intersect_segment() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(8, 0, 9, 16), true);
+        let hit = |line: ILine| pm.intersect_segment(&line, |n| *n.value());
+
+        assert_eq!(hit(iline((-10, 4), (30, 4))), Some(UVec2::new(8, 4)));
+        assert_eq!(hit(iline((30, 4), (-10, 4))), Some(UVec2::new(8, 4)));
+        assert_eq!(hit(iline((-4, -4), (20, 20))), Some(UVec2::new(8, 8)));
+        assert_eq!(hit(iline((-4, 2), (4, 2))), None);
+        assert_eq!(hit(iline((-4, -2), (30, -2))), None);
+    }
+
+    #[test]
+    fn test_find_first() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.draw_rect(&URect::new(2, 9, 14, 16), 1);
+        pm.set_pixel((12, 5), 2);
+        pm.set_pixel((3, 5), 2);
+
+        let first = pm.find_first(&pm.map_rect(), |n, _| *n.value() > 0);
+        assert_eq!(first, Some((UVec2::new(3, 5), &2)));
+        let first = pm.find_first(&URect::new(4, 0, 16, 16), |n, _| *n.value() > 0);
+        assert_eq!(first, Some((UVec2::new(12, 5), &2)));
+        let first = pm.find_first(&URect::new(0, 6, 16, 16), |n, _| *n.value() > 0);
+        assert_eq!(first, Some((UVec2::new(2, 9), &1)));
+        assert_eq!(
+            pm.find_first(&URect::new(0, 0, 2, 16), |n, _| *n.value() > 0),
+            None
+        );
+
+        // The first free pixel above the ground
+        let above = pm.find_first_along(&iline((8, 0), (8, 15)), |n| *n.value() == 1);
+        assert_eq!(above, Some((UVec2::new(8, 9), &1)));
+        let above = pm.find_first_along(&iline((8, 30), (8, -4)), |n| *n.value() == 0);
+        assert_eq!(above, Some((UVec2::new(8, 8), &0)));
+        assert_eq!(
+            pm.find_first_along(&iline((0, 0), (0, 15)), |n| *n.value() > 0),
+            None
+        );
+        assert_eq!(
+            pm.find_first_along(&iline((-4, -2), (30, -2)), |_| true),
+            None
+        );
+    }
+
+    #[test]
+    fn test_decimate_with() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 100, 1);
+        for y in 0..8 {
+            for x in 0..8 {
+                pm.set_pixel((x, y), 100 + ((x * 3 + y * 5) % 4) as u8);
+            }
+        }
+        pm.set_pixel((7, 7), 200);
+        let nodes = pm.stats().node_count;
+        pm.clear_dirty(true);
+
+        // Values never differ by more than 3, except for the outlier
+        assert_eq!(pm.decimate_with(|a, b| a == b), 0);
+        assert!(!pm.dirty());
+        assert!(pm.decimate_with(|a, b| a.abs_diff(*b) <= 3) > 0);
+        assert!(pm.stats().node_count < nodes);
+        assert!(pm.dirty());
+        assert_eq!(pm.get_pixel((7, 7)), Some(&200));
+        assert_eq!(pm.get_pixel((0, 0)), Some(&100));
+        pm.visit(|n, _| assert!(*n.value() <= 103 || *n.value() == 200));
+
+        assert!(pm.decimate_with(|_, _| true) > 0);
+        assert_eq!(pm.stats().node_count, 1);
+    }
+
+    #[test]
+    fn test_quantize() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 0, 1);
+        for y in 0..8 {
+            for x in 0..8 {
+                pm.set_pixel((x, y), (x * 8 + y) as u8 * 4);
+            }
+        }
+        let nodes = pm.stats().node_count;
+        let metric = |a: &u8, b: &u8| a.abs_diff(*b) as u32;
+
+        assert!(pm.quantize(&[0, 128, 255], metric));
+        assert!(pm.stats().node_count < nodes);
+        assert_eq!(pm.get_pixel((0, 7)), Some(&0));
+        assert_eq!(pm.get_pixel((2, 0)), Some(&0)); // Equally near
+        assert_eq!(pm.get_pixel((3, 0)), Some(&128));
+        assert_eq!(pm.get_pixel((7, 7)), Some(&255));
+        pm.visit(|n, _| assert!([0, 128, 255].contains(n.value())));
+
+        // Already quantized
+        assert!(!pm.quantize(&[0, 128, 255], metric));
+        assert!(pm.quantize(&[7], metric));
+        assert_eq!(pm.stats().node_count, 1);
+    }
+
+    #[test]
+    fn test_draw_rect_clean() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.clear_dirty(true);
+        let version = pm.version();
+
+        assert!(pm.draw_rect_clean(&URect::new(1, 1, 9, 7), 1));
+        assert!(pm.draw_rect_clean(&URect::new(3, 3, 5, 5), 2));
+        assert_eq!(pm.get_pixel((4, 4)), Some(&2));
+        assert!(!pm.dirty());
+        assert_ne!(pm.version(), version);
+
+        // Merging clean nodes does not mark them dirty
+        assert!(pm.draw_rect_clean(&pm.map_rect(), 0));
+        assert_eq!(pm.stats().node_count, 1);
+        assert!(!pm.dirty());
+
+        // Dirty nodes remain dirty
+        pm.set_pixel((2, 2), 3);
+        pm.draw_rect_clean(&URect::new(0, 0, 4, 4), 4);
+        let mut dirty = Vec::new();
+        pm.visit_dirty(|_, r| dirty.push(*r));
+        assert!(dirty.iter().any(|r| r.contains(UVec2::new(2, 2))));
+        assert!(!pm.draw_rect_clean(&URect::new(20, 20, 30, 30), 4));
+    }
+
+    #[test]
+    fn test_without_dirty() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(32), 0, 1);
+        pm.clear_dirty(true);
+        pm.enable_active_tracking(8);
+        pm.enable_node_budget(1, 8);
+        let watch = pm.watch(&pm.map_rect());
+        let version = pm.version();
+
+        let drawn = pm.without_dirty(|pm| {
+            pm.set_pixel((30, 30), 1);
+            pm.set_from_point_cloud([IVec2::new(1, 30), IVec2::new(2, 30)], 2);
+            pm.draw_circle(&ICircle::new((10, 10), 6), 3);
+            pm.draw_shape(&ITriangle::new((16, 0), (31, 0), (16, 15)), 4);
+            pm.draw_line_aa(Vec2::new(0.0, 20.0), Vec2::new(31.0, 25.0), 1.0, |_, _| 5)
+        });
+        assert!(drawn);
+        assert_eq!(pm.get_pixel((10, 10)), Some(&3));
+        assert_eq!(pm.get_pixel((20, 2)), Some(&4));
+        assert!(!pm.dirty());
+        assert!(pm.version() > version);
+        assert_eq!(pm.visit_active(|_, _| {}), 0);
+        assert!(pm.poll_changes(watch).is_empty());
+
+        // Without modification history, all coarsening candidates are equally old
+        let coarsened = pm.enforce_node_budget(dominant_value);
+        assert!(!coarsened.is_empty());
+
+        // Dirty marking resumes after the closure
+        pm.set_pixel((0, 0), 6);
+        assert!(pm.dirty());
+        assert!(!pm.poll_changes(watch).is_empty());
+    }
+
+    #[test]
+    fn test_presubdivide_to() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.clear_dirty(true);
+        assert_eq!(pm.presubdivide_to(&URect::new(0, 0, 4, 4), 2), 2);
+        assert_eq!(pm.depth_at((0, 0)), Some(2));
+        assert_eq!(pm.depth_at((15, 15)), Some(1));
+        assert!(!pm.dirty());
+
+        // Clamped to the depth limit
+        assert_eq!(pm.presubdivide_to(&URect::new(0, 0, 1, 1), 10), 2);
+        assert_eq!(pm.depth_at((0, 0)), Some(4));
+        assert_eq!(pm.presubdivide_to(&URect::new(20, 20, 30, 30), 2), 0);
+    }
+
+    #[test]
+    fn test_draw_rect_outline() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        assert!(pm.draw_rect_outline(&URect::new(2, 2, 10, 8), 2, 1));
+        let count = |pm: &PixelMap<u8, u32>| {
+            let mut count = 0;
+            pm.visit(|n, r| {
+                if *n.value() == 1 {
+                    count += r.width() * r.height();
+                }
+            });
+            count
+        };
+        assert_eq!(count(&pm), 8 * 6 - 4 * 2);
+        assert_eq!(pm.get_pixel((3, 3)), Some(&1));
+        assert_eq!(pm.get_pixel((4, 4)), Some(&0));
+        assert_eq!(pm.get_pixel((9, 7)), Some(&1));
+        assert!(!pm.draw_rect_outline(&URect::new(2, 2, 10, 8), 0, 1));
+        assert!(!pm.draw_rect_outline(&URect::new(20, 20, 30, 30), 1, 1));
+
+        // Filled when too thick
+        pm.clear(0);
+        assert!(pm.draw_rect_outline(&URect::new(0, 0, 4, 4), 2, 1));
+        assert_eq!(count(&pm), 16);
+
+        // Rotated, hollow, and within the filled rotated rectangle
+        let rrect = RotatedIRect::new(IRect::new(2, 2, 13, 13), std::f32::consts::FRAC_PI_6);
+        let mut filled: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        filled.draw_rotated_rect(&rrect, 1);
+        pm.clear(0);
+        assert!(pm.draw_rotated_rect_outline(&rrect, 2, 1));
+        assert_eq!(pm.get_pixel((7, 7)), Some(&0));
+        assert!(count(&pm) < count(&filled));
+        pm.visit(|n, r| {
+            if *n.value() == 1 {
+                assert_eq!(filled.get_pixel(r.min), Some(&1));
+            }
+        });
+    }
+
+    #[test]
+    fn test_draw_rotated_rect_matches_pixels() {
+        for i in 1..24 {
+            let rotation = i as f32 * std::f32::consts::PI / 24.0;
+            let rrect = RotatedIRect::new(IRect::new(6, 9, 25, 20), rotation);
+            let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), false, 1);
+            assert!(pm.draw_rotated_rect(&rrect, true));
+
+            let mut expected: Vec<UVec2> = rrect.unsigned_pixels().collect();
+            expected.sort_unstable_by_key(|p| (p.y, p.x));
+            expected.dedup();
+            let mut actual = pm.matching_points(&pm.map_rect(), |n, _| *n.value());
+            actual.sort_unstable_by_key(|p| (p.y, p.x));
+            assert_eq!(actual, expected, "rotation {rotation}");
+        }
+    }
+
+    #[test]
+    fn test_draw_clipped_to_map_rect() {
+        // The root region extends beyond the top of a non-square map
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::new(8, 2), 0, 1);
+        assert!(pm.draw_circle(&ICircle::new((4, 1), 3), 1));
+        assert!(pm.draw_rotated_rect(
+            &RotatedIRect::new(IRect::new(0, -4, 8, 6), std::f32::consts::FRAC_PI_8),
+            2
+        ));
+        assert_eq!(pm.get_pixel((4, 1)), Some(&2));
+
+        // Nothing is drawn outside of the map
+        let mut outside = 0;
+        pm.root.visit_leaves_in_rect(
+            &URect::new(0, 2, 8, 8),
+            &mut |node, _| {
+                assert_eq!(*node.value(), 0);
+                outside += 1;
+            },
+            &mut 0,
+        );
+        assert!(outside > 0);
+    }
+
+    #[test]
+    fn test_draw_circle_lod() {
+        let circle = ICircle::new((16, 16), 10);
+        let mut exact: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), false, 1);
+        exact.draw_circle(&circle, true);
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), false, 1);
+        assert!(pm.draw_circle_lod(&circle, true, 10));
+        assert!(pm == exact);
+
+        // Cells of 4x4 pixels
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), false, 1);
+        assert!(pm.draw_circle_lod(&circle, true, 3));
+        assert!(pm.stats().node_count < exact.stats().node_count);
+        pm.visit(|n, r| assert!(!*n.value() || r.width() >= 4));
+        assert_eq!(pm.get_pixel((16, 16)), Some(&true));
+        assert_eq!(pm.get_pixel((16, 22)), Some(&true));
+        assert_eq!(pm.get_pixel((16, 24)), Some(&false)); // Cell center outside
+        assert_eq!(pm.get_pixel((2, 2)), Some(&false));
+        assert!(!pm.draw_circle_lod(&ICircle::new((60, 60), 4), true, 3));
+    }
+
+    #[test]
+    fn test_scatter() {
+        let count = |pm: &PixelMap<bool, u32>| {
+            let mut count = 0;
+            pm.visit(|n, r| {
+                if *n.value() {
+                    count += r.width() * r.height();
+                }
+            });
+            count
+        };
+        let scatter = |rect: URect, density: f32, seed: u64| {
+            let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), false, 1);
+            pm.scatter(&rect, density, seed, true);
+            pm
+        };
+
+        let a = scatter(URect::new(0, 0, 64, 64), 0.1, 42);
+        assert!(a == scatter(URect::new(0, 0, 64, 64), 0.1, 42));
+        assert!(a != scatter(URect::new(0, 0, 64, 64), 0.1, 43));
+        let placed = count(&a);
+        assert!((300..520).contains(&placed), "{placed}");
+
+        let b = scatter(URect::new(8, 8, 16, 16), 0.5, 1);
+        assert!(count(&b) > 0);
+        assert_eq!(
+            b.find_first(&URect::new(0, 0, 64, 8), |n, _| *n.value()),
+            None
+        );
+        assert_eq!(
+            b.find_first(&URect::new(16, 0, 64, 64), |n, _| *n.value()),
+            None
+        );
+
+        assert_eq!(count(&scatter(URect::new(0, 0, 4, 4), 1.0, 1)), 16);
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(64), false, 1);
+        assert!(!pm.scatter(&pm.map_rect(), 0.0, 1, true));
+        assert!(!pm.scatter(&URect::new(70, 70, 80, 80), 0.5, 1, true));
+    }
+
+    #[test]
+    fn test_depth() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        assert_eq!(pm.depth_limit(), 4);
+        assert_eq!(pm.max_depth(), 0);
+        assert_eq!(pm.depth_at((3, 3)), Some(0));
+
+        pm.set_pixel((3, 3), true);
+        assert_eq!(pm.max_depth(), 4);
+        assert_eq!(pm.depth_at((3, 3)), Some(4));
+        assert_eq!(pm.depth_at((12, 12)), Some(1));
+        assert_eq!(pm.depth_at((20, 0)), None);
+
+        let pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 4);
+        assert_eq!(pm.depth_limit(), 2);
+    }
+
+    #[test]
+    fn test_to_tile_grid() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::new(10, 6), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 3, 4), 1);
+        pm.set_pixel((9, 5), 2);
+
+        // The value covering the greatest area of each tile
+        let dominant = pm.to_tile_grid(4, |_, leaves| {
+            let mut areas = [0u32; 3];
+            for (value, rect) in leaves {
+                areas[*value as usize] += rect.width() * rect.height();
+            }
+            (0..3).max_by_key(|v| areas[*v as usize]).unwrap()
+        });
+        assert_eq!(dominant, vec![1, 0, 0, 0, 0, 0]);
+
+        let any_set = pm.to_tile_grid(4, |_, leaves| leaves.iter().any(|(v, _)| *v != 0));
+        assert_eq!(any_set, vec![true, false, false, false, false, true]);
+
+        // Tiles are cropped to the map
+        let areas = pm.to_tile_grid(4, |tile, _| tile.width() * tile.height());
+        assert_eq!(areas, vec![16, 16, 8, 8, 8, 4]);
+    }
+
+    #[test]
+    fn test_node_handles() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(8), false, 1);
+        pm.set_pixel((5, 5), true);
+
+        let handle = pm.get_handle((5, 5)).unwrap();
+        let (node, rect) = pm.resolve(&handle).unwrap();
+        assert!(*node.value());
+        assert_eq!(rect, URect::new(5, 5, 6, 6));
+        assert_eq!(pm.get_handle((9, 0)), None);
+
+        let handles = pm.leaf_handles_in_rect(&URect::new(0, 0, 4, 4));
+        assert_eq!(handles.len(), 1);
+        let (node, rect) = pm.resolve(&handles[0]).unwrap();
+        assert!(!*node.value());
+        assert_eq!(rect, URect::new(0, 0, 4, 4));
+
+        // Subdividing a node invalidates its handle, but an unrelated edit does not
+        pm.clear_dirty(true);
+        assert!(pm.resolve(&handle).is_some());
+        let version = pm.version();
+        pm.draw_rect(&URect::new(0, 0, 1, 1), true);
+        assert_ne!(pm.version(), version);
+        assert!(pm.resolve(&handles[0]).is_none());
+        let (node, rect) = pm.resolve(&handle).unwrap();
+        assert!(*node.value());
+        assert_eq!(rect, URect::new(5, 5, 6, 6));
+
+        // Merging a node invalidates its handle
+        let handle = pm.get_handle((1, 0)).unwrap();
+        pm.draw_rect(&URect::new(0, 0, 4, 4), false);
+        assert!(pm.resolve(&handle).is_none());
+        let handle = pm.get_handle((5, 5)).unwrap();
+        pm.set_pixel((5, 5), false);
+        assert!(pm.resolve(&handle).is_none());
+        let (node, rect) = pm.resolve(&pm.get_handle((5, 5)).unwrap()).unwrap();
+        assert!(!*node.value());
+        assert_eq!(rect, pm.map_rect());
+    }
+
     #[test]
     #[cfg(feature = "serialize")]
     fn test_serialization() {
@@ -0,0 +1,175 @@
+use crate::PNode;
+use bevy_math::URect;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// Common capabilities of pixel values, allowing algorithms taking a predicate, such as
+/// [crate::PixelMap::contour], [crate::PixelMap::maximal_rects] or
+/// [crate::PixelMap::build_navmesh], to be given [solid] or [empty] rather than a closure
+/// classifying node values at every call site.
+///
+/// Implemented for `bool`, the primitive integer and floating point types, where non-zero
+/// values are solid, and `Option` of any implementing type, where `None` is empty.
+pub trait PixelValue: Copy + PartialEq {
+    /// Determine if this value is solid, such as a wall, as opposed to empty space.
+    #[must_use]
+    fn is_solid(&self) -> bool;
+
+    /// Determine if this value is empty space. The default implementation negates
+    /// [PixelValue::is_solid].
+    #[inline]
+    #[must_use]
+    fn is_empty(&self) -> bool {
+        !self.is_solid()
+    }
+
+    /// Blend this value with another, where `t` of `0.0` yields this value, and `1.0` yields
+    /// the other. The default implementation selects the nearer of the two values.
+    #[inline]
+    #[must_use]
+    fn blend(&self, other: &Self, t: f32) -> Self {
+        if t < 0.5 {
+            *self
+        } else {
+            *other
+        }
+    }
+
+    /// Obtain the RGBA color of this value, such as for a debug overlay or image export. The
+    /// default implementation yields opaque white for solid values, and transparent black
+    /// for empty values.
+    #[inline]
+    #[must_use]
+    fn to_rgba(&self) -> [u8; 4] {
+        if self.is_solid() {
+            [255; 4]
+        } else {
+            [0; 4]
+        }
+    }
+}
+
+/// A predicate accepting leaf nodes whose values are solid, as per [PixelValue::is_solid].
+#[inline]
+#[must_use]
+pub fn solid<T, U>(node: &PNode<T, U>, _rect: &URect) -> bool
+where
+    T: PixelValue,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    node.value().is_solid()
+}
+
+/// A predicate accepting leaf nodes whose values are empty, as per [PixelValue::is_empty].
+#[inline]
+#[must_use]
+pub fn empty<T, U>(node: &PNode<T, U>, _rect: &URect) -> bool
+where
+    T: PixelValue,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    node.value().is_empty()
+}
+
+impl PixelValue for bool {
+    #[inline]
+    fn is_solid(&self) -> bool {
+        *self
+    }
+}
+
+macro_rules! impl_pixel_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl PixelValue for $t {
+                #[inline]
+                fn is_solid(&self) -> bool {
+                    *self != 0
+                }
+
+                #[inline]
+                fn blend(&self, other: &Self, t: f32) -> Self {
+                    let t = t.clamp(0.0, 1.0) as f64;
+                    (*self as f64 + (*other as f64 - *self as f64) * t).round() as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_pixel_value_int!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+macro_rules! impl_pixel_value_float {
+    ($($t:ty),*) => {
+        $(
+            impl PixelValue for $t {
+                #[inline]
+                fn is_solid(&self) -> bool {
+                    *self != 0.0
+                }
+
+                #[inline]
+                fn blend(&self, other: &Self, t: f32) -> Self {
+                    let t = t.clamp(0.0, 1.0) as $t;
+                    *self + (*other - *self) * t
+                }
+            }
+        )*
+    };
+}
+
+impl_pixel_value_float!(f32, f64);
+
+impl<T: PixelValue> PixelValue for Option<T> {
+    #[inline]
+    fn is_solid(&self) -> bool {
+        self.is_some_and(|value| value.is_solid())
+    }
+
+    #[inline]
+    fn blend(&self, other: &Self, t: f32) -> Self {
+        match (self, other) {
+            (Some(a), Some(b)) => Some(a.blend(b, t)),
+            _ if t < 0.5 => *self,
+            _ => *other,
+        }
+    }
+
+    #[inline]
+    fn to_rgba(&self) -> [u8; 4] {
+        self.map_or([0; 4], |value| value.to_rgba())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_pixel_value() {
+        assert!(true.is_solid());
+        assert!(0u8.is_empty());
+        assert!((-3i32).is_solid());
+        assert!(!None::<u8>.is_solid());
+        assert!(!Some(0u8).is_solid());
+        assert_eq!(10u8.blend(&20, 0.25), 13);
+        assert_eq!(Some(0.0f32).blend(&Some(2.0), 0.5), Some(1.0));
+        assert_eq!(None.blend(&Some(4u16), 0.75), Some(4));
+        assert!(!false.blend(&true, 0.4));
+        assert_eq!(true.to_rgba(), [255; 4]);
+        assert_eq!(Some(0u32).to_rgba(), [0; 4]);
+
+        // As a default predicate
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 0, 1);
+        pm.draw_rect(&URect::new(2, 2, 6, 4), 3);
+        assert_eq!(
+            pm.maximal_rects(&pm.map_rect(), solid),
+            vec![URect::new(2, 2, 6, 4)]
+        );
+        assert_eq!(
+            pm.find_first(&pm.map_rect(), empty),
+            Some((UVec2::ZERO, &0))
+        );
+    }
+}
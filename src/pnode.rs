@@ -2,18 +2,60 @@
 use serde::{Deserialize, Serialize};
 
 use super::{ICircle, RayCast, RayCastContext, RayCastQuery, RayCastResult, Region};
-use crate::{exclusive_urect, to_cropped_urect, CellFill, NodePath, Quadrant};
+use crate::compress::CompressedNodes;
+use crate::pool::NodePool;
+use crate::{
+    inclusive_to_cropped_urect, to_cropped_urect, CellFill, Direction, InclusiveRect, NodePath,
+    NodeVisitor, Quadrant, TraversalOrder,
+};
 use bevy_math::{URect, UVec2};
 use num_traits::{NumCast, Unsigned};
 use std::fmt::Debug;
+use std::ops::ControlFlow;
 
 pub type Children<T, U> = Box<[PNode<T, U>; 4]>;
 
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
-#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+    rkyv(
+        archive_bounds(U: rkyv::Archive),
+        serialize_bounds(
+            U: rkyv::Serialize<__S>,
+            __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+            __S::Error: rkyv::rancor::Source,
+        ),
+        deserialize_bounds(
+            U::Archived: rkyv::Deserialize<U, __D>,
+            __D::Error: rkyv::rancor::Source,
+        ),
+        bytecheck(bounds(
+            U::Archived: rkyv::bytecheck::CheckBytes<__C>,
+            __C: rkyv::validation::ArchiveContext,
+            __C::Error: rkyv::rancor::Source,
+        )),
+    )
+)]
+#[derive(Clone, Debug)]
 enum PNodeKind<T: Copy + PartialEq = bool, U: Unsigned + NumCast + Copy + Debug = u16> {
     Leaf(T),
-    Branch(Children<T, U>),
+    Branch(#[cfg_attr(feature = "rkyv", rkyv(omit_bounds))] Children<T, U>),
+    // A branch node of which the children are compressed, as per
+    // [crate::PixelMap::compress_cold].
+    Compressed(#[cfg_attr(feature = "rkyv", rkyv(omit_bounds))] CompressedNodes<T, U>),
+}
+
+// Compressed children are equal to the same children when inflated.
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PartialEq for PNodeKind<T, U> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (PNodeKind::Leaf(a), PNodeKind::Leaf(b)) => a == b,
+            (PNodeKind::Compressed(a), PNodeKind::Compressed(b)) => a == b,
+            (PNodeKind::Leaf(_), _) | (_, PNodeKind::Leaf(_)) => false,
+            _ => self.children() == other.children(),
+        }
+    }
 }
 
 impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNodeKind<T, U> {
@@ -21,7 +63,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNodeKind<T, U>
     pub fn value(&self) -> &T {
         match self {
             PNodeKind::Leaf(value) => value,
-            PNodeKind::Branch(_) => {
+            PNodeKind::Branch(_) | PNodeKind::Compressed(_) => {
                 panic!("pixel map leaf node value accessed in branch node context");
             }
         }
@@ -29,27 +71,51 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNodeKind<T, U>
 
     #[inline]
     pub fn children(&self) -> &Children<T, U> {
-        match self {
-            PNodeKind::Leaf(_) => {
-                panic!("pixel map branch node children accessed in leaf node context");
-            }
-            PNodeKind::Branch(children) => children,
+        match self.branch() {
+            Some(children) => children,
+            None => panic!("pixel map branch node children accessed in leaf node context"),
         }
     }
 
+    // Compressed children are inflated, and remain so.
     #[inline]
     pub fn children_mut(&mut self) -> &mut Children<T, U> {
+        if let PNodeKind::Compressed(nodes) = self {
+            *self = PNodeKind::Branch(nodes.take_children());
+        }
         match self {
-            PNodeKind::Leaf(_) => {
-                panic!("pixel map branch node children accessed in leaf node context");
-            }
             PNodeKind::Branch(children) => children,
+            _ => panic!("pixel map branch node children accessed in leaf node context"),
+        }
+    }
+
+    // Obtain the children of a branch node, or `None` for a leaf node.
+    #[inline]
+    pub fn branch(&self) -> Option<&Children<T, U>> {
+        match self {
+            PNodeKind::Leaf(_) => None,
+            PNodeKind::Branch(children) => Some(children),
+            PNodeKind::Compressed(nodes) => Some(nodes.children()),
+        }
+    }
+
+    // Obtain the children of a branch node, as per [Self::children_mut], or `None` for a leaf
+    // node.
+    #[inline]
+    pub fn branch_mut(&mut self) -> Option<&mut Children<T, U>> {
+        match self {
+            PNodeKind::Leaf(_) => None,
+            _ => Some(self.children_mut()),
         }
     }
 }
 
 /// A node of a [crate::PixelMap] quadtree.
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 #[derive(Clone, Debug, PartialEq)]
 pub struct PNode<T: Copy + PartialEq = bool, U: Unsigned + NumCast + Copy + Debug = u16> {
     region: Region<U>,
@@ -68,6 +134,17 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         }
     }
 
+    // Create a clean branch node with the given children.
+    #[inline]
+    #[must_use]
+    pub(super) fn new_branch(region: Region<U>, children: Children<T, U>) -> Self {
+        Self {
+            region,
+            kind: PNodeKind::Branch(children),
+            dirty: false,
+        }
+    }
+
     /// Obtain the region represented by this node.
     #[inline]
     #[must_use]
@@ -105,6 +182,43 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         self.kind = PNodeKind::Leaf(value);
     }
 
+    /// Set the value of this node, as per [Self::set_value], but only marking the node as dirty
+    /// if `mark_dirty` is true. Otherwise, the node is dirty only if it, or any of its
+    /// discarded children, was already dirty. Discarded children are retired to the `pool`.
+    #[inline]
+    pub(super) fn set_value_pooled(
+        &mut self,
+        value: T,
+        mark_dirty: bool,
+        pool: &mut NodePool<T, U>,
+    ) {
+        if mark_dirty {
+            self.dirty = true;
+        }
+        if let PNodeKind::Branch(children) =
+            std::mem::replace(&mut self.kind, PNodeKind::Leaf(value))
+        {
+            pool.retire(children);
+        }
+    }
+
+    // Replace this node's children, if any, with a leaf node of an arbitrary value, being
+    // that of a descendant, and return them. Compressed children are discarded.
+    pub(super) fn detach_children(&mut self) -> Option<Children<T, U>> {
+        let mut leaf: &PNode<T, U> = self;
+        while let PNodeKind::Branch(children) = &leaf.kind {
+            leaf = &children[0];
+        }
+        let value = match &leaf.kind {
+            PNodeKind::Compressed(nodes) => nodes.first_value(),
+            _ => *leaf.value(),
+        };
+        match std::mem::replace(&mut self.kind, PNodeKind::Leaf(value)) {
+            PNodeKind::Branch(children) => Some(children),
+            _ => None,
+        }
+    }
+
     /// Obtain an array of the children of this node.
     /// Panics if this node is [Self::is_leaf()].
     #[inline]
@@ -119,6 +233,13 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         self.kind.children_mut()
     }
 
+    // Count a visit of this node, as per [crate::measure_traversal].
+    #[inline(always)]
+    fn record_visit(&self) {
+        #[cfg(feature = "traversal_stats")]
+        crate::traversal_stats::record(self.region.size_as::<u32>(), self.is_leaf());
+    }
+
     /// Determine if this node is a leaf node. Leaves don't have children.
     #[inline]
     #[must_use]
@@ -126,13 +247,22 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         matches!(self.kind, PNodeKind::Leaf(_))
     }
 
+    // Determine if the children of this node are compressed, as per
+    // [crate::PixelMap::compress_cold].
+    #[cfg(feature = "serialize")]
+    #[inline]
+    #[must_use]
+    pub(crate) fn is_compressed(&self) -> bool {
+        matches!(self.kind, PNodeKind::Compressed(_))
+    }
+
     /// Determine if all immediate children of this node are leaf nodes.
     #[inline]
     #[must_use]
     pub fn is_leaf_parent(&self) -> bool {
-        match &self.kind {
-            PNodeKind::Leaf(_) => false,
-            PNodeKind::Branch(children) => children.iter().all(|c| c.is_leaf()),
+        match self.kind.branch() {
+            None => false,
+            Some(children) => children.iter().all(|c| c.is_leaf()),
         }
     }
 
@@ -238,11 +368,12 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         F: FnMut(&PNode<T, U>, &URect) -> CellFill,
     {
         *traversed += 1;
+        self.record_visit();
 
         let sub_rect = self.region().intersect(rect);
         if !sub_rect.is_empty() {
             let node_profile = visitor(self, &sub_rect);
-            if let PNodeKind::Branch(children) = &self.kind {
+            if let Some(children) = self.kind.branch() {
                 let node_profile = node_profile as u8;
                 for q in Quadrant::iter() {
                     if node_profile & q.as_bit() != 0 {
@@ -253,18 +384,49 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         }
     }
 
+    // Visit all nodes within the given rectangle boundary with a [NodeVisitor], entering and
+    // leaving each branch around those of its children selected by the visitor.
+    pub(super) fn visit_nodes_in_rect_with<V>(
+        &self,
+        rect: &URect,
+        visitor: &mut V,
+        traversed: &mut u32,
+    ) where
+        V: NodeVisitor<T, U> + ?Sized,
+    {
+        *traversed += 1;
+        self.record_visit();
+
+        let sub_rect = self.region().intersect(rect);
+        if !sub_rect.is_empty() {
+            match self.kind.branch() {
+                None => visitor.visit_leaf(self, &sub_rect),
+                Some(children) => {
+                    let node_profile = visitor.enter_branch(self, &sub_rect) as u8;
+                    for q in Quadrant::iter() {
+                        if node_profile & q.as_bit() != 0 {
+                            children[q as usize].visit_nodes_in_rect_with(rect, visitor, traversed);
+                        }
+                    }
+                    visitor.leave_branch(self, &sub_rect);
+                }
+            }
+        }
+    }
+
     // Visit all leaf nodes within the given rectangle boundary.
     pub(super) fn visit_leaves_in_rect<F>(&self, rect: &URect, visitor: &mut F, traversed: &mut u32)
     where
         F: FnMut(&PNode<T, U>, &URect),
     {
         *traversed += 1;
+        self.record_visit();
 
         let sub_rect = self.region().intersect(rect);
         if !sub_rect.is_empty() {
-            match self.kind {
-                PNodeKind::Leaf(_) => visitor(self, &sub_rect),
-                PNodeKind::Branch(ref children) => {
+            match self.kind.branch() {
+                None => visitor(self, &sub_rect),
+                Some(children) => {
                     for child in children.as_ref() {
                         child.visit_leaves_in_rect(rect, visitor, traversed);
                     }
@@ -273,21 +435,79 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         }
     }
 
+    // Visit all leaf nodes within the given rectangle boundary, until the visitor breaks.
+    pub(super) fn try_visit_leaves_in_rect<B, F>(
+        &self,
+        rect: &URect,
+        visitor: &mut F,
+        traversed: &mut u32,
+    ) -> ControlFlow<B>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> ControlFlow<B>,
+    {
+        *traversed += 1;
+        self.record_visit();
+
+        let sub_rect = self.region().intersect(rect);
+        if !sub_rect.is_empty() {
+            match self.kind.branch() {
+                None => return visitor(self, &sub_rect),
+                Some(children) => {
+                    for child in children.as_ref() {
+                        child.try_visit_leaves_in_rect(rect, visitor, traversed)?;
+                    }
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Visit all leaf nodes within the given rectangle boundary, descending into the children
+    // of each branch node in the given order, until the visitor breaks.
+    pub(super) fn try_visit_leaves_in_rect_ordered<B, F>(
+        &self,
+        rect: &URect,
+        order: &TraversalOrder,
+        visitor: &mut F,
+        traversed: &mut u32,
+    ) -> ControlFlow<B>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> ControlFlow<B>,
+    {
+        *traversed += 1;
+        self.record_visit();
+
+        let sub_rect = self.region().intersect(rect);
+        if !sub_rect.is_empty() {
+            match self.kind.branch() {
+                None => return visitor(self, &sub_rect),
+                Some(children) => {
+                    for q in order.child_order(self.region().center()) {
+                        children[q as usize]
+                            .try_visit_leaves_in_rect_ordered(rect, order, visitor, traversed)?;
+                    }
+                }
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
     pub(super) fn any_leaves_in_rect<F>(&self, rect: &URect, f: &mut F) -> Option<bool>
     where
         F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
+        self.record_visit();
         let sub_rect = self.region().intersect(rect);
         if !sub_rect.is_empty() {
-            match self.kind {
-                PNodeKind::Branch(ref children) => {
+            match self.kind.branch() {
+                Some(children) => {
                     for child in children.as_ref() {
                         if let Some(true) = child.any_leaves_in_rect(rect, f) {
                             return Some(true);
                         }
                     }
                 }
-                PNodeKind::Leaf(_) => {
+                None => {
                     if f(self, &sub_rect) {
                         return Some(true);
                     }
@@ -302,17 +522,18 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
     where
         F: FnMut(&PNode<T, U>, &URect) -> bool,
     {
+        self.record_visit();
         let sub_rect = self.region().intersect(rect);
         if !sub_rect.is_empty() {
-            match self.kind {
-                PNodeKind::Branch(ref children) => {
+            match self.kind.branch() {
+                Some(children) => {
                     for child in children.as_ref() {
                         if let Some(false) = child.all_leaves_in_rect(rect, f) {
                             return Some(false);
                         }
                     }
                 }
-                PNodeKind::Leaf(_) => {
+                None => {
                     if !f(self, &sub_rect) {
                         return Some(false);
                     }
@@ -323,6 +544,74 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         None
     }
 
+    // Like `any_leaves_in_rect`, but nodes for which `overlaps` returns `false`, given the
+    // intersection of the node's region and `rect`, are pruned along with their children.
+    pub(super) fn any_overlapping_leaves_in_rect<O, F>(
+        &self,
+        rect: &URect,
+        overlaps: &O,
+        f: &mut F,
+    ) -> Option<bool>
+    where
+        O: Fn(&URect) -> bool,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.record_visit();
+        let sub_rect = self.region().intersect(rect);
+        if sub_rect.is_empty() || !overlaps(&sub_rect) {
+            return None;
+        }
+        match self.kind.branch() {
+            Some(children) => {
+                for child in children.as_ref() {
+                    if let Some(true) = child.any_overlapping_leaves_in_rect(rect, overlaps, f) {
+                        return Some(true);
+                    }
+                }
+            }
+            None => {
+                if f(self, &sub_rect) {
+                    return Some(true);
+                }
+            }
+        }
+        Some(false)
+    }
+
+    // Like `all_leaves_in_rect`, but nodes for which `overlaps` returns `false`, given the
+    // intersection of the node's region and `rect`, are pruned along with their children.
+    pub(super) fn all_overlapping_leaves_in_rect<O, F>(
+        &self,
+        rect: &URect,
+        overlaps: &O,
+        f: &mut F,
+    ) -> Option<bool>
+    where
+        O: Fn(&URect) -> bool,
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        self.record_visit();
+        let sub_rect = self.region().intersect(rect);
+        if sub_rect.is_empty() || !overlaps(&sub_rect) {
+            return None;
+        }
+        match self.kind.branch() {
+            Some(children) => {
+                for child in children.as_ref() {
+                    if let Some(false) = child.all_overlapping_leaves_in_rect(rect, overlaps, f) {
+                        return Some(false);
+                    }
+                }
+            }
+            None => {
+                if !f(self, &sub_rect) {
+                    return Some(false);
+                }
+            }
+        }
+        Some(true)
+    }
+
     // This node must be known to be dirty.
     pub(super) fn visit_dirty_leaves_in_rect<F>(
         &self,
@@ -333,18 +622,19 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         F: FnMut(&PNode<T, U>, &URect),
     {
         *traversed += 1;
+        self.record_visit();
 
         let sub_rect = self.region().intersect(rect);
         if !sub_rect.is_empty() {
-            match self.kind {
-                PNodeKind::Branch(ref children) => {
+            match self.kind.branch() {
+                Some(children) => {
                     for child in children.as_ref() {
                         if child.dirty() {
                             child.visit_dirty_leaves_in_rect(rect, visitor, traversed);
                         }
                     }
                 }
-                PNodeKind::Leaf(_) => visitor(self, &sub_rect),
+                None => visitor(self, &sub_rect),
             }
         }
     }
@@ -355,17 +645,18 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         F: FnMut(&PNode<T, U>),
     {
         *traversed += 1;
+        self.record_visit();
 
         self.clear_dirty();
-        match self.kind {
-            PNodeKind::Branch(ref mut children) => {
+        match self.kind.branch_mut() {
+            Some(children) => {
                 for child in children.as_mut() {
                     if child.dirty() {
                         child.drain_dirty_leaves(visitor, traversed);
                     }
                 }
             }
-            PNodeKind::Leaf(_) => visitor(self),
+            None => visitor(self),
         }
     }
 
@@ -376,7 +667,8 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
     pub(super) fn find_node(&self, point: UVec2) -> &PNode<T, U> {
         let mut node = self;
         loop {
-            if let PNodeKind::Branch(children) = &node.kind {
+            node.record_visit();
+            if let Some(children) = node.kind.branch() {
                 let q = node.region.quadrant_for_upoint(point);
                 node = &children[q as usize];
             } else {
@@ -385,6 +677,44 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         }
     }
 
+    // Visit the leaf nodes containing each of the given points, descending into each node
+    // once for all of its points. The points must be within the bounds of this node, and
+    // sorted by [crate::math::morton_code] relative to the root node, such that the points
+    // of each child are contiguous, in the order of bottom-left, bottom-right, top-left and
+    // top-right. The visitor receives the index accompanying each point.
+    pub(super) fn visit_sorted_points<F>(&self, points: &[(UVec2, usize)], f: &mut F)
+    where
+        F: FnMut(usize, &PNode<T, U>),
+    {
+        self.record_visit();
+        match self.kind.branch() {
+            Some(children) => {
+                let mut points = points;
+                for q in [
+                    Quadrant::BottomLeft,
+                    Quadrant::BottomRight,
+                    Quadrant::TopLeft,
+                    Quadrant::TopRight,
+                ] {
+                    if points.is_empty() {
+                        break;
+                    }
+                    let child = &children[q as usize];
+                    let count = points.partition_point(|(p, _)| child.region.contains_upoint(*p));
+                    if count > 0 {
+                        child.visit_sorted_points(&points[..count], f);
+                        points = &points[count..];
+                    }
+                }
+            }
+            None => {
+                for (_, index) in points {
+                    f(*index, self);
+                }
+            }
+        }
+    }
+
     #[inline]
     #[must_use]
     pub(super) fn node_path(&self, point: UVec2) -> (&PNode<T, U>, NodePath) {
@@ -392,7 +722,8 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         let mut node = self;
         let mut path = 0;
         loop {
-            if let PNodeKind::Branch(children) = &node.kind {
+            node.record_visit();
+            if let Some(children) = node.kind.branch() {
                 let q = node.region.quadrant_for_upoint(point);
                 path |= (q as u64) << (depth * 2);
                 depth += 1;
@@ -406,7 +737,6 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
 
     #[inline]
     #[must_use]
-    #[allow(dead_code)]
     pub(super) fn find_node_by_path(&self, path: NodePath) -> Option<&PNode<T, U>> {
         let mut path_depth = path.depth() as u64;
         if path_depth == 0 {
@@ -420,7 +750,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
             if depth == path_depth {
                 return Some(node);
             }
-            if let PNodeKind::Branch(children) = &node.kind {
+            if let Some(children) = node.kind.branch() {
                 let q = (*path >> (depth * 2)) & 0b11;
                 depth += 1;
                 node = &children[q as usize];
@@ -442,10 +772,11 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
     {
         loop {
             ctx.traversed += 1;
+            self.record_visit();
             let current_point = ctx.line_iter.peek()?;
             if self.region.contains_ipoint(current_point) {
-                match self.kind {
-                    PNodeKind::Branch(ref children) => {
+                match self.kind.branch() {
+                    Some(children) => {
                         let q = self.region.quadrant_for_ipoint(current_point);
                         let result = children[q as usize].ray_cast(query, ctx, visitor);
                         if result.is_some() {
@@ -453,7 +784,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
                         }
                         continue;
                     }
-                    PNodeKind::Leaf(_) => {
+                    None => {
                         return match visitor(self) {
                             RayCast::Continue => {
                                 ctx.line_iter.seek_bounds(&self.region().into());
@@ -481,79 +812,194 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         }
     }
 
-    pub(super) fn set_pixel(&mut self, point: UVec2, pixel_size: u8, value: T) -> bool {
-        if self.region.contains_upoint(point) {
-            if self.is_leaf() && &value == self.value() {
-                return true;
-            }
-            if self.region.is_unit(pixel_size) {
-                self.set_value(value);
-            } else {
-                self.subdivide();
-                let q = self.region.quadrant_for_upoint(point);
-                self.children_mut()[q as usize].set_pixel(point, pixel_size, value);
-                self.decimate();
-                self.recalc_dirty();
+    // When `mark_dirty` is false, the dirty state of affected nodes is retained, rather than
+    // being set. Returns whether the value of the pixel changed.
+    pub(super) fn set_pixel(
+        &mut self,
+        point: UVec2,
+        pixel_size: u8,
+        value: T,
+        mark_dirty: bool,
+        pool: &mut NodePool<T, U>,
+    ) -> bool {
+        if !self.region.contains_upoint(point) {
+            return false;
+        }
+        self.record_visit();
+        if self.is_leaf() && &value == self.value() {
+            return false;
+        }
+        if self.region.is_unit(pixel_size) {
+            self.set_value_pooled(value, mark_dirty, pool);
+            true
+        } else {
+            self.subdivide(pool);
+            let q = self.region.quadrant_for_upoint(point);
+            let changed = self.children_mut()[q as usize]
+                .set_pixel(point, pixel_size, value, mark_dirty, pool);
+            self.decimate(pool);
+            self.recalc_dirty();
+            changed
+        }
+    }
+
+    // All `points` must be contained by this node's region. They are reordered by quadrant
+    // so that each child is descended into once for all of its points. The dirty state of
+    // affected nodes is set only if `mark_dirty` is true. Returns whether the value of any
+    // pixel changed.
+    pub(super) fn set_points(
+        &mut self,
+        points: &mut [UVec2],
+        pixel_size: u8,
+        value: T,
+        mark_dirty: bool,
+        pool: &mut NodePool<T, U>,
+    ) -> bool {
+        self.record_visit();
+        if points.is_empty() || (self.is_leaf() && &value == self.value()) {
+            return false;
+        }
+        if self.region.is_unit(pixel_size) {
+            self.set_value_pooled(value, mark_dirty, pool);
+            true
+        } else {
+            let region = self.region.clone();
+            points.sort_unstable_by_key(|p| region.quadrant_for_upoint(*p) as u8);
+            self.subdivide(pool);
+            let children = self.children_mut();
+            let mut rest = points;
+            let mut changed = false;
+            for (i, child) in children.iter_mut().enumerate() {
+                let n = rest.partition_point(|p| (region.quadrant_for_upoint(*p) as usize) <= i);
+                let (child_points, remainder) = rest.split_at_mut(n);
+                changed |= child.set_points(child_points, pixel_size, value, mark_dirty, pool);
+                rest = remainder;
             }
-            return true;
+            self.decimate(pool);
+            self.recalc_dirty();
+            changed
         }
-        false
     }
 
-    pub(super) fn draw_rect(&mut self, rect: &URect, pixel_size: u8, value: T) {
+    // When `mark_dirty` is false, the dirty state of affected nodes is retained, rather than
+    // being set. Returns whether the value of any pixel changed.
+    pub(super) fn draw_rect(
+        &mut self,
+        rect: &URect,
+        pixel_size: u8,
+        value: T,
+        mark_dirty: bool,
+        pool: &mut NodePool<T, U>,
+        traversed: &mut u32,
+    ) -> bool {
+        *traversed += 1;
+        self.record_visit();
+
         if self.contained_by_rect(rect) {
-            self.set_value(value);
+            let changed = self.differs_from(&value);
+            self.set_value_pooled(value, mark_dirty && changed, pool);
+            return changed;
+        }
+        let sub_rect = self.region().intersect(rect);
+        if sub_rect.is_empty() || (self.is_leaf() && &value == self.value()) {
+            return false;
+        }
+        if self.region.is_unit(pixel_size) {
+            self.set_value_pooled(value, mark_dirty, pool);
+            true
         } else {
-            let sub_rect = self.region().intersect(rect);
-            if !sub_rect.is_empty() {
-                if self.is_leaf() && &value == self.value() {
-                    return;
-                }
-                if self.region.is_unit(pixel_size) {
-                    self.set_value(value);
-                } else {
-                    self.subdivide();
-                    let children = self.children_mut();
-                    children[0].draw_rect(&sub_rect, pixel_size, value);
-                    children[1].draw_rect(&sub_rect, pixel_size, value);
-                    children[2].draw_rect(&sub_rect, pixel_size, value);
-                    children[3].draw_rect(&sub_rect, pixel_size, value);
-                    self.decimate();
-                    self.recalc_dirty();
-                }
+            self.subdivide(pool);
+            let mut changed = false;
+            for child in self.children_mut().iter_mut() {
+                changed |=
+                    child.draw_rect(&sub_rect, pixel_size, value, mark_dirty, pool, traversed);
             }
+            self.decimate(pool);
+            self.recalc_dirty();
+            changed
+        }
+    }
+
+    // Subdivide leaf nodes overlapping the given rectangle until their size is no greater than
+    // `min_size`, counting the nodes subdivided.
+    pub(super) fn presubdivide(
+        &mut self,
+        rect: &URect,
+        min_size: u32,
+        pool: &mut NodePool<T, U>,
+        count: &mut usize,
+    ) {
+        if self.region.size_as::<u32>() <= min_size || self.region().intersect(rect).is_empty() {
+            return;
+        }
+        if self.is_leaf() {
+            self.subdivide(pool);
+            *count += 1;
+        }
+        for child in self.children_mut().iter_mut() {
+            child.presubdivide(rect, min_size, pool, count);
         }
     }
 
-    pub(super) fn draw_circle(&mut self, circle: &ICircle, pixel_size: u8, value: T) {
-        let outer_rect = to_cropped_urect(&circle.aabb());
-        let inner_rect = to_cropped_urect(&circle.inner_rect());
-        if self.contained_by_rect(&inner_rect) {
-            self.set_value(value);
+    // Pixels outside the `clip` rectangle are not drawn. The dirty state of affected nodes is
+    // set only if `mark_dirty` is true. Returns whether the value of any pixel changed.
+    pub(super) fn draw_circle(
+        &mut self,
+        circle: &ICircle,
+        clip: &URect,
+        pixel_size: u8,
+        value: T,
+        mark_dirty: bool,
+        pool: &mut NodePool<T, U>,
+    ) -> bool {
+        let outer_rect = to_cropped_urect(&circle.aabb()).intersect(*clip);
+        let inner_rect = to_cropped_urect(&circle.inner_rect()).intersect(*clip);
+        if !inner_rect.is_empty() && self.contained_by_rect(&inner_rect) {
+            let changed = self.differs_from(&value);
+            self.set_value_pooled(value, mark_dirty && changed, pool);
+            changed
         } else if !self.region().intersect(&outer_rect).is_empty() {
-            self.draw_rect(&inner_rect, pixel_size, value);
-            let inner_rect = exclusive_urect(&inner_rect);
-            for p in circle.unsigned_pixels() {
-                if inner_rect.contains(p) {
-                    continue;
+            let mut changed =
+                self.draw_rect(&inner_rect, pixel_size, value, mark_dirty, pool, &mut 0);
+            // Only the rows of the circle within this node and the clip are examined
+            let rows = inclusive_to_cropped_urect(&circle.aabb()).intersect(*clip);
+            let Some(bounds) = self.region().intersect(&rows).as_irect().to_inclusive() else {
+                return changed;
+            };
+            for span in circle.spans_in(&bounds) {
+                for x in span.start().x..=span.end().x {
+                    let p = UVec2::new(x as u32, span.start().y as u32);
+                    if !inner_rect.contains_exclusive(p) {
+                        changed |= self.set_pixel(p, pixel_size, value, mark_dirty, pool);
+                    }
                 }
-                self.set_pixel(p, pixel_size, value);
             }
+            changed
+        } else {
+            false
         }
     }
 
-    pub(super) fn visit_neighbor_pairs_face<F>(&self, rect: &URect, visitor: &mut F)
-    where
+    // Counts the nodes traversed, each being counted once, though the leaf nodes along the
+    // edges between children are visited again as neighbors.
+    pub(super) fn visit_neighbor_pairs_face<F>(
+        &self,
+        rect: &URect,
+        visitor: &mut F,
+        traversed: &mut u32,
+    ) where
         F: FnMut(NeighborOrientation, &PNode<T, U>, &URect, &PNode<T, U>, &URect),
     {
-        if let PNodeKind::Branch(ref children) = self.kind {
+        *traversed += 1;
+        self.record_visit();
+        if let Some(children) = self.kind.branch() {
             let sub_rect = self.region().intersect(rect);
             if sub_rect.is_empty() {
                 return;
             }
 
             for child in children.as_ref() {
-                child.visit_neighbor_pairs_face(&sub_rect, visitor);
+                child.visit_neighbor_pairs_face(&sub_rect, visitor, traversed);
             }
 
             Self::visit_neighbor_pairs_edge_h(
@@ -713,13 +1159,157 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         }
     }
 
+    // Visit the pairs of leaf nodes meeting diagonally at the center of each branch node
+    // intersecting the given rectangle. Leaf nodes only ever touch at a corner, without
+    // sharing an edge, at the center of a branch node.
+    pub(super) fn visit_neighbor_pairs_corner<F>(&self, rect: &URect, visitor: &mut F)
+    where
+        F: FnMut(Direction, &PNode<T, U>, &URect, &PNode<T, U>, &URect),
+    {
+        self.record_visit();
+        if let Some(children) = self.kind.branch() {
+            let sub_rect = self.region().intersect(rect);
+            if sub_rect.is_empty() {
+                return;
+            }
+
+            for child in children.as_ref() {
+                child.visit_neighbor_pairs_corner(&sub_rect, visitor);
+            }
+
+            // The leaf node of each child touching the center of this node
+            let inner = |quadrant: Quadrant, toward: Quadrant| {
+                let mut node = &children[quadrant as usize];
+                while let Some(children) = node.kind.branch() {
+                    node = &children[toward as usize];
+                }
+                node
+            };
+            let pairs = [
+                (
+                    Direction::NorthEast,
+                    inner(Quadrant::BottomLeft, Quadrant::TopRight),
+                    inner(Quadrant::TopRight, Quadrant::BottomLeft),
+                ),
+                (
+                    Direction::NorthWest,
+                    inner(Quadrant::BottomRight, Quadrant::TopLeft),
+                    inner(Quadrant::TopLeft, Quadrant::BottomRight),
+                ),
+            ];
+            for (direction, bottom, top) in pairs {
+                let sub_rect_b = bottom.region().intersect(rect);
+                let sub_rect_t = top.region().intersect(rect);
+                if !sub_rect_b.is_empty() && !sub_rect_t.is_empty() {
+                    visitor(direction, bottom, &sub_rect_b, top, &sub_rect_t);
+                }
+            }
+        }
+    }
+
+    // Move this node, and its descendants, such that its region begins at the given point.
+    // All nodes are marked dirty.
+    pub(super) fn relocate(&mut self, origin: UVec2) {
+        let point = self.region.point();
+        self.region = Region::new(
+            U::from(origin.x).unwrap(),
+            U::from(origin.y).unwrap(),
+            self.region.size(),
+        );
+        self.dirty = true;
+        if let Some(children) = self.kind.branch_mut() {
+            for child in children.iter_mut() {
+                let child_origin = origin + (child.region.point() - point);
+                child.relocate(child_origin);
+            }
+        }
+    }
+
+    // Replace the node at the given path with `node`, which must have the same region.
+    // Ancestors of the replaced node are decimated, and their dirty state recalculated.
+    // Returns false if the path does not lead to a node.
+    pub(super) fn graft(&mut self, path: NodePath, depth: u64, node: PNode<T, U>) -> bool {
+        if depth + 1 == path.depth() as u64 {
+            debug_assert_eq!(self.region, node.region);
+            *self = node;
+            return true;
+        }
+        let Some(children) = self.kind.branch_mut() else {
+            return false;
+        };
+        let q = (*path >> (depth * 2)) & 0b11;
+        if !children[q as usize].graft(path, depth + 1, node) {
+            return false;
+        }
+        self.decimate(&mut NodePool::default());
+        self.recalc_dirty();
+        true
+    }
+
+    // Compress the clean branch nodes at the given depth below this node, for which `is_cold`
+    // returns true given their region, discarding the inflated children of those already
+    // compressed. Returns the number of nodes compressed.
+    pub(super) fn compress_cold<F>(&mut self, depth: u32, is_cold: &mut F) -> usize
+    where
+        F: FnMut(&Region<U>) -> bool,
+    {
+        match &mut self.kind {
+            PNodeKind::Branch(children) if depth > 0 => children
+                .iter_mut()
+                .map(|child| child.compress_cold(depth - 1, is_cold))
+                .sum(),
+            PNodeKind::Branch(children) if !self.dirty && is_cold(&self.region) => {
+                let nodes = CompressedNodes::new(self.region.clone(), children);
+                self.kind = PNodeKind::Compressed(nodes);
+                1
+            }
+            PNodeKind::Compressed(nodes) => {
+                nodes.discard_inflated();
+                0
+            }
+            _ => 0,
+        }
+    }
+
+    // Inflate all compressed nodes of this subtree.
+    pub(super) fn inflate(&mut self) {
+        if let Some(children) = self.kind.branch_mut() {
+            for child in children.iter_mut() {
+                child.inflate();
+            }
+        }
+    }
+
+    // Count the compressed nodes of this subtree.
+    #[must_use]
+    pub(super) fn compressed_count(&self) -> usize {
+        match &self.kind {
+            PNodeKind::Leaf(_) => 0,
+            PNodeKind::Branch(children) => children.iter().map(|c| c.compressed_count()).sum(),
+            PNodeKind::Compressed(_) => 1,
+        }
+    }
+
+    // Determine if any leaf node of this subtree has a value other than the given value, such
+    // that writing the value would change pixel data. Branch nodes usually do, unless they
+    // were subdivided without modification.
+    #[must_use]
+    pub(super) fn differs_from(&self, value: &T) -> bool {
+        match &self.kind {
+            PNodeKind::Leaf(v) => v != value,
+            PNodeKind::Branch(children) => children.iter().any(|child| child.differs_from(value)),
+            PNodeKind::Compressed(nodes) => nodes.differs_from(value),
+        }
+    }
+
     #[inline]
     #[must_use]
     fn contained_by_rect(&self, rect: &URect) -> bool {
         rect.contains(self.region.point()) && rect.contains(self.region.end_point())
     }
 
-    fn subdivide(&mut self) {
+    // Reuses the allocation of retired children from the `pool`, if any.
+    fn subdivide(&mut self, pool: &mut NodePool<T, U>) {
         if !self.is_leaf() {
             return;
         }
@@ -729,7 +1319,7 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
         let half_size = self.region.half_size();
 
         let value = *self.value();
-        self.kind = PNodeKind::Branch(Box::new([
+        let nodes = [
             PNode::new(Region::new(x, y, half_size), value, self.dirty),
             PNode::new(Region::new(x + half_size, y, half_size), value, self.dirty),
             PNode::new(
@@ -738,15 +1328,24 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
                 self.dirty,
             ),
             PNode::new(Region::new(x, y + half_size, half_size), value, self.dirty),
-        ]));
+        ];
+        let children = match pool.take() {
+            Some(mut children) => {
+                *children = nodes;
+                children
+            }
+            None => Box::new(nodes),
+        };
+        self.kind = PNodeKind::Branch(children);
     }
 
-    fn decimate(&mut self) {
+    // Merged children are retired to the `pool`.
+    fn decimate(&mut self, pool: &mut NodePool<T, U>) {
         if !self.is_leaf_parent() {
             return;
         }
 
-        if let PNodeKind::Branch(children) = &self.kind {
+        if let Some(children) = self.kind.branch() {
             let mut all_same = true;
             let mut c: Option<&T> = None;
 
@@ -761,10 +1360,44 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
                 }
             }
 
+            // Merged children are already marked dirty as appropriate
             if all_same {
-                self.set_value(*c.unwrap());
+                let value = *c.unwrap();
+                self.recalc_dirty();
+                self.set_value_pooled(value, false, pool);
+            }
+        }
+    }
+
+    /// Merge the children of leaf parent nodes, bottom-up, where each child's value is
+    /// accepted by `tolerance` when compared with the first child's value, taking the first
+    /// child's value. Returns the number of nodes merged.
+    pub(super) fn decimate_with<F>(&mut self, tolerance: &F) -> usize
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let Some(children) = self.kind.branch_mut() else {
+            return 0;
+        };
+        let mut merged = 0;
+        for child in children.iter_mut() {
+            merged += child.decimate_with(tolerance);
+        }
+        if merged > 0 {
+            self.recalc_dirty();
+        }
+        if self.is_leaf_parent() {
+            let children = self.children();
+            let first = *children[0].value();
+            if children[1..]
+                .iter()
+                .all(|child| tolerance(&first, child.value()))
+            {
+                self.set_value(first);
+                merged += 1;
             }
         }
+        merged
     }
 
     #[inline]
@@ -775,6 +1408,104 @@ impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PNode<T, U> {
     }
 }
 
+/// Queries of an archived [PNode], as per [crate::ArchivedPixelMap].
+#[cfg(feature = "rkyv")]
+impl<T, U> ArchivedPNode<T, U>
+where
+    T: Copy + PartialEq + rkyv::Archive,
+    U: Unsigned + NumCast + Copy + Debug + rkyv::Archive,
+    U::Archived: Copy + Into<U>,
+{
+    /// Obtain the region represented by this node.
+    #[inline]
+    #[must_use]
+    pub fn region(&self) -> Region<U> {
+        self.region.to_region()
+    }
+
+    /// Determine if this node was dirty when archived.
+    #[inline]
+    #[must_use]
+    pub fn dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Determine if this node is a leaf node.
+    #[inline]
+    #[must_use]
+    pub fn is_leaf(&self) -> bool {
+        matches!(self.kind, ArchivedPNodeKind::Leaf(_))
+    }
+
+    /// Obtain the archived value of this node.
+    ///
+    /// # Panics
+    ///
+    /// If this node is not a leaf node.
+    #[inline]
+    #[must_use]
+    pub fn value(&self) -> &T::Archived {
+        match &self.kind {
+            ArchivedPNodeKind::Leaf(value) => value,
+            ArchivedPNodeKind::Branch(_) | ArchivedPNodeKind::Compressed(_) => {
+                panic!("pixel map leaf node value accessed in branch node context");
+            }
+        }
+    }
+
+    /// Obtain the children of this node.
+    ///
+    /// # Panics
+    ///
+    /// If this node is a leaf node.
+    #[inline]
+    #[must_use]
+    pub fn children(&self) -> &[ArchivedPNode<T, U>; 4] {
+        match &self.kind {
+            ArchivedPNodeKind::Leaf(_) => {
+                panic!("pixel map branch node children accessed in leaf node context");
+            }
+            ArchivedPNodeKind::Branch(children) | ArchivedPNodeKind::Compressed(children) => {
+                children.get()
+            }
+        }
+    }
+
+    // Get the node that contains the given coordinates. The coordinates must be
+    // known to be within the bounds of this node.
+    #[must_use]
+    pub(crate) fn find_node(&self, point: UVec2) -> &ArchivedPNode<T, U> {
+        let mut node = self;
+        while let ArchivedPNodeKind::Branch(children) | ArchivedPNodeKind::Compressed(children) =
+            &node.kind
+        {
+            let q = node.region().quadrant_for_upoint(point);
+            node = &children.get()[q as usize];
+        }
+        node
+    }
+
+    // Visit all leaf nodes within the given rectangle boundary.
+    pub(crate) fn visit_leaves_in_rect<F>(&self, rect: &URect, visitor: &mut F, traversed: &mut u32)
+    where
+        F: FnMut(&ArchivedPNode<T, U>, &URect),
+    {
+        *traversed += 1;
+
+        let sub_rect = self.region().intersect(rect);
+        if !sub_rect.is_empty() {
+            match &self.kind {
+                ArchivedPNodeKind::Leaf(_) => visitor(self, &sub_rect),
+                ArchivedPNodeKind::Branch(children) | ArchivedPNodeKind::Compressed(children) => {
+                    for child in children.get() {
+                        child.visit_leaves_in_rect(rect, visitor, traversed);
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Describes the orientation of a pair of neighboring nodes.
 #[derive(Debug, PartialEq)]
 pub enum NeighborOrientation {
@@ -789,7 +1520,7 @@ mod test {
     #[test]
     fn test_subdivide() {
         let mut n = PNode::new(Region::new(0u32, 0, 4), false, false);
-        n.subdivide();
+        n.subdivide(&mut NodePool::default());
         let children = n.children();
         assert_eq!(
             &children[0],
@@ -812,25 +1543,25 @@ mod test {
     #[test]
     fn test_decimate_frees_children() {
         let mut n = PNode::new(Region::new(0u32, 0, 4), false, false);
-        n.subdivide();
+        n.subdivide(&mut NodePool::default());
         assert!(!n.is_leaf());
-        n.decimate();
+        n.decimate(&mut NodePool::default());
         assert!(n.is_leaf());
     }
 
     #[test]
     fn test_decimate_retains_children() {
         let mut n = PNode::new(Region::new(0u32, 0, 4), false, false);
-        n.subdivide();
+        n.subdivide(&mut NodePool::default());
         n.children_mut()[0].set_value(true);
-        n.decimate();
+        n.decimate(&mut NodePool::default());
         assert!(!n.is_leaf());
     }
 
     #[test]
     fn test_find_node() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.subdivide();
+        n.subdivide(&mut NodePool::default());
         n.children_mut()[0].set_value(true);
         assert!(n.find_node((0, 0).into()).value());
         assert!(!n.find_node((1, 0).into()).value());
@@ -845,13 +1576,13 @@ mod test {
         let (_, path) = n.node_path((0, 0).into());
         assert_eq!(path.depth(), 1);
 
-        n.subdivide();
+        n.subdivide(&mut NodePool::default());
 
         let (_, path) = n.node_path((0, 0).into());
         assert_eq!(path.path_bits(), 0);
         assert_eq!(path.depth(), 2);
 
-        n.children_mut()[0].subdivide();
+        n.children_mut()[0].subdivide(&mut NodePool::default());
 
         let (_, path) = n.node_path((0, 0).into());
         assert_eq!(path.path_bits(), 0);
@@ -883,7 +1614,7 @@ mod test {
         let node = n.find_node_by_path(NodePath::encode(2, 0));
         assert_eq!(node, None);
 
-        n.subdivide();
+        n.subdivide(&mut NodePool::default());
 
         let node = n.find_node_by_path(NodePath::ROOT);
         assert_eq!(node, None);
@@ -906,7 +1637,7 @@ mod test {
         let node = n.find_node_by_path(NodePath::encode(3, 0b11));
         assert_eq!(node, None);
 
-        n.children_mut()[0].subdivide();
+        n.children_mut()[0].subdivide(&mut NodePool::default());
 
         let node = n.find_node_by_path(NodePath::encode(2, 0));
         assert_eq!(*node.unwrap(), n.children()[0]);
@@ -921,7 +1652,7 @@ mod test {
     #[test]
     fn test_set_pixel_subdivides() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.set_pixel((0, 0).into(), 1, true);
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default());
         assert!(!n.is_leaf());
         assert!(n.find_node((0, 0).into()).value());
         assert!(!n.find_node((1, 0).into()).value());
@@ -932,10 +1663,10 @@ mod test {
     #[test]
     fn test_set_pixel_on_decimates() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.set_pixel((0, 0).into(), 1, true);
-        n.set_pixel((1, 0).into(), 1, true);
-        n.set_pixel((0, 1).into(), 1, true);
-        n.set_pixel((1, 1).into(), 1, true);
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default());
+        n.set_pixel((1, 0).into(), 1, true, true, &mut NodePool::default());
+        n.set_pixel((0, 1).into(), 1, true, true, &mut NodePool::default());
+        n.set_pixel((1, 1).into(), 1, true, true, &mut NodePool::default());
         assert!(n.value());
         assert!(n.is_leaf());
     }
@@ -943,8 +1674,8 @@ mod test {
     #[test]
     fn test_set_pixel_off_decimates() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.set_pixel((0, 0).into(), 1, true);
-        n.set_pixel((0, 0).into(), 1, false);
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default());
+        n.set_pixel((0, 0).into(), 1, false, true, &mut NodePool::default());
         assert!(!n.value());
         assert!(n.is_leaf());
     }
@@ -952,10 +1683,10 @@ mod test {
     #[test]
     fn test_visit_nodes() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.set_pixel((0, 0).into(), 1, true);
-        n.set_pixel((1, 0).into(), 1, false);
-        n.set_pixel((0, 1).into(), 1, true);
-        n.set_pixel((1, 1).into(), 1, false);
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default());
+        n.set_pixel((1, 0).into(), 1, false, true, &mut NodePool::default());
+        n.set_pixel((0, 1).into(), 1, true, true, &mut NodePool::default());
+        n.set_pixel((1, 1).into(), 1, false, true, &mut NodePool::default());
         let mut count = 0;
         n.visit_nodes_in_rect(
             &n.region().into(),
@@ -971,7 +1702,14 @@ mod test {
     #[test]
     fn test_set_rect_full() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.draw_rect(&URect::new(0, 0, 2, 2), 1, true);
+        n.draw_rect(
+            &URect::new(0, 0, 2, 2),
+            1,
+            true,
+            true,
+            &mut NodePool::default(),
+            &mut 0,
+        );
         assert!(n.value());
         assert!(n.is_leaf());
     }
@@ -979,7 +1717,14 @@ mod test {
     #[test]
     fn test_set_rect_contained() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.draw_rect(&URect::new(0, 0, 1, 1), 1, true);
+        n.draw_rect(
+            &URect::new(0, 0, 1, 1),
+            1,
+            true,
+            true,
+            &mut NodePool::default(),
+            &mut 0,
+        );
         assert!(!n.is_leaf());
         assert!(n.children()[Quadrant::BottomLeft as usize].value());
     }
@@ -988,7 +1733,7 @@ mod test {
     fn test_dirty() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
         assert!(!n.dirty);
-        n.set_pixel((0, 0).into(), 1, true);
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default());
         assert!(n.dirty);
         assert!(n.children_mut()[Quadrant::BottomLeft as usize].dirty);
         assert!(!n.children_mut()[Quadrant::BottomRight as usize].dirty);
@@ -999,7 +1744,7 @@ mod test {
     #[test]
     fn test_drain_dirty_leaves() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.set_pixel((0, 0).into(), 1, true);
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default());
         let mut traversed = 0;
         n.drain_dirty_leaves(
             &mut |node| {
@@ -1032,7 +1777,7 @@ mod test {
     #[test]
     fn test_visit_neighbor_pairs_face() {
         let mut n = PNode::new(Region::new(0u32, 0, 2), false, false);
-        n.set_pixel((0, 0).into(), 1, true); // Cause subdivision
+        n.set_pixel((0, 0).into(), 1, true, true, &mut NodePool::default()); // Cause subdivision
 
         let mut calls: Vec<(NeighborOrientation, URect, URect, URect, URect)> = Vec::new();
 
@@ -1047,6 +1792,7 @@ mod test {
                     *right_rect,
                 ));
             },
+            &mut 0,
         );
 
         assert_eq!(calls.len(), 4);
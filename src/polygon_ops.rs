@@ -0,0 +1,324 @@
+use crate::{contour_polygons, ContourPolygon, IsoLine};
+use bevy_math::IVec2;
+use fxhash::FxHashMap;
+
+/// A boolean operation on two sets of polygons, as per [polygon_boolean].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BooleanOp {
+    /// The area covered by either set of polygons.
+    Union,
+
+    /// The area covered by both sets of polygons.
+    Intersection,
+
+    /// The area covered by the first set of polygons, but not the second.
+    Difference,
+
+    /// The area covered by exactly one of the sets of polygons.
+    Xor,
+}
+
+impl BooleanOp {
+    #[inline]
+    fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            BooleanOp::Union => a || b,
+            BooleanOp::Intersection => a && b,
+            BooleanOp::Difference => a && !b,
+            BooleanOp::Xor => a != b,
+        }
+    }
+}
+
+/// Combine two sets of polygons, such as those produced by [contour_polygons] from the result
+/// of [crate::PixelMap::contour], by the given boolean operation, without rasterizing them.
+/// For example, the visible area of a map less the area covered by water.
+///
+/// Polygons must have only axis-aligned edges, as is the case for contours that have not been
+/// simplified, such that the result is exact in integer coordinates. Polygons of a set may
+/// overlap each other, in which case the set covers the area covered by an odd number of
+/// rings, including holes.
+///
+/// # Parameters
+///
+/// - `a`: The first set of polygons.
+/// - `b`: The second set of polygons.
+/// - `op`: The operation with which to combine the sets.
+///
+/// # Returns
+///
+/// The polygons covering the resulting area, with exterior rings winding counter-clockwise
+/// and holes winding clockwise, without redundant collinear points. Polygons that touch only
+/// at a corner are separate polygons.
+///
+/// # Panics
+///
+/// If any edge of the given polygons is not axis-aligned.
+#[must_use]
+pub fn polygon_boolean(
+    a: &[ContourPolygon],
+    b: &[ContourPolygon],
+    op: BooleanOp,
+) -> Vec<ContourPolygon> {
+    // Divide the plane into a grid of cells by every distinct coordinate, such that each cell
+    // is entirely inside or outside each polygon
+    let rings = || a.iter().chain(b).flat_map(rings_of);
+    let mut xs: Vec<i32> = rings().flat_map(|r| r.iter().map(|p| p.x)).collect();
+    let mut ys: Vec<i32> = rings().flat_map(|r| r.iter().map(|p| p.y)).collect();
+    for coords in [&mut xs, &mut ys] {
+        coords.sort_unstable();
+        coords.dedup();
+    }
+    if xs.len() < 2 || ys.len() < 2 {
+        return vec![];
+    }
+
+    let grid = Grid::new(&xs, &ys);
+    let inside_a = grid.inside(a);
+    let inside_b = grid.inside(b);
+    let cells: Vec<bool> = inside_a
+        .iter()
+        .zip(&inside_b)
+        .map(|(a, b)| op.apply(*a, *b))
+        .collect();
+
+    let lines = grid.trace(&cells);
+    contour_polygons(&lines)
+}
+
+/// The union of two sets of polygons. See [polygon_boolean].
+#[inline]
+#[must_use]
+pub fn polygon_union(a: &[ContourPolygon], b: &[ContourPolygon]) -> Vec<ContourPolygon> {
+    polygon_boolean(a, b, BooleanOp::Union)
+}
+
+/// The intersection of two sets of polygons. See [polygon_boolean].
+#[inline]
+#[must_use]
+pub fn polygon_intersection(a: &[ContourPolygon], b: &[ContourPolygon]) -> Vec<ContourPolygon> {
+    polygon_boolean(a, b, BooleanOp::Intersection)
+}
+
+/// The difference of two sets of polygons, being the area of `a` that is not covered by `b`.
+/// See [polygon_boolean].
+#[inline]
+#[must_use]
+pub fn polygon_difference(a: &[ContourPolygon], b: &[ContourPolygon]) -> Vec<ContourPolygon> {
+    polygon_boolean(a, b, BooleanOp::Difference)
+}
+
+// The rings of a polygon, as slices of points, without the closing point of closed rings.
+fn rings_of(polygon: &ContourPolygon) -> impl Iterator<Item = &[IVec2]> {
+    std::iter::once(&polygon.exterior)
+        .chain(&polygon.holes)
+        .map(|ring: &IsoLine| {
+            if ring.is_closed() {
+                &ring.points[..ring.len() - 1]
+            } else {
+                &ring.points[..]
+            }
+        })
+}
+
+// A grid of cells between consecutive distinct coordinates, indexed by column, then row.
+struct Grid<'a> {
+    xs: &'a [i32],
+    ys: &'a [i32],
+    columns: usize,
+    rows: usize,
+}
+
+impl<'a> Grid<'a> {
+    fn new(xs: &'a [i32], ys: &'a [i32]) -> Self {
+        Self {
+            xs,
+            ys,
+            columns: xs.len() - 1,
+            rows: ys.len() - 1,
+        }
+    }
+
+    #[inline]
+    fn index(&self, column: usize, row: usize) -> usize {
+        row * self.columns + column
+    }
+
+    // Determine which cells are inside the given polygons, by the even-odd rule.
+    fn inside(&self, polygons: &[ContourPolygon]) -> Vec<bool> {
+        // Toggle each cell to the right of a vertical edge, then accumulate along each row
+        let mut cells = vec![false; self.columns * self.rows];
+        for ring in polygons.iter().flat_map(rings_of) {
+            for (i, start) in ring.iter().enumerate() {
+                let end = ring[(i + 1) % ring.len()];
+                assert!(
+                    start.x == end.x || start.y == end.y,
+                    "edge {:?} to {:?} is not axis-aligned",
+                    start,
+                    end
+                );
+                if start.x != end.x || start.y == end.y {
+                    continue;
+                }
+                let column = self.xs.binary_search(&start.x).unwrap();
+                if column == self.columns {
+                    continue;
+                }
+                let row0 = self.ys.binary_search(&start.y.min(end.y)).unwrap();
+                let row1 = self.ys.binary_search(&start.y.max(end.y)).unwrap();
+                for row in row0..row1 {
+                    let index = self.index(column, row);
+                    cells[index] = !cells[index];
+                }
+            }
+        }
+        for row in 0..self.rows {
+            let mut inside = false;
+            for column in 0..self.columns {
+                let index = self.index(column, row);
+                inside ^= cells[index];
+                cells[index] = inside;
+            }
+        }
+        cells
+    }
+
+    // Trace the boundaries of the given cells into closed lines, with the cells to the left
+    // of each edge, such that exteriors wind counter-clockwise and holes clockwise.
+    fn trace(&self, cells: &[bool]) -> Vec<IsoLine> {
+        let at = |column: isize, row: isize| {
+            column >= 0
+                && row >= 0
+                && (column as usize) < self.columns
+                && (row as usize) < self.rows
+                && cells[self.index(column as usize, row as usize)]
+        };
+
+        // Directed edges between grid points, by start point
+        let mut edges: FxHashMap<IVec2, Vec<IVec2>> = FxHashMap::default();
+        let mut add = |start: IVec2, end: IVec2| edges.entry(start).or_default().push(end);
+        for row in 0..=self.rows as isize {
+            for column in 0..=self.columns as isize {
+                let point = IVec2::new(column as i32, row as i32);
+                let inside = at(column, row);
+                if inside != at(column, row - 1) {
+                    if inside {
+                        add(point, point + IVec2::X);
+                    } else {
+                        add(point + IVec2::X, point);
+                    }
+                }
+                if inside != at(column - 1, row) {
+                    if inside {
+                        add(point + IVec2::Y, point);
+                    } else {
+                        add(point, point + IVec2::Y);
+                    }
+                }
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut starts: Vec<IVec2> = edges.keys().copied().collect();
+        starts.sort_unstable_by_key(|p| (p.y, p.x));
+        for start in starts {
+            while let Some(first) = edges.get_mut(&start).and_then(|ends| ends.pop()) {
+                let mut points = vec![start];
+                let mut previous = start;
+                let mut current = first;
+                while current != start {
+                    points.push(current);
+                    // Where two corners touch, turn left to keep the boundaries separate
+                    let direction = current - previous;
+                    let ends = edges.get_mut(&current).unwrap();
+                    let next = if ends.len() > 1 {
+                        let left = current + direction.perp();
+                        let i = ends.iter().position(|e| *e == left).unwrap_or(0);
+                        ends.swap_remove(i)
+                    } else {
+                        ends.pop().unwrap()
+                    };
+                    previous = current;
+                    current = next;
+                }
+                points.push(start);
+                lines.push(IsoLine {
+                    points: self.to_coordinates(&points),
+                });
+            }
+        }
+        lines
+    }
+
+    // Convert a closed line of grid points to coordinates, removing collinear points.
+    fn to_coordinates(&self, points: &[IVec2]) -> Vec<IVec2> {
+        let ring = &points[..points.len() - 1];
+        let n = ring.len();
+        let mut result: Vec<IVec2> = (0..n)
+            .filter(|i| {
+                let previous = ring[(i + n - 1) % n];
+                let next = ring[(i + 1) % n];
+                (ring[*i] - previous).perp_dot(next - ring[*i]) != 0
+            })
+            .map(|i| IVec2::new(self.xs[ring[i].x as usize], self.ys[ring[i].y as usize]))
+            .collect();
+        result.push(result[0]);
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_polygon_boolean() {
+        let polygons = |rects: &[URect]| {
+            let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), false, 1);
+            for rect in rects {
+                pm.draw_rect(rect, true);
+            }
+            contour_polygons(&pm.contour(&pm.map_rect(), |n, _| *n.value()))
+        };
+        let area = |polygons: &[ContourPolygon]| -> f32 {
+            polygons
+                .iter()
+                .map(|p| {
+                    p.exterior.signed_area() + p.holes.iter().map(|h| h.signed_area()).sum::<f32>()
+                })
+                .sum()
+        };
+
+        let a = polygons(&[URect::new(2, 2, 10, 10)]);
+        let b = polygons(&[URect::new(6, 6, 14, 14)]);
+
+        let union = polygon_union(&a, &b);
+        assert_eq!(union.len(), 1);
+        assert_eq!(union[0].exterior.len(), 9);
+        assert_eq!(area(&union), 112.0);
+
+        let intersection = polygon_intersection(&a, &b);
+        assert_eq!(intersection.len(), 1);
+        assert_eq!(area(&intersection), 16.0);
+        assert_eq!(intersection[0].exterior.len(), 5);
+
+        assert_eq!(area(&polygon_difference(&a, &b)), 48.0);
+        assert_eq!(area(&polygon_boolean(&a, &b, BooleanOp::Xor)), 96.0);
+
+        // Producing a hole
+        let c = polygons(&[URect::new(4, 4, 6, 6)]);
+        let difference = polygon_difference(&a, &c);
+        assert_eq!(difference.len(), 1);
+        assert_eq!(difference[0].holes.len(), 1);
+        assert_eq!(area(&difference), 60.0);
+
+        // Corners touching remain separate polygons
+        let d = polygons(&[URect::new(10, 10, 12, 12)]);
+        assert_eq!(polygon_union(&a, &d).len(), 2);
+
+        // Disjoint
+        let e = polygons(&[URect::new(14, 2, 16, 4)]);
+        assert!(polygon_intersection(&a, &e).is_empty());
+    }
+}
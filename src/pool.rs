@@ -0,0 +1,187 @@
+use crate::{Children, PixelMap};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+// The capacity of the pool enabled by [PixelMap::reset_into_pool], if none is enabled.
+const DEFAULT_POOL_CAPACITY: usize = 4096;
+
+// The children of branch nodes that have been merged or discarded, retained for reuse by the
+// subdivision of nodes rather than being freed and reallocated. Retired children are leaf
+// nodes, their own children being retired in turn, such that each entry retains exactly four
+// nodes. Children retired beyond the capacity are dropped, as are all children if the
+// capacity is zero, which is the default.
+pub(crate) struct NodePool<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> {
+    free: Vec<Children<T, U>>,
+    capacity: usize,
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> NodePool<T, U> {
+    pub(crate) fn retire(&mut self, mut children: Children<T, U>) {
+        if self.free.len() >= self.capacity {
+            return;
+        }
+        for child in children.iter_mut() {
+            if let Some(grandchildren) = child.detach_children() {
+                self.retire(grandchildren);
+            }
+        }
+        if self.free.len() < self.capacity {
+            self.free.push(children);
+        }
+    }
+
+    #[inline]
+    pub(crate) fn take(&mut self) -> Option<Children<T, U>> {
+        self.free.pop()
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> Default for NodePool<T, U> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            free: Vec::new(),
+            capacity: 0,
+        }
+    }
+}
+
+// A clone of a map has the same pool capacity, but does not share, or duplicate, the pooled
+// allocations of the original.
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> Clone for NodePool<T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            free: Vec::new(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Reset all pixels of this [PixelMap] to the given value, as per [PixelMap::clear], but
+    /// leaving the root node clean rather than dirty, such as for frame-scoped scratch maps,
+    /// like visibility masks or brush previews, that are rebuilt every frame and whose
+    /// changes are never drained.
+    ///
+    /// The nodes of the discarded quadtree are retained in an internal pool, and reused by
+    /// subsequent drawing, such that rebuilding a map of similar structure every frame does
+    /// not allocate. Resetting, like [PixelMap::clear], takes time proportional to the number
+    /// of nodes discarded. If no pool is enabled, one is enabled with a capacity of 4096
+    /// entries. See [PixelMap::enable_pool].
+    ///
+    /// # Parameters
+    ///
+    /// - `value`: The value to assign to all pixels.
+    pub fn reset_into_pool(&mut self, value: T) {
+        self.touch(&self.map_rect());
+        if self.pool.capacity == 0 {
+            self.pool.capacity = DEFAULT_POOL_CAPACITY;
+        }
+        self.root.set_value_pooled(value, false, &mut self.pool);
+        self.root.clear_dirty();
+    }
+
+    /// Retain the nodes discarded by [PixelMap::clear], [PixelMap::reset_into_pool] and
+    /// drawing, for reuse by subsequent drawing rather than freeing and reallocating them.
+    /// Pooling is disabled by default, such that discarded nodes are freed.
+    ///
+    /// If a pool is already enabled, its capacity is changed, freeing any retained nodes in
+    /// excess of it.
+    ///
+    /// # Parameters
+    ///
+    /// - `capacity`: The maximum number of entries retained, each being the four children of
+    ///   a former branch node.
+    pub fn enable_pool(&mut self, capacity: usize) {
+        self.pool.capacity = capacity;
+        self.pool.free.truncate(capacity);
+    }
+
+    /// Free the nodes retained by this [PixelMap] for reuse, and disable pooling.
+    /// See [PixelMap::enable_pool].
+    #[inline]
+    pub fn disable_pool(&mut self) {
+        self.pool = NodePool::default();
+    }
+
+    /// Free the nodes retained by this [PixelMap] for reuse, such as after rebuilding a map
+    /// of much greater complexity than usual, leaving pooling enabled.
+    /// See [PixelMap::enable_pool].
+    #[inline]
+    pub fn release_pool(&mut self) {
+        self.pool.free = Vec::new();
+    }
+
+    /// Obtain the number of nodes retained by this [PixelMap] for reuse.
+    /// See [PixelMap::enable_pool].
+    #[inline]
+    #[must_use]
+    pub fn pooled_node_count(&self) -> usize {
+        self.pool.free.len() * 4
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_reset_into_pool() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(64), 0, 1);
+        let draw = |pm: &mut PixelMap<u8, u32>| {
+            pm.draw_circle(&ICircle::new((30, 30), 20), 1);
+            pm.draw_rect(&URect::new(5, 7, 41, 13), 2);
+        };
+        draw(&mut pm);
+        let expected = pm.clone();
+
+        for _ in 0..3 {
+            let version = pm.version();
+            pm.reset_into_pool(0);
+            assert!(pm.version() > version);
+            assert!(!pm.dirty());
+            assert_eq!(pm.stats().node_count, 1);
+            assert_eq!(pm.get_pixel((30, 30)), Some(&0));
+
+            draw(&mut pm);
+            assert_eq!(pm.stats(), expected.stats());
+            pm.visit(|node, rect| assert_eq!(expected.get_pixel(rect.min), Some(node.value())));
+        }
+
+        pm.clear(0);
+        assert!(pm.dirty());
+        assert_eq!(pm.stats().node_count, 1);
+        assert!(pm.pooled_node_count() > 0);
+        pm.release_pool();
+        assert_eq!(pm.pooled_node_count(), 0);
+        draw(&mut pm);
+        assert_eq!(pm.stats(), expected.stats());
+
+        // Bounded by capacity
+        pm.enable_pool(2);
+        pm.clear(0);
+        assert_eq!(pm.pooled_node_count(), 8);
+        draw(&mut pm);
+        assert_eq!(pm.stats(), expected.stats());
+
+        // Disabled pools retain nothing
+        pm.disable_pool();
+        pm.clear(0);
+        assert_eq!(pm.pooled_node_count(), 0);
+        draw(&mut pm);
+        assert_eq!(pm.stats(), expected.stats());
+    }
+
+    #[test]
+    fn test_pool_disabled_by_default() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(64), 0, 1);
+        pm.draw_circle(&ICircle::new((30, 30), 20), 1);
+        pm.clear(0);
+        pm.draw_rect(&URect::new(5, 7, 41, 13), 2);
+        pm.draw_rect(&URect::new(5, 7, 41, 13), 0);
+        assert_eq!(pm.stats().node_count, 1);
+        assert_eq!(pm.pooled_node_count(), 0);
+    }
+}
@@ -0,0 +1,20 @@
+//! The types and traits most commonly used with a [PixelMap], for glob importing:
+//!
+//! ```
+//! use pixel_map::prelude::*;
+//!
+//! let mut pm: PixelMap<u8> = PixelMap::new(&UVec2::splat(16), 0, 1);
+//! pm.draw_rect(&URect::new(2, 2, 6, 6), 1);
+//! assert!(URect::new(2, 2, 6, 6).contains_exclusive(UVec2::new(5, 5)));
+//! ```
+//!
+//! The integer rectangle and vector types of [bevy_math] are included, along with the rectangle
+//! helpers of [crate::rect_math].
+
+pub use crate::rect_math::*;
+pub use crate::{
+    CellFill, Direction, ICapsule, ICircle, ILine, IRoundedRect, ITriangle, LeafVisitor, MapPoint,
+    MapTransform, NodePath, NodeVisitor, PNode, PixelMap, PixelValue, Quadrant, RasterShape,
+    Region, RotatedIRect, Stats, TraversalOrder, WorldPoint,
+};
+pub use bevy_math::{ivec2, uvec2, IRect, IVec2, URect, UVec2};
@@ -0,0 +1,172 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{dominant_value, CellFill, PixelMap};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A depth-limited copy of a rectangle of a [PixelMap], as created by
+/// [PixelMap::serialize_region], such as for sending a low resolution preview of a map over
+/// the network ahead of its full contents. Subtrees deeper than the depth limit are reduced
+/// to a single leaf of their dominant value, as per [dominant_value].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelMapPreview<T> {
+    /// The position of the previewed rectangle within the source [PixelMap].
+    pub origin: UVec2,
+
+    /// The dimensions of the previewed rectangle.
+    pub map_size: UVec2,
+
+    /// The pixel size of the source [PixelMap].
+    pub pixel_size: u8,
+
+    /// The rectangles of the leaf nodes, relative to the `origin`, and their values.
+    pub leaves: Vec<(URect, T)>,
+}
+
+impl<T: Copy + PartialEq> PixelMapPreview<T> {
+    /// Create a standalone [PixelMap] from this preview, having the dimensions of the
+    /// previewed rectangle. Pixel `(0, 0)` of the new map corresponds to the `origin` of the
+    /// preview. All nodes of the new map are dirty.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the preview has no leaves, its dimensions are not a multiple of its pixel
+    /// size, or its dimensions can't be represented by the coordinate type `U`.
+    #[must_use]
+    pub fn to_pixel_map<U>(&self) -> Option<PixelMap<T, U>>
+    where
+        U: Unsigned + NumCast + Copy + Debug,
+    {
+        let (_, value) = self.leaves.first()?;
+        let pixel_size = self.pixel_size as u32;
+        if !pixel_size.is_power_of_two()
+            || self.map_size.cmpeq(UVec2::ZERO).any()
+            || !self.map_size.x.is_multiple_of(pixel_size)
+            || !self.map_size.y.is_multiple_of(pixel_size)
+            || num_traits::cast::<u32, U>(self.map_size.max_element().next_power_of_two()).is_none()
+        {
+            return None;
+        }
+        let mut pixel_map = PixelMap::new(&self.map_size, *value, self.pixel_size);
+        for (rect, value) in &self.leaves {
+            pixel_map.draw_rect(rect, *value);
+        }
+        Some(pixel_map)
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Create a [PixelMapPreview] of the given rectangle, reduced to the given depth, such as
+    /// for sending a low resolution preview of a region to a client before streaming its
+    /// full contents. Leaf nodes no deeper than `max_depth` are copied as is, while deeper
+    /// subtrees are reduced to a single leaf of their [dominant_value] within the rectangle.
+    ///
+    /// The rectangle is expanded to a multiple of the [PixelMap::pixel_size], and cropped to
+    /// the [PixelMap::map_rect]. Use [PixelMapPreview::to_pixel_map] to reconstruct a
+    /// standalone [PixelMap] from the preview.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle to preview.
+    /// - `max_depth`: The depth of the deepest nodes to preserve, where the root node has a
+    ///   depth of zero. This is clamped to the [PixelMap::depth_limit], which preserves all
+    ///   nodes.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the rectangle does not overlap the [PixelMap::map_rect].
+    #[must_use]
+    pub fn serialize_region(&self, rect: &URect, max_depth: u16) -> Option<PixelMapPreview<T>> {
+        let pixel_size = self.pixel_size as u32;
+        let rect = URect::from_corners(
+            rect.min / pixel_size * pixel_size,
+            (rect.max + pixel_size - 1) / pixel_size * pixel_size,
+        )
+        .intersect(self.map_rect);
+        if rect.is_empty() {
+            return None;
+        }
+        let cell_size = self.region().size_as::<u32>() >> max_depth.min(self.depth_limit());
+
+        let mut leaves = Vec::new();
+        let relative = |sub_rect: &URect| {
+            URect::from_corners(sub_rect.min - rect.min, sub_rect.max - rect.min)
+        };
+        self.root.visit_nodes_in_rect(
+            &rect,
+            &mut |node, sub_rect| {
+                if node.is_leaf() {
+                    leaves.push((relative(sub_rect), *node.value()));
+                    CellFill::Empty
+                } else if node.region().size_as::<u32>() <= cell_size {
+                    let mut replaced = Vec::new();
+                    node.visit_leaves_in_rect(
+                        &rect,
+                        &mut |leaf, leaf_rect| replaced.push((*leaf_rect, *leaf.value())),
+                        &mut 0,
+                    );
+                    leaves.push((relative(sub_rect), dominant_value(&replaced)));
+                    CellFill::Empty
+                } else {
+                    CellFill::Full
+                }
+            },
+            &mut 0,
+        );
+
+        Some(PixelMapPreview {
+            origin: rect.min,
+            map_size: rect.size(),
+            pixel_size: self.pixel_size,
+            leaves,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_serialize_region() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(16), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 3, 4), 1);
+        pm.set_pixel((12, 12), 2);
+
+        // Full depth preserves all pixels
+        let preview = pm.serialize_region(&pm.map_rect(), u16::MAX).unwrap();
+        let copy = preview.to_pixel_map::<u16>().unwrap();
+        assert_eq!(copy.map_size(), pm.map_size());
+        pm.visit(|n, r| {
+            copy.visit_in_rect(r, |m, _| assert_eq!(m.value(), n.value()));
+        });
+
+        // At depth 2, each 4x4 cell holds its dominant value
+        let preview = pm.serialize_region(&pm.map_rect(), 2).unwrap();
+        let coarse = preview.to_pixel_map::<u16>().unwrap();
+        assert_eq!(coarse.max_depth(), 2);
+        assert_eq!(coarse.get_pixel((3, 3)), Some(&1));
+        assert_eq!(coarse.get_pixel((12, 12)), Some(&0));
+
+        // A sub-rectangle is relative to its origin
+        let preview = pm.serialize_region(&URect::new(2, 2, 14, 6), 4).unwrap();
+        assert_eq!(preview.origin, UVec2::new(2, 2));
+        assert_eq!(preview.map_size, UVec2::new(12, 4));
+        let part = preview.to_pixel_map::<u8>().unwrap();
+        assert_eq!(part.get_pixel((0, 0)), Some(&1));
+        assert_eq!(part.get_pixel((1, 2)), Some(&0));
+        assert_eq!(part.get_pixel((0, 1)), Some(&1));
+        assert_eq!(part.get_pixel((1, 1)), Some(&0));
+
+        assert!(pm
+            .serialize_region(&URect::new(16, 16, 20, 20), 2)
+            .is_none());
+        let mut empty = preview;
+        empty.leaves.clear();
+        assert!(empty.to_pixel_map::<u8>().is_none());
+    }
+}
@@ -0,0 +1,241 @@
+//! Python bindings of a [PixelMap] of `u32` pixel values, by way of
+//! [pyo3](https://crates.io/crates/pyo3), for scripting the construction, drawing and querying
+//! of maps, and exchanging pixel data with `numpy`.
+//!
+//! The `pixel_map` Python module is initialized by the unmangled `PyInit_pixel_map` function,
+//! so it is importable from any `cdylib` crate depending on this crate with the `python`
+//! feature, such as one built by `maturin`. Coordinates are those of the map, having the
+//! origin at the bottom left, and rectangles exclude their maximum point.
+
+use crate::{ICapsule, ICircle, ILine, PixelMap};
+use bevy_math::{URect, UVec2};
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray2, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// A Python class wrapping a [PixelMap] of `u32` pixel values, exposed as `PixelMap`.
+#[pyclass(name = "PixelMap", module = "pixel_map")]
+#[derive(Debug, Clone)]
+pub struct PyPixelMap {
+    map: PixelMap<u32, u32>,
+}
+
+impl PyPixelMap {
+    /// Obtain the wrapped [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn map(&self) -> &PixelMap<u32, u32> {
+        &self.map
+    }
+
+    /// Obtain the wrapped [PixelMap], mutably.
+    #[inline]
+    #[must_use]
+    pub fn map_mut(&mut self) -> &mut PixelMap<u32, u32> {
+        &mut self.map
+    }
+
+    // Create a map, raising a `ValueError` rather than panicking on invalid dimensions.
+    fn create(width: u32, height: u32, value: u32, pixel_size: u8) -> PyResult<Self> {
+        if !pixel_size.is_power_of_two() {
+            return Err(PyValueError::new_err("pixel_size must be a power of 2"));
+        }
+        let valid_size =
+            |size: u32| (1..=1 << 31).contains(&size) && size.is_multiple_of(pixel_size as u32);
+        if !valid_size(width) || !valid_size(height) {
+            return Err(PyValueError::new_err(
+                "dimensions must be non-zero multiples of pixel_size",
+            ));
+        }
+        Ok(Self {
+            map: PixelMap::new(&UVec2::new(width, height), value, pixel_size),
+        })
+    }
+}
+
+#[pymethods]
+impl PyPixelMap {
+    /// Create a new map of the given dimensions, as per [PixelMap::new].
+    #[new]
+    #[pyo3(signature = (width, height, value = 0, pixel_size = 1))]
+    fn new(width: u32, height: u32, value: u32, pixel_size: u8) -> PyResult<Self> {
+        Self::create(width, height, value, pixel_size)
+    }
+
+    /// Create a new map from a two-dimensional array of shape `(height, width)`, where row
+    /// `y` of the array holds the pixels of row `y` of the map.
+    #[staticmethod]
+    #[pyo3(signature = (array, pixel_size = 1))]
+    fn from_numpy(array: PyReadonlyArray2<'_, u32>, pixel_size: u8) -> PyResult<Self> {
+        let array = array.as_array();
+        let (height, width) = array.dim();
+        let first = array.first().copied().unwrap_or_default();
+        let mut result = Self::create(width as u32, height as u32, first, pixel_size)?;
+        let step = pixel_size as usize;
+        for ((y, x), value) in array.indexed_iter() {
+            if x % step == 0 && y % step == 0 && *value != first {
+                result.map.set_pixel((x as u32, y as u32), *value);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Create a two-dimensional array of shape `(height, width)` of the pixels of this map,
+    /// where row `y` of the array holds the pixels of row `y` of the map.
+    fn to_numpy<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<u32>> {
+        let size = self.map.map_size();
+        let mut array = Array2::zeros((size.y as usize, size.x as usize));
+        self.map.visit(|node, sub_rect| {
+            for y in sub_rect.min.y..sub_rect.max.y {
+                for x in sub_rect.min.x..sub_rect.max.x {
+                    array[(y as usize, x as usize)] = *node.value();
+                }
+            }
+        });
+        array.into_pyarray(py)
+    }
+
+    /// The width of this map.
+    #[getter]
+    fn width(&self) -> u32 {
+        self.map.map_size().x
+    }
+
+    /// The height of this map.
+    #[getter]
+    fn height(&self) -> u32 {
+        self.map.map_size().y
+    }
+
+    /// The pixel size of this map.
+    #[getter]
+    fn pixel_size(&self) -> u8 {
+        self.map.pixel_size()
+    }
+
+    /// Obtain the value of the pixel at the given coordinates, or `None` if outside the map.
+    fn get_pixel(&self, x: u32, y: u32) -> Option<u32> {
+        let point = UVec2::new(x, y);
+        if point.cmpge(self.map.map_size()).any() {
+            return None;
+        }
+        self.map.get_pixel(point).copied()
+    }
+
+    /// Set the value of the pixel at the given coordinates, returning whether it is within
+    /// the map.
+    fn set_pixel(&mut self, x: u32, y: u32, value: u32) -> bool {
+        let point = UVec2::new(x, y);
+        if point.cmpge(self.map.map_size()).any() {
+            return false;
+        }
+        self.map.set_pixel(point, value)
+    }
+
+    /// Set the value of all pixels.
+    fn clear(&mut self, value: u32) {
+        self.map.clear(value);
+    }
+
+    /// Set the value of the pixels within a rectangle, returning whether it overlaps the
+    /// map.
+    fn draw_rect(&mut self, min_x: u32, min_y: u32, max_x: u32, max_y: u32, value: u32) -> bool {
+        let rect = URect::new(min_x, min_y, max_x, max_y);
+        !rect.is_empty() && self.map.draw_rect(&rect, value)
+    }
+
+    /// Set the value of the pixels within a circle, returning whether it overlaps the map.
+    fn draw_circle(&mut self, x: i32, y: i32, radius: u32, value: u32) -> bool {
+        self.map.draw_circle(&ICircle::new((x, y), radius), value)
+    }
+
+    /// Set the value of the pixels along a line, widened by the given radius with round
+    /// ends, returning whether it overlaps the map.
+    #[pyo3(signature = (start_x, start_y, end_x, end_y, value, radius = 0))]
+    fn draw_line(
+        &mut self,
+        start_x: i32,
+        start_y: i32,
+        end_x: i32,
+        end_y: i32,
+        value: u32,
+        radius: u32,
+    ) -> bool {
+        let line = ILine::new((start_x, start_y), (end_x, end_y));
+        self.map.draw_capsule(&ICapsule::new(line, radius), value)
+    }
+
+    /// Count the pixels having the given value.
+    fn count(&self, value: u32) -> u64 {
+        let mut count = 0;
+        self.map.visit(|node, sub_rect| {
+            if *node.value() == value {
+                count += sub_rect.width() as u64 * sub_rect.height() as u64;
+            }
+        });
+        count
+    }
+
+    /// Obtain the leaf nodes of this map as a list of `(min_x, min_y, max_x, max_y, value)`
+    /// tuples, where each rectangle of pixels has a uniform value.
+    fn leaves(&self) -> Vec<(u32, u32, u32, u32, u32)> {
+        let mut leaves = Vec::new();
+        self.map.visit(|node, r| {
+            leaves.push((r.min.x, r.min.y, r.max.x, r.max.y, *node.value()));
+        });
+        leaves
+    }
+
+    /// The number of nodes of this map.
+    #[getter]
+    fn node_count(&self) -> usize {
+        self.map.stats().node_count
+    }
+
+    fn __repr__(&self) -> String {
+        let size = self.map.map_size();
+        format!(
+            "PixelMap(width={}, height={}, pixel_size={})",
+            size.x,
+            size.y,
+            self.map.pixel_size()
+        )
+    }
+}
+
+/// The `pixel_map` Python module.
+#[pymodule]
+fn pixel_map(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPixelMap>()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::python::*;
+
+    #[test]
+    fn test_python_pixel_map() {
+        assert!(PyPixelMap::new(0, 4, 0, 1).is_err());
+        assert!(PyPixelMap::new(6, 4, 0, 3).is_err());
+
+        let mut pm = PyPixelMap::new(6, 4, 7, 1).unwrap();
+        assert_eq!((pm.width(), pm.height()), (6, 4));
+        assert!(pm.set_pixel(5, 3, 1));
+        assert!(!pm.set_pixel(6, 3, 1));
+        assert!(pm.draw_rect(0, 0, 2, 2, 2));
+        assert!(pm.draw_line(0, 3, 1, 3, 3, 0));
+        assert!(!pm.draw_circle(-20, -20, 2, 4));
+        assert_eq!(pm.get_pixel(5, 3), Some(1));
+        assert_eq!(pm.get_pixel(6, 0), None);
+        assert_eq!(pm.count(7), 24 - 4 - 2 - 1);
+        assert_eq!(
+            pm.leaves()
+                .iter()
+                .map(|l| (l.2 - l.0) * (l.3 - l.1))
+                .sum::<u32>(),
+            24
+        );
+        assert_eq!(pm.__repr__(), "PixelMap(width=6, height=4, pixel_size=1)");
+    }
+}
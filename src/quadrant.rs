@@ -89,6 +89,56 @@ impl Quadrant {
         }
     }
 
+    /// Returns the quadrant diagonally opposite this one.
+    #[inline]
+    #[must_use]
+    pub fn opposite(&self) -> Quadrant {
+        match self {
+            Quadrant::BottomLeft => Quadrant::TopRight,
+            Quadrant::BottomRight => Quadrant::TopLeft,
+            Quadrant::TopRight => Quadrant::BottomLeft,
+            Quadrant::TopLeft => Quadrant::BottomRight,
+        }
+    }
+
+    /// Returns the quadrant mirroring this one across the vertical axis, exchanging left
+    /// and right.
+    #[inline]
+    #[must_use]
+    pub fn mirror_horizontal(&self) -> Quadrant {
+        match self {
+            Quadrant::BottomLeft => Quadrant::BottomRight,
+            Quadrant::BottomRight => Quadrant::BottomLeft,
+            Quadrant::TopRight => Quadrant::TopLeft,
+            Quadrant::TopLeft => Quadrant::TopRight,
+        }
+    }
+
+    /// Returns the quadrant mirroring this one across the horizontal axis, exchanging top
+    /// and bottom.
+    #[inline]
+    #[must_use]
+    pub fn mirror_vertical(&self) -> Quadrant {
+        match self {
+            Quadrant::BottomLeft => Quadrant::TopLeft,
+            Quadrant::BottomRight => Quadrant::TopRight,
+            Quadrant::TopRight => Quadrant::BottomRight,
+            Quadrant::TopLeft => Quadrant::BottomLeft,
+        }
+    }
+
+    /// Returns the diagonal direction of this quadrant from the center of the box.
+    #[inline]
+    #[must_use]
+    pub fn direction(&self) -> Direction {
+        match self {
+            Quadrant::BottomLeft => Direction::SouthWest,
+            Quadrant::BottomRight => Direction::SouthEast,
+            Quadrant::TopRight => Direction::NorthEast,
+            Quadrant::TopLeft => Direction::NorthWest,
+        }
+    }
+
     /// Obtains the neighboring quadrant in the given direction, if there is one.
     #[inline]
     #[must_use]
@@ -229,10 +279,65 @@ pub enum CellFill {
 }
 
 impl CellFill {
+    /// Obtain the fill pattern of the given bitmask of quadrants, as per [Quadrant::as_bit].
+    /// Bits other than the lowest four are ignored.
+    #[inline]
+    #[must_use]
+    pub fn from_bits(bits: u8) -> CellFill {
+        // Every combination of the four quadrant bits is a variant
+        unsafe { std::mem::transmute(bits & 0b1111) }
+    }
+
+    /// Obtain the fill pattern covering the given quadrants.
+    #[inline]
+    #[must_use]
+    pub fn from_quadrants<I>(quadrants: I) -> CellFill
+    where
+        I: IntoIterator<Item = Quadrant>,
+    {
+        Self::from_bits(quadrants.into_iter().fold(0, |bits, q| bits | q.as_bit()))
+    }
+
+    /// The bitmask of the quadrants covered by this fill pattern, as per [Quadrant::as_bit].
+    #[inline]
+    #[must_use]
+    pub fn bits(&self) -> u8 {
+        *self as u8
+    }
+
+    /// Determine if this fill pattern covers the given quadrant.
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, quadrant: Quadrant) -> bool {
+        self.bits() & quadrant.as_bit() != 0
+    }
+
+    /// Obtain an iterator over the quadrants covered by this fill pattern, in the order
+    /// of [Quadrant::iter].
+    #[inline]
+    pub fn quadrants(&self) -> impl Iterator<Item = Quadrant> {
+        let fill = *self;
+        Quadrant::iter().filter(move |q| fill.contains(*q))
+    }
+
+    /// Combine this fill pattern with another, covering the quadrants of either.
+    #[inline]
+    #[must_use]
+    pub fn union(&self, other: CellFill) -> CellFill {
+        Self::from_bits(self.bits() | other.bits())
+    }
+
+    /// Obtain the quadrants covered by both this fill pattern and another.
+    #[inline]
+    #[must_use]
+    pub fn intersection(&self, other: CellFill) -> CellFill {
+        Self::from_bits(self.bits() & other.bits())
+    }
+
     /// Negate the node fill pattern.
     #[inline]
     pub fn invert(&self) -> CellFill {
-        unsafe { std::mem::transmute(!(*self as u8) & 0b1111) }
+        Self::from_bits(!self.bits())
     }
 
     /// If the fill represents a single quadrant, return that quadrant. `None`, otherwise.
@@ -247,6 +352,20 @@ impl CellFill {
     }
 }
 
+impl From<Quadrant> for CellFill {
+    #[inline]
+    fn from(quadrant: Quadrant) -> Self {
+        CellFill::from_bits(quadrant.as_bit())
+    }
+}
+
+impl FromIterator<Quadrant> for CellFill {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Quadrant>>(iter: I) -> Self {
+        CellFill::from_quadrants(iter)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::Quadrant;
@@ -277,4 +396,37 @@ mod test {
         assert_eq!(CellFill::TopLeft.invert(), CellFill::NotTopLeft);
         assert_eq!(CellFill::NotTopLeft.invert(), CellFill::TopLeft);
     }
+
+    #[test]
+    fn test_node_fill_quadrants() {
+        let fill = CellFill::from_quadrants([Quadrant::BottomLeft, Quadrant::TopLeft]);
+        assert_eq!(fill, CellFill::Left);
+        assert!(fill.contains(Quadrant::TopLeft));
+        assert!(!fill.contains(Quadrant::TopRight));
+        assert_eq!(
+            fill.quadrants().collect::<Vec<_>>(),
+            vec![Quadrant::BottomLeft, Quadrant::TopLeft]
+        );
+        assert_eq!(
+            fill.quadrants()
+                .map(|q| q.mirror_horizontal())
+                .collect::<CellFill>(),
+            CellFill::Right
+        );
+        assert_eq!(CellFill::Empty.quadrants().count(), 0);
+        assert_eq!(CellFill::from(Quadrant::TopRight), CellFill::TopRight);
+        assert_eq!(fill.union(CellFill::Bottom), CellFill::NotTopRight);
+        assert_eq!(fill.intersection(CellFill::Bottom), CellFill::BottomLeft);
+        assert_eq!(CellFill::from_bits(0xff), CellFill::Full);
+    }
+
+    #[test]
+    fn test_quadrant_symmetry() {
+        for q in Quadrant::iter() {
+            assert_eq!(q.opposite().opposite(), q);
+            assert_eq!(q.mirror_horizontal().mirror_vertical(), q.opposite());
+            assert_eq!(q.direction().opposite(), q.opposite().direction());
+        }
+        assert_eq!(Quadrant::BottomLeft.mirror_vertical(), Quadrant::TopLeft);
+    }
 }
@@ -3,7 +3,10 @@ use serde::{Deserialize, Serialize};
 
 use super::ILine;
 use super::LinePixelIterator;
-use bevy_math::UVec2;
+use crate::{PNode, PixelMap, Quadrant, Region};
+use bevy_math::{IVec2, Rect, URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
 
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
@@ -46,3 +49,301 @@ pub enum RayCast {
     Continue,
     Hit,
 }
+
+/// The passage of a ray through a leaf node, as reported by [PixelMap::ray_cast_dda].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayCrossing {
+    /// The region of the node, cropped to the [PixelMap::map_rect].
+    pub rect: URect,
+
+    /// The point at which the ray enters the node's region, or the ray origin if
+    /// it originates within the node.
+    pub entry: Vec2,
+
+    /// The point at which the ray exits the node's region, or the end of the ray
+    /// if it ends within the node.
+    pub exit: Vec2,
+
+    /// The distance along the ray to the entry point.
+    pub distance: f32,
+
+    /// The outward normal of the face, or faces at a corner, of the node's region through
+    /// which the ray entered. Zero if the ray originates within the node.
+    pub normal: IVec2,
+}
+
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DdaRayCastResult {
+    pub hit: Option<RayCrossing>,
+    pub traversed: u32,
+}
+
+impl DdaRayCastResult {
+    #[inline]
+    #[must_use]
+    pub fn is_hit(&self) -> bool {
+        self.hit.is_some()
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Visit the leaf nodes along a ray, in order, by stepping directly between node boundaries
+    /// in the manner of Amanatides and Woo's grid traversal. Unlike [PixelMap::ray_cast], the ray
+    /// has sub-pixel precision, with pixel `(x, y)` covering the area from `(x, y)` to
+    /// `(x + 1, y + 1)`, and the exact point at which the ray crosses each node is reported.
+    /// Ancestor nodes are retained between steps, so the tree is not descended from the root
+    /// for each node.
+    ///
+    /// # Parameters
+    ///
+    /// - `origin`: The origin of the ray. It may be outside the [PixelMap::map_rect].
+    /// - `direction`: The direction of the ray. It need not be normalized.
+    /// - `max_distance`: The length of the ray.
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to the
+    ///   [RayCrossing] of the node. It returns a [RayCast] value that determines if the node
+    ///   represents a collision or if the ray should continue.
+    ///
+    /// # Returns
+    ///
+    /// A [DdaRayCastResult] having the [RayCrossing] of the collided node, if any.
+    pub fn ray_cast_dda<F>(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mut visitor: F,
+    ) -> DdaRayCastResult
+    where
+        F: FnMut(&PNode<T, U>, &RayCrossing) -> RayCast,
+    {
+        let mut result = DdaRayCastResult {
+            hit: None,
+            traversed: 0,
+        };
+        let dir = direction.normalize_or_zero();
+        if dir == Vec2::ZERO || max_distance.is_nan() || max_distance < 0.0 {
+            return result;
+        }
+        let bounds = self.map_rect().as_rect();
+
+        // Clip the ray to the map
+        let mut t = 0.0;
+        let mut t_max = max_distance;
+        let mut normal = IVec2::ZERO;
+        let mut point = origin;
+        for axis in 0..2 {
+            if dir[axis] == 0.0 {
+                if origin[axis] < bounds.min[axis] || origin[axis] >= bounds.max[axis] {
+                    return result;
+                }
+                continue;
+            }
+            let t0 = (bounds.min[axis] - origin[axis]) / dir[axis];
+            let t1 = (bounds.max[axis] - origin[axis]) / dir[axis];
+            if t0.min(t1) > t {
+                t = t0.min(t1);
+                normal = face_normal(axis, dir);
+                point = origin + dir * t;
+                point[axis] = if dir[axis] > 0.0 {
+                    bounds.min[axis]
+                } else {
+                    bounds.max[axis]
+                };
+            }
+            t_max = t_max.min(t0.max(t1));
+        }
+        if t > t_max || !ray_within(&bounds, point, dir) {
+            return result;
+        }
+
+        let mut ancestors: Vec<&PNode<T, U>> = vec![&self.root];
+        loop {
+            // Ascend to the innermost ancestor containing the point, then descend to its leaf
+            while ancestors.len() > 1
+                && !ray_within(
+                    &region_rect(ancestors[ancestors.len() - 1].region()),
+                    point,
+                    dir,
+                )
+            {
+                ancestors.pop();
+            }
+            let node = loop {
+                let node = ancestors[ancestors.len() - 1];
+                result.traversed += 1;
+                if node.is_leaf() {
+                    break node;
+                }
+                let center = node.region().center().as_vec2();
+                let right = point.x > center.x || (point.x == center.x && dir.x >= 0.0);
+                let top = point.y > center.y || (point.y == center.y && dir.y >= 0.0);
+                let q = match (right, top) {
+                    (false, false) => Quadrant::BottomLeft,
+                    (true, false) => Quadrant::BottomRight,
+                    (true, true) => Quadrant::TopRight,
+                    (false, true) => Quadrant::TopLeft,
+                };
+                ancestors.push(&node.children()[q as usize]);
+            };
+
+            // Find the nearest boundary crossed on the way out of the node
+            let rect = node.region().intersect(&self.map_rect());
+            let bounds = rect.as_rect();
+            let mut t_exit = t_max;
+            let mut exit_axes = [false; 2];
+            for axis in 0..2 {
+                if dir[axis] == 0.0 {
+                    continue;
+                }
+                let boundary = if dir[axis] > 0.0 {
+                    bounds.max[axis]
+                } else {
+                    bounds.min[axis]
+                };
+                let t_axis = ((boundary - origin[axis]) / dir[axis]).max(t);
+                if t_axis < t_exit {
+                    t_exit = t_axis;
+                    exit_axes = [false; 2];
+                }
+                exit_axes[axis] |= t_axis == t_exit;
+            }
+            let mut exit = origin + dir * t_exit;
+            let mut exit_normal = IVec2::ZERO;
+            for axis in (0..2).filter(|axis| exit_axes[*axis]) {
+                // Snap to the boundary, so that the next point is exactly beyond this node
+                exit[axis] = if dir[axis] > 0.0 {
+                    bounds.max[axis]
+                } else {
+                    bounds.min[axis]
+                };
+                exit_normal += face_normal(axis, dir);
+            }
+
+            let crossing = RayCrossing {
+                rect,
+                entry: point,
+                exit,
+                distance: t,
+                normal,
+            };
+            if visitor(node, &crossing) == RayCast::Hit {
+                result.hit = Some(crossing);
+                return result;
+            }
+            if exit_normal == IVec2::ZERO || !ray_within(&self.map_rect().as_rect(), exit, dir) {
+                return result;
+            }
+            t = t_exit;
+            point = exit;
+            normal = exit_normal;
+        }
+    }
+}
+
+// The outward normal, on the given axis, of a face entered by a ray of the given direction.
+fn face_normal(axis: usize, dir: Vec2) -> IVec2 {
+    let mut normal = IVec2::ZERO;
+    normal[axis] = if dir[axis] > 0.0 { -1 } else { 1 };
+    normal
+}
+
+fn region_rect<U: Unsigned + NumCast + Copy + Debug>(region: &Region<U>) -> Rect {
+    region.as_urect().as_rect()
+}
+
+// Determine if a ray at the given point is within the rectangle, such that a ray on a
+// boundary is within the rectangle on the side it is moving towards.
+fn ray_within(rect: &Rect, point: Vec2, dir: Vec2) -> bool {
+    (0..2).all(|axis| {
+        let p = point[axis];
+        let d = dir[axis];
+        (p > rect.min[axis] || (p == rect.min[axis] && d >= 0.0))
+            && (p < rect.max[axis] || (p == rect.max[axis] && d < 0.0))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{ivec2, vec2, URect, UVec2, Vec2};
+
+    fn hit_test(node: &PNode<bool, u32>, _: &RayCrossing) -> RayCast {
+        if *node.value() {
+            RayCast::Hit
+        } else {
+            RayCast::Continue
+        }
+    }
+
+    #[test]
+    fn test_ray_cast_dda() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(10, 0, 12, 16), true);
+
+        let result = pm.ray_cast_dda(vec2(0.5, 3.5), Vec2::X, 100.0, hit_test);
+        let hit = result.hit.unwrap();
+        assert_eq!(hit.entry, vec2(10.0, 3.5));
+        assert_eq!(hit.normal, ivec2(-1, 0));
+        assert_eq!(hit.distance, 9.5);
+
+        // Heading away from the wall, or stopping short of it
+        assert!(!pm
+            .ray_cast_dda(vec2(8.5, 3.5), -Vec2::X, 100.0, hit_test)
+            .is_hit());
+        assert!(!pm
+            .ray_cast_dda(vec2(0.5, 3.5), Vec2::X, 9.0, hit_test)
+            .is_hit());
+
+        // From beyond the map, entering through its top edge
+        let hit = pm
+            .ray_cast_dda(vec2(11.0, 20.0), -Vec2::Y, 100.0, |_, _| RayCast::Hit)
+            .hit
+            .unwrap();
+        assert_eq!(hit.entry, vec2(11.0, 16.0));
+        assert_eq!(hit.normal, ivec2(0, 1));
+        assert_eq!(hit.distance, 4.0);
+
+        assert!(!pm
+            .ray_cast_dda(vec2(20.0, 20.0), Vec2::X, 100.0, hit_test)
+            .is_hit());
+    }
+
+    #[test]
+    fn test_ray_cast_dda_crossings() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((5, 5), true);
+        pm.set_pixel((3, 12), true);
+
+        // Crossings are contiguous, including through node corners
+        let mut crossings: Vec<RayCrossing> = Vec::new();
+        pm.ray_cast_dda(Vec2::ZERO, Vec2::ONE, 100.0, |_, c| {
+            crossings.push(*c);
+            RayCast::Continue
+        });
+        assert_eq!(crossings.first().unwrap().entry, Vec2::ZERO);
+        assert_eq!(crossings.last().unwrap().exit, Vec2::splat(16.0));
+        for pair in crossings.windows(2) {
+            assert_eq!(pair[0].exit, pair[1].entry);
+            assert!(pair[0].distance < pair[1].distance);
+        }
+
+        let hit = pm
+            .ray_cast_dda(Vec2::ZERO, Vec2::ONE, 100.0, hit_test)
+            .hit
+            .unwrap();
+        assert_eq!(hit.rect, URect::new(5, 5, 6, 6));
+        assert_eq!(hit.entry, Vec2::splat(5.0));
+        assert_eq!(hit.normal, ivec2(-1, -1));
+
+        // Crossings are cropped to the map
+        let pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::new(12, 10), false, 1);
+        let mut last = None;
+        pm.ray_cast_dda(vec2(0.5, 0.5), Vec2::X, 100.0, |_, c| {
+            last = Some(*c);
+            RayCast::Continue
+        });
+        assert_eq!(last.unwrap().exit, vec2(12.0, 0.5));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::{InclusiveRect, PNode, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Determine the pixels that are reachable from the `start` point, by flooding across
+    /// navigable leaf nodes that share an edge. Leaf nodes are flooded wholesale, so large
+    /// uniform regions are not examined pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: The point from which to flood.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle being the intersection of the node's region and the [PixelMap::map_rect].
+    ///   It returns `true` if the node is navigable, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// A [PixelMap] of the same dimensions and pixel size as this one, where reachable pixels
+    /// are `true`. All pixels are `false` if the `start` point is outside the
+    /// [PixelMap::map_rect], or its node is not navigable.
+    pub fn reachable<F>(&self, start: UVec2, mut predicate: F) -> PixelMap<bool, U>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let map_rect = self.map_rect();
+        let mut result = PixelMap::new(&self.map_size(), false, self.pixel_size());
+        if !map_rect.contains_exclusive(start) {
+            return result;
+        }
+
+        let graph = self.leaf_graph(&mut predicate);
+        let Some(&first) = graph
+            .index
+            .get(&self.root.find_node(start).region().point())
+        else {
+            return result;
+        };
+        for i in graph.component(first, &mut vec![false; graph.leaves.len()]) {
+            result.draw_rect(&graph.leaves[i].0, true);
+        }
+        result
+    }
+
+    /// Determine the distance of each pixel from the `start` point, by a breadth-first flood
+    /// across navigable pixels that share an edge. Distances are measured in steps of
+    /// [PixelMap::pixel_size].
+    ///
+    /// # Parameters
+    ///
+    /// - `start`: The point from which to flood.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle being the intersection of the node's region and the [PixelMap::map_rect].
+    ///   It returns `true` if the node is navigable, or `false` otherwise.
+    /// - `max`: The greatest distance to flood.
+    ///
+    /// # Returns
+    ///
+    /// A [PixelMap] of the same dimensions and pixel size as this one, having the distance of
+    /// each reached pixel. Pixels that are not reached within `max` steps are `u32::MAX`.
+    pub fn flood_distance<F>(&self, start: UVec2, mut predicate: F, max: u32) -> PixelMap<u32, U>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let map_rect = self.map_rect();
+        let step = self.pixel_size() as u32;
+        let mut result = PixelMap::new(&self.map_size(), u32::MAX, self.pixel_size());
+
+        // Navigability of leaf nodes, by the origin of their region
+        let mut navigable: FxHashMap<UVec2, bool> = FxHashMap::default();
+        let mut is_navigable = |point: UVec2| {
+            let node = self.root.find_node(point);
+            *navigable
+                .entry(node.region().point())
+                .or_insert_with(|| predicate(node, &node.region().as_urect().intersect(map_rect)))
+        };
+
+        if !map_rect.contains_exclusive(start) || !is_navigable(start) {
+            return result;
+        }
+
+        // Align to the pixel grid
+        let start = start / step * step;
+        let mut distances: FxHashMap<UVec2, u32> = FxHashMap::default();
+        distances.insert(start, 0);
+        let mut to_see = VecDeque::from([start]);
+        while let Some(point) = to_see.pop_front() {
+            let distance = distances[&point];
+            result.draw_rect(&URect::from_corners(point, point + step), distance);
+            if distance == max {
+                continue;
+            }
+            let neighbors = [
+                point.x.checked_sub(step).map(|x| UVec2::new(x, point.y)),
+                point.y.checked_sub(step).map(|y| UVec2::new(point.x, y)),
+                Some(UVec2::new(point.x + step, point.y)),
+                Some(UVec2::new(point.x, point.y + step)),
+            ];
+            for n in neighbors.into_iter().flatten() {
+                if map_rect.contains_exclusive(n) && !distances.contains_key(&n) && is_navigable(n)
+                {
+                    distances.insert(n, distance + 1);
+                    to_see.push_back(n);
+                }
+            }
+        }
+        result
+    }
+
+    /// Split the shapes determined by the given `predicate` into separate [PixelMap]s, one per
+    /// connected component, such as to break a destructible body into independent pieces once
+    /// it is cut apart. Components are connected across leaf nodes that share an edge, and each
+    /// is extracted into a map of its tight bounds.
+    ///
+    /// # Parameters
+    ///
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle being the intersection of the node's region and the [PixelMap::map_rect].
+    ///   It returns `true` if the node is part of a shape, or `false` otherwise.
+    /// - `empty`: The value of pixels within the bounds of a component that are not part of it.
+    ///
+    /// # Returns
+    ///
+    /// The bounds of each component within this map, and the [PixelMap] of the component,
+    /// having the same pixel size as this one. Ordered by the bottom-left point of the bounds,
+    /// in row-major order.
+    #[must_use]
+    pub fn split_components<F>(&self, mut predicate: F, empty: T) -> Vec<(URect, PixelMap<T, U>)>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let graph = self.leaf_graph(&mut predicate);
+        let mut seen = vec![false; graph.leaves.len()];
+        let mut components = Vec::new();
+        for first in 0..graph.leaves.len() {
+            if seen[first] {
+                continue;
+            }
+            let members = graph.component(first, &mut seen);
+            let bounds = members
+                .iter()
+                .fold(graph.leaves[first].0, |b, i| b.union(graph.leaves[*i].0));
+            let mut component = PixelMap::new(&bounds.size(), empty, self.pixel_size());
+            for i in members {
+                let (rect, value) = graph.leaves[i];
+                component.draw_rect(
+                    &URect::from_corners(rect.min - bounds.min, rect.max - bounds.min),
+                    value,
+                );
+            }
+            components.push((bounds, component));
+        }
+        components.sort_by_key(|(bounds, _)| (bounds.min.y, bounds.min.x));
+        components
+    }
+
+    // Index the leaf nodes that match the predicate, and their neighbors sharing an edge.
+    fn leaf_graph<F>(&self, predicate: &mut F) -> LeafGraph<T>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> bool,
+    {
+        let mut leaves: Vec<(URect, T)> = Vec::new();
+        let mut index: FxHashMap<UVec2, usize> = FxHashMap::default();
+        self.visit(|node, sub_rect| {
+            if predicate(node, sub_rect) {
+                index.insert(node.region().point(), leaves.len());
+                leaves.push((*sub_rect, *node.value()));
+            }
+        });
+
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); leaves.len()];
+        self.root.visit_neighbor_pairs_face(
+            &self.map_rect(),
+            &mut |_, a, _, b, _| {
+                let a = index.get(&a.region().point());
+                let b = index.get(&b.region().point());
+                if let (Some(&a), Some(&b)) = (a, b) {
+                    neighbors[a].push(b);
+                    neighbors[b].push(a);
+                }
+            },
+            &mut 0,
+        );
+
+        LeafGraph {
+            leaves,
+            index,
+            neighbors,
+        }
+    }
+}
+
+// Leaf nodes matching a predicate, indexed by the origin of their region, and the indices of
+// their neighbors sharing an edge.
+struct LeafGraph<T> {
+    leaves: Vec<(URect, T)>,
+    index: FxHashMap<UVec2, usize>,
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl<T> LeafGraph<T> {
+    // The indices of the leaves connected to the given leaf, marking them as seen.
+    fn component(&self, first: usize, seen: &mut [bool]) -> Vec<usize> {
+        let mut members = Vec::new();
+        seen[first] = true;
+        let mut to_see = vec![first];
+        while let Some(i) = to_see.pop() {
+            members.push(i);
+            for &n in &self.neighbors[i] {
+                if !seen[n] {
+                    seen[n] = true;
+                    to_see.push(n);
+                }
+            }
+        }
+        members
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    fn map_with_wall() -> PixelMap<bool, u32> {
+        // A closed room in the bottom-left, and open space elsewhere
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), true, 1);
+        pm.draw_rect(&URect::new(0, 6, 7, 7), false);
+        pm.draw_rect(&URect::new(6, 0, 7, 7), false);
+        pm
+    }
+
+    #[test]
+    fn test_reachable() {
+        let pm = map_with_wall();
+        let reachable = pm.reachable(UVec2::new(2, 2), |n, _| *n.value());
+        assert_eq!(reachable.get_pixel((5, 5)), Some(&true));
+        assert_eq!(reachable.get_pixel((6, 6)), Some(&false));
+        assert_eq!(reachable.get_pixel((10, 10)), Some(&false));
+
+        let reachable = pm.reachable(UVec2::new(10, 10), |n, _| *n.value());
+        assert_eq!(reachable.get_pixel((2, 2)), Some(&false));
+        assert_eq!(reachable.get_pixel((0, 15)), Some(&true));
+        assert_eq!(reachable.get_pixel((15, 0)), Some(&true));
+
+        // Start within a wall, and outside the map
+        let reachable = pm.reachable(UVec2::new(6, 2), |n, _| *n.value());
+        assert_eq!(reachable.stats().leaf_count, 1);
+        assert_eq!(reachable.get_pixel((2, 2)), Some(&false));
+        let reachable = pm.reachable(UVec2::new(20, 0), |n, _| *n.value());
+        assert_eq!(reachable.get_pixel((10, 10)), Some(&false));
+    }
+
+    #[test]
+    fn test_flood_distance() {
+        let pm = map_with_wall();
+        let distances = pm.flood_distance(UVec2::new(0, 0), |n, _| *n.value(), 100);
+        assert_eq!(distances.get_pixel((0, 0)), Some(&0));
+        assert_eq!(distances.get_pixel((5, 5)), Some(&10));
+        assert_eq!(distances.get_pixel((7, 0)), Some(&u32::MAX));
+
+        // Around the wall, from outside the room
+        let distances = pm.flood_distance(UVec2::new(7, 0), |n, _| *n.value(), 100);
+        assert_eq!(distances.get_pixel((7, 7)), Some(&7));
+        assert_eq!(distances.get_pixel((0, 7)), Some(&14));
+        assert_eq!(distances.get_pixel((2, 2)), Some(&u32::MAX));
+
+        // Limited by the maximum distance
+        let distances = pm.flood_distance(UVec2::new(7, 0), |n, _| *n.value(), 3);
+        assert_eq!(distances.get_pixel((10, 0)), Some(&3));
+        assert_eq!(distances.get_pixel((11, 0)), Some(&u32::MAX));
+    }
+
+    #[test]
+    fn test_split_components() {
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(16), 0, 1);
+        // A ship cut in half, and a debris pixel touching only by a corner
+        pm.draw_rect(&URect::new(2, 2, 6, 5), 1);
+        pm.set_pixel((4, 5), 2);
+        pm.draw_rect(&URect::new(8, 2, 12, 5), 1);
+        pm.set_pixel((12, 5), 3);
+
+        let components = pm.split_components(|n, _| *n.value() > 0, 0);
+        assert_eq!(components.len(), 3);
+
+        let (bounds, left) = &components[0];
+        assert_eq!(*bounds, URect::new(2, 2, 6, 6));
+        assert_eq!(left.map_size(), UVec2::new(4, 4));
+        assert_eq!(left.get_pixel((2, 3)), Some(&2));
+        assert_eq!(left.get_pixel((0, 3)), Some(&0));
+        assert_eq!(left.get_pixel((0, 0)), Some(&1));
+
+        let (bounds, right) = &components[1];
+        assert_eq!(*bounds, URect::new(8, 2, 12, 5));
+        assert_eq!(
+            right.find_first(&right.map_rect(), |n, _| *n.value() != 1),
+            None
+        );
+
+        assert_eq!(components[2].0, URect::new(12, 5, 13, 6));
+        assert_eq!(components[2].1.get_pixel((0, 0)), Some(&3));
+
+        assert!(pm.split_components(|n, _| *n.value() > 5, 0).is_empty());
+    }
+}
@@ -0,0 +1,449 @@
+//! Helpers for the integer rectangles of [bevy_math], being [URect] and [IRect], used
+//! throughout this crate to describe regions of pixels. Conversions between the exclusive and
+//! inclusive treatment of the `max` point of a rectangle are provided by [InclusiveRect].
+//!
+//! These helpers are also re-exported from the crate root.
+
+use crate::{iline, Direction, ILine};
+use bevy_math::{ivec2, uvec2, vec2, IRect, IVec2, Rect, URect, UVec2, Vec2};
+
+pub use crate::{PerimeterPixelIterator, URectPixelIterator};
+
+/// Get the four points that make up the corners of the given `rect`.
+#[inline]
+#[must_use]
+pub fn rect_points(rect: &Rect) -> [Vec2; 4] {
+    [
+        rect.min,
+        rect.min + vec2(rect.width(), 0.),
+        rect.max,
+        rect.min + vec2(0., rect.height()),
+    ]
+}
+
+/// Get the four points that make up the corners of the given `rect`.
+#[inline]
+#[must_use]
+pub fn irect_points(rect: &IRect) -> [IVec2; 4] {
+    [
+        rect.min,
+        rect.min + ivec2(rect.width(), 0),
+        rect.max,
+        rect.min + ivec2(0, rect.height()),
+    ]
+}
+
+/// Get the four points that make up the corners of the given `rect`.
+#[inline]
+#[must_use]
+pub fn urect_points(rect: &URect) -> [UVec2; 4] {
+    [
+        rect.min,
+        rect.min + uvec2(rect.width(), 0),
+        rect.max,
+        rect.min + uvec2(0, rect.height()),
+    ]
+}
+
+/// Get the four lines that make up the edges of this rectangle.
+#[inline]
+#[must_use]
+pub fn irect_edges(rect: &IRect) -> [ILine; 4] {
+    let min = rect.min;
+    let max = rect.max;
+    let width = rect.width();
+    let height = rect.height();
+    [
+        iline(min, min + ivec2(width, 0)),
+        iline(min + ivec2(width, 0), max),
+        iline(max, min + ivec2(0, height)),
+        iline(min + ivec2(0, height), min),
+    ]
+}
+
+/// Obtain an iterator over the pixels on the perimeter of the given `rect`, for which the
+/// `max` point is inclusive, each exactly once, such as to draw the outline of a selection.
+/// Pixels are yielded in the order of the edges of [irect_edges].
+#[inline]
+#[must_use]
+pub fn irect_edge_pixels(rect: &IRect) -> PerimeterPixelIterator {
+    PerimeterPixelIterator::new(*rect)
+}
+
+/// Obtain an iterator over the pixels on the perimeter of the given `rect`, for which the
+/// `max` point is exclusive, each exactly once, such as to draw the outline of a selection.
+/// Nothing is yielded for an empty `rect`.
+#[inline]
+pub fn urect_perimeter_pixels(rect: &URect) -> impl Iterator<Item = UVec2> {
+    // An inverted rectangle has an empty perimeter
+    let rect = rect.to_inclusive().map_or(
+        IRect {
+            min: IVec2::ZERO,
+            max: IVec2::NEG_ONE,
+        },
+        |r| r.as_irect(),
+    );
+    PerimeterPixelIterator::new(rect).map(|p| p.as_uvec2())
+}
+
+/// Convert the given `IRect` into a `URect`, without wrapping negative values,
+/// effectively cropping the rectangle to the positive quadrant.
+#[inline]
+#[must_use]
+pub fn to_cropped_urect(rect: &IRect) -> URect {
+    URect::from_corners(
+        rect.min.max(IVec2::ZERO).as_uvec2(),
+        rect.max.max(IVec2::ZERO).as_uvec2(),
+    )
+}
+
+/// Convert the given `IRect`, for which the `max` point is inclusive, into a `URect` for which
+/// the `max` point is exclusive, cropping the rectangle to the positive quadrant.
+#[inline]
+#[must_use]
+pub fn inclusive_to_cropped_urect(rect: &IRect) -> URect {
+    to_cropped_urect(&IRect::from_corners(rect.min, rect.max + IVec2::ONE))
+}
+
+/// Subtract one from the maximum point of the given `rect`, allowing
+/// for exclusive handling with `contains`, for example.
+///
+/// An empty `rect` is returned unchanged, such that `contains` still reports the points on
+/// its edges as contained. Prefer [InclusiveRect::to_inclusive], which returns `None` instead.
+#[deprecated(note = "use `InclusiveRect::to_inclusive`, which returns `None` for empty rects")]
+#[inline]
+#[must_use]
+pub fn exclusive_irect(rect: &IRect) -> IRect {
+    rect.to_inclusive().unwrap_or(*rect)
+}
+
+/// Subtract one from the maximum point of the given `rect`, allowing
+/// for exclusive handling with `contains`, for example.
+///
+/// An empty `rect`, including any having a `max` coordinate of zero, is returned unchanged,
+/// such that `contains` still reports the points on its edges as contained. Prefer
+/// [InclusiveRect::to_inclusive], which returns `None` instead.
+#[deprecated(note = "use `InclusiveRect::to_inclusive`, which returns `None` for empty rects")]
+#[inline]
+#[must_use]
+pub fn exclusive_urect(rect: &URect) -> URect {
+    rect.to_inclusive().unwrap_or(*rect)
+}
+
+/// Makes the treatment of the `max` point of an integer rectangle explicit.
+///
+/// A rectangle is used with one of two conventions in this crate:
+///
+/// - Exclusive: the `max` point lies just outside the rectangle, such that `max - min` is the
+///   number of pixels on each axis. [crate::PixelMap] methods taking a `URect`, such as
+///   [crate::PixelMap::draw_rect] and [crate::PixelMap::visit_in_rect], as well as
+///   [crate::Region::as_urect], use this convention.
+/// - Inclusive: the `max` point is the last pixel within the rectangle. The `contains` methods
+///   of bevy's `IRect` and `URect`, and the shapes of this crate, use this convention.
+///
+/// Mixing the two conventions leads to off-by-one errors. For example, a pixel on the `max`
+/// edge of a rectangle passed to `draw_rect` is not drawn, but `URect::contains` reports it as
+/// contained. Use the methods of this trait to be explicit about which convention applies.
+pub trait InclusiveRect: Sized {
+    /// The type of point contained by the rectangle.
+    type Point;
+
+    /// Determine if the rectangle contains the given point, treating `max` as exclusive.
+    #[must_use]
+    fn contains_exclusive(&self, point: Self::Point) -> bool;
+
+    /// Determine if the rectangle contains the given point, treating `max` as inclusive.
+    /// This is equivalent to the `contains` method of the rectangle.
+    #[must_use]
+    fn contains_inclusive(&self, point: Self::Point) -> bool;
+
+    /// Convert this rectangle, having an exclusive `max` point, into one having an inclusive
+    /// `max` point. An empty exclusive rectangle has no inclusive equivalent, so `None`
+    /// is returned.
+    #[must_use]
+    fn to_inclusive(&self) -> Option<Self>;
+
+    /// Convert this rectangle, having an inclusive `max` point, into one having an exclusive
+    /// `max` point.
+    #[must_use]
+    fn to_exclusive(&self) -> Self;
+
+    /// Get the number of pixels within the rectangle, treating `max` as exclusive.
+    #[must_use]
+    fn exclusive_area(&self) -> u64;
+
+    /// Get the number of pixels within the rectangle, treating `max` as inclusive.
+    #[must_use]
+    fn inclusive_area(&self) -> u64;
+}
+
+impl InclusiveRect for IRect {
+    type Point = IVec2;
+
+    #[inline]
+    fn contains_exclusive(&self, point: IVec2) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+
+    #[inline]
+    fn contains_inclusive(&self, point: IVec2) -> bool {
+        self.contains(point)
+    }
+
+    #[inline]
+    fn to_inclusive(&self) -> Option<Self> {
+        if self.min.cmpge(self.max).any() {
+            return None;
+        }
+        Some(IRect {
+            min: self.min,
+            max: self.max - IVec2::ONE,
+        })
+    }
+
+    #[inline]
+    fn to_exclusive(&self) -> Self {
+        IRect {
+            min: self.min,
+            max: self.max + IVec2::ONE,
+        }
+    }
+
+    #[inline]
+    fn exclusive_area(&self) -> u64 {
+        let size = (self.max - self.min).max(IVec2::ZERO).as_u64vec2();
+        size.x * size.y
+    }
+
+    #[inline]
+    fn inclusive_area(&self) -> u64 {
+        let size = (self.max - self.min + IVec2::ONE)
+            .max(IVec2::ZERO)
+            .as_u64vec2();
+        size.x * size.y
+    }
+}
+
+impl InclusiveRect for URect {
+    type Point = UVec2;
+
+    #[inline]
+    fn contains_exclusive(&self, point: UVec2) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+
+    #[inline]
+    fn contains_inclusive(&self, point: UVec2) -> bool {
+        self.contains(point)
+    }
+
+    #[inline]
+    fn to_inclusive(&self) -> Option<Self> {
+        if self.min.cmpge(self.max).any() {
+            return None;
+        }
+        Some(URect {
+            min: self.min,
+            max: self.max - UVec2::ONE,
+        })
+    }
+
+    #[inline]
+    fn to_exclusive(&self) -> Self {
+        URect {
+            min: self.min,
+            max: self.max + UVec2::ONE,
+        }
+    }
+
+    #[inline]
+    fn exclusive_area(&self) -> u64 {
+        let size = self.max.saturating_sub(self.min).as_u64vec2();
+        size.x * size.y
+    }
+
+    #[inline]
+    fn inclusive_area(&self) -> u64 {
+        if self.min.cmpgt(self.max).any() {
+            return 0;
+        }
+        let size = (self.max - self.min + UVec2::ONE).as_u64vec2();
+        size.x * size.y
+    }
+}
+
+/// Obtain a rect that encompasses the outer edge of the given `rect`, for the
+/// desired `direction`. The returned edge rect is 1 pixel "thick".
+/// The returned rect for diagonal edges (corners) is 1x1 pixel in size.
+#[inline]
+#[must_use]
+pub fn rect_outer_edge(rect: &URect, direction: Direction) -> URect {
+    match direction {
+        Direction::North => URect::from_corners(
+            uvec2(rect.min.x, rect.max.y),
+            uvec2(rect.max.x, rect.max.y + 1),
+        ),
+        Direction::NorthEast => URect::from_corners(rect.max, rect.max + 1),
+        Direction::East => URect::from_corners(
+            uvec2(rect.max.x, rect.min.y),
+            uvec2(rect.max.x + 1, rect.max.y),
+        ),
+        Direction::SouthEast => URect::from_corners(
+            uvec2(rect.max.x, rect.min.y.saturating_sub(1)),
+            uvec2(rect.max.x + 1, rect.min.y),
+        ),
+        Direction::South => URect::from_corners(
+            uvec2(rect.min.x, rect.min.y.saturating_sub(1)),
+            uvec2(rect.max.x, rect.min.y),
+        ),
+        Direction::SouthWest => URect::from_corners(
+            uvec2(rect.min.x.saturating_sub(1), rect.min.y.saturating_sub(1)),
+            rect.min,
+        ),
+        Direction::West => URect::from_corners(
+            uvec2(rect.min.x.saturating_sub(1), rect.min.y),
+            uvec2(rect.min.x, rect.max.y),
+        ),
+        Direction::NorthWest => URect::from_corners(
+            uvec2(rect.min.x.saturating_sub(1), rect.max.y),
+            uvec2(rect.min.x, rect.max.y + 1),
+        ),
+    }
+}
+
+/// Obtain an iterator over the coordinates of the pixels within the given `rect`, for which
+/// the `max` point is exclusive, in rows from the bottom.
+#[inline]
+#[must_use]
+pub fn urect_pixels(rect: &URect) -> URectPixelIterator {
+    URectPixelIterator::new(*rect)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_contains_edges() {
+        let rect = URect::new(2, 2, 4, 4);
+        assert!(rect.contains_exclusive(uvec2(2, 2)));
+        assert!(rect.contains_exclusive(uvec2(3, 3)));
+        assert!(!rect.contains_exclusive(uvec2(4, 3)));
+        assert!(!rect.contains_exclusive(uvec2(3, 4)));
+        assert!(rect.contains_inclusive(uvec2(4, 4)));
+        assert!(!rect.contains_inclusive(uvec2(5, 4)));
+
+        let rect = IRect::new(-2, -2, 0, 0);
+        assert!(rect.contains_exclusive(ivec2(-1, -1)));
+        assert!(!rect.contains_exclusive(ivec2(0, -1)));
+        assert!(rect.contains_inclusive(ivec2(0, 0)));
+    }
+
+    #[test]
+    fn test_inclusive_exclusive_conversion() {
+        let rect = URect::new(2, 3, 6, 5);
+        let inclusive = rect.to_inclusive().unwrap();
+        assert_eq!(inclusive, URect::new(2, 3, 5, 4));
+        assert_eq!(inclusive.to_exclusive(), rect);
+        assert_eq!(rect.exclusive_area(), 8);
+        assert_eq!(inclusive.inclusive_area(), 8);
+
+        // A single pixel
+        let rect = IRect::new(-1, -1, 0, 0);
+        let inclusive = rect.to_inclusive().unwrap();
+        assert_eq!(inclusive, IRect::new(-1, -1, -1, -1));
+        assert_eq!(inclusive.inclusive_area(), 1);
+        assert_eq!(inclusive.to_exclusive(), rect);
+
+        // Empty rects have no inclusive equivalent
+        assert_eq!(URect::new(0, 0, 0, 0).to_inclusive(), None);
+        assert_eq!(URect::new(3, 1, 3, 8).to_inclusive(), None);
+        assert_eq!(IRect::new(-4, 1, 2, 1).to_inclusive(), None);
+        assert_eq!(URect::new(3, 1, 3, 8).exclusive_area(), 0);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_exclusive_rect_shims() {
+        assert_eq!(
+            exclusive_urect(&URect::new(2, 3, 6, 5)),
+            URect::new(2, 3, 5, 4)
+        );
+        assert_eq!(
+            exclusive_irect(&IRect::new(-1, -1, 0, 0)),
+            IRect::new(-1, -1, -1, -1)
+        );
+
+        // Empty rects, including those with a zero max, are returned unchanged
+        for rect in [
+            URect::new(0, 0, 0, 0),
+            URect::new(3, 1, 3, 8),
+            URect::new(0, 4, 0, 9),
+            URect::new(5, 0, 9, 0),
+        ] {
+            assert_eq!(exclusive_urect(&rect), rect);
+            assert!(exclusive_urect(&rect).contains(rect.min));
+            assert_eq!(rect.to_inclusive(), None);
+        }
+        let rect = IRect::new(-4, 1, 2, 1);
+        assert_eq!(exclusive_irect(&rect), rect);
+        assert!(exclusive_irect(&rect).contains(rect.max));
+    }
+
+    #[test]
+    fn test_inclusive_to_cropped_urect() {
+        let rect = IRect::new(-3, 2, 4, 5);
+        assert_eq!(inclusive_to_cropped_urect(&rect), URect::new(0, 2, 5, 6));
+        let rect = IRect::new(-3, -3, -1, -1);
+        assert!(inclusive_to_cropped_urect(&rect).is_empty());
+    }
+
+    #[test]
+    fn test_rect_edges_and_points() {
+        let rect = URect::new(2, 3, 6, 5);
+        assert_eq!(
+            urect_points(&rect),
+            [uvec2(2, 3), uvec2(6, 3), uvec2(6, 5), uvec2(2, 5)]
+        );
+        let edges = irect_edges(&rect.as_irect());
+        assert_eq!(edges[0], iline((2, 3), (6, 3)));
+        assert_eq!(edges[3], iline((2, 5), (2, 3)));
+
+        assert_eq!(
+            rect_outer_edge(&rect, Direction::North),
+            URect::new(2, 5, 6, 6)
+        );
+        assert_eq!(
+            rect_outer_edge(&rect, Direction::SouthWest),
+            URect::new(1, 2, 2, 3)
+        );
+        // Edges beyond the origin are empty
+        assert!(rect_outer_edge(&URect::new(0, 0, 2, 2), Direction::West).is_empty());
+
+        let pixels: Vec<_> = urect_pixels(&URect::new(1, 1, 3, 3)).collect();
+        assert_eq!(
+            pixels,
+            vec![uvec2(1, 1), uvec2(2, 1), uvec2(1, 2), uvec2(2, 2)]
+        );
+        assert_eq!(urect_pixels(&URect::new(1, 1, 1, 3)).count(), 0);
+    }
+
+    #[test]
+    fn test_perimeter_pixels() {
+        let pixels: Vec<_> = urect_perimeter_pixels(&URect::new(1, 1, 4, 3)).collect();
+        assert_eq!(pixels.len(), 6);
+        assert!(pixels
+            .iter()
+            .all(|p| URect::new(1, 1, 4, 3).contains_exclusive(*p)));
+        assert_eq!(pixels[0], uvec2(1, 1));
+        assert_eq!(urect_perimeter_pixels(&URect::new(1, 1, 1, 3)).count(), 0);
+
+        let rect = IRect::new(-2, -2, 2, 2);
+        assert_eq!(irect_edge_pixels(&rect).len(), 16);
+        let edges: usize = irect_edges(&rect)
+            .iter()
+            .map(|e| e.pixels().count() - 1)
+            .sum();
+        assert_eq!(edges, 16);
+    }
+}
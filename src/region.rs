@@ -7,6 +7,10 @@ use num_traits::{NumCast, Unsigned};
 
 /// A square region defined by a bottom-left point and a size, in integer units.
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)
+)]
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Region<U: Unsigned + Copy = u16> {
     x: U,
@@ -181,7 +185,6 @@ impl<U: Unsigned + NumCast + Copy> Region<U> {
 #[allow(clippy::from_over_into)]
 impl<U: Unsigned + NumCast + Copy> Into<URect> for Region<U> {
     #[inline]
-    #[must_use]
     fn into(self) -> URect {
         URect::from_corners(self.point(), self.end_point())
     }
@@ -190,7 +193,6 @@ impl<U: Unsigned + NumCast + Copy> Into<URect> for Region<U> {
 #[allow(clippy::from_over_into)]
 impl<U: Unsigned + NumCast + Copy> Into<URect> for &Region<U> {
     #[inline]
-    #[must_use]
     fn into(self) -> URect {
         URect::from_corners(self.point(), self.end_point())
     }
@@ -199,7 +201,6 @@ impl<U: Unsigned + NumCast + Copy> Into<URect> for &Region<U> {
 #[allow(clippy::from_over_into)]
 impl<U: Unsigned + NumCast + Copy> Into<IRect> for Region<U> {
     #[inline]
-    #[must_use]
     fn into(self) -> IRect {
         IRect::from_corners(self.point().as_ivec2(), self.end_point().as_ivec2())
     }
@@ -208,12 +209,24 @@ impl<U: Unsigned + NumCast + Copy> Into<IRect> for Region<U> {
 #[allow(clippy::from_over_into)]
 impl<U: Unsigned + NumCast + Copy> Into<IRect> for &Region<U> {
     #[inline]
-    #[must_use]
     fn into(self) -> IRect {
         IRect::from_corners(self.point().as_ivec2(), self.end_point().as_ivec2())
     }
 }
 
+#[cfg(feature = "rkyv")]
+impl<U: Unsigned + NumCast + Copy + rkyv::Archive> ArchivedRegion<U>
+where
+    U::Archived: Copy + Into<U>,
+{
+    /// Obtain the [Region] of which this is the archived form.
+    #[inline]
+    #[must_use]
+    pub fn to_region(&self) -> Region<U> {
+        Region::new(self.x.into(), self.y.into(), self.size.into())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,478 @@
+use crate::InclusiveRect;
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{PNode, PixelMap};
+use bevy_math::{uvec2, Mat2, URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// Conversion of a pixel value into an `f32` scalar, such that it may be interpolated.
+/// See [PixelMap::sample_bilinear].
+pub trait ToF32 {
+    /// Obtain the scalar representation of this value.
+    #[must_use]
+    fn to_f32(&self) -> f32;
+}
+
+macro_rules! impl_to_f32 {
+    ($($t:ty),*) => {
+        $(
+            impl ToF32 for $t {
+                #[inline]
+                fn to_f32(&self) -> f32 {
+                    *self as f32
+                }
+            }
+        )*
+    };
+}
+
+impl_to_f32!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl ToF32 for bool {
+    #[inline]
+    fn to_f32(&self) -> f32 {
+        if *self {
+            1.
+        } else {
+            0.
+        }
+    }
+}
+
+/// Conversion of an `f32` scalar into a pixel value, the inverse of [ToF32], such that
+/// interpolated values may be stored. Integer values are rounded to the nearest integer, and
+/// saturate at the bounds of their type. See [PixelMap::draw_rect_gradient].
+pub trait FromF32 {
+    /// Obtain the value represented by the given scalar.
+    #[must_use]
+    fn from_f32(value: f32) -> Self;
+}
+
+macro_rules! impl_from_f32 {
+    ($($t:ty),*) => {
+        $(
+            impl FromF32 for $t {
+                #[inline]
+                fn from_f32(value: f32) -> Self {
+                    value.round() as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_from_f32!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl FromF32 for f32 {
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+}
+
+impl FromF32 for f64 {
+    #[inline]
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+}
+
+impl<T: Copy + PartialEq + ToF32, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Sample the value of this [PixelMap] at the given position by bilinear interpolation
+    /// of the four nearest pixels. Pixel centers are treated as sample points, such that the
+    /// pixel at `(x, y)` is sampled exactly at `(x + 0.5, y + 0.5)`. Positions between the
+    /// outermost pixel centers and the edges of the map are clamped to the edge pixels.
+    ///
+    /// # Parameters
+    ///
+    /// - `pos`: The position at which to sample.
+    ///
+    /// # Returns
+    ///
+    /// The interpolated value, or `None` if `pos` is outside the [PixelMap::map_rect].
+    #[must_use]
+    pub fn sample_bilinear(&self, pos: Vec2) -> Option<f32> {
+        let size = self.map_size().as_vec2();
+        if pos.cmplt(Vec2::ZERO).any() || pos.cmpgt(size).any() {
+            return None;
+        }
+
+        let max = self.map_size() - UVec2::ONE;
+        let p = (pos - Vec2::splat(0.5)).clamp(Vec2::ZERO, max.as_vec2());
+        let p0 = p.floor().as_uvec2();
+        let p1 = (p0 + UVec2::ONE).min(max);
+        let t = p - p0.as_vec2();
+
+        // Neighboring samples often share a leaf node, which avoids further descents
+        let node = self.root.find_node(p0);
+        if node.region().contains_upoint(p1) {
+            return Some(node.value().to_f32());
+        }
+        let sample = |point: UVec2| -> f32 { sample_near(node, self, point) };
+
+        let v00 = node.value().to_f32();
+        let v10 = sample(uvec2(p1.x, p0.y));
+        let v01 = sample(uvec2(p0.x, p1.y));
+        let v11 = sample(p1);
+
+        let bottom = v00 + (v10 - v00) * t.x;
+        let top = v01 + (v11 - v01) * t.x;
+        Some(bottom + (top - bottom) * t.y)
+    }
+}
+
+/// Aggregated gradient magnitudes of the pixels within a rectangle.
+/// See [PixelMap::steepness_in_rect].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Steepness {
+    /// The smallest gradient magnitude.
+    pub min: f32,
+
+    /// The largest gradient magnitude.
+    pub max: f32,
+
+    /// The mean gradient magnitude.
+    pub mean: f32,
+}
+
+impl<T: Copy + PartialEq + ToF32, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Compute the gradient of the value of this [PixelMap] at the given pixel, by central
+    /// differences of its neighboring pixels. At the edges of the map, one-sided differences are
+    /// used. When the neighboring pixels lie in the same leaf node, the gradient is known to be
+    /// zero without further descents of the quadtree.
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The coordinates of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// The gradient, in value units per pixel, pointing in the direction of increasing value.
+    /// `None` if `point` is outside the [PixelMap::map_rect].
+    #[must_use]
+    pub fn gradient_at(&self, point: UVec2) -> Option<Vec2> {
+        if !self.map_rect().contains_exclusive(point) {
+            return None;
+        }
+        let node = self.root.find_node(point);
+        Some(self.gradient_near(node, point))
+    }
+
+    /// Aggregate the gradient magnitudes, as per [PixelMap::gradient_at], of every pixel
+    /// within the given rectangle. Pixels in the interior of leaf nodes are known to have a
+    /// zero gradient, so only pixels on the boundaries of leaf nodes are examined.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle of pixels to aggregate.
+    ///
+    /// # Returns
+    ///
+    /// The aggregated [Steepness], or `None` if `rect` does not overlap the [PixelMap::map_rect].
+    #[must_use]
+    pub fn steepness_in_rect(&self, rect: &URect) -> Option<Steepness> {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return None;
+        }
+
+        let mut min = f32::MAX;
+        let mut max = 0f32;
+        let mut sum = 0f32;
+        self.visit_in_rect(&rect, |node, sub_rect| {
+            let region = node.region().as_urect();
+            let interior = URect {
+                min: region.min + UVec2::ONE,
+                max: region.max.saturating_sub(UVec2::ONE),
+            }
+            .intersect(*sub_rect);
+            if !interior.is_empty() {
+                min = 0.;
+            }
+            for y in sub_rect.min.y..sub_rect.max.y {
+                for x in sub_rect.min.x..sub_rect.max.x {
+                    if !interior.is_empty() && interior.contains_exclusive(uvec2(x, y)) {
+                        continue;
+                    }
+                    let magnitude = self.gradient_near(node, uvec2(x, y)).length();
+                    min = min.min(magnitude);
+                    max = max.max(magnitude);
+                    sum += magnitude;
+                }
+            }
+        });
+
+        Some(Steepness {
+            min,
+            max,
+            mean: sum / rect.exclusive_area() as f32,
+        })
+    }
+
+    // Compute the gradient at `point`, which must be within the map, and contained by `node`.
+    fn gradient_near(&self, node: &PNode<T, U>, point: UVec2) -> Vec2 {
+        let max = self.map_size() - UVec2::ONE;
+        let left = uvec2(point.x.saturating_sub(1), point.y);
+        let right = uvec2((point.x + 1).min(max.x), point.y);
+        let bottom = uvec2(point.x, point.y.saturating_sub(1));
+        let top = uvec2(point.x, (point.y + 1).min(max.y));
+
+        let region = node.region();
+        if [left, right, bottom, top]
+            .iter()
+            .all(|p| region.contains_upoint(*p))
+        {
+            return Vec2::ZERO;
+        }
+
+        let dx = (sample_near(node, self, right) - sample_near(node, self, left))
+            / (right.x - left.x).max(1) as f32;
+        let dy = (sample_near(node, self, top) - sample_near(node, self, bottom))
+            / (top.y - bottom.y).max(1) as f32;
+        Vec2::new(dx, dy)
+    }
+}
+
+/// The mass distribution of the pixels within a rectangle, where the value of each pixel is
+/// its weight. See [PixelMap::moments].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moments {
+    /// The sum of the weights of all pixels.
+    pub mass: f32,
+
+    /// The weighted mean position, being the center of mass.
+    pub centroid: Vec2,
+
+    /// The weighted covariance of positions about the centroid.
+    pub covariance: Mat2,
+}
+
+impl Moments {
+    /// The angle, in radians, of the principal axis, being the direction of greatest spread
+    /// about the centroid. Measured counter-clockwise from the `x` axis, in the range
+    /// `(-PI/2, PI/2]`.
+    #[must_use]
+    pub fn orientation(&self) -> f32 {
+        let xx = self.covariance.x_axis.x;
+        let xy = self.covariance.x_axis.y;
+        let yy = self.covariance.y_axis.y;
+        0.5 * (2.0 * xy).atan2(xx - yy)
+    }
+
+    /// The unit vector of the principal axis. See [Moments::orientation].
+    #[inline]
+    #[must_use]
+    pub fn principal_axis(&self) -> Vec2 {
+        Vec2::from_angle(self.orientation())
+    }
+}
+
+impl<T: Copy + PartialEq + ToF32, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Compute the center of mass of the pixels within the given rectangle, where the value of
+    /// each pixel is its weight. See [PixelMap::moments].
+    ///
+    /// # Returns
+    ///
+    /// The centroid, or `None` if `rect` does not overlap the [PixelMap::map_rect], or the total
+    /// weight is not positive.
+    #[inline]
+    #[must_use]
+    pub fn weighted_centroid(&self, rect: &URect) -> Option<Vec2> {
+        self.moments(rect).map(|m| m.centroid)
+    }
+
+    /// Compute the mass, centroid and covariance of the pixels within the given rectangle,
+    /// where the value of each pixel is its weight, and each pixel is a unit area. Moments are
+    /// integrated over each leaf node in closed form, so large uniform regions are not examined
+    /// pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle of pixels to measure.
+    ///
+    /// # Returns
+    ///
+    /// The [Moments], or `None` if `rect` does not overlap the [PixelMap::map_rect], or the total
+    /// weight is not positive.
+    #[must_use]
+    pub fn moments(&self, rect: &URect) -> Option<Moments> {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return None;
+        }
+
+        // Raw moments: mass, first moments, and second moments
+        let (mut m, mut mx, mut my, mut mxx, mut mxy, mut myy) =
+            (0f64, 0f64, 0f64, 0f64, 0f64, 0f64);
+        self.visit_in_rect(&rect, |node, sub_rect| {
+            let w = node.value().to_f32() as f64;
+            if w == 0.0 {
+                return;
+            }
+            let (x0, x1) = (sub_rect.min.x as f64, sub_rect.max.x as f64);
+            let (y0, y1) = (sub_rect.min.y as f64, sub_rect.max.y as f64);
+            let (ix, iy) = (x1 - x0, y1 - y0);
+            let (ixx, iyy) = ((x1 * x1 - x0 * x0) / 2.0, (y1 * y1 - y0 * y0) / 2.0);
+            m += w * ix * iy;
+            mx += w * ixx * iy;
+            my += w * ix * iyy;
+            mxx += w * (x1.powi(3) - x0.powi(3)) / 3.0 * iy;
+            myy += w * ix * (y1.powi(3) - y0.powi(3)) / 3.0;
+            mxy += w * ixx * iyy;
+        });
+        if m <= 0.0 {
+            return None;
+        }
+
+        let (cx, cy) = (mx / m, my / m);
+        let xx = (mxx / m - cx * cx) as f32;
+        let xy = (mxy / m - cx * cy) as f32;
+        let yy = (myy / m - cy * cy) as f32;
+        Some(Moments {
+            mass: m as f32,
+            centroid: Vec2::new(cx as f32, cy as f32),
+            covariance: Mat2::from_cols(Vec2::new(xx, xy), Vec2::new(xy, yy)),
+        })
+    }
+}
+
+// Obtain the scalar value at `point`, using `node` if it contains the point.
+#[inline]
+fn sample_near<T, U>(node: &PNode<T, U>, pixel_map: &PixelMap<T, U>, point: UVec2) -> f32
+where
+    T: Copy + PartialEq + ToF32,
+    U: Unsigned + NumCast + Copy + Debug,
+{
+    if node.region().contains_upoint(point) {
+        node.value().to_f32()
+    } else {
+        pixel_map.root.find_node(point).value().to_f32()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{vec2, URect, UVec2};
+
+    #[test]
+    fn test_sample_bilinear() {
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(8), 0, 1);
+        pm.set_pixel((4, 4), 100);
+
+        // Pixel centers are sampled exactly
+        assert_eq!(pm.sample_bilinear(vec2(4.5, 4.5)), Some(100.));
+        assert_eq!(pm.sample_bilinear(vec2(3.5, 4.5)), Some(0.));
+
+        // Midway between pixel centers
+        assert_eq!(pm.sample_bilinear(vec2(4.0, 4.5)), Some(50.));
+        assert_eq!(pm.sample_bilinear(vec2(5.0, 5.0)), Some(25.));
+        assert_eq!(pm.sample_bilinear(vec2(4.75, 4.5)), Some(75.));
+
+        // Uniform leaves
+        assert_eq!(pm.sample_bilinear(vec2(1.2, 1.7)), Some(0.));
+
+        // Out of bounds
+        assert_eq!(pm.sample_bilinear(vec2(-0.1, 1.)), None);
+        assert_eq!(pm.sample_bilinear(vec2(1., 8.1)), None);
+    }
+
+    #[test]
+    fn test_sample_bilinear_edges() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(4), 0., 1);
+        pm.draw_rect(&URect::new(0, 0, 2, 4), 10.);
+
+        // Clamped to the edge pixels
+        assert_eq!(pm.sample_bilinear(vec2(0., 0.)), Some(10.));
+        assert_eq!(pm.sample_bilinear(vec2(4., 4.)), Some(0.));
+        assert_eq!(pm.sample_bilinear(vec2(2., 0.)), Some(5.));
+        assert_eq!(pm.sample_bilinear(vec2(2., 4.)), Some(5.));
+    }
+
+    #[test]
+    fn test_gradient_at() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(8), 0., 1);
+        for x in 0..8 {
+            pm.draw_rect(&URect::new(x, 0, x + 1, 8), x as f32 * 2.);
+        }
+        assert_eq!(pm.gradient_at(UVec2::new(3, 3)), Some(vec2(2., 0.)));
+        // One-sided at the edges
+        assert_eq!(pm.gradient_at(UVec2::new(0, 0)), Some(vec2(2., 0.)));
+        assert_eq!(pm.gradient_at(UVec2::new(7, 7)), Some(vec2(2., 0.)));
+        assert_eq!(pm.gradient_at(UVec2::new(8, 0)), None);
+
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(8), 0, 1);
+        pm.set_pixel((4, 4), 10);
+        assert_eq!(pm.gradient_at(UVec2::new(1, 1)), Some(vec2(0., 0.)));
+        assert_eq!(pm.gradient_at(UVec2::new(4, 3)), Some(vec2(0., 5.)));
+        assert_eq!(pm.gradient_at(UVec2::new(5, 4)), Some(vec2(-5., 0.)));
+        assert_eq!(pm.gradient_at(UVec2::new(4, 4)), Some(vec2(0., 0.)));
+    }
+
+    #[test]
+    fn test_steepness_in_rect() {
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(16), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 8, 16), 10);
+
+        let rect = URect::new(0, 0, 16, 16);
+        let steepness = pm.steepness_in_rect(&rect).unwrap();
+
+        let mut max = 0f32;
+        let mut min = f32::MAX;
+        let mut sum = 0f32;
+        for y in 0..16 {
+            for x in 0..16 {
+                let g = pm.gradient_at(UVec2::new(x, y)).unwrap().length();
+                max = max.max(g);
+                min = min.min(g);
+                sum += g;
+            }
+        }
+        assert_eq!(steepness.max, max);
+        assert_eq!(steepness.min, min);
+        assert_eq!(steepness.mean, sum / 256.);
+        assert_eq!(steepness.max, 5.);
+        assert_eq!(steepness.min, 0.);
+
+        assert_eq!(pm.steepness_in_rect(&URect::new(20, 20, 30, 30)), None);
+    }
+
+    #[test]
+    fn test_moments() {
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(16), 0, 1);
+        assert_eq!(pm.moments(&pm.map_rect()), None);
+
+        pm.draw_rect(&URect::new(0, 0, 4, 2), 1);
+        let m = pm.moments(&pm.map_rect()).unwrap();
+        assert_eq!(m.mass, 8.0);
+        assert_eq!(m.centroid, vec2(2.0, 1.0));
+        assert!((m.covariance.x_axis.x - 16.0 / 12.0).abs() < 1e-5);
+        assert!((m.covariance.y_axis.y - 4.0 / 12.0).abs() < 1e-5);
+        assert!(m.covariance.x_axis.y.abs() < 1e-5);
+        assert!((m.principal_axis() - vec2(1.0, 0.0)).length() < 1e-5);
+
+        // Weighted by value
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(16), 0, 1);
+        pm.set_pixel((0, 0), 1);
+        pm.set_pixel((4, 0), 3);
+        assert_eq!(pm.weighted_centroid(&pm.map_rect()), Some(vec2(3.5, 0.5)));
+        assert_eq!(
+            pm.weighted_centroid(&URect::new(0, 0, 2, 2)),
+            Some(vec2(0.5, 0.5))
+        );
+        assert_eq!(pm.weighted_centroid(&URect::new(8, 8, 12, 12)), None);
+
+        // A diagonal blob
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        for i in 0..8 {
+            pm.set_pixel((i + 2, i + 2), true);
+        }
+        let m = pm.moments(&pm.map_rect()).unwrap();
+        assert_eq!(m.centroid, vec2(6.0, 6.0));
+        assert!((m.orientation() - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+    }
+}
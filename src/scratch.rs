@@ -0,0 +1,111 @@
+use crate::isocontour::FragmentAccumulator;
+use crate::pathfinding::GridScratch;
+use bevy_math::IVec2;
+use fxhash::FxHasher;
+use std::collections::HashSet;
+use std::fmt::{Debug, Formatter};
+use std::hash::BuildHasherDefault;
+
+/// Reusable allocations for queries that otherwise allocate internally on every call, as
+/// accepted by the `*_with` variants of those queries, such as [PixelMap::points_with],
+/// [PixelMap::contour_with] and [PixelMap::pathfind_a_star_grid_with]. Keeping a
+/// [QueryScratch] alive across calls, such as once per frame, amortizes those allocations
+/// to zero once it has grown to the size of the largest query.
+///
+/// A [QueryScratch] holds no state between queries other than allocated capacity, so one may
+/// be shared by unrelated queries and [PixelMap]s. Being mutably borrowed by each query, it
+/// may be kept in a `RefCell` or thread-local where the caller only has shared access.
+///
+/// [PixelMap]: crate::PixelMap
+/// [PixelMap::points_with]: crate::PixelMap::points_with
+/// [PixelMap::contour_with]: crate::PixelMap::contour_with
+/// [PixelMap::pathfind_a_star_grid_with]: crate::PixelMap::pathfind_a_star_grid_with
+pub struct QueryScratch {
+    pub(crate) points: HashSet<IVec2, BuildHasherDefault<FxHasher>>,
+    pub(crate) fragments: FragmentAccumulator,
+    pub(crate) grid: GridScratch,
+}
+
+impl QueryScratch {
+    /// Create an empty [QueryScratch], which does not allocate until first used.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            points: HashSet::default(),
+            fragments: FragmentAccumulator::new(0),
+            grid: GridScratch::default(),
+        }
+    }
+}
+
+impl Default for QueryScratch {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debug for QueryScratch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryScratch").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pathfinding::euclidean_heuristic;
+    use crate::*;
+    use bevy_math::{IVec2, URect, UVec2};
+
+    #[test]
+    fn test_query_scratch() {
+        let mut pm: PixelMap<bool, u32> = PixelMap::new(&UVec2::splat(32), true, 1);
+        pm.draw_rect(&URect::new(8, 0, 10, 24), false);
+        let mut scratch = QueryScratch::new();
+
+        // Reused across queries and maps, with the same results as allocating variants
+        for _ in 0..2 {
+            let rect = pm.map_rect();
+            let expected = pm.points(&rect, IVec2::ZERO, |n, _| !*n.value());
+            let points = pm.points_with(&rect, IVec2::ZERO, |n, _| !*n.value(), &mut scratch);
+            assert_eq!(*points, expected);
+
+            let expected = pm.contour(&rect, |n, _| !*n.value());
+            let lines = pm.contour_with(&rect, |n, _| !*n.value(), &mut scratch);
+            assert_eq!(lines.len(), expected.len());
+            assert_eq!(lines[0].points.len(), expected[0].points.len());
+
+            let path = |scratch: Option<&mut QueryScratch>| {
+                let (start, goal) = (UVec2::new(2, 2), UVec2::new(20, 2));
+                let predicate = |n: &PNode<bool, u32>, _: &URect| *n.value();
+                match scratch {
+                    Some(scratch) => pm.pathfind_a_star_grid_with(
+                        &rect,
+                        2,
+                        start,
+                        goal,
+                        euclidean_heuristic,
+                        predicate,
+                        scratch,
+                    ),
+                    None => pm.pathfind_a_star_grid(
+                        &rect,
+                        2,
+                        start,
+                        goal,
+                        euclidean_heuristic,
+                        predicate,
+                    ),
+                }
+                .unwrap()
+            };
+            let expected = path(None);
+            let result = path(Some(&mut scratch));
+            assert_eq!(result.path, expected.path);
+            assert_eq!(result.cost, expected.cost);
+
+            pm.set_pixel((30, 30), false);
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{InclusiveRect, PixelMap};
+use bevy_math::{URect, UVec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Debug;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Segment this [PixelMap] into labelled regions by growing from the given seeds, in the
+    /// manner of a watershed. Regions grow across neighboring leaf nodes in order of least
+    /// accumulated cost, such that each leaf node is claimed wholesale by the seed that reaches
+    /// it most cheaply.
+    ///
+    /// # Parameters
+    ///
+    /// - `seeds`: The pixel coordinates from which regions grow, and the label of each region.
+    ///   Seeds outside the [PixelMap::map_rect] are ignored. When more than one seed lies in
+    ///   the same leaf node, the first takes precedence.
+    /// - `cost`: A closure that takes the values of two neighboring leaf nodes, and returns the
+    ///   cost of growing a region from the first into the second.
+    ///
+    /// # Returns
+    ///
+    /// A [PixelMap] of the same dimensions and pixel size as this one, having the label of each
+    /// pixel's region. `None` if none of the seeds lie within the [PixelMap::map_rect].
+    pub fn segment<L, F>(&self, seeds: &[(UVec2, L)], cost: F) -> Option<PixelMap<L, U>>
+    where
+        L: Copy + PartialEq,
+        F: Fn(&T, &T) -> u32,
+    {
+        let map_rect = self.map_rect();
+
+        // Index all leaf nodes by the origin of their region
+        let mut leaves: Vec<(UVec2, T, URect)> = Vec::new();
+        self.visit(|node, sub_rect| leaves.push((node.region().point(), *node.value(), *sub_rect)));
+        let index: FxHashMap<UVec2, usize> = leaves
+            .iter()
+            .enumerate()
+            .map(|(i, (origin, _, _))| (*origin, i))
+            .collect();
+
+        // Pairs may include leaf nodes outside the map, which are not indexed
+        let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); leaves.len()];
+        self.root.visit_neighbor_pairs_face(
+            &map_rect,
+            &mut |_, a, _, b, _| {
+                let a = index.get(&a.region().point());
+                let b = index.get(&b.region().point());
+                if let (Some(&a), Some(&b)) = (a, b) {
+                    neighbors[a].push(b);
+                    neighbors[b].push(a);
+                }
+            },
+            &mut 0,
+        );
+
+        let mut costs: Vec<u64> = vec![u64::MAX; leaves.len()];
+        let mut labels: Vec<Option<L>> = vec![None; leaves.len()];
+        let mut to_see = BinaryHeap::with_capacity(leaves.len());
+        for (point, label) in seeds {
+            if !map_rect.contains_exclusive(*point) {
+                continue;
+            }
+            let Some(&i) = index.get(&self.root.find_node(*point).region().point()) else {
+                continue;
+            };
+            if labels[i].is_none() {
+                costs[i] = 0;
+                labels[i] = Some(*label);
+                to_see.push(Reverse((0u64, i)));
+            }
+        }
+        let first_label = labels.iter().flatten().next().copied()?;
+
+        while let Some(Reverse((c, i))) = to_see.pop() {
+            if c > costs[i] {
+                continue;
+            }
+            let value = &leaves[i].1;
+            for &n in &neighbors[i] {
+                let new_cost = c + cost(value, &leaves[n].1) as u64;
+                if new_cost < costs[n] {
+                    costs[n] = new_cost;
+                    labels[n] = labels[i];
+                    to_see.push(Reverse((new_cost, n)));
+                }
+            }
+        }
+
+        let mut result = PixelMap::new(&self.map_size(), first_label, self.pixel_size());
+        for ((_, _, sub_rect), label) in leaves.iter().zip(labels) {
+            if let Some(label) = label {
+                if label != first_label {
+                    result.draw_rect(sub_rect, label);
+                }
+            }
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_segment() {
+        // Two basins separated by a wall
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::splat(16), 0, 1);
+        pm.draw_rect(&URect::new(7, 0, 9, 16), 9);
+
+        let labels = pm
+            .segment(
+                &[(UVec2::new(2, 2), 'a'), (UVec2::new(13, 2), 'b')],
+                |a, b| 1 + a.abs_diff(*b) as u32 * 10,
+            )
+            .unwrap();
+        assert_eq!(labels.map_size(), pm.map_size());
+        for y in 0..16 {
+            for x in 0..7 {
+                assert_eq!(labels.get_pixel((x, y)), Some(&'a'));
+            }
+            for x in 9..16 {
+                assert_eq!(labels.get_pixel((x, y)), Some(&'b'));
+            }
+        }
+    }
+
+    #[test]
+    fn test_segment_seeds() {
+        let pm = PixelMap::<u8, u32>::new(&UVec2::splat(16), 0, 1);
+        assert!(pm.segment::<u8, _>(&[], |_, _| 1).is_none());
+        assert!(pm.segment(&[(UVec2::new(20, 2), 1u8)], |_, _| 1).is_none());
+
+        // A uniform map is claimed wholesale by the first seed
+        let labels = pm
+            .segment(
+                &[(UVec2::new(2, 2), 1u8), (UVec2::new(13, 2), 2u8)],
+                |_, _| 1,
+            )
+            .unwrap();
+        assert_eq!(labels.stats().leaf_count, 1);
+        assert_eq!(labels.get_pixel((15, 15)), Some(&1));
+    }
+
+    #[test]
+    fn test_segment_non_square() {
+        // Leaf nodes of the root region outside the map are not segmented
+        let mut pm = PixelMap::<u8, u32>::new(&UVec2::new(20, 12), 0, 1);
+        pm.draw_rect(&URect::new(9, 0, 11, 12), 9);
+
+        let labels = pm
+            .segment(
+                &[(UVec2::new(2, 2), 'a'), (UVec2::new(17, 10), 'b')],
+                |a, b| 1 + a.abs_diff(*b) as u32 * 10,
+            )
+            .unwrap();
+        assert_eq!(labels.map_size(), UVec2::new(20, 12));
+        for y in 0..12 {
+            for x in 0..9 {
+                assert_eq!(labels.get_pixel((x, y)), Some(&'a'));
+            }
+            for x in 11..20 {
+                assert_eq!(labels.get_pixel((x, y)), Some(&'b'));
+            }
+        }
+    }
+}
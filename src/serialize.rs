@@ -0,0 +1,247 @@
+use crate::{PNode, PixelMap};
+use num_traits::{NumCast, Unsigned};
+use serde::ser::{SerializeStruct, SerializeTuple};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Debug;
+
+/// Options controlling which transient state of a [PixelMap] is persisted by serialization,
+/// as per [PixelMap::with_serialize_options] and [PixelMap::deserialize_with].
+///
+/// By default, all state is persisted, as with the plain [Serialize] and [Deserialize]
+/// implementations of [PixelMap].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Whether the dirty state of nodes is persisted, such that regions awaiting upload to the
+    /// GPU when saved are still dirty when loaded. Otherwise, all nodes are clean.
+    pub dirty: bool,
+
+    /// Whether the [PixelMap::version] is persisted. Otherwise, it is zero.
+    pub version: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            dirty: true,
+            version: true,
+        }
+    }
+}
+
+impl SerializeOptions {
+    /// Options that persist only pixel data, resetting all transient state.
+    pub const DATA_ONLY: Self = Self {
+        dirty: false,
+        version: false,
+    };
+}
+
+/// A [Serialize] adapter of a [PixelMap], as per [PixelMap::with_serialize_options]. Its
+/// serialized form is that of the [PixelMap] itself.
+#[derive(Debug, Clone, Copy)]
+pub struct PixelMapSerializer<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> {
+    pixel_map: &'a PixelMap<T, U>,
+    options: SerializeOptions,
+}
+
+impl<T, U> PixelMap<T, U>
+where
+    T: Copy + PartialEq + Serialize,
+    U: Unsigned + NumCast + Copy + Debug + Serialize,
+{
+    /// Obtain a [Serialize] adapter of this [PixelMap] that persists transient state as
+    /// determined by the given `options`. The serialized form is the same as that of this
+    /// [PixelMap], so may be deserialized as usual.
+    #[inline]
+    #[must_use]
+    pub fn with_serialize_options(
+        &self,
+        options: SerializeOptions,
+    ) -> PixelMapSerializer<'_, T, U> {
+        PixelMapSerializer {
+            pixel_map: self,
+            options,
+        }
+    }
+}
+
+impl<'de, T, U> PixelMap<T, U>
+where
+    T: Copy + PartialEq + Deserialize<'de>,
+    U: Unsigned + NumCast + Copy + Debug + Deserialize<'de>,
+{
+    /// Deserialize a [PixelMap], resetting transient state that is not to be persisted as
+    /// determined by the given `options`.
+    pub fn deserialize_with<D>(options: SerializeOptions, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut pixel_map = Self::deserialize(deserializer)?;
+        if !options.dirty {
+            pixel_map.clear_dirty(true);
+        }
+        if !options.version {
+            pixel_map.version = 0;
+        }
+        Ok(pixel_map)
+    }
+}
+
+// The following mirror the derived implementations of `PixelMap`, `PNode` and `PNodeKind`,
+// but override persisted state as per the options.
+
+impl<T, U> Serialize for PixelMapSerializer<'_, T, U>
+where
+    T: Copy + PartialEq + Serialize,
+    U: Unsigned + NumCast + Copy + Debug + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let pixel_map = self.pixel_map;
+        let version = if self.options.version {
+            pixel_map.version
+        } else {
+            0
+        };
+        let mut state = serializer.serialize_struct("PixelMap", 4)?;
+        state.serialize_field("root", &NodeSerializer(&pixel_map.root, self.options))?;
+        state.serialize_field("map_rect", &pixel_map.map_rect)?;
+        state.serialize_field("pixel_size", &pixel_map.pixel_size)?;
+        state.serialize_field("version", &version)?;
+        state.end()
+    }
+}
+
+struct NodeSerializer<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug>(
+    &'a PNode<T, U>,
+    SerializeOptions,
+);
+
+impl<T, U> Serialize for NodeSerializer<'_, T, U>
+where
+    T: Copy + PartialEq + Serialize,
+    U: Unsigned + NumCast + Copy + Debug + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let NodeSerializer(node, options) = *self;
+        let mut state = serializer.serialize_struct("PNode", 3)?;
+        state.serialize_field("region", node.region())?;
+        state.serialize_field("kind", &KindSerializer(node, options))?;
+        state.serialize_field("dirty", &(options.dirty && node.dirty()))?;
+        state.end()
+    }
+}
+
+struct KindSerializer<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug>(
+    &'a PNode<T, U>,
+    SerializeOptions,
+);
+
+impl<T, U> Serialize for KindSerializer<'_, T, U>
+where
+    T: Copy + PartialEq + Serialize,
+    U: Unsigned + NumCast + Copy + Debug + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let KindSerializer(node, options) = *self;
+        if node.is_leaf() {
+            serializer.serialize_newtype_variant("PNodeKind", 0, "Leaf", node.value())
+        } else if node.is_compressed() {
+            serializer.serialize_newtype_variant(
+                "PNodeKind",
+                2,
+                "Compressed",
+                &ChildrenSerializer(node, options),
+            )
+        } else {
+            serializer.serialize_newtype_variant(
+                "PNodeKind",
+                1,
+                "Branch",
+                &ChildrenSerializer(node, options),
+            )
+        }
+    }
+}
+
+struct ChildrenSerializer<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug>(
+    &'a PNode<T, U>,
+    SerializeOptions,
+);
+
+impl<T, U> Serialize for ChildrenSerializer<'_, T, U>
+where
+    T: Copy + PartialEq + Serialize,
+    U: Unsigned + NumCast + Copy + Debug + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ChildrenSerializer(node, options) = *self;
+        let mut state = serializer.serialize_tuple(4)?;
+        for child in node.children().iter() {
+            state.serialize_element(&NodeSerializer(child, options))?;
+        }
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_serialize_options() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 4, 4), 1);
+        pm.clear_dirty(true);
+        pm.set_pixel((9, 9), 2);
+        let dirty_rects = |pm: &PixelMap<u8, u32>| {
+            let mut rects = Vec::new();
+            pm.visit_dirty(|_, r| rects.push(*r));
+            rects
+        };
+
+        // By default, all state persists, and the form matches the derived implementation
+        let ron = ron::to_string(&pm.with_serialize_options(SerializeOptions::default())).unwrap();
+        assert_eq!(ron, ron::to_string(&pm).unwrap());
+        let loaded: PixelMap<u8, u32> = ron::from_str(&ron).unwrap();
+        assert!(loaded == pm);
+        assert_eq!(loaded.version(), pm.version());
+        assert_eq!(dirty_rects(&loaded), dirty_rects(&pm));
+        assert!(!dirty_rects(&loaded).is_empty());
+
+        // Reset on save, where node equality includes the dirty state
+        let mut clean = pm.clone();
+        clean.clear_dirty(true);
+        let ron = ron::to_string(&pm.with_serialize_options(SerializeOptions::DATA_ONLY)).unwrap();
+        let loaded: PixelMap<u8, u32> = ron::from_str(&ron).unwrap();
+        assert!(loaded == clean);
+        assert_eq!(loaded.version(), 0);
+        assert!(!loaded.dirty());
+
+        // Reset on load
+        let ron = ron::to_string(&pm).unwrap();
+        let mut deserializer = ron::Deserializer::from_str(&ron).unwrap();
+        let loaded: PixelMap<u8, u32> =
+            PixelMap::deserialize_with(SerializeOptions::DATA_ONLY, &mut deserializer).unwrap();
+        assert!(loaded == clean);
+        assert_eq!(loaded.version(), 0);
+        assert!(dirty_rects(&loaded).is_empty());
+    }
+
+    #[test]
+    fn test_serialize_compressed() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+        pm.enable_compression(1);
+        pm.set_pixel((1, 1), 1);
+        pm.set_pixel((9, 9), 2);
+        pm.clear_dirty(true);
+        assert_eq!(pm.compress_cold(pm.version() + 1), 2);
+
+        let ron = ron::to_string(&pm.with_serialize_options(SerializeOptions::default())).unwrap();
+        assert_eq!(ron, ron::to_string(&pm).unwrap());
+        let loaded: PixelMap<u8, u32> = ron::from_str(&ron).unwrap();
+        assert_eq!(loaded.compressed_count(), 2);
+        assert!(loaded == pm);
+        assert_eq!(loaded.get_pixel((9, 9)), Some(&2));
+    }
+}
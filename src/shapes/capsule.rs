@@ -0,0 +1,211 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::circle::isqrt_wide;
+use super::span_iterator::{convex_spans, linear_range};
+use crate::{ILine, SpanPixelIterator, UnsignedPixelIterator};
+use bevy_math::{I64Vec2, IRect, IVec2};
+
+/// A capsule represented by a line segment, in integer coordinates, and a radius.
+/// In other words, the set of points within `radius` of the line segment.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct ICapsule {
+    line: ILine,
+    radius: u32,
+}
+
+impl ICapsule {
+    pub const ZERO: Self = Self {
+        line: ILine::ZERO,
+        radius: 0,
+    };
+
+    /// Creates a new capsule with the given line segment and radius.
+    #[inline]
+    #[must_use]
+    pub fn new(line: ILine, radius: u32) -> Self {
+        Self { line, radius }
+    }
+
+    /// Get the line segment.
+    #[inline]
+    #[must_use]
+    pub fn line(&self) -> ILine {
+        self.line
+    }
+
+    /// Get the radius.
+    #[inline]
+    #[must_use]
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Determine if the capsule contains the given point.
+    #[inline]
+    #[must_use]
+    pub fn contains<P>(&self, point: P) -> bool
+    where
+        P: Into<IVec2>,
+    {
+        let p = point.into().as_i64vec2();
+        let s = self.line.start().as_i64vec2();
+        let e = self.line.end().as_i64vec2();
+        let r2 = self.radius as i64 * self.radius as i64;
+
+        let d = e - s;
+        let sp = p - s;
+        let t = sp.dot(d);
+        if t <= 0 {
+            return sp.length_squared() <= r2;
+        }
+        let l2 = d.length_squared();
+        if t >= l2 {
+            return (p - e).length_squared() <= r2;
+        }
+        // Perpendicular distance squared is `cross^2 / l2`
+        let cross = (sp.x * d.y - sp.y * d.x) as i128;
+        cross * cross <= r2 as i128 * l2 as i128
+    }
+
+    /// Get the axis-aligned bounding box of the capsule.
+    #[inline]
+    #[must_use]
+    pub fn aabb(&self) -> IRect {
+        let r = IVec2::splat(self.radius as i32);
+        let aabb = self.line.aabb();
+        IRect::from_corners(aabb.min - r, aabb.max + r)
+    }
+
+    /// Obtain the horizontal spans of pixels in the capsule, bottom to top.
+    #[inline]
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
+        self.spans_in(&self.aabb())
+    }
+
+    /// Obtain the horizontal spans of pixels in the capsule, bottom to top, within the
+    /// inclusive bounds of `clip`, such as to draw only the visible part of a mostly
+    /// off-screen capsule.
+    #[must_use]
+    pub fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        let s = self.line.start().as_i64vec2();
+        let e = self.line.end().as_i64vec2();
+        let d = e - s;
+        let r2 = self.radius as i128 * self.radius as i128;
+        let l2 = d.length_squared() as i128;
+        // The greatest perpendicular distance of a contained point, scaled by the length
+        let max_cross = isqrt_wide(r2.saturating_mul(l2));
+
+        convex_spans(&self.aabb(), clip, |y| {
+            // The union of the rows of the circles at each end, and of the band between them
+            let cap = |c: I64Vec2| {
+                let dy = (y - c.y) as i128;
+                let h = isqrt_wide(r2 - dy * dy) as i64;
+                (dy * dy <= r2).then_some((c.x - h, c.x + h))
+            };
+            let sy = (y - s.y) as i128;
+            let (sx, dx, dy) = (s.x as i128, d.x as i128, d.y as i128);
+            let band = (l2 > 0)
+                .then(|| linear_range(dx, sy * dy - sx * dx, 0, l2))
+                .flatten()
+                .zip(linear_range(dy, -sy * dx - sx * dy, -max_cross, max_cross))
+                .map(|(t, c)| (t.0.max(c.0), t.1.min(c.1)))
+                .filter(|(min, max)| min <= max);
+            [cap(s), cap(e), band]
+                .into_iter()
+                .flatten()
+                .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+        })
+    }
+
+    /// Iterator over pixels in the capsule.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> SpanPixelIterator {
+        SpanPixelIterator::new(self.spans())
+    }
+
+    /// Iterator over pixels in the capsule, filtered to only positive points.
+    #[inline]
+    #[must_use]
+    pub fn unsigned_pixels(&self) -> UnsignedPixelIterator<SpanPixelIterator> {
+        UnsignedPixelIterator::<SpanPixelIterator>::new(self.pixels())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{iline, ICircle};
+    use bevy_math::ivec2;
+
+    #[test]
+    fn test_contains() {
+        let capsule = ICapsule::new(iline((0, 0), (10, 0)), 2);
+        assert!(capsule.contains((0, 0)));
+        assert!(capsule.contains((5, 2)));
+        assert!(capsule.contains((5, -2)));
+        assert!(!capsule.contains((5, 3)));
+        assert!(capsule.contains((-2, 0)));
+        assert!(capsule.contains((12, 0)));
+        assert!(!capsule.contains((13, 0)));
+        assert!(!capsule.contains((12, 1)));
+    }
+
+    #[test]
+    fn test_aabb() {
+        let capsule = ICapsule::new(iline((10, 0), (0, 5)), 2);
+        assert_eq!(capsule.aabb(), IRect::new(-2, -2, 12, 7));
+    }
+
+    #[test]
+    fn test_degenerate_line_matches_circle() {
+        let capsule = ICapsule::new(iline((3, 4), (3, 4)), 5);
+        let circle = ICircle::new((3, 4), 5);
+        let mut a: Vec<IVec2> = capsule.pixels().collect();
+        let mut b: Vec<IVec2> = circle.pixels().collect();
+        a.sort_by_key(|p| (p.y, p.x));
+        b.sort_by_key(|p| (p.y, p.x));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_pixels() {
+        let capsule = ICapsule::new(iline((-3, 2), (7, -5)), 3);
+        let pixels: Vec<IVec2> = capsule.pixels().collect();
+        let aabb = capsule.aabb();
+        let mut expected = Vec::new();
+        for y in aabb.min.y..=aabb.max.y {
+            for x in aabb.min.x..=aabb.max.x {
+                if capsule.contains((x, y)) {
+                    expected.push(ivec2(x, y));
+                }
+            }
+        }
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn test_spans_in() {
+        let clip = IRect::new(0, 0, 63, 63);
+        for capsule in [
+            ICapsule::new(iline((-30000, -30000), (30000, 30000)), 3),
+            ICapsule::new(iline((70, 10), (-5, 40)), 6),
+            ICapsule::new(iline((20, -3), (20, -3)), 5),
+            ICapsule::new(iline((100, 0), (100, 60)), 1),
+        ] {
+            let mut expected = Vec::new();
+            for y in clip.min.y..=clip.max.y {
+                for x in clip.min.x..=clip.max.x {
+                    if capsule.contains((x, y)) {
+                        expected.push(ivec2(x, y));
+                    }
+                }
+            }
+            let pixels: Vec<IVec2> = SpanPixelIterator::new(capsule.spans_in(&clip)).collect();
+            assert_eq!(pixels, expected, "{:?}", capsule);
+        }
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
 
-use crate::UnsignedPixelIterator;
+use crate::{iline, ILine, IRing, SpanPixelIterator, UnsignedPixelIterator};
 use bevy_math::{ivec2, IRect, IVec2, URect};
 
 /// A circle represented by a center point, in integer coordinates, and a radius.
@@ -66,8 +66,25 @@ impl ICircle {
     where
         P: Into<IVec2>,
     {
-        let d = point.into() - self.point;
-        d.x * d.x + d.y * d.y <= self.radius as i32 * self.radius as i32
+        let d = (point.into() - self.point).as_i64vec2();
+        d.length_squared() <= self.radius as i64 * self.radius as i64
+    }
+
+    /// Determine if the circle intersects the given rectangle, inclusive of its `max` edges.
+    #[inline]
+    #[must_use]
+    pub fn intersects_rect(&self, rect: &IRect) -> bool {
+        let nearest = self.point.clamp(rect.min, rect.max.max(rect.min));
+        self.contains(nearest)
+    }
+
+    /// Determine if the circle intersects the given circle.
+    #[inline]
+    #[must_use]
+    pub fn intersects_circle(&self, other: &ICircle) -> bool {
+        let d = (other.point - self.point).as_i64vec2();
+        let r = self.radius as i64 + other.radius as i64;
+        d.length_squared() <= r * r
     }
 
     /// Get the axis-aligned bounding box of the circle.
@@ -99,6 +116,53 @@ impl ICircle {
     pub fn unsigned_pixels(&self) -> UnsignedPixelIterator<ICirclePixelIterator> {
         UnsignedPixelIterator::<ICirclePixelIterator>::new(self.pixels())
     }
+
+    /// Obtain the horizontal spans of pixels in the circle, bottom to top.
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
+        let r = self.radius as i64;
+        (-r..=r)
+            .map(|dy| {
+                let half_width = isqrt(r * r - dy * dy) as i32;
+                let y = self.point.y + dy as i32;
+                iline(
+                    (self.point.x - half_width, y),
+                    (self.point.x + half_width, y),
+                )
+            })
+            .collect()
+    }
+
+    /// Obtain the horizontal spans of pixels in the circle, bottom to top, within the
+    /// inclusive bounds of `clip`.
+    #[must_use]
+    pub fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        let r = self.radius as i64;
+        let (x, y) = (self.point.x as i64, self.point.y as i64);
+        ((y - r).max(clip.min.y as i64)..=(y + r).min(clip.max.y as i64))
+            .filter_map(|row| {
+                let dy = row - y;
+                let half_width = isqrt(r * r - dy * dy);
+                let min_x = (x - half_width).max(clip.min.x as i64);
+                let max_x = (x + half_width).min(clip.max.x as i64);
+                (min_x <= max_x)
+                    .then(|| iline((min_x as i32, row as i32), (max_x as i32, row as i32)))
+            })
+            .collect()
+    }
+
+    /// Iterator over pixels in a ring along the circumference of the circle, `thickness`
+    /// pixels wide. In other words, pixels in the circle that are not within the circle
+    /// of radius `radius - thickness`. A `thickness` greater than the radius yields all pixels
+    /// in the circle.
+    #[must_use]
+    pub fn ring_pixels(&self, thickness: u32) -> SpanPixelIterator {
+        match thickness {
+            0 => SpanPixelIterator::new(Vec::new()),
+            t if t > self.radius => SpanPixelIterator::new(self.spans()),
+            t => IRing::new(self.point, self.radius - t, self.radius).pixels(),
+        }
+    }
 }
 
 impl From<IRect> for ICircle {
@@ -132,6 +196,40 @@ impl From<&URect> for ICircle {
     }
 }
 
+// Integer square root, rounded down.
+#[inline]
+#[must_use]
+pub(crate) fn isqrt(n: i64) -> i64 {
+    if n <= 0 {
+        return 0;
+    }
+    let mut x = (n as f64).sqrt() as i64;
+    while x * x > n {
+        x -= 1;
+    }
+    while (x + 1) * (x + 1) <= n {
+        x += 1;
+    }
+    x
+}
+
+// Integer square root, rounded down, of a product too large for [isqrt].
+#[must_use]
+pub(crate) fn isqrt_wide(n: i128) -> i128 {
+    if n <= 0 {
+        return 0;
+    }
+    let n = n as u128;
+    let mut x = (n as f64).sqrt() as u128;
+    while x * x > n {
+        x -= 1;
+    }
+    while (x + 1) * (x + 1) <= n {
+        x += 1;
+    }
+    x as i128
+}
+
 #[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
 pub struct ICirclePixelIterator {
     circle: ICircle,
@@ -189,6 +287,48 @@ mod test {
         assert_eq!(circle.radius(), 5);
     }
 
+    #[test]
+    fn test_contains() {
+        let circle = ICircle::new((2, 2), 2);
+        assert!(circle.contains((2, 2)));
+        assert!(circle.contains((4, 2)));
+        assert!(circle.contains((3, 3)));
+        assert!(!circle.contains((4, 4)));
+    }
+
+    #[test]
+    fn test_intersects_rect() {
+        let circle = ICircle::new((0, 0), 5);
+        assert!(circle.intersects_rect(&IRect::new(-1, -1, 1, 1)));
+        assert!(circle.intersects_rect(&IRect::new(-10, -10, 10, 10)));
+        assert!(circle.intersects_rect(&IRect::new(5, -2, 10, 2)));
+        assert!(circle.intersects_rect(&IRect::new(3, 4, 10, 10)));
+        assert!(!circle.intersects_rect(&IRect::new(4, 4, 10, 10)));
+        assert!(!circle.intersects_rect(&IRect::new(6, -2, 10, 2)));
+    }
+
+    #[test]
+    fn test_intersects_circle() {
+        let circle = ICircle::new((0, 0), 5);
+        assert!(circle.intersects_circle(&ICircle::new((0, 0), 1)));
+        assert!(circle.intersects_circle(&ICircle::new((8, 0), 3)));
+        assert!(!circle.intersects_circle(&ICircle::new((9, 0), 3)));
+    }
+
+    #[test]
+    fn test_ring_pixels() {
+        let circle = ICircle::new((3, -1), 6);
+        for thickness in 0..=8 {
+            let inner = ICircle::new(circle.point(), circle.radius().saturating_sub(thickness));
+            let ring: Vec<IVec2> = circle.ring_pixels(thickness).collect();
+            let expected: Vec<IVec2> = circle
+                .pixels()
+                .filter(|&p| thickness > circle.radius() || !inner.contains(p))
+                .collect();
+            assert_eq!(ring, expected, "thickness: {}", thickness);
+        }
+    }
+
     #[test]
     fn test_pixels() {
         let mut iter = ICircle::new((0, 0), 2).pixels();
@@ -0,0 +1,190 @@
+use crate::iline;
+use bevy_math::{ivec2, DVec2, IRect, IVec2};
+
+/// Clip a line strip to the given rectangle, such as to draw only the visible parts of a
+/// mostly off-screen path. The rectangle is treated as closed, such that points on its `max`
+/// edges are retained, as per [crate::ILine::clip_to_rect].
+///
+/// # Parameters
+///
+/// - `points`: The points of the line strip, where a line segment joins each point and the
+///   next.
+/// - `rect`: The rectangle to clip to.
+///
+/// # Returns
+///
+/// The parts of the line strip within the rectangle, each being a line strip of at least two
+/// points, in the order of the given points. A part ends wherever the line strip leaves the
+/// rectangle. Clipped endpoints are rounded to the nearest integer coordinate.
+#[must_use]
+pub fn clip_polyline_to_rect(points: &[IVec2], rect: &IRect) -> Vec<Vec<IVec2>> {
+    if let [point] = points {
+        return if rect.contains(*point) {
+            vec![vec![*point, *point]]
+        } else {
+            vec![]
+        };
+    }
+
+    let mut strips = Vec::new();
+    let mut strip: Vec<IVec2> = Vec::new();
+    for segment in points.windows(2) {
+        let Some(clipped) = iline(segment[0], segment[1]).clip_to_rect(rect) else {
+            continue;
+        };
+        if strip.last() != Some(&clipped.start()) {
+            if !strip.is_empty() {
+                strips.push(std::mem::take(&mut strip));
+            }
+            strip.push(clipped.start());
+        }
+        strip.push(clipped.end());
+        if clipped.end() != segment[1] {
+            // The line strip leaves the rectangle
+            strips.push(std::mem::take(&mut strip));
+        }
+    }
+    if !strip.is_empty() {
+        strips.push(strip);
+    }
+    strips
+}
+
+/// Clip a polygon to the given rectangle, using the Sutherland-Hodgman algorithm, such as to
+/// fill only the visible part of a mostly off-screen polygon. The rectangle is treated as
+/// closed, such that points on its `max` edges are retained.
+///
+/// # Parameters
+///
+/// - `points`: The vertices of the polygon, in either winding order. The polygon is
+///   implicitly closed, joining the last vertex to the first.
+/// - `rect`: The rectangle to clip to.
+///
+/// # Returns
+///
+/// The vertices of the clipped polygon, in the winding order of the given polygon, though not
+/// necessarily starting from the same vertex, and without consecutive duplicates. Clipped
+/// vertices are rounded to the nearest integer coordinate.
+/// Empty if the polygon does not intersect the rectangle. A concave polygon that intersects
+/// the rectangle in several places yields a single polygon joining the places along the
+/// edges of the rectangle.
+#[must_use]
+pub fn clip_polygon_to_rect(points: &[IVec2], rect: &IRect) -> Vec<IVec2> {
+    let min = rect.min.as_dvec2();
+    let max = rect.max.as_dvec2();
+    // Each edge of the rectangle, as an axis, the bound along that axis, and whether points
+    // below the bound are inside
+    let edges = [
+        (0, min.x, false),
+        (0, max.x, true),
+        (1, min.y, false),
+        (1, max.y, true),
+    ];
+
+    let mut polygon: Vec<DVec2> = points.iter().map(|p| p.as_dvec2()).collect();
+    for (axis, bound, below) in edges {
+        let inside = |p: DVec2| {
+            if below {
+                p[axis] <= bound
+            } else {
+                p[axis] >= bound
+            }
+        };
+        let input = std::mem::take(&mut polygon);
+        let Some(&last) = input.last() else {
+            break;
+        };
+        let mut previous = last;
+        for &current in &input {
+            let crossing = || {
+                let t = (bound - previous[axis]) / (current[axis] - previous[axis]);
+                let mut point = previous + (current - previous) * t;
+                point[axis] = bound;
+                point
+            };
+            match (inside(previous), inside(current)) {
+                (true, true) => polygon.push(current),
+                (true, false) => polygon.push(crossing()),
+                (false, true) => {
+                    polygon.push(crossing());
+                    polygon.push(current);
+                }
+                (false, false) => {}
+            }
+            previous = current;
+        }
+    }
+
+    let mut clipped: Vec<IVec2> = polygon
+        .into_iter()
+        .map(|p| ivec2(p.x.round() as i32, p.y.round() as i32))
+        .collect();
+    clipped.dedup();
+    if clipped.len() > 1 && clipped.first() == clipped.last() {
+        clipped.pop();
+    }
+    clipped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{ivec2, IRect};
+
+    #[test]
+    fn test_clip_polyline_to_rect() {
+        let rect = IRect::new(0, 0, 10, 10);
+
+        // Entirely inside
+        let points = [ivec2(1, 1), ivec2(5, 1), ivec2(5, 5)];
+        assert_eq!(clip_polyline_to_rect(&points, &rect), vec![points.to_vec()]);
+
+        // Leaves and re-enters
+        let points = [ivec2(2, 2), ivec2(2, 15), ivec2(8, 15), ivec2(8, 2)];
+        assert_eq!(
+            clip_polyline_to_rect(&points, &rect),
+            vec![
+                vec![ivec2(2, 2), ivec2(2, 10)],
+                vec![ivec2(8, 10), ivec2(8, 2)]
+            ]
+        );
+
+        // Crosses through
+        let points = [ivec2(-5, 5), ivec2(15, 5), ivec2(15, 20)];
+        assert_eq!(
+            clip_polyline_to_rect(&points, &rect),
+            vec![vec![ivec2(0, 5), ivec2(10, 5)]]
+        );
+
+        // Entirely outside
+        let points = [ivec2(-5, -5), ivec2(-5, 20)];
+        assert!(clip_polyline_to_rect(&points, &rect).is_empty());
+    }
+
+    #[test]
+    fn test_clip_polygon_to_rect() {
+        let rect = IRect::new(0, 0, 10, 10);
+
+        // Entirely inside
+        let points = [ivec2(1, 1), ivec2(5, 1), ivec2(5, 5)];
+        assert_eq!(clip_polygon_to_rect(&points, &rect), points.to_vec());
+
+        // Containing the rectangle
+        let points = [ivec2(-5, -5), ivec2(15, -5), ivec2(15, 15), ivec2(-5, 15)];
+        assert_eq!(
+            clip_polygon_to_rect(&points, &rect),
+            vec![ivec2(0, 10), ivec2(0, 0), ivec2(10, 0), ivec2(10, 10)]
+        );
+
+        // Overlapping a corner
+        let points = [ivec2(5, 5), ivec2(15, 5), ivec2(5, 15)];
+        assert_eq!(
+            clip_polygon_to_rect(&points, &rect),
+            vec![ivec2(5, 10), ivec2(5, 5), ivec2(10, 5), ivec2(10, 10)]
+        );
+
+        // Entirely outside
+        let points = [ivec2(20, 20), ivec2(30, 20), ivec2(30, 30)];
+        assert!(clip_polygon_to_rect(&points, &rect).is_empty());
+    }
+}
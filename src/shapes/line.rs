@@ -4,7 +4,7 @@ use std::fmt;
 
 use super::line_interval::LineInterval;
 use super::line_iterator::{plot_line, LinePixelIterator};
-use crate::{distance_squared_to_line, distance_to_line, irect_edges, Direction};
+use crate::{distance_squared_to_line, distance_to_line, Direction, RasterShape};
 use bevy_math::{ivec2, IRect, IVec2, Vec2};
 
 /// An alias for [ILine::new].
@@ -212,16 +212,81 @@ impl ILine {
         seg1.relate(&seg2).unique_intersection()
     }
 
-    /// Determine if this line intersects the given rectangle.
+    /// Determine if this line intersects the given rectangle. A line that is fully
+    /// contained by the rectangle is considered to intersect it.
     #[inline]
     #[must_use]
     pub fn intersects_rect(&self, rect: &IRect) -> bool {
-        for edge in irect_edges(rect) {
-            if self.intersects_line(&edge).is_some() {
-                return true;
+        self.clip_to_rect(rect).is_some()
+    }
+
+    /// Clip this line to the given rectangle, using the Liang-Barsky algorithm. The rectangle
+    /// is treated as closed, such that points on its `max` edges are retained.
+    /// Unlike [Self::axis_aligned_intersect_rect], this supports lines of any orientation.
+    ///
+    /// # Returns
+    ///
+    /// The segment of this line that lies within the rectangle, retaining the orientation of
+    /// this line. Clipped endpoints are rounded to the nearest integer coordinate.
+    /// `None` if the line does not intersect the rectangle.
+    #[must_use]
+    pub fn clip_to_rect(&self, rect: &IRect) -> Option<ILine> {
+        let min = (rect.min.x as f64, rect.min.y as f64);
+        let max = (rect.max.x as f64, rect.max.y as f64);
+        let (t0, t1) = self.clip_params(min, max)?;
+
+        let (x0, y0) = (self.start.x as f64, self.start.y as f64);
+        let dx = (self.end.x - self.start.x) as f64;
+        let dy = (self.end.y - self.start.y) as f64;
+        let point_at = |t: f64| ivec2((x0 + t * dx).round() as i32, (y0 + t * dy).round() as i32);
+        let start = if t0 == 0. { self.start } else { point_at(t0) };
+        let end = if t1 == 1. { self.end } else { point_at(t1) };
+        Some(iline(start, end))
+    }
+
+    /// Determine if this line passes through any of the pixels of the given rectangle,
+    /// inclusive of its `max` point. Each pixel is treated as a unit square centered on
+    /// its coordinates, such that the line need only graze a pixel to pass through it.
+    #[inline]
+    #[must_use]
+    pub fn passes_through_rect(&self, rect: &IRect) -> bool {
+        let min = (rect.min.x as f64 - 0.5, rect.min.y as f64 - 0.5);
+        let max = (rect.max.x as f64 + 0.5, rect.max.y as f64 + 0.5);
+        self.clip_params(min, max).is_some()
+    }
+
+    // Liang-Barsky clipping of this line against the closed rectangle given by `min` and `max`.
+    // Returns the parametric interval of the line within the rectangle, if any.
+    fn clip_params(&self, min: (f64, f64), max: (f64, f64)) -> Option<(f64, f64)> {
+        let (x0, y0) = (self.start.x as f64, self.start.y as f64);
+        let dx = (self.end.x - self.start.x) as f64;
+        let dy = (self.end.y - self.start.y) as f64;
+
+        let p = [-dx, dx, -dy, dy];
+        let q = [x0 - min.0, max.0 - x0, y0 - min.1, max.1 - y0];
+
+        let mut t0 = 0f64;
+        let mut t1 = 1f64;
+        for (p, q) in p.into_iter().zip(q) {
+            if p == 0. {
+                // Parallel to this edge; reject if outside of it
+                if q < 0. {
+                    return None;
+                }
+            } else {
+                let t = q / p;
+                if p < 0. {
+                    t0 = t0.max(t);
+                } else {
+                    t1 = t1.min(t);
+                }
             }
         }
-        false
+
+        if t0 > t1 {
+            return None;
+        }
+        Some((t0, t1))
     }
 
     /// Obtain the segment of this line that intersects the given rectangle, if any, otherwise `None`.
@@ -351,6 +416,22 @@ impl ILine {
     pub fn pixels(&self) -> LinePixelIterator {
         LinePixelIterator::new(self)
     }
+
+    /// Determine if any of the pixels of this line are contained by the given shape, such as
+    /// an [IRect]. Lines that do not intersect the shape's aabb are rejected without visiting
+    /// their pixels.
+    #[must_use]
+    pub fn segment_overlaps<S: RasterShape>(&self, shape: &S) -> bool {
+        let aabb = shape.aabb();
+        if !self.intersects_rect(&aabb) {
+            return false;
+        }
+        // The pixels of a line within a rectangle are contiguous, as both axes are monotonic
+        self.pixels()
+            .skip_while(|p| !aabb.contains(*p))
+            .take_while(|p| aabb.contains(*p))
+            .any(|p| shape.contains(p))
+    }
 }
 
 impl From<&ILine> for [Vec2; 2] {
@@ -443,4 +524,71 @@ mod test {
         let line = iline((10, 10), (0, 0));
         assert_eq!(line.diagonal_axis_alignment(), Some(Direction::SouthWest));
     }
+
+    #[test]
+    fn test_intersects_rect() {
+        let rect = IRect::new(0, 0, 10, 10);
+        // Fully contained
+        assert!(iline((2, 2), (8, 5)).intersects_rect(&rect));
+        // Crossing
+        assert!(iline((-5, 5), (15, 6)).intersects_rect(&rect));
+        // Along an edge
+        assert!(iline((0, -5), (0, 15)).intersects_rect(&rect));
+        // Outside
+        assert!(!iline((11, 0), (20, 10)).intersects_rect(&rect));
+        assert!(!iline((-5, 4), (4, -5)).intersects_rect(&rect));
+    }
+
+    #[test]
+    fn test_clip_to_rect() {
+        let rect = IRect::new(0, 0, 10, 10);
+
+        // Contained lines are unchanged
+        let line = iline((2, 3), (7, 9));
+        assert_eq!(line.clip_to_rect(&rect), Some(line));
+
+        // Diagonal crossing the whole rect
+        let line = iline((-5, -5), (15, 15));
+        assert_eq!(line.clip_to_rect(&rect), Some(iline((0, 0), (10, 10))));
+
+        // Orientation is retained
+        let line = iline((15, 15), (-5, -5));
+        assert_eq!(line.clip_to_rect(&rect), Some(iline((10, 10), (0, 0))));
+
+        // Partially inside
+        let line = iline((5, 5), (5, 20));
+        assert_eq!(line.clip_to_rect(&rect), Some(iline((5, 5), (5, 10))));
+
+        let line = iline((-10, 0), (10, 10));
+        assert_eq!(line.clip_to_rect(&rect), Some(iline((0, 5), (10, 10))));
+
+        // Outside
+        assert_eq!(iline((-5, 20), (20, 12)).clip_to_rect(&rect), None);
+        assert_eq!(iline((11, 11), (11, 11)).clip_to_rect(&rect), None);
+
+        // Degenerate point
+        let line = iline((4, 4), (4, 4));
+        assert_eq!(line.clip_to_rect(&rect), Some(line));
+    }
+
+    #[test]
+    fn test_segment_overlaps() {
+        let line = iline((-20, -20), (40, 40));
+        assert!(line.segment_overlaps(&IRect::new(5, 5, 6, 6)));
+        assert!(!line.segment_overlaps(&IRect::new(5, 8, 6, 9)));
+        assert!(line.segment_overlaps(&crate::ICircle::new((10, 12), 2)));
+        assert!(!line.segment_overlaps(&crate::ICircle::new((10, 14), 2)));
+        assert!(!iline((-5, 0), (-1, 0)).segment_overlaps(&IRect::new(0, 0, 2, 2)));
+    }
+
+    #[test]
+    fn test_passes_through_rect() {
+        // Bresenham pixels are (0, 0), (1, 0), (2, 1), (3, 1)
+        let line = iline((0, 0), (3, 1));
+        assert!(line.passes_through_rect(&IRect::new(2, 1, 2, 1)));
+        assert!(line.passes_through_rect(&IRect::new(1, 0, 1, 0)));
+        assert!(!line.passes_through_rect(&IRect::new(0, 1, 0, 1)));
+        assert!(!line.passes_through_rect(&IRect::new(3, 0, 3, 0)));
+        assert!(!line.passes_through_rect(&IRect::new(5, 5, 8, 8)));
+    }
 }
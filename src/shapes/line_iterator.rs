@@ -2,7 +2,7 @@
 use serde::{Deserialize, Serialize};
 
 use super::ILine;
-use crate::{exclusive_irect, Direction};
+use crate::{Direction, InclusiveRect};
 use bevy_math::{ivec2, IRect, IVec2};
 
 pub fn plot_line<F>(x0: i32, y0: i32, x1: i32, y1: i32, mut plot: F)
@@ -220,10 +220,9 @@ impl AngleLineIterator {
 
     #[inline]
     pub fn seek_bounds(&mut self, bounds: &IRect) -> Option<IVec2> {
-        let bounds = exclusive_irect(bounds);
         while let Some(point) = self.next() {
             if let Some(next) = self.peek() {
-                if !bounds.contains(next) {
+                if !bounds.contains_exclusive(next) {
                     return Some(point);
                 }
             } else {
@@ -1,13 +1,22 @@
+mod capsule;
 mod circle;
+mod clip;
 mod line;
 mod line_interval;
 mod line_iterator;
 mod line_strip_iterator;
 mod pixel_iterator;
+mod raster_shape;
 mod rect_iterator;
+mod ring;
 mod rotated_rect;
+mod rounded_rect;
+mod sector;
+mod span_iterator;
+mod triangle;
 
 pub use self::{
-    circle::*, line::*, line_interval::*, line_iterator::*, line_strip_iterator::*,
-    pixel_iterator::*, rect_iterator::*, rotated_rect::*,
+    capsule::*, circle::*, clip::*, line::*, line_interval::*, line_iterator::*,
+    line_strip_iterator::*, pixel_iterator::*, raster_shape::*, rect_iterator::*, ring::*,
+    rotated_rect::*, rounded_rect::*, sector::*, span_iterator::*, triangle::*,
 };
@@ -0,0 +1,434 @@
+use crate::{
+    iline, ICapsule, ICircle, ICirclePixelIterator, ILine, IRing, IRoundedRect, ISector, ITriangle,
+    LineStripPixelIterator, RotatedIRect, SpanPixelIterator,
+};
+use bevy_math::{ivec2, IRect, IVec2};
+
+/// A shape that can be rasterized into pixels, in integer coordinates. Shapes are inclusive
+/// of their bounds: a pixel belongs to the shape when [RasterShape::contains] returns `true`
+/// for its coordinates.
+///
+/// Implementing this trait allows a shape to be drawn with [crate::PixelMap::draw_shape], and
+/// queried with [crate::PixelMap::any_in_shape] and [crate::PixelMap::all_in_shape].
+pub trait RasterShape {
+    /// The type of iterator returned by [RasterShape::pixels].
+    type PixelIterator: Iterator<Item = IVec2>;
+
+    /// Get the axis-aligned bounding box of the shape, inclusive of its `max` point.
+    #[must_use]
+    fn aabb(&self) -> IRect;
+
+    /// Obtain axis-aligned rectangles, inclusive of their `max` points, that are fully
+    /// contained by the shape. These allow large areas of the shape to be handled without
+    /// examining individual pixels. The default implementation returns no rectangles.
+    #[must_use]
+    fn inner_rects(&self) -> Vec<IRect> {
+        Vec::new()
+    }
+
+    /// Determine if the shape contains the given point.
+    #[must_use]
+    fn contains(&self, point: IVec2) -> bool;
+
+    /// Iterator over pixels in the shape.
+    #[must_use]
+    fn pixels(&self) -> Self::PixelIterator;
+
+    /// Obtain the horizontal spans of pixels in the shape, bottom to top, and left to right
+    /// within a row. The default implementation groups the pixels produced by
+    /// [RasterShape::pixels] into contiguous runs.
+    #[must_use]
+    fn spans(&self) -> Vec<ILine> {
+        let mut pixels: Vec<IVec2> = self.pixels().collect();
+        pixels.sort_unstable_by_key(|p| (p.y, p.x));
+        pixels.dedup();
+
+        let mut spans: Vec<ILine> = Vec::new();
+        for p in pixels {
+            match spans.last_mut() {
+                Some(span) if span.end().y == p.y && span.end().x + 1 == p.x => {
+                    *span = iline(span.start(), p);
+                }
+                _ => spans.push(iline(p, p)),
+            }
+        }
+        spans
+    }
+
+    /// Obtain the horizontal spans of pixels in the shape, as per [RasterShape::spans], within
+    /// the inclusive bounds of `clip`. Shapes that can find their spans within the bounds
+    /// directly should override this, such that drawing a large shape that mostly lies
+    /// outside of a [crate::PixelMap] does not examine the pixels outside of it. The default
+    /// implementation clips the spans produced by [RasterShape::spans].
+    #[must_use]
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        self.spans()
+            .into_iter()
+            .filter_map(|span| {
+                let y = span.start().y;
+                let min_x = span.start().x.max(clip.min.x);
+                let max_x = span.end().x.min(clip.max.x);
+                (clip.min.y <= y && y <= clip.max.y && min_x <= max_x)
+                    .then(|| iline((min_x, y), (max_x, y)))
+            })
+            .collect()
+    }
+}
+
+impl RasterShape for IRect {
+    type PixelIterator = SpanPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        *self
+    }
+
+    #[inline]
+    fn inner_rects(&self) -> Vec<IRect> {
+        vec![*self]
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        IRect::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        SpanPixelIterator::new(RasterShape::spans(self))
+    }
+
+    fn spans(&self) -> Vec<ILine> {
+        if self.min.x > self.max.x || self.min.y > self.max.y {
+            return Vec::new();
+        }
+        (self.min.y..=self.max.y)
+            .map(|y| iline(ivec2(self.min.x, y), ivec2(self.max.x, y)))
+            .collect()
+    }
+
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        RasterShape::spans(&IRect {
+            min: self.min.max(clip.min),
+            max: self.max.min(clip.max),
+        })
+    }
+}
+
+impl RasterShape for ICircle {
+    type PixelIterator = ICirclePixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        ICircle::aabb(self)
+    }
+
+    #[inline]
+    fn inner_rects(&self) -> Vec<IRect> {
+        vec![self.inner_rect()]
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        ICircle::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        ICircle::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        ICircle::spans(self)
+    }
+
+    #[inline]
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        ICircle::spans_in(self, clip)
+    }
+}
+
+impl RasterShape for RotatedIRect {
+    type PixelIterator = LineStripPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        RotatedIRect::aabb(self)
+    }
+
+    #[inline]
+    fn inner_rects(&self) -> Vec<IRect> {
+        vec![self.inner_rect()]
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        RotatedIRect::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        RotatedIRect::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        RotatedIRect::spans(self)
+    }
+}
+
+impl RasterShape for ICapsule {
+    type PixelIterator = SpanPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        ICapsule::aabb(self)
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        ICapsule::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        ICapsule::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        ICapsule::spans(self)
+    }
+
+    #[inline]
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        ICapsule::spans_in(self, clip)
+    }
+}
+
+impl RasterShape for IRoundedRect {
+    type PixelIterator = SpanPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        IRoundedRect::aabb(self)
+    }
+
+    #[inline]
+    fn inner_rects(&self) -> Vec<IRect> {
+        let rect = self.rect();
+        let inner = self.corner_centers_rect();
+        vec![
+            IRect::from_corners(
+                ivec2(rect.min.x, inner.min.y),
+                ivec2(rect.max.x, inner.max.y),
+            ),
+            IRect::from_corners(
+                ivec2(inner.min.x, rect.min.y),
+                ivec2(inner.max.x, rect.max.y),
+            ),
+        ]
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        IRoundedRect::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        IRoundedRect::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        IRoundedRect::spans(self)
+    }
+
+    #[inline]
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        IRoundedRect::spans_in(self, clip)
+    }
+}
+
+impl RasterShape for ITriangle {
+    type PixelIterator = SpanPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        ITriangle::aabb(self)
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        ITriangle::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        ITriangle::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        ITriangle::spans(self)
+    }
+
+    #[inline]
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        ITriangle::spans_in(self, clip)
+    }
+}
+
+impl RasterShape for IRing {
+    type PixelIterator = SpanPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        IRing::aabb(self)
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        IRing::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        IRing::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        IRing::spans(self)
+    }
+}
+
+impl RasterShape for ISector {
+    type PixelIterator = SpanPixelIterator;
+
+    #[inline]
+    fn aabb(&self) -> IRect {
+        ISector::aabb(self)
+    }
+
+    #[inline]
+    fn contains(&self, point: IVec2) -> bool {
+        ISector::contains(self, point)
+    }
+
+    #[inline]
+    fn pixels(&self) -> Self::PixelIterator {
+        ISector::pixels(self)
+    }
+
+    #[inline]
+    fn spans(&self) -> Vec<ILine> {
+        ISector::spans(self)
+    }
+
+    #[inline]
+    fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        ISector::spans_in(self, clip)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Every pixel of the inner rects, spans and pixel iterator must be contained by the shape.
+    fn assert_consistent<S: RasterShape>(shape: &S) {
+        for rect in shape.inner_rects() {
+            for p in RasterShape::pixels(&rect) {
+                assert!(shape.contains(p), "inner rect point: {}", p);
+            }
+        }
+        let mut from_spans: Vec<IVec2> = Vec::new();
+        for span in shape.spans() {
+            assert_eq!(span.start().y, span.end().y);
+            for x in span.start().x..=span.end().x {
+                from_spans.push(ivec2(x, span.start().y));
+            }
+        }
+        let mut pixels: Vec<IVec2> = shape.pixels().collect();
+        pixels.sort_unstable_by_key(|p| (p.y, p.x));
+        pixels.dedup();
+        assert_eq!(from_spans, pixels);
+
+        // Spans within a clip are those of the pixels within it
+        let aabb = shape.aabb();
+        let clip = IRect::from_corners(aabb.center(), aabb.max + IVec2::splat(3));
+        let mut from_clipped_spans: Vec<IVec2> = Vec::new();
+        for span in shape.spans_in(&clip) {
+            for x in span.start().x..=span.end().x {
+                from_clipped_spans.push(ivec2(x, span.start().y));
+            }
+        }
+        pixels.retain(|p| clip.contains(*p));
+        assert_eq!(from_clipped_spans, pixels);
+    }
+
+    #[test]
+    fn test_shapes_consistent() {
+        assert_consistent(&IRect::new(-2, 1, 5, 4));
+        assert_consistent(&ICircle::new((3, 3), 7));
+        assert_consistent(&ICapsule::new(iline((0, 0), (9, 4)), 3));
+        assert_consistent(&IRoundedRect::new(IRect::new(0, 0, 12, 9), 3));
+        assert_consistent(&ITriangle::new((0, 0), (9, 4), (2, 11)));
+        assert_consistent(&RotatedIRect::new(IRect::new(0, 0, 12, 6), 0.3));
+        assert_consistent(&IRing::new((1, -2), 3, 7));
+        assert_consistent(&ISector::new((1, -2), 7, 0.5, 4.0));
+    }
+
+    #[test]
+    fn test_rotated_rect_contains_pixels() {
+        for rotation in [0.0, 0.3, 0.5, 0.785, 1.2, 1.9, 2.6, -0.7] {
+            for rect in [IRect::new(0, 0, 12, 6), IRect::new(-3, 2, 4, 17)] {
+                let rrect = RotatedIRect::new(rect, rotation);
+                let pixels: Vec<IVec2> = RasterShape::pixels(&rrect).collect();
+                for p in RasterShape::pixels(&RasterShape::aabb(&rrect).inflate(1)) {
+                    assert_eq!(
+                        RasterShape::contains(&rrect, p),
+                        pixels.contains(&p),
+                        "rotation {rotation}, point {p}"
+                    );
+                }
+                assert_consistent(&rrect);
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_spans() {
+        struct Plus;
+        impl RasterShape for Plus {
+            type PixelIterator = std::vec::IntoIter<IVec2>;
+            fn aabb(&self) -> IRect {
+                IRect::new(-1, -1, 1, 1)
+            }
+            fn contains(&self, point: IVec2) -> bool {
+                point.x == 0 || point.y == 0
+            }
+            fn pixels(&self) -> Self::PixelIterator {
+                vec![
+                    ivec2(0, 1),
+                    ivec2(-1, 0),
+                    ivec2(0, 0),
+                    ivec2(1, 0),
+                    ivec2(0, -1),
+                ]
+                .into_iter()
+            }
+        }
+        assert_eq!(
+            Plus.spans(),
+            vec![
+                iline((0, -1), (0, -1)),
+                iline((-1, 0), (1, 0)),
+                iline((0, 1), (0, 1)),
+            ]
+        );
+    }
+}
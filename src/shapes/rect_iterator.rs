@@ -1,4 +1,4 @@
-use bevy_math::{uvec2, URect, UVec2};
+use bevy_math::{ivec2, uvec2, IRect, IVec2, URect, UVec2};
 #[cfg(feature = "serialize")]
 use serde::{Deserialize, Serialize};
 
@@ -14,6 +14,14 @@ impl URectPixelIterator {
     #[inline]
     #[must_use]
     pub fn new(rect: URect) -> Self {
+        if rect.is_empty() {
+            // Begin past the end
+            return Self {
+                rect,
+                x: rect.max.x,
+                y: rect.max.y,
+            };
+        }
         let x = rect.min.x;
         let y = rect.min.y;
         Self { rect, x, y }
@@ -29,7 +37,7 @@ impl Iterator for URectPixelIterator {
             let x = self.x;
             self.x += 1;
             Some(uvec2(x, self.y))
-        } else if self.y < self.rect.max.y - 1 {
+        } else if self.y + 1 < self.rect.max.y {
             self.x = self.rect.min.x;
             self.y += 1;
             self.next()
@@ -39,6 +47,75 @@ impl Iterator for URectPixelIterator {
     }
 }
 
+/// Iterate the pixel coordinates on the perimeter of an [IRect], for which the `max` point is
+/// inclusive, each exactly once. Pixels are yielded counter-clockwise from the `min` point,
+/// along the bottom, right, top and left edges in turn, as per [crate::irect_edges].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone)]
+pub struct PerimeterPixelIterator {
+    rect: IRect,
+    index: u64,
+    count: u64,
+}
+
+impl PerimeterPixelIterator {
+    /// Create an iterator over the perimeter of the given inclusive rectangle.
+    /// See [crate::InclusiveRect].
+    #[inline]
+    #[must_use]
+    pub fn new(rect: IRect) -> Self {
+        let size = (rect.max - rect.min).as_i64vec2() + 1;
+        let count = if size.x <= 1 || size.y <= 1 {
+            size.x.max(0) * size.y.max(0)
+        } else {
+            2 * (size.x + size.y) - 4
+        };
+        Self {
+            rect,
+            index: 0,
+            count: count as u64,
+        }
+    }
+}
+
+impl Iterator for PerimeterPixelIterator {
+    type Item = IVec2;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let (min, max) = (self.rect.min, self.rect.max);
+        let width = (max.x - min.x) as u64 + 1;
+        let height = (max.y - min.y) as u64 + 1;
+        let mut i = self.index;
+        self.index += 1;
+
+        if i < width {
+            return Some(ivec2(min.x + i as i32, min.y));
+        }
+        i -= width;
+        if i < height - 1 {
+            return Some(ivec2(max.x, min.y + 1 + i as i32));
+        }
+        i -= height - 1;
+        if i < width - 1 {
+            return Some(ivec2(max.x - 1 - i as i32, max.y));
+        }
+        i -= width - 1;
+        Some(ivec2(min.x, max.y - 1 - i as i32))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.count - self.index) as usize;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for PerimeterPixelIterator {}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -53,4 +130,44 @@ mod test {
         assert_eq!(iter.next(), Some((2, 2).into()));
         assert_eq!(iter.next(), None);
     }
+
+    #[test]
+    fn test_pixel_iterator_empty() {
+        assert_eq!(URectPixelIterator::new(URect::new(1, 1, 3, 1)).count(), 0);
+        assert_eq!(URectPixelIterator::new(URect::default()).count(), 0);
+    }
+
+    #[test]
+    fn test_perimeter_pixel_iterator() {
+        let pixels: Vec<_> = PerimeterPixelIterator::new(IRect::new(-1, 0, 1, 2)).collect();
+        assert_eq!(
+            pixels,
+            vec![
+                ivec2(-1, 0),
+                ivec2(0, 0),
+                ivec2(1, 0),
+                ivec2(1, 1),
+                ivec2(1, 2),
+                ivec2(0, 2),
+                ivec2(-1, 2),
+                ivec2(-1, 1),
+            ]
+        );
+
+        // Degenerate rectangles
+        let iter = PerimeterPixelIterator::new(IRect::new(2, 2, 2, 4));
+        assert_eq!(iter.len(), 3);
+        assert_eq!(
+            iter.collect::<Vec<_>>(),
+            vec![ivec2(2, 2), ivec2(2, 3), ivec2(2, 4)]
+        );
+        assert_eq!(
+            PerimeterPixelIterator::new(IRect::new(2, 2, 4, 2)).count(),
+            3
+        );
+        assert_eq!(
+            PerimeterPixelIterator::new(IRect::new(2, 2, 2, 2)).count(),
+            1
+        );
+    }
 }
@@ -0,0 +1,153 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::circle::isqrt;
+use crate::{iline, ILine, SpanPixelIterator, UnsignedPixelIterator};
+use bevy_math::{IRect, IVec2};
+
+/// A ring, or annulus, represented by a center point, in integer coordinates, and an inner
+/// and outer radius. In other words, the set of points within `outer_radius` of the center,
+/// but not within `inner_radius` of it.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct IRing {
+    point: IVec2,
+    inner_radius: u32,
+    outer_radius: u32,
+}
+
+impl IRing {
+    /// Creates a new ring with the given center point and radii. The ring is empty when
+    /// `inner_radius` is not less than `outer_radius`.
+    #[inline]
+    #[must_use]
+    pub fn new<P>(point: P, inner_radius: u32, outer_radius: u32) -> Self
+    where
+        P: Into<IVec2>,
+    {
+        Self {
+            point: point.into(),
+            inner_radius,
+            outer_radius,
+        }
+    }
+
+    /// Get the center point.
+    #[inline]
+    #[must_use]
+    pub fn point(&self) -> IVec2 {
+        self.point
+    }
+
+    /// Get the inner radius, within which points are not contained.
+    #[inline]
+    #[must_use]
+    pub fn inner_radius(&self) -> u32 {
+        self.inner_radius
+    }
+
+    /// Get the outer radius.
+    #[inline]
+    #[must_use]
+    pub fn outer_radius(&self) -> u32 {
+        self.outer_radius
+    }
+
+    /// Determine if the ring contains the given point.
+    #[inline]
+    #[must_use]
+    pub fn contains<P>(&self, point: P) -> bool
+    where
+        P: Into<IVec2>,
+    {
+        let d2 = (point.into() - self.point).as_i64vec2().length_squared();
+        let ri = self.inner_radius as i64;
+        let ro = self.outer_radius as i64;
+        d2 > ri * ri && d2 <= ro * ro
+    }
+
+    /// Get the axis-aligned bounding box of the ring.
+    #[inline]
+    #[must_use]
+    pub fn aabb(&self) -> IRect {
+        let size = self.outer_radius * 2;
+        IRect::from_center_size(self.point, IVec2::splat(size as i32))
+    }
+
+    /// Obtain the horizontal spans of pixels in the ring, bottom to top, and left to right
+    /// within a row. Rows passing through the inner circle have a span on either side of it.
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
+        let ro = self.outer_radius as i64;
+        let ri = self.inner_radius as i64;
+        let mut spans = Vec::with_capacity(self.outer_radius as usize * 4 + 2);
+        if ri >= ro {
+            return spans;
+        }
+        let (x, y) = (self.point.x, self.point.y);
+        for dy in -ro..=ro {
+            let outer = isqrt(ro * ro - dy * dy) as i32;
+            let row = y + dy as i32;
+            if dy.abs() <= ri {
+                let inner = isqrt(ri * ri - dy * dy) as i32;
+                if inner < outer {
+                    spans.push(iline((x - outer, row), (x - inner - 1, row)));
+                    spans.push(iline((x + inner + 1, row), (x + outer, row)));
+                }
+            } else {
+                spans.push(iline((x - outer, row), (x + outer, row)));
+            }
+        }
+        spans
+    }
+
+    /// Iterator over pixels in the ring.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> SpanPixelIterator {
+        SpanPixelIterator::new(self.spans())
+    }
+
+    /// Iterator over pixels in the ring, filtered to only positive points.
+    #[inline]
+    #[must_use]
+    pub fn unsigned_pixels(&self) -> UnsignedPixelIterator<SpanPixelIterator> {
+        UnsignedPixelIterator::<SpanPixelIterator>::new(self.pixels())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_math::ivec2;
+
+    #[test]
+    fn test_contains() {
+        let ring = IRing::new((2, 3), 2, 4);
+        assert!(!ring.contains((2, 3)));
+        assert!(!ring.contains((4, 3)));
+        assert!(ring.contains((5, 3)));
+        assert!(ring.contains((6, 3)));
+        assert!(!ring.contains((7, 3)));
+        assert!(!IRing::new((0, 0), 4, 4).contains((4, 0)));
+    }
+
+    #[test]
+    fn test_pixels() {
+        for inner in 0..=8 {
+            let ring = IRing::new((-3, 5), inner, 8);
+            let pixels: Vec<IVec2> = ring.pixels().collect();
+            let aabb = ring.aabb();
+            let mut expected = Vec::new();
+            for y in aabb.min.y..=aabb.max.y {
+                for x in aabb.min.x..=aabb.max.x {
+                    if ring.contains((x, y)) {
+                        expected.push(ivec2(x, y));
+                    }
+                }
+            }
+            assert_eq!(pixels, expected);
+        }
+        assert_eq!(IRing::new((0, 0), 5, 3).pixels().count(), 0);
+    }
+}
@@ -143,9 +143,33 @@ impl RotatedIRect {
         UnsignedPixelIterator::<LineStripPixelIterator>::new(self.edge_pixels())
     }
 
-    /// Iterator over all pixel coordinates, inclusive, within the rotated rectangle.
+    /// Determine if the rotated rectangle contains the given point. The point is contained
+    /// when it lies within the span of its row, as per [RotatedIRect::spans], such that
+    /// exactly the pixels of [RotatedIRect::pixels] are contained. This examines the pixels
+    /// of the edges, so is proportional to the perimeter of the rectangle.
+    #[inline]
     #[must_use]
-    pub fn pixels(&self) -> LineStripPixelIterator {
+    pub fn contains<P>(&self, point: P) -> bool
+    where
+        P: Into<IVec2>,
+    {
+        let point = point.into();
+        if !self.aabb().contains(point) {
+            return false;
+        }
+        let (min_x, max_x) = self
+            .edge_pixels()
+            .filter(|p| p.y == point.y)
+            .fold((i32::MAX, i32::MIN), |(min_x, max_x), p| {
+                (min_x.min(p.x), max_x.max(p.x))
+            });
+        (min_x..=max_x).contains(&point.x)
+    }
+
+    /// Obtain the horizontal spans of pixels, inclusive, within the rotated rectangle,
+    /// bottom to top.
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
         // Get all edge pixel coordinates
         let mut edge_pixels: Vec<IVec2> = self.edge_pixels().collect();
 
@@ -197,7 +221,14 @@ impl RotatedIRect {
             Ordering::Less => {}
         }
 
-        LineStripPixelIterator::from_lines(&rows)
+        rows
+    }
+
+    /// Iterator over all pixel coordinates, inclusive, within the rotated rectangle.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> LineStripPixelIterator {
+        LineStripPixelIterator::from_lines(&self.spans())
     }
 
     /// Iterator over all positive pixel coordinates, inclusive, within the rotated rectangle.
@@ -247,6 +278,16 @@ mod tests {
         assert_eq!(points[3], vec2(10.0, 10.0));
     }
 
+    #[test]
+    fn test_contains() {
+        let rotated_rect = RotatedIRect::new(IRect::new(0, 0, 10, 4), std::f32::consts::PI / 2.0);
+        assert!(rotated_rect.contains((5, 2)));
+        assert!(rotated_rect.contains((5, -3)));
+        assert!(rotated_rect.contains((3, 7)));
+        assert!(!rotated_rect.contains((1, 2)));
+        assert!(!rotated_rect.contains((5, 8)));
+    }
+
     #[test]
     fn test_pixels() {
         let rect = IRect::new(0, 0, 4, 4);
@@ -0,0 +1,164 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::circle::isqrt;
+use super::span_iterator::convex_spans;
+use crate::{ILine, SpanPixelIterator, UnsignedPixelIterator};
+use bevy_math::{IRect, IVec2};
+
+/// An axis-aligned rectangle, in integer coordinates, having corners rounded by a radius.
+/// The radius is limited to half of the rectangle's shortest side.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct IRoundedRect {
+    rect: IRect,
+    radius: u32,
+}
+
+impl IRoundedRect {
+    /// Creates a new rounded rectangle with the given rectangle and corner radius.
+    #[inline]
+    #[must_use]
+    pub fn new(rect: IRect, radius: u32) -> Self {
+        let max_radius = (rect.width().min(rect.height()) / 2).max(0) as u32;
+        Self {
+            rect,
+            radius: radius.min(max_radius),
+        }
+    }
+
+    /// Get the rectangle.
+    #[inline]
+    #[must_use]
+    pub fn rect(&self) -> IRect {
+        self.rect
+    }
+
+    /// Get the corner radius.
+    #[inline]
+    #[must_use]
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Get the rectangle formed by the centers of the corner arcs.
+    #[inline]
+    #[must_use]
+    pub fn corner_centers_rect(&self) -> IRect {
+        let r = IVec2::splat(self.radius as i32);
+        IRect::from_corners(self.rect.min + r, self.rect.max - r)
+    }
+
+    /// Determine if the rounded rectangle contains the given point.
+    #[inline]
+    #[must_use]
+    pub fn contains<P>(&self, point: P) -> bool
+    where
+        P: Into<IVec2>,
+    {
+        let point = point.into();
+        if !self.rect.contains(point) {
+            return false;
+        }
+        let inner = self.corner_centers_rect();
+        let nearest = point.clamp(inner.min, inner.max);
+        let d = (point - nearest).as_i64vec2();
+        d.length_squared() <= self.radius as i64 * self.radius as i64
+    }
+
+    /// Get the axis-aligned bounding box of the rounded rectangle.
+    #[inline]
+    #[must_use]
+    pub fn aabb(&self) -> IRect {
+        self.rect
+    }
+
+    /// Obtain the horizontal spans of pixels in the rounded rectangle, bottom to top.
+    #[inline]
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
+        self.spans_in(&self.aabb())
+    }
+
+    /// Obtain the horizontal spans of pixels in the rounded rectangle, bottom to top, within
+    /// the inclusive bounds of `clip`.
+    #[must_use]
+    pub fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        let inner = self.corner_centers_rect();
+        let r = self.radius as i64;
+        convex_spans(&self.rect, clip, |y| {
+            // Distance from the row to the nearest corner arc center
+            let dy = y - y.clamp(inner.min.y as i64, inner.max.y as i64);
+            let h = isqrt(r * r - dy * dy);
+            (dy.abs() <= r).then_some((
+                (inner.min.x as i64 - h).max(self.rect.min.x as i64),
+                (inner.max.x as i64 + h).min(self.rect.max.x as i64),
+            ))
+        })
+    }
+
+    /// Iterator over pixels in the rounded rectangle.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> SpanPixelIterator {
+        SpanPixelIterator::new(self.spans())
+    }
+
+    /// Iterator over pixels in the rounded rectangle, filtered to only positive points.
+    #[inline]
+    #[must_use]
+    pub fn unsigned_pixels(&self) -> UnsignedPixelIterator<SpanPixelIterator> {
+        UnsignedPixelIterator::<SpanPixelIterator>::new(self.pixels())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_radius_limit() {
+        let rrect = IRoundedRect::new(IRect::new(0, 0, 10, 4), 5);
+        assert_eq!(rrect.radius(), 2);
+    }
+
+    #[test]
+    fn test_contains() {
+        let rrect = IRoundedRect::new(IRect::new(0, 0, 10, 10), 3);
+        assert!(rrect.contains((5, 5)));
+        assert!(rrect.contains((0, 5)));
+        assert!(rrect.contains((10, 5)));
+        assert!(rrect.contains((5, 10)));
+        assert!(!rrect.contains((0, 0)));
+        assert!(!rrect.contains((10, 10)));
+        assert!(rrect.contains((1, 1)));
+        assert!(!rrect.contains((11, 5)));
+    }
+
+    #[test]
+    fn test_zero_radius_is_rect() {
+        let rrect = IRoundedRect::new(IRect::new(0, 0, 3, 2), 0);
+        assert_eq!(rrect.pixels().count(), 4 * 3);
+    }
+
+    #[test]
+    fn test_spans_in() {
+        let clip = IRect::new(-4, 3, 9, 30);
+        for rrect in [
+            IRoundedRect::new(IRect::new(0, 0, 12, 9), 3),
+            IRoundedRect::new(IRect::new(-20000, -5, 20000, 40), 7),
+            IRoundedRect::new(IRect::new(5, 5, 5, 5), 2),
+        ] {
+            let mut expected = Vec::new();
+            for y in clip.min.y..=clip.max.y {
+                for x in clip.min.x..=clip.max.x {
+                    if rrect.contains((x, y)) {
+                        expected.push(IVec2::new(x, y));
+                    }
+                }
+            }
+            let pixels: Vec<IVec2> = SpanPixelIterator::new(rrect.spans_in(&clip)).collect();
+            assert_eq!(pixels, expected, "{:?}", rrect);
+        }
+    }
+}
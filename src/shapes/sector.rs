@@ -0,0 +1,298 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::circle::isqrt;
+use crate::{iline, ILine, SpanPixelIterator, UnsignedPixelIterator};
+use bevy_math::{ivec2, DVec2, IRect, IVec2};
+use std::f32::consts::{PI, TAU};
+
+/// A circular sector, such as a cone of vision, represented by a center point, in integer
+/// coordinates, a radius, and the range of angles that it spans. Angles are in radians,
+/// counter-clockwise from the positive `x` axis.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ISector {
+    point: IVec2,
+    radius: u32,
+    start_angle: f32,
+    sweep: f32,
+}
+
+impl ISector {
+    /// Creates a new sector with the given center point, radius, and range of angles.
+    /// A negative `sweep` spans clockwise from `start_angle`, and a `sweep` of a full turn
+    /// or more yields the whole circle.
+    #[inline]
+    #[must_use]
+    pub fn new<P>(point: P, radius: u32, start_angle: f32, sweep: f32) -> Self
+    where
+        P: Into<IVec2>,
+    {
+        let (start_angle, sweep) = if sweep < 0.0 {
+            (start_angle + sweep, -sweep)
+        } else {
+            (start_angle, sweep)
+        };
+        Self {
+            point: point.into(),
+            radius,
+            start_angle: start_angle.rem_euclid(TAU),
+            sweep: sweep.min(TAU),
+        }
+    }
+
+    /// Get the center point.
+    #[inline]
+    #[must_use]
+    pub fn point(&self) -> IVec2 {
+        self.point
+    }
+
+    /// Get the radius.
+    #[inline]
+    #[must_use]
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Get the angle at which the sector starts, normalized to `[0, TAU)`, such that the
+    /// sector spans counter-clockwise from it.
+    #[inline]
+    #[must_use]
+    pub fn start_angle(&self) -> f32 {
+        self.start_angle
+    }
+
+    /// Get the angle spanned by the sector, counter-clockwise from
+    /// [ISector::start_angle], no greater than a full turn.
+    #[inline]
+    #[must_use]
+    pub fn sweep(&self) -> f32 {
+        self.sweep
+    }
+
+    /// Determine if the sector contains the given point. The center point is always
+    /// contained, and other points are contained when within the radius, and the direction
+    /// from the center to the point is within the range of angles of the sector.
+    #[inline]
+    #[must_use]
+    pub fn contains<P>(&self, point: P) -> bool
+    where
+        P: Into<IVec2>,
+    {
+        let d = point.into() - self.point;
+        let r = self.radius as i64;
+        if d.as_i64vec2().length_squared() > r * r {
+            return false;
+        }
+        if d == IVec2::ZERO || self.sweep >= TAU {
+            return true;
+        }
+        let angle = (d.y as f32).atan2(d.x as f32);
+        (angle - self.start_angle).rem_euclid(TAU) <= self.sweep
+    }
+
+    /// Get the axis-aligned bounding box of the sector, being that of its circle.
+    #[inline]
+    #[must_use]
+    pub fn aabb(&self) -> IRect {
+        let size = self.radius * 2;
+        IRect::from_center_size(self.point, IVec2::splat(size as i32))
+    }
+
+    /// Obtain the horizontal spans of pixels in the sector, bottom to top, and left to right
+    /// within a row. A sector sweeping more than half a turn may have two spans in a row.
+    #[inline]
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
+        self.spans_in(&self.aabb())
+    }
+
+    /// Obtain the horizontal spans of pixels in the sector, as per [ISector::spans], within the
+    /// inclusive bounds of `clip`, such as to draw only the visible part of a large sector.
+    #[must_use]
+    pub fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        let r = self.radius as i64;
+        let (cx, cy) = (self.point.x as i64, self.point.y as i64);
+        let min_y = (cy - r).max(clip.min.y as i64);
+        let max_y = (cy + r).min(clip.max.y as i64);
+        let mut spans = Vec::with_capacity((max_y - min_y + 1).max(0) as usize);
+
+        let start = DVec2::from_angle(self.start_angle as f64);
+        let end = DVec2::from_angle((self.start_angle + self.sweep) as f64);
+        let mid = DVec2::from_angle((self.start_angle + self.sweep / 2.0) as f64);
+        // A sector sweeping more than half a turn is its circle less a convex wedge, where the
+        // pixels of each row of either wedge are contiguous
+        let reflex = self.sweep > PI;
+        let wedge = if reflex {
+            [end, start, -mid]
+        } else {
+            [start, end, mid]
+        };
+
+        for y in min_y..=max_y {
+            let dy = y - cy;
+            let half_width = isqrt(r * r - dy * dy);
+            let min_x = (cx - half_width).max(clip.min.x as i64);
+            let max_x = (cx + half_width).min(clip.max.x as i64);
+            let mut push = |a: i64, b: i64| {
+                if a <= b {
+                    spans.push(iline((a as i32, y as i32), (b as i32, y as i32)));
+                }
+            };
+            if self.sweep >= TAU {
+                push(min_x, max_x);
+                continue;
+            }
+            let estimate = wedge_row(wedge, cx, dy);
+            let contains = |x: i64| self.contains(ivec2(x as i32, y as i32));
+            if reflex {
+                match refine_row(estimate, min_x, max_x, |x| !contains(x)) {
+                    Some((a, b)) => {
+                        push(min_x, a - 1);
+                        push(b + 1, max_x);
+                    }
+                    None => push(min_x, max_x),
+                }
+            } else if let Some((a, b)) = refine_row(estimate, min_x, max_x, contains) {
+                push(a, b);
+            }
+        }
+        spans
+    }
+
+    /// Iterator over pixels in the sector.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> SpanPixelIterator {
+        SpanPixelIterator::new(self.spans())
+    }
+
+    /// Iterator over pixels in the sector, filtered to only positive points.
+    #[inline]
+    #[must_use]
+    pub fn unsigned_pixels(&self) -> UnsignedPixelIterator<SpanPixelIterator> {
+        UnsignedPixelIterator::<SpanPixelIterator>::new(self.pixels())
+    }
+}
+
+// Estimate the range of `x` in the row `dy` from the center that lies within the convex wedge
+// bounded by the directions `start` and `end`, counter-clockwise, about the direction `mid`,
+// being the intersection of the half-planes of each. The range is widened by a pixel on
+// either side, such that the rounding of [ISector::contains] is accounted for.
+#[must_use]
+fn wedge_row([start, end, mid]: [DVec2; 3], cx: i64, dy: i64) -> (i64, i64) {
+    let dy = dy as f64;
+    let (mut min, mut max) = (f64::NEG_INFINITY, f64::INFINITY);
+    // Each half-plane as `a * x + b >= 0`
+    for (a, b) in [
+        (-start.y, start.x * dy),
+        (end.y, -end.x * dy),
+        (mid.x, mid.y * dy),
+    ] {
+        if a > 0.0 {
+            min = min.max(-b / a);
+        } else if a < 0.0 {
+            max = max.min(-b / a);
+        } else if b < 0.0 {
+            return (1, 0);
+        }
+    }
+    let cx = cx as f64;
+    (
+        (cx + min - 1.0).ceil() as i64,
+        (cx + max + 1.0).floor() as i64,
+    )
+}
+
+// Find the contiguous range of `x` within `min..=max` for which `predicate` holds, starting
+// from an estimate of the range, such that only pixels near its ends are examined.
+#[must_use]
+fn refine_row<F>(estimate: (i64, i64), min: i64, max: i64, predicate: F) -> Option<(i64, i64)>
+where
+    F: Fn(i64) -> bool,
+{
+    let (mut lo, mut hi) = (estimate.0.max(min), estimate.1.min(max));
+    while lo <= hi && !predicate(lo) {
+        lo += 1;
+    }
+    while hi >= lo && !predicate(hi) {
+        hi -= 1;
+    }
+    if lo > hi {
+        return None;
+    }
+    while lo > min && predicate(lo - 1) {
+        lo -= 1;
+    }
+    while hi < max && predicate(hi + 1) {
+        hi += 1;
+    }
+    Some((lo, hi))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ICircle;
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    #[test]
+    fn test_contains() {
+        // The upper-right quadrant
+        let sector = ISector::new((0, 0), 5, 0.0, FRAC_PI_2);
+        assert!(sector.contains((0, 0)));
+        assert!(sector.contains((3, 3)));
+        assert!(sector.contains((5, 0)));
+        assert!(sector.contains((0, 5)));
+        assert!(!sector.contains((-1, 1)));
+        assert!(!sector.contains((1, -1)));
+        assert!(!sector.contains((4, 4)));
+
+        // Clockwise sweep across the positive x axis
+        let sector = ISector::new((0, 0), 5, FRAC_PI_2 / 2.0, -FRAC_PI_2);
+        assert!(sector.contains((3, 1)));
+        assert!(sector.contains((3, -1)));
+        assert!(!sector.contains((-3, 0)));
+        assert!(!sector.contains((0, 3)));
+    }
+
+    #[test]
+    fn test_spans() {
+        // Excluding a quarter turn around the positive y axis splits its rows in two
+        let sector = ISector::new((0, 0), 6, 0.75 * PI, 1.5 * PI);
+        let spans = sector.spans();
+        let row = |y: i32| spans.iter().filter(|s| s.start().y == y).count();
+        assert_eq!(row(3), 2);
+        assert_eq!(row(0), 1);
+        assert_eq!(row(-3), 1);
+
+        let full = ISector::new((2, 1), 6, 1.0, 2.0 * PI);
+        assert_eq!(full.spans(), ICircle::new((2, 1), 6).spans());
+    }
+
+    #[test]
+    fn test_spans_in() {
+        let clip = IRect::new(-10, -6, 25, 30);
+        for sector in [
+            ISector::new((3, 4), 12, 0.3, 1.1),
+            ISector::new((3, 4), 12, 2.0, 4.5),
+            ISector::new((3, 4), 12, FRAC_PI_2, PI),
+            ISector::new((3, 4), 12, 1.0, 0.0),
+            ISector::new((0, 0), 20, 0.0, 0.001),
+            ISector::new((-9, 0), 6000, -0.4, 0.7),
+            ISector::new((40, 20), 6000, 0.5, 5.5),
+        ] {
+            let mut expected = Vec::new();
+            for y in clip.min.y..=clip.max.y {
+                for x in clip.min.x..=clip.max.x {
+                    if sector.contains((x, y)) {
+                        expected.push(ivec2(x, y));
+                    }
+                }
+            }
+            let pixels: Vec<IVec2> = SpanPixelIterator::new(sector.spans_in(&clip)).collect();
+            assert_eq!(pixels, expected, "{:?}", sector);
+        }
+    }
+}
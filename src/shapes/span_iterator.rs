@@ -0,0 +1,149 @@
+use crate::{iline, ILine};
+use bevy_math::{ivec2, IRect, IVec2};
+
+/// Iterate all pixel coordinates of a series of horizontal line spans, in order.
+/// Each span is inclusive of its start and end points.
+pub struct SpanPixelIterator {
+    spans: Vec<ILine>,
+    index: usize,
+    x: i32,
+}
+
+impl SpanPixelIterator {
+    /// Create a new iterator from a list of horizontal spans, where each span's start point
+    /// is the left-most point of the span.
+    #[inline]
+    #[must_use]
+    pub fn new(spans: Vec<ILine>) -> Self {
+        let x = spans.first().map(|s| s.start().x).unwrap_or_default();
+        Self { spans, index: 0, x }
+    }
+}
+
+impl Iterator for SpanPixelIterator {
+    type Item = IVec2;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let span = self.spans.get(self.index)?;
+            if self.x <= span.end().x {
+                let x = self.x;
+                self.x += 1;
+                return Some(ivec2(x, span.start().y));
+            }
+            self.index += 1;
+            if let Some(span) = self.spans.get(self.index) {
+                self.x = span.start().x;
+            }
+        }
+    }
+}
+
+/// Obtain the horizontal spans, bottom to top, of a convex shape within the inclusive bounds
+/// of both `aabb` and `clip`. The `row` closure gives the inclusive range of `x` coordinates
+/// of the shape's pixels in row `y`, if any, which needn't lie within the bounds.
+pub(crate) fn convex_spans<F>(aabb: &IRect, clip: &IRect, row: F) -> Vec<ILine>
+where
+    F: Fn(i64) -> Option<(i64, i64)>,
+{
+    // Not IRect::intersect, which collapses disjoint rectangles onto their boundary
+    let bounds = IRect {
+        min: aabb.min.max(clip.min),
+        max: aabb.max.min(clip.max),
+    };
+    if bounds.min.x > bounds.max.x || bounds.min.y > bounds.max.y {
+        return Vec::new();
+    }
+    let mut spans = Vec::with_capacity(bounds.height() as usize + 1);
+    for y in bounds.min.y..=bounds.max.y {
+        let Some((min_x, max_x)) = row(y as i64) else {
+            continue;
+        };
+        let min_x = min_x.max(bounds.min.x as i64);
+        let max_x = max_x.min(bounds.max.x as i64);
+        if min_x <= max_x {
+            spans.push(iline(ivec2(min_x as i32, y), ivec2(max_x as i32, y)));
+        }
+    }
+    spans
+}
+
+// The inclusive range of integer `x` for which `lo <= a * x + c <= hi`, if any, saturated
+// to the range of `i64`.
+#[must_use]
+pub(crate) fn linear_range(a: i128, c: i128, lo: i128, hi: i128) -> Option<(i64, i64)> {
+    let (min, max) = match a.signum() {
+        0 if lo <= c && c <= hi => (i128::MIN, i128::MAX),
+        0 => return None,
+        1 => (
+            div_ceil(lo.saturating_sub(c), a),
+            div_floor(hi.saturating_sub(c), a),
+        ),
+        _ => (
+            div_ceil(hi.saturating_sub(c), a),
+            div_floor(lo.saturating_sub(c), a),
+        ),
+    };
+    let saturate = |x: i128| x.clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+    (min <= max).then(|| (saturate(min), saturate(max)))
+}
+
+// Integer division, rounded towards negative infinity.
+#[inline]
+#[must_use]
+fn div_floor(n: i128, d: i128) -> i128 {
+    let q = n / d;
+    if n % d != 0 && (n < 0) != (d < 0) {
+        q - 1
+    } else {
+        q
+    }
+}
+
+// Integer division, rounded towards positive infinity.
+#[inline]
+#[must_use]
+fn div_ceil(n: i128, d: i128) -> i128 {
+    -div_floor(-n, d)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_span_pixel_iterator() {
+        let spans = vec![iline((1, 0), (2, 0)), iline((0, 1), (0, 1))];
+        let mut iter = SpanPixelIterator::new(spans);
+        assert_eq!(iter.next(), Some(ivec2(1, 0)));
+        assert_eq!(iter.next(), Some(ivec2(2, 0)));
+        assert_eq!(iter.next(), Some(ivec2(0, 1)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next(), None);
+
+        let mut iter = SpanPixelIterator::new(vec![]);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_linear_range() {
+        // 2x + 1 within [-4, 6]
+        assert_eq!(linear_range(2, 1, -4, 6), Some((-2, 2)));
+        // -3x within [1, 7]
+        assert_eq!(linear_range(-3, 0, 1, 7), Some((-2, -1)));
+        assert_eq!(linear_range(4, 0, 1, 3), None);
+        assert_eq!(linear_range(0, 5, 0, 5), Some((i64::MIN, i64::MAX)));
+        assert_eq!(linear_range(0, 6, 0, 5), None);
+        assert_eq!(linear_range(1, -5, 0, i128::MAX), Some((5, i64::MAX)));
+    }
+
+    #[test]
+    fn test_convex_spans_clip() {
+        let aabb = IRect::new(-10, -10, 10, 10);
+        let clip = IRect::new(0, 8, 100, 100);
+        let spans = convex_spans(&aabb, &clip, |y| (y != 9).then_some((-y, y)));
+        assert_eq!(spans, vec![iline((0, 8), (8, 8)), iline((0, 10), (10, 10))]);
+        assert!(convex_spans(&aabb, &IRect::new(11, 0, 20, 5), |_| Some((0, 20))).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use super::span_iterator::{convex_spans, linear_range};
+use crate::{iline, ILine, SpanPixelIterator, UnsignedPixelIterator};
+use bevy_math::{I64Vec2, IRect, IVec2};
+
+/// A triangle represented by three points, in integer coordinates, of any winding order.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
+pub struct ITriangle {
+    points: [IVec2; 3],
+}
+
+impl ITriangle {
+    /// Creates a new triangle with the given points.
+    #[inline]
+    #[must_use]
+    pub fn new<P>(a: P, b: P, c: P) -> Self
+    where
+        P: Into<IVec2>,
+    {
+        Self {
+            points: [a.into(), b.into(), c.into()],
+        }
+    }
+
+    /// Get the three points of the triangle.
+    #[inline]
+    #[must_use]
+    pub fn points(&self) -> [IVec2; 3] {
+        self.points
+    }
+
+    /// Get the three edges of the triangle.
+    #[inline]
+    #[must_use]
+    pub fn edges(&self) -> [ILine; 3] {
+        let [a, b, c] = self.points;
+        [iline(a, b), iline(b, c), iline(c, a)]
+    }
+
+    /// Get twice the signed area of the triangle. The result is positive when the
+    /// points are in counter-clockwise winding, negative when clockwise, and zero when the
+    /// points are collinear.
+    #[inline]
+    #[must_use]
+    pub fn signed_area2(&self) -> i64 {
+        let [a, b, c] = self.points.map(|p| p.as_i64vec2());
+        edge_function(a, b, c)
+    }
+
+    /// Determine if the triangle contains the given point. Points on the edges of the
+    /// triangle are contained.
+    #[inline]
+    #[must_use]
+    pub fn contains<P>(&self, point: P) -> bool
+    where
+        P: Into<IVec2>,
+    {
+        let point = point.into();
+        if !self.aabb().contains(point) {
+            return false;
+        }
+        let p = point.as_i64vec2();
+        let [a, b, c] = self.points.map(|p| p.as_i64vec2());
+        let w0 = edge_function(b, c, p);
+        let w1 = edge_function(c, a, p);
+        let w2 = edge_function(a, b, p);
+        (w0 >= 0 && w1 >= 0 && w2 >= 0) || (w0 <= 0 && w1 <= 0 && w2 <= 0)
+    }
+
+    /// Get the axis-aligned bounding box of the triangle.
+    #[inline]
+    #[must_use]
+    pub fn aabb(&self) -> IRect {
+        let [a, b, c] = self.points;
+        IRect::from_corners(a.min(b).min(c), a.max(b).max(c))
+    }
+
+    /// Obtain the horizontal spans of pixels in the triangle, bottom to top.
+    #[inline]
+    #[must_use]
+    pub fn spans(&self) -> Vec<ILine> {
+        self.spans_in(&self.aabb())
+    }
+
+    /// Obtain the horizontal spans of pixels in the triangle, bottom to top, within the
+    /// inclusive bounds of `clip`, such as to draw only the visible part of a mostly off-screen
+    /// triangle.
+    #[must_use]
+    pub fn spans_in(&self, clip: &IRect) -> Vec<ILine> {
+        let sign = self.signed_area2().signum() as i128;
+        let [a, b, c] = self.points.map(|p| p.as_i64vec2());
+        let edges = [(b, c), (c, a), (a, b)];
+        convex_spans(&self.aabb(), clip, |y| {
+            // The edge function of each edge is linear in `x` along the row
+            let mut range = (i64::MIN, i64::MAX);
+            for (s, e) in edges {
+                let slope = -(e.y - s.y) as i128;
+                let offset = (e.x - s.x) as i128 * (y - s.y) as i128 - slope * s.x as i128;
+                // A degenerate triangle contains the pixels on the line through its points,
+                // where an edge of non-zero length has an edge function of zero
+                let (min, max) = match sign {
+                    0 if s == e => continue,
+                    0 => linear_range(slope, offset, 0, 0)?,
+                    _ => linear_range(sign * slope, sign * offset, 0, i128::MAX)?,
+                };
+                range = (range.0.max(min), range.1.min(max));
+            }
+            Some(range)
+        })
+    }
+
+    /// Iterator over pixels in the triangle, determined by half-space tests of each edge.
+    #[inline]
+    #[must_use]
+    pub fn pixels(&self) -> SpanPixelIterator {
+        SpanPixelIterator::new(self.spans())
+    }
+
+    /// Iterator over pixels in the triangle, filtered to only positive points.
+    #[inline]
+    #[must_use]
+    pub fn unsigned_pixels(&self) -> UnsignedPixelIterator<SpanPixelIterator> {
+        UnsignedPixelIterator::<SpanPixelIterator>::new(self.pixels())
+    }
+}
+
+// Twice the signed area of the triangle `abc`, which determines the side of the
+// line `ab` that `c` lies on.
+#[inline]
+#[must_use]
+fn edge_function(a: I64Vec2, b: I64Vec2, c: I64Vec2) -> i64 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bevy_math::ivec2;
+
+    #[test]
+    fn test_contains() {
+        let tri = ITriangle::new((0, 0), (10, 0), (0, 10));
+        assert!(tri.contains((0, 0)));
+        assert!(tri.contains((5, 5)));
+        assert!(tri.contains((2, 3)));
+        assert!(!tri.contains((6, 5)));
+        assert!(!tri.contains((-1, 0)));
+
+        // Winding order does not matter
+        let tri = ITriangle::new((0, 0), (0, 10), (10, 0));
+        assert!(tri.contains((5, 5)));
+        assert!(!tri.contains((6, 5)));
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let tri = ITriangle::new((0, 0), (2, 2), (4, 4));
+        assert_eq!(tri.signed_area2(), 0);
+        assert!(tri.contains((3, 3)));
+        assert!(!tri.contains((5, 5)));
+        assert!(!tri.contains((3, 2)));
+    }
+
+    #[test]
+    fn test_pixels() {
+        let tri = ITriangle::new((0, 0), (2, 0), (0, 2));
+        let pixels: Vec<IVec2> = tri.pixels().collect();
+        assert_eq!(
+            pixels,
+            vec![
+                ivec2(0, 0),
+                ivec2(1, 0),
+                ivec2(2, 0),
+                ivec2(0, 1),
+                ivec2(1, 1),
+                ivec2(0, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spans_in() {
+        let clip = IRect::new(-3, -2, 40, 30);
+        for tri in [
+            ITriangle::new((-100000, -90000), (90000, -100000), (5, 200000)),
+            ITriangle::new((0, 0), (9, 4), (2, 11)),
+            ITriangle::new((2, 11), (9, 4), (0, 0)),
+            ITriangle::new((-10, -5), (50, 25), (20, 10)),
+            ITriangle::new((3, 3), (3, 3), (3, 3)),
+            ITriangle::new((60, 0), (70, 20), (65, 5)),
+        ] {
+            let mut expected = Vec::new();
+            for y in clip.min.y..=clip.max.y {
+                for x in clip.min.x..=clip.max.x {
+                    if tri.contains((x, y)) {
+                        expected.push(ivec2(x, y));
+                    }
+                }
+            }
+            let pixels: Vec<IVec2> = SpanPixelIterator::new(tri.spans_in(&clip)).collect();
+            assert_eq!(pixels, expected, "{:?}", tri);
+        }
+    }
+}
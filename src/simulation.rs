@@ -0,0 +1,210 @@
+use crate::{NeighborOrientation, PixelMap};
+use bevy_math::{uvec2, URect, UVec2, Vec2};
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// Numeric kernels that treat a [PixelMap] of `f32` values as a simulated scalar field,
+/// such as temperature or density. Regions of the field that are uniform remain coarse, as
+/// each kernel examines leaf nodes rather than individual pixels wherever possible.
+impl<U: Unsigned + NumCast + Copy + Debug> PixelMap<f32, U> {
+    /// Diffuse the field by one explicit step of the heat equation, such that each pixel moves
+    /// toward the values of its four neighbors. The edges of the map are insulated, so the sum
+    /// of all pixel values is preserved.
+    ///
+    /// Only pixels on the boundaries of leaf nodes can change, as the neighbors of interior
+    /// pixels share their value. So, large uniform leaf nodes are not examined pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `rate`: The diffusion coefficient.
+    /// - `dt`: The time step. The step is stable when `rate * dt` does not exceed `0.25`.
+    pub fn diffuse(&mut self, rate: f32, dt: f32) {
+        let map_rect = self.map_rect();
+        let step = self.pixel_size() as u32;
+        let k = rate * dt;
+
+        // The current value, and the sum of differences to neighbors, of each boundary pixel
+        let mut deltas: FxHashMap<UVec2, (f32, f32)> = FxHashMap::default();
+        let mut flow = |point: UVec2, current: f32, neighbor: f32| {
+            deltas.entry(point).or_insert((current, 0.)).1 += neighbor - current;
+        };
+
+        self.root.visit_neighbor_pairs_face(
+            &map_rect,
+            &mut |or, a, a_rect, b, b_rect| {
+                // Leaf nodes of the root region outside the map are not part of the field
+                if a_rect.is_empty() || b_rect.is_empty() {
+                    return;
+                }
+                let (a_value, b_value) = (*a.value(), *b.value());
+                match or {
+                    NeighborOrientation::Horizontal => {
+                        let min_y = a_rect.min.y.max(b_rect.min.y);
+                        let max_y = a_rect.max.y.min(b_rect.max.y);
+                        for y in (min_y..max_y).step_by(step as usize) {
+                            flow(uvec2(b_rect.min.x, y), b_value, a_value);
+                            flow(uvec2(a_rect.max.x - step, y), a_value, b_value);
+                        }
+                    }
+                    NeighborOrientation::Vertical => {
+                        let min_x = a_rect.min.x.max(b_rect.min.x);
+                        let max_x = a_rect.max.x.min(b_rect.max.x);
+                        for x in (min_x..max_x).step_by(step as usize) {
+                            flow(uvec2(x, b_rect.min.y), b_value, a_value);
+                            flow(uvec2(x, a_rect.max.y - step), a_value, b_value);
+                        }
+                    }
+                }
+            },
+            &mut 0,
+        );
+
+        for (point, (value, delta)) in deltas {
+            if delta != 0. {
+                self.set_pixel(point, value + k * delta);
+            }
+        }
+    }
+
+    /// Advect the field through the given velocity field by one semi-Lagrangian step, such that
+    /// each pixel takes the value found by tracing its center backward along its velocity,
+    /// sampled by [PixelMap::sample_bilinear]. Positions traced beyond the map are clamped
+    /// to its edges.
+    ///
+    /// Where a region of uniform velocity traces back entirely into pixels of a uniform
+    /// value, the region takes that value without being examined pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `velocity_field`: The velocity of each pixel, in pixels per unit of time. It must
+    ///   have the same dimensions as this [PixelMap].
+    /// - `dt`: The time step.
+    ///
+    /// # Panics
+    ///
+    /// If `velocity_field` differs in dimensions from this [PixelMap].
+    pub fn advect(&mut self, velocity_field: &PixelMap<Vec2, U>, dt: f32) {
+        assert_eq!(
+            self.map_rect, velocity_field.map_rect,
+            "velocity_field must have the same dimensions"
+        );
+        let map_rect = self.map_rect();
+        let size = self.map_size().as_vec2();
+        let step = self.pixel_size() as u32;
+        let half = Vec2::splat(step as f32 / 2.);
+
+        let mut writes: Vec<(URect, f32)> = Vec::new();
+        velocity_field.visit(|node, sub_rect| {
+            let offset = *node.value() * dt;
+            let trace = |point: UVec2| (point.as_vec2() + half - offset).clamp(Vec2::ZERO, size);
+
+            // The pixels read by bilinear sampling of all traced positions
+            let min = trace(sub_rect.min);
+            let max = trace(sub_rect.max - step);
+            let source = URect::from_corners(
+                (min - Vec2::splat(0.5)).max(Vec2::ZERO).floor().as_uvec2(),
+                (max + Vec2::splat(0.5)).floor().as_uvec2() + UVec2::ONE,
+            )
+            .intersect(map_rect);
+            if let Some(value) = self.find_node(source.min).map(|n| *n.value()) {
+                if self.all_in_rect(&source, |n, _| *n.value() == value) == Some(true) {
+                    writes.push((*sub_rect, value));
+                    return;
+                }
+            }
+
+            for y in (sub_rect.min.y..sub_rect.max.y).step_by(step as usize) {
+                for x in (sub_rect.min.x..sub_rect.max.x).step_by(step as usize) {
+                    let point = uvec2(x, y);
+                    if let Some(value) = self.sample_bilinear(trace(point)) {
+                        writes.push((URect::from_corners(point, point + step), value));
+                    }
+                }
+            }
+        });
+
+        for (rect, value) in writes {
+            if self.all_in_rect(&rect, |n, _| *n.value() == value) != Some(true) {
+                self.draw_rect(&rect, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2, Vec2};
+
+    #[test]
+    fn test_diffuse() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(8), 0., 1);
+        pm.set_pixel((4, 4), 1.);
+        pm.diffuse(1., 0.25);
+
+        assert_eq!(pm.get_pixel((4, 4)), Some(&0.));
+        for p in [(3, 4), (5, 4), (4, 3), (4, 5)] {
+            assert_eq!(pm.get_pixel(p), Some(&0.25));
+        }
+        assert_eq!(pm.get_pixel((3, 3)), Some(&0.));
+
+        // Insulated edges preserve the total
+        pm.set_pixel((0, 0), 1.);
+        pm.diffuse(1., 0.25);
+        let mut total = 0.;
+        pm.visit(|n, r| total += *n.value() * (r.width() * r.height()) as f32);
+        assert!((total - 2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_diffuse_non_square() {
+        let total = |pm: &PixelMap<f32, u32>| {
+            let mut total = 0.;
+            pm.visit(|n, r| total += *n.value() * (r.width() * r.height()) as f32);
+            total
+        };
+
+        // The edges of the map are insulated from the rest of the root region
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::new(20, 12), 0., 1);
+        pm.set_pixel((19, 5), 1.);
+        pm.set_pixel((7, 11), 1.);
+        pm.diffuse(1., 0.25);
+        assert!((total(&pm) - 2.).abs() < 1e-6);
+
+        // Pixels outside the map retain the initial value
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::new(20, 12), 1., 1);
+        pm.draw_rect(&pm.map_rect(), 0.);
+        pm.diffuse(1., 0.25);
+        assert_eq!(total(&pm), 0.);
+    }
+
+    #[test]
+    fn test_diffuse_uniform() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(8), 3., 1);
+        pm.diffuse(1., 0.25);
+        assert!(pm.empty());
+        assert_eq!(pm.get_pixel((5, 5)), Some(&3.));
+    }
+
+    #[test]
+    fn test_advect() {
+        let mut pm = PixelMap::<f32, u32>::new(&UVec2::splat(16), 0., 1);
+        pm.draw_rect(&URect::new(2, 0, 3, 16), 1.);
+        let velocity = PixelMap::<Vec2, u32>::new(&UVec2::splat(16), Vec2::new(1., 0.), 1);
+        pm.advect(&velocity, 1.);
+
+        let mut expected = PixelMap::<f32, u32>::new(&UVec2::splat(16), 0., 1);
+        expected.draw_rect(&URect::new(3, 0, 4, 16), 1.);
+        for y in 0..16 {
+            for x in 0..16 {
+                assert_eq!(pm.get_pixel((x, y)), expected.get_pixel((x, y)), "{x},{y}");
+            }
+        }
+
+        // Uniform regions remain coarse
+        let mut leaves = 0;
+        pm.visit_in_rect(&URect::new(8, 0, 16, 16), |_, _| leaves += 1);
+        assert_eq!(leaves, 2);
+    }
+}
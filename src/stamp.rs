@@ -0,0 +1,367 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{InclusiveRect, PixelMap};
+use bevy_math::{Affine2, URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// The manner in which a source [PixelMap] is sampled, such as by
+/// [PixelMap::draw_map_transformed].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sampler {
+    /// Take the value of the source pixel containing the sample point.
+    #[default]
+    Nearest,
+}
+
+/// The manner in which a pattern repeats, such as by [PixelMap::fill_tiled].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileMode {
+    /// Repeat the pattern as is.
+    #[default]
+    Repeat,
+
+    /// Repeat the pattern, mirroring every other repetition along each axis, such that
+    /// neighboring repetitions meet seamlessly.
+    MirrorRepeat,
+}
+
+/// The placement of another [PixelMap] of different dimensions or resolution relative to this
+/// one, as per [PixelMap::combine_resampled].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Resample {
+    /// Stretch the other map to cover this map exactly.
+    #[default]
+    Fit,
+
+    /// Scale the other map by the given factor, such that each of its pixels covers `factor`
+    /// pixels of this map, and then translate it by `offset`.
+    Scale { factor: Vec2, offset: Vec2 },
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Combine this [PixelMap] with another having different dimensions, pixel size or value
+    /// type, such as a coarse influence map with a fine terrain map, without first resampling
+    /// the other map into a temporary map as required by [PixelMap::combine]. Only pixels
+    /// overlapped by the resampled map are combined.
+    ///
+    /// # Parameters
+    ///
+    /// - `other`: The [PixelMap] to combine with this one.
+    /// - `resample`: The placement of the other map relative to this one.
+    /// - `sampler`: The manner in which the other map is sampled.
+    /// - `combiner`: A closure that takes the value of a pixel of this map, and the sampled
+    ///   value of the other map, and returns the combined value.
+    ///
+    /// # Returns
+    ///
+    /// If the resampled map overlaps the [PixelMap::map_rect], `true` is returned. Otherwise,
+    /// `false` is returned.
+    pub fn combine_resampled<S, V, F>(
+        &mut self,
+        other: &PixelMap<S, V>,
+        resample: Resample,
+        sampler: Sampler,
+        combiner: F,
+    ) -> bool
+    where
+        S: Copy + PartialEq,
+        V: Unsigned + NumCast + Copy + Debug,
+        F: Fn(&T, &S) -> T,
+    {
+        let transform = match resample {
+            Resample::Fit => {
+                Affine2::from_scale(self.map_size().as_vec2() / other.map_size().as_vec2())
+            }
+            Resample::Scale { factor, offset } => {
+                Affine2::from_scale_angle_translation(factor, 0.0, offset)
+            }
+        };
+        self.draw_map_transformed(other, &transform, sampler, combiner)
+    }
+
+    /// Fill the given rectangle with repetitions of a pattern, being another [PixelMap] of the
+    /// same value type. Repetitions are aligned with the `min` point of the rectangle. Each
+    /// leaf node of the pattern is drawn wholesale, so patterns having large uniform regions
+    /// are not drawn pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle to fill.
+    /// - `pattern`: The [PixelMap] to repeat.
+    /// - `mode`: The manner in which the pattern repeats.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn fill_tiled<V>(&mut self, rect: &URect, pattern: &PixelMap<T, V>, mode: TileMode) -> bool
+    where
+        V: Unsigned + NumCast + Copy + Debug,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return false;
+        }
+
+        let mut leaves: Vec<(URect, T)> = Vec::new();
+        pattern.visit(|node, sub_rect| leaves.push((*sub_rect, *node.value())));
+        if let [(_, value)] = leaves[..] {
+            return self.draw_rect(&rect, value);
+        }
+
+        let size = pattern.map_size();
+        let tiles = (rect.size() + size - 1) / size;
+        for ty in 0..tiles.y {
+            for tx in 0..tiles.x {
+                let origin = rect.min + UVec2::new(tx, ty) * size;
+                let mirror = UVec2::new(tx % 2, ty % 2);
+                for (leaf, value) in &leaves {
+                    let mut min = leaf.min;
+                    let mut max = leaf.max;
+                    if mode == TileMode::MirrorRepeat {
+                        if mirror.x == 1 {
+                            (min.x, max.x) = (size.x - leaf.max.x, size.x - leaf.min.x);
+                        }
+                        if mirror.y == 1 {
+                            (min.y, max.y) = (size.y - leaf.max.y, size.y - leaf.min.y);
+                        }
+                    }
+                    let target = URect::from_corners(origin + min, origin + max).intersect(rect);
+                    if !target.is_empty() {
+                        self.draw_rect(&target, *value);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Draw another [PixelMap] into this one under the given transformation, such as to
+    /// place a rotated or scaled prefab or brush. Sampling is driven by the pixels of this map,
+    /// where the center of each pixel covered by the transformed source is mapped back into the
+    /// source by the inverse transformation, so the result has no holes regardless of rotation
+    /// or scale.
+    ///
+    /// Leaf nodes of this map that fall entirely within a single leaf node of the source are
+    /// drawn wholesale, rather than pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `src`: The [PixelMap] to draw into this one.
+    /// - `transform`: The transformation from source coordinates to the coordinates of this map.
+    /// - `sampler`: The manner in which the source is sampled.
+    /// - `blend`: A closure that takes the current value of a pixel of this map, and the sampled
+    ///   value of the source, and returns the new value of the pixel.
+    ///
+    /// # Returns
+    ///
+    /// If the transformed source overlaps the [PixelMap::map_rect], `true` is returned.
+    /// Otherwise, `false` is returned, including when the transformation is not invertible.
+    pub fn draw_map_transformed<S, V, F>(
+        &mut self,
+        src: &PixelMap<S, V>,
+        transform: &Affine2,
+        sampler: Sampler,
+        mut blend: F,
+    ) -> bool
+    where
+        S: Copy + PartialEq,
+        V: Unsigned + NumCast + Copy + Debug,
+        F: FnMut(&T, &S) -> T,
+    {
+        let inverse = transform.inverse();
+        if !inverse.is_finite() {
+            return false;
+        }
+
+        // The bounds of the transformed source within this map
+        let src_rect = src.map_rect().as_rect();
+        let corners = [
+            src_rect.min,
+            Vec2::new(src_rect.max.x, src_rect.min.y),
+            src_rect.max,
+            Vec2::new(src_rect.min.x, src_rect.max.y),
+        ]
+        .map(|p| transform.transform_point2(p));
+        let min = corners.iter().fold(Vec2::MAX, |m, p| m.min(*p));
+        let max = corners.iter().fold(Vec2::MIN, |m, p| m.max(*p));
+        let bounds = URect::from_corners(
+            min.floor().max(Vec2::ZERO).as_uvec2(),
+            max.ceil().max(Vec2::ZERO).as_uvec2(),
+        )
+        .intersect(self.map_rect());
+        if bounds.is_empty() {
+            return false;
+        }
+
+        let src_bounds = src.map_rect();
+        let sample = |point: Vec2| -> Option<(&S, URect)> {
+            let point = inverse.transform_point2(point);
+            if point.x < 0.0 || point.y < 0.0 || !src_bounds.contains_exclusive(point.as_uvec2()) {
+                return None;
+            }
+            match sampler {
+                Sampler::Nearest => {
+                    let node = src.find_node(point.as_uvec2())?;
+                    Some((node.value(), node.region().as_urect()))
+                }
+            }
+        };
+
+        let step = self.pixel_size as u32;
+        let half = step as f32 / 2.0;
+        let mut updates: Vec<(URect, T)> = Vec::new();
+        self.visit_in_rect(&bounds, |node, sub_rect| {
+            let value = node.value();
+
+            // Wholesale, when the corner pixels sample the same source leaf node
+            let first = sub_rect.min.as_vec2() + half;
+            let last = sub_rect.max.as_vec2() - half;
+            let samples = [
+                first,
+                Vec2::new(last.x, first.y),
+                last,
+                Vec2::new(first.x, last.y),
+            ]
+            .map(|p| sample(p).map(|(_, region)| region));
+            if let Some(region) = samples[0] {
+                if samples.iter().all(|s| *s == Some(region)) {
+                    let (src_value, _) = sample(first).unwrap(); // Cannot fail
+                    let new_value = blend(value, src_value);
+                    if new_value != *value {
+                        updates.push((*sub_rect, new_value));
+                    }
+                    return;
+                }
+            }
+
+            for y in (sub_rect.min.y..sub_rect.max.y).step_by(step as usize) {
+                for x in (sub_rect.min.x..sub_rect.max.x).step_by(step as usize) {
+                    let point = UVec2::new(x, y);
+                    if let Some((src_value, _)) = sample(point.as_vec2() + half) {
+                        let new_value = blend(value, src_value);
+                        if new_value != *value {
+                            updates.push((URect::from_corners(point, point + step), new_value));
+                        }
+                    }
+                }
+            }
+        });
+
+        for (rect, value) in updates {
+            self.draw_rect(&rect, value);
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{Affine2, URect, UVec2, Vec2};
+    use std::f32::consts::FRAC_PI_2;
+
+    fn count(pm: &PixelMap<u8, u32>, value: u8) -> u32 {
+        let mut count = 0;
+        pm.visit(|n, sub_rect| {
+            if *n.value() == value {
+                count += sub_rect.width() * sub_rect.height();
+            }
+        });
+        count
+    }
+
+    #[test]
+    fn test_fill_tiled() {
+        let mut pattern: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(2), 1, 1);
+        pattern.set_pixel((1, 0), 2);
+        let row = |pm: &PixelMap<u8, u32>, y: u32| -> Vec<u8> {
+            (0..8).map(|x| *pm.get_pixel((x, y)).unwrap()).collect()
+        };
+
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 0, 1);
+        assert!(pm.fill_tiled(&URect::new(0, 0, 8, 8), &pattern, TileMode::Repeat));
+        assert_eq!(row(&pm, 0), vec![1, 2, 1, 2, 1, 2, 1, 2]);
+        assert_eq!(row(&pm, 1), vec![1; 8]);
+        assert_eq!(row(&pm, 2), row(&pm, 0));
+
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 0, 1);
+        pm.fill_tiled(&URect::new(1, 0, 7, 8), &pattern, TileMode::MirrorRepeat);
+        assert_eq!(row(&pm, 0), vec![0, 1, 2, 2, 1, 1, 2, 0]);
+        assert_eq!(row(&pm, 2), vec![0, 1, 1, 1, 1, 1, 1, 0]);
+        assert_eq!(row(&pm, 3), row(&pm, 0));
+
+        // A uniform pattern
+        let pattern: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(4), 5, 1);
+        assert!(pm.fill_tiled(&URect::new(0, 0, 20, 20), &pattern, TileMode::Repeat));
+        assert_eq!(pm.stats().leaf_count, 1);
+        assert!(!pm.fill_tiled(&URect::new(8, 8, 20, 20), &pattern, TileMode::Repeat));
+    }
+
+    #[test]
+    fn test_combine_resampled() {
+        let mut coarse: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(4), 0, 1);
+        coarse.set_pixel((1, 2), 5);
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 1, 1);
+
+        assert!(pm.combine_resampled(&coarse, Resample::Fit, Sampler::Nearest, |a, b| a + b));
+        assert_eq!(count(&pm, 6), 16);
+        assert_eq!(pm.get_pixel((4, 8)), Some(&6));
+        assert_eq!(pm.get_pixel((7, 11)), Some(&6));
+        assert_eq!(pm.get_pixel((8, 8)), Some(&1));
+
+        // Scaled and offset, combining only where overlapped
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 1, 1);
+        let resample = Resample::Scale {
+            factor: Vec2::splat(2.0),
+            offset: Vec2::new(8.0, 0.0),
+        };
+        assert!(pm.combine_resampled(&coarse, resample, Sampler::Nearest, |a, b| a + b));
+        assert_eq!(count(&pm, 6), 4);
+        assert_eq!(pm.get_pixel((10, 4)), Some(&6));
+        assert_eq!(count(&pm, 1), 256 - 4);
+
+        let resample = Resample::Scale {
+            factor: Vec2::ONE,
+            offset: Vec2::splat(20.0),
+        };
+        assert!(!pm.combine_resampled(&coarse, resample, Sampler::Nearest, |a, b| a + b));
+    }
+
+    #[test]
+    fn test_draw_map_transformed_rotated() {
+        let mut src: PixelMap<u8, u32> = PixelMap::new(&UVec2::new(4, 2), 1, 1);
+        src.set_pixel((0, 0), 2);
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+
+        let transform = Affine2::from_angle_translation(FRAC_PI_2, Vec2::splat(8.0));
+        assert!(pm.draw_map_transformed(&src, &transform, Sampler::Nearest, |_, s| *s));
+        assert_eq!(count(&pm, 1), 7);
+        assert_eq!(count(&pm, 2), 1);
+        assert_eq!(pm.get_pixel((7, 8)), Some(&2));
+        assert_eq!(pm.get_pixel((6, 11)), Some(&1));
+        assert_eq!(pm.get_pixel((8, 8)), Some(&0));
+
+        // Entirely outside the map
+        let transform = Affine2::from_translation(Vec2::splat(20.0));
+        assert!(!pm.draw_map_transformed(&src, &transform, Sampler::Nearest, |_, s| *s));
+    }
+
+    #[test]
+    fn test_draw_map_transformed_scaled() {
+        let mut src: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(4), 1, 1);
+        src.draw_rect(&URect::new(0, 0, 2, 2), 3);
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(16), 0, 1);
+
+        // Scaled up without holes, and blended with the destination
+        let transform = Affine2::from_scale(Vec2::splat(2.5));
+        pm.draw_map_transformed(&src, &transform, Sampler::Nearest, |d, s| d + s);
+        assert_eq!(count(&pm, 0), 256 - 100);
+        assert_eq!(count(&pm, 3), 25);
+        assert_eq!(count(&pm, 1), 75);
+    }
+}
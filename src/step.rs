@@ -0,0 +1,134 @@
+use crate::PixelMap;
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Apply a per-pixel rule to this [PixelMap], writing the results into `target`, such as
+    /// when stepping a cellular automaton or simulation between two buffers. Only pixels
+    /// whose value in `target` differs from the result are written, so the leaf structure
+    /// of `target` is reused where values are unchanged.
+    ///
+    /// The rule may read the values of pixels up to `radius` pixels away from the pixel being
+    /// evaluated, but must not otherwise depend on the pixel coordinates. Under this constraint,
+    /// the pixels of a leaf node that are farther than `radius` from its boundary all
+    /// produce the same result, so the rule is evaluated once for that interior, and
+    /// per pixel only within a band along the boundary of the leaf node.
+    ///
+    /// # Parameters
+    ///
+    /// - `target`: The [PixelMap] to write results into, having the same dimensions and
+    ///   pixel size as this [PixelMap].
+    /// - `radius`: The distance, in pixels, over which the rule reads neighboring pixels.
+    ///   A radius of zero indicates the rule depends only on the value of the evaluated pixel.
+    /// - `rule`: A closure that takes a reference to this [PixelMap], the coordinates of
+    ///   the evaluated pixel, and a reference to its value. It returns the new value of the pixel.
+    ///
+    /// # Panics
+    ///
+    /// If `target` differs in dimensions or pixel size from this [PixelMap].
+    pub fn step_into<F>(&self, target: &mut PixelMap<T, U>, radius: u32, mut rule: F)
+    where
+        F: FnMut(&Self, UVec2, &T) -> T,
+    {
+        assert!(
+            self.map_rect == target.map_rect && self.pixel_size == target.pixel_size,
+            "target must have the same dimensions and pixel size"
+        );
+        let step = self.pixel_size as u32;
+        let band = radius * step;
+
+        let mut write = |rect: URect, value: T| {
+            if target.all_in_rect(&rect, |n, _| *n.value() == value) != Some(true) {
+                target.draw_rect(&rect, value);
+            }
+        };
+
+        self.visit(|node, sub_rect| {
+            let value = node.value();
+            let inner = URect {
+                min: sub_rect.min + band,
+                max: sub_rect.max.saturating_sub(UVec2::splat(band)),
+            };
+            let inner = (!inner.is_empty()).then_some(inner);
+            if let Some(inner) = inner {
+                write(inner, rule(self, inner.min, value));
+            }
+
+            for y in (sub_rect.min.y..sub_rect.max.y).step_by(step as usize) {
+                let mut eval = |x: u32| {
+                    let point = UVec2::new(x, y);
+                    let result = rule(self, point, value);
+                    write(URect::from_corners(point, point + step), result);
+                };
+                match inner {
+                    Some(inner) if inner.min.y <= y && y < inner.max.y => {
+                        (sub_rect.min.x..inner.min.x)
+                            .step_by(step as usize)
+                            .for_each(&mut eval);
+                        (inner.max.x..sub_rect.max.x)
+                            .step_by(step as usize)
+                            .for_each(&mut eval);
+                    }
+                    _ => (sub_rect.min.x..sub_rect.max.x)
+                        .step_by(step as usize)
+                        .for_each(&mut eval),
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_step_into_dilate() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((8, 8), true);
+        let mut target = PixelMap::<bool, u32>::new(&UVec2::splat(16), true, 1);
+
+        let dilate = |map: &PixelMap<bool, u32>, p: UVec2, value: &bool| {
+            *value
+                || Direction::ALL.iter().any(|d| {
+                    let n = p.as_ivec2() + d.unit();
+                    n.x >= 0 && n.y >= 0 && map.get_pixel(n.as_uvec2()) == Some(&true)
+                })
+        };
+        pm.step_into(&mut target, 1, dilate);
+
+        let mut expected = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        expected.draw_rect(&URect::new(7, 7, 10, 10), true);
+        assert_eq!(target, expected);
+    }
+
+    #[test]
+    fn test_step_into_reuses_target() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect(&URect::new(0, 0, 5, 3), true);
+        let mut target = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+
+        pm.step_into(&mut target, 0, |_, _, v| !*v);
+        let mut expected = pm.clone();
+        expected.draw_rect(&expected.map_rect(), true);
+        expected.draw_rect(&URect::new(0, 0, 5, 3), false);
+        assert_eq!(target, expected);
+
+        // Unchanged results are not written
+        target.clear_dirty(true);
+        let version = target.version();
+        pm.step_into(&mut target, 0, |_, _, v| !*v);
+        assert_eq!(target.version(), version);
+        assert!(!target.dirty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_step_into_mismatched() {
+        let pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        let mut target = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        pm.step_into(&mut target, 0, |_, _, v| *v);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{NodePath, PixelMap};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Create an independent [PixelMap] from the subtree of the node at the given path,
+    /// such as for streaming a chunk of a larger map. The origin of the new map corresponds
+    /// to the origin of the node's region. All nodes of the new map are dirty.
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: The path to the root node of the subtree, as obtained from [PixelMap::get_path]
+    ///   or a [NodePath::parent] thereof.
+    ///
+    /// # Returns
+    ///
+    /// A [PixelMap] whose dimensions are those of the node's region, cropped to the
+    /// [PixelMap::map_rect]. `None` if the path does not lead to a node, or the node
+    /// is outside the [PixelMap::map_rect].
+    #[must_use]
+    pub fn extract(&self, path: NodePath) -> Option<PixelMap<T, U>> {
+        let node = self.root.find_node_by_path(path)?;
+        let rect = node.region().intersect(&self.map_rect);
+        if rect.is_empty() {
+            return None;
+        }
+        let size = rect.size();
+        let mut map = PixelMap::new(
+            &size,
+            *self.root.find_node(rect.min).value(),
+            self.pixel_size,
+        );
+
+        if map.region().size() == node.region().size() {
+            let mut root = node.clone();
+            root.relocate(UVec2::ZERO);
+            map.root = root;
+        } else {
+            // The cropped dimensions require a smaller root region
+            self.visit_in_rect(&rect, |node, sub_rect| {
+                let rect = URect::from_corners(sub_rect.min - rect.min, sub_rect.max - rect.min);
+                map.draw_rect(&rect, *node.value());
+            });
+        }
+        Some(map)
+    }
+
+    /// Replace the subtree of the node at the given path with the contents of another
+    /// [PixelMap], such as one obtained from [PixelMap::extract], or generated in parallel.
+    /// When the root region of `subtree` matches the region of the node, the subtree is
+    /// moved into place without visiting its nodes. All grafted nodes are dirty.
+    ///
+    /// # Parameters
+    ///
+    /// - `path`: The path to the node to replace.
+    /// - `subtree`: The [PixelMap] to graft, having dimensions matching the node's region,
+    ///   cropped to the [PixelMap::map_rect], and the same pixel size as this [PixelMap].
+    ///
+    /// # Returns
+    ///
+    /// If the path does not lead to a node, `false` is returned. Otherwise, `true` is returned.
+    ///
+    /// # Panics
+    ///
+    /// If `subtree` differs in dimensions from the node's cropped region, or in pixel size
+    /// from this [PixelMap].
+    pub fn graft(&mut self, path: NodePath, subtree: PixelMap<T, U>) -> bool {
+        let Some(node) = self.root.find_node_by_path(path) else {
+            return false;
+        };
+        let region = node.region().clone();
+        let rect = region.intersect(&self.map_rect);
+        assert!(
+            subtree.map_size() == rect.size() && subtree.pixel_size == self.pixel_size,
+            "subtree must match the dimensions of the node and the pixel size of the map"
+        );
+
+        self.touch(&rect);
+        if subtree.region().size() == region.size() {
+            let mut root = subtree.root;
+            root.relocate(region.point());
+            self.root.graft(path, 0, root)
+        } else {
+            self.graft_at(rect.min, &subtree)
+        }
+    }
+
+    /// Copy the contents of another [PixelMap] into this one at the given position, such as
+    /// for stamping a prefab. Unlike [PixelMap::graft], the position need not align with a
+    /// node's region. Leaf nodes of `other` are drawn as a whole, and cropped to the
+    /// [PixelMap::map_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `origin`: The position in this [PixelMap] at which to place the origin of `other`.
+    /// - `other`: The [PixelMap] to copy.
+    ///
+    /// # Returns
+    ///
+    /// If any of `other` overlaps the [PixelMap::map_rect], `true` is returned. Otherwise, `false` is returned.
+    pub fn graft_at(&mut self, origin: UVec2, other: &PixelMap<T, U>) -> bool {
+        let mut changed = false;
+        other.visit(|node, sub_rect| {
+            let rect = URect::from_corners(sub_rect.min + origin, sub_rect.max + origin);
+            changed |= self.draw_rect(&rect, *node.value());
+        });
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pixel_map::next_pow2;
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    fn true_pixels(pm: &PixelMap<bool, u32>) -> Vec<UVec2> {
+        pm.matching_points(&pm.map_rect(), |n, _| *n.value())
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((9, 10), true);
+        pm.set_pixel((1, 1), true);
+
+        let path = pm.get_path((9, 10)).unwrap().truncate(3);
+        let chunk = pm.extract(path).unwrap();
+        assert_eq!(chunk.map_size(), UVec2::splat(8));
+        assert_eq!(chunk.region().size(), next_pow2(8));
+        assert_eq!(true_pixels(&chunk), vec![UVec2::new(1, 2)]);
+        assert!(chunk.dirty());
+
+        assert!(pm.extract(NodePath::ROOT).is_none());
+    }
+
+    #[test]
+    fn test_extract_cropped() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::new(12, 12), false, 1);
+        pm.set_pixel((9, 10), true);
+
+        // The top-right quadrant is cropped to 4x4
+        let path = pm.get_path((9, 10)).unwrap().truncate(3);
+        let chunk = pm.extract(path).unwrap();
+        assert_eq!(chunk.map_size(), UVec2::splat(4));
+        assert_eq!(chunk.region().size(), next_pow2(4));
+        assert_eq!(true_pixels(&chunk), vec![UVec2::new(1, 2)]);
+    }
+
+    #[test]
+    fn test_graft() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((9, 10), true);
+        let path = pm.get_path((9, 10)).unwrap().truncate(3);
+
+        let mut chunk = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        chunk.draw_rect(&URect::new(0, 0, 2, 2), true);
+        pm.clear_dirty(true);
+        assert!(pm.graft(path, chunk));
+        assert!(pm.dirty());
+        assert_eq!(
+            true_pixels(&pm),
+            vec![
+                UVec2::new(8, 8),
+                UVec2::new(9, 8),
+                UVec2::new(8, 9),
+                UVec2::new(9, 9)
+            ]
+        );
+
+        // Grafting a uniform subtree merges with uniform siblings
+        let chunk = PixelMap::<bool, u32>::new(&UVec2::splat(8), false, 1);
+        assert!(pm.graft(path, chunk));
+        assert!(pm.empty());
+    }
+
+    #[test]
+    fn test_graft_at() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        let mut prefab = PixelMap::<bool, u32>::new(&UVec2::splat(4), true, 1);
+        prefab.set_pixel((0, 0), false);
+        assert!(pm.graft_at(UVec2::new(14, 3), &prefab));
+        assert_eq!(true_pixels(&pm).len(), 2 * 4 - 1);
+        assert!(!pm.graft_at(UVec2::new(20, 3), &prefab));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_graft_mismatched() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((9, 10), true);
+        let path = pm.get_path((9, 10)).unwrap().truncate(3);
+        pm.graft(path, PixelMap::new(&UVec2::splat(4), false, 1));
+    }
+}
@@ -0,0 +1,296 @@
+use crate::{PNode, PixelMap, Quadrant};
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+use std::ops::ControlFlow;
+
+/// The order in which [PixelMap::visit_in_rect_ordered] visits leaf nodes, determined by the
+/// order of descent into the children of each branch node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraversalOrder {
+    /// Children are visited in order of distance from the given pixel, nearest first, such
+    /// as for front-to-back shadow casting. A leaf node is visited before any farther leaf
+    /// node of a sibling subtree.
+    NearestFirst(UVec2),
+
+    /// Children to the left are visited before those to the right, such that leaf nodes
+    /// sharing any row are visited from left to right.
+    LeftToRight,
+
+    /// Children to the right are visited before those to the left, such that leaf nodes
+    /// sharing any row are visited from right to left.
+    RightToLeft,
+
+    /// Children to the bottom are visited before those to the top, such that leaf nodes
+    /// sharing any column are visited from bottom to top.
+    BottomToTop,
+
+    /// Children to the top are visited before those to the bottom, such that leaf nodes
+    /// sharing any column are visited from top to bottom.
+    TopToBottom,
+}
+
+impl TraversalOrder {
+    /// Obtain the order in which to visit the children of a branch node having the given
+    /// center point.
+    #[must_use]
+    pub fn child_order(&self, center: UVec2) -> [Quadrant; 4] {
+        match *self {
+            TraversalOrder::NearestFirst(point) => {
+                let left = point.x < center.x;
+                let bottom = point.y < center.y;
+                let nearest = match (left, bottom) {
+                    (true, true) => Quadrant::BottomLeft,
+                    (false, true) => Quadrant::BottomRight,
+                    (false, false) => Quadrant::TopRight,
+                    (true, false) => Quadrant::TopLeft,
+                };
+                // Distances of the center of the pixel from the axes through the center,
+                // doubled to remain integral
+                let dx = (2 * point.x as i64 + 1 - 2 * center.x as i64).abs();
+                let dy = (2 * point.y as i64 + 1 - 2 * center.y as i64).abs();
+                let (near, far) = if dx <= dy {
+                    (nearest.mirror_horizontal(), nearest.mirror_vertical())
+                } else {
+                    (nearest.mirror_vertical(), nearest.mirror_horizontal())
+                };
+                [nearest, near, far, nearest.opposite()]
+            }
+            TraversalOrder::LeftToRight => [
+                Quadrant::BottomLeft,
+                Quadrant::TopLeft,
+                Quadrant::BottomRight,
+                Quadrant::TopRight,
+            ],
+            TraversalOrder::RightToLeft => [
+                Quadrant::BottomRight,
+                Quadrant::TopRight,
+                Quadrant::BottomLeft,
+                Quadrant::TopLeft,
+            ],
+            TraversalOrder::BottomToTop => [
+                Quadrant::BottomLeft,
+                Quadrant::BottomRight,
+                Quadrant::TopLeft,
+                Quadrant::TopRight,
+            ],
+            TraversalOrder::TopToBottom => [
+                Quadrant::TopLeft,
+                Quadrant::TopRight,
+                Quadrant::BottomLeft,
+                Quadrant::BottomRight,
+            ],
+        }
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Visit all leaf nodes in this [PixelMap] that overlap with the given rectangle, in the
+    /// given order, such as for painter's order rendering.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `order`: The order in which to descend into the children of each branch node.
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle as parameters. This rectangle represents the intersection of the node's
+    ///   region and the `rect` parameter supplied to this method.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit_in_rect_ordered<F>(
+        &self,
+        rect: &URect,
+        order: TraversalOrder,
+        mut visitor: F,
+    ) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &URect),
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return 0;
+        }
+        let mut traversed = 0u32;
+        let _: ControlFlow<()> = self.root.try_visit_leaves_in_rect_ordered(
+            &rect,
+            &order,
+            &mut |node, sub_rect| {
+                visitor(node, sub_rect);
+                ControlFlow::Continue(())
+            },
+            &mut traversed,
+        );
+        traversed
+    }
+
+    /// Visit all leaf nodes in this [PixelMap], until the visitor breaks, such as to search
+    /// for a single feature without traversing the whole map.
+    ///
+    /// # Parameters
+    ///
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle as parameters. It returns [ControlFlow::Break] to stop the traversal.
+    ///
+    /// # Returns
+    ///
+    /// The value with which the visitor broke, if it did.
+    #[inline]
+    pub fn try_visit<B, F>(&self, visitor: F) -> ControlFlow<B>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> ControlFlow<B>,
+    {
+        self.try_visit_in_rect(&self.map_rect(), visitor)
+    }
+
+    /// Visit all leaf nodes in this [PixelMap] that overlap with the given rectangle, until
+    /// the visitor breaks, such as to search for a single feature without traversing the
+    /// whole rectangle.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to a
+    ///   rectangle as parameters. This rectangle represents the intersection of the node's
+    ///   region and the `rect` parameter supplied to this method. It returns
+    ///   [ControlFlow::Break] to stop the traversal.
+    ///
+    /// # Returns
+    ///
+    /// The value with which the visitor broke, if it did.
+    pub fn try_visit_in_rect<B, F>(&self, rect: &URect, mut visitor: F) -> ControlFlow<B>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> ControlFlow<B>,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        self.root
+            .try_visit_leaves_in_rect(&rect, &mut visitor, &mut 0)
+    }
+
+    /// Visit all leaf nodes in this [PixelMap] that overlap with the given rectangle, in the
+    /// given order, until the visitor breaks, such as to find the nearest node matching some
+    /// criteria with [TraversalOrder::NearestFirst].
+    /// See [PixelMap::visit_in_rect_ordered] and [PixelMap::try_visit_in_rect].
+    ///
+    /// # Returns
+    ///
+    /// The value with which the visitor broke, if it did.
+    pub fn try_visit_in_rect_ordered<B, F>(
+        &self,
+        rect: &URect,
+        order: TraversalOrder,
+        mut visitor: F,
+    ) -> ControlFlow<B>
+    where
+        F: FnMut(&PNode<T, U>, &URect) -> ControlFlow<B>,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        self.root
+            .try_visit_leaves_in_rect_ordered(&rect, &order, &mut visitor, &mut 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn test_visit_in_rect_ordered() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(4), 0, 1);
+        pm.set_pixel((1, 1), 1);
+        pm.set_pixel((3, 3), 2);
+
+        let visit = |order| {
+            let mut rects = Vec::new();
+            pm.visit_in_rect_ordered(&pm.map_rect(), order, |_, r| rects.push(*r));
+            rects
+        };
+
+        // Every leaf is visited once
+        let mut all = Vec::new();
+        pm.visit(|_, r| all.push(*r));
+        assert_eq!(visit(TraversalOrder::LeftToRight).len(), all.len());
+
+        // Sweeps visit leaves sharing a row or column in order
+        let rects = visit(TraversalOrder::LeftToRight);
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                if a.min.y < b.max.y && b.min.y < a.max.y {
+                    assert!(b.min.x >= a.max.x, "{:?} visited after {:?}", b, a);
+                }
+            }
+        }
+        let rects = visit(TraversalOrder::TopToBottom);
+        for (i, a) in rects.iter().enumerate() {
+            for b in &rects[i + 1..] {
+                if a.min.x < b.max.x && b.min.x < a.max.x {
+                    assert!(b.max.y <= a.min.y, "{:?} visited after {:?}", b, a);
+                }
+            }
+        }
+
+        // Nearest first, from the top right
+        let rects = visit(TraversalOrder::NearestFirst(UVec2::new(3, 3)));
+        assert_eq!(rects[0], URect::new(3, 3, 4, 4));
+        assert_eq!(rects.last(), Some(&URect::new(0, 0, 1, 1)));
+
+        // Only within the given rectangle
+        let mut count = 0;
+        pm.visit_in_rect_ordered(
+            &URect::new(0, 0, 2, 2),
+            TraversalOrder::RightToLeft,
+            |_, _| count += 1,
+        );
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn test_try_visit() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::splat(8), 0, 1);
+        pm.set_pixel((1, 6), 1);
+        pm.set_pixel((6, 1), 1);
+
+        // Stops at the first match
+        let mut visited = 0;
+        let found = pm.try_visit(|node, r| {
+            visited += 1;
+            if *node.value() == 1 {
+                ControlFlow::Break(r.min)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert!(matches!(found, ControlFlow::Break(p) if p == UVec2::new(6, 1)));
+        let mut all = 0;
+        pm.visit(|_, _| all += 1);
+        assert!(visited < all);
+
+        // Nearest match first
+        let found = pm.try_visit_in_rect_ordered(
+            &pm.map_rect(),
+            TraversalOrder::NearestFirst(UVec2::new(0, 7)),
+            |node, r| match *node.value() {
+                1 => ControlFlow::Break(r.min),
+                _ => ControlFlow::Continue(()),
+            },
+        );
+        assert_eq!(found, ControlFlow::Break(UVec2::new(1, 6)));
+
+        // No match
+        let found: ControlFlow<()> =
+            pm.try_visit_in_rect(&URect::new(0, 0, 4, 4), |node, _| match *node.value() {
+                1 => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            });
+        assert_eq!(found, ControlFlow::Continue(()));
+    }
+}
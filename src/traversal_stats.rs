@@ -0,0 +1,152 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use std::cell::Cell;
+
+/// Counts of the quadtree nodes traversed while running a closure, as measured by
+/// [measure_traversal], such as to assert upon the cost of queries in performance tests.
+///
+/// Nodes are counted by every traversal of a [crate::PixelMap], including visitation,
+/// queries such as [crate::PixelMap::any_in_rect], neighbor visitation and contouring, ray
+/// casting, and modification. Nodes visited more than once are counted each time.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct TraversalStats {
+    /// The number of nodes visited, including leaf nodes.
+    pub nodes_visited: u64,
+
+    /// The number of leaf nodes visited.
+    pub leaves_visited: u64,
+
+    /// The depth of the deepest node visited, relative to the largest node visited, which is
+    /// the root node of the traversed map.
+    pub max_depth: u16,
+}
+
+// The counters of an active measurement.
+#[derive(Debug, Default, Clone, Copy)]
+struct Counters {
+    nodes: u64,
+    leaves: u64,
+    min_size: u32,
+    max_size: u32,
+}
+
+impl Counters {
+    fn merge(&mut self, other: &Counters) {
+        if other.nodes == 0 {
+            return;
+        }
+        if self.nodes == 0 {
+            *self = *other;
+            return;
+        }
+        self.nodes += other.nodes;
+        self.leaves += other.leaves;
+        self.min_size = self.min_size.min(other.min_size);
+        self.max_size = self.max_size.max(other.max_size);
+    }
+
+    fn stats(&self) -> TraversalStats {
+        TraversalStats {
+            nodes_visited: self.nodes,
+            leaves_visited: self.leaves,
+            max_depth: if self.nodes == 0 {
+                0
+            } else {
+                (self.max_size / self.min_size).trailing_zeros() as u16
+            },
+        }
+    }
+}
+
+thread_local! {
+    static COUNTERS: Cell<Option<Counters>> = const { Cell::new(None) };
+}
+
+// Restores the counters of an enclosing measurement, merging those of the inner measurement,
+// even if the measured closure panics.
+struct Restore(Option<Counters>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let inner = COUNTERS.take().unwrap_or_default();
+        let mut outer = self.0.take();
+        if let Some(outer) = &mut outer {
+            outer.merge(&inner);
+        }
+        COUNTERS.set(outer);
+    }
+}
+
+/// Run the given closure, counting the quadtree nodes traversed by it on the current thread.
+/// Measurements may be nested, in which case the nodes counted by the inner measurement
+/// are also counted by the outer.
+///
+/// Requires the `traversal_stats` feature, without which no counting code is compiled into
+/// traversals.
+///
+/// # Parameters
+///
+/// - `f`: The closure to measure.
+///
+/// # Returns
+///
+/// The result of the closure, and the [TraversalStats] of the nodes it traversed.
+pub fn measure_traversal<R, F>(f: F) -> (R, TraversalStats)
+where
+    F: FnOnce() -> R,
+{
+    let restore = Restore(COUNTERS.replace(Some(Counters::default())));
+    let result = f();
+    let stats = COUNTERS.get().unwrap_or_default().stats();
+    drop(restore);
+    (result, stats)
+}
+
+// Count a visit of a node having a region of the given size, if a measurement is active.
+#[inline]
+pub(crate) fn record(size: u32, leaf: bool) {
+    COUNTERS.with(|counters| {
+        if let Some(mut c) = counters.get() {
+            c.merge(&Counters {
+                nodes: 1,
+                leaves: leaf as u64,
+                min_size: size,
+                max_size: size,
+            });
+            counters.set(Some(c));
+        }
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_measure_traversal() {
+        let mut pm = PixelMap::<bool, u16>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((3, 3), true);
+
+        let (traversed, stats) = measure_traversal(|| pm.visit(|_, _| {}));
+        assert_eq!(stats.nodes_visited, traversed as u64);
+        assert_eq!(stats.leaves_visited, 13);
+        assert_eq!(stats.max_depth, 4);
+
+        let (_, stats) = measure_traversal(|| pm.contour(&pm.map_rect(), |n, _| *n.value()));
+        assert!(stats.nodes_visited > 0);
+
+        // Nested measurements are counted by the outer
+        let (inner, outer) = measure_traversal(|| {
+            pm.draw_rect(&URect::new(8, 8, 16, 16), true);
+            measure_traversal(|| pm.any_in_rect(&pm.map_rect(), |n, _| *n.value())).1
+        });
+        assert!(inner.nodes_visited > 0);
+        assert!(outer.nodes_visited > inner.nodes_visited);
+
+        let (_, stats) = measure_traversal(|| ());
+        assert_eq!(stats, TraversalStats::default());
+    }
+}
@@ -0,0 +1,168 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::PixelMap;
+use bevy_math::{URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A rectangle of pixels to be uploaded to a texture, as planned by [PixelMap::plan_uploads].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UploadRect {
+    /// The rectangle of pixels, in map coordinates, exclusive of its `max` point.
+    pub rect: URect,
+}
+
+impl UploadRect {
+    /// Get the number of pixels covered by this rectangle.
+    #[inline]
+    #[must_use]
+    pub fn area(&self) -> u32 {
+        self.rect.width() * self.rect.height()
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Plan the texture uploads required to reflect the dirty leaf nodes of this [PixelMap],
+    /// such as when updating a region of a texture atlas. Dirty leaf nodes that are adjacent,
+    /// and share a full edge, are merged into larger rectangles, reducing the number of
+    /// uploads. Rectangles exceeding `max_rect_size` are then split. The planned rectangles
+    /// cover exactly the dirty pixels, so no clean pixels are uploaded.
+    /// Dirty status is not changed, see [PixelMap::clear_dirty].
+    ///
+    /// # Parameters
+    ///
+    /// - `max_rect_size`: The maximum width and height of a planned rectangle.
+    ///
+    /// # Returns
+    ///
+    /// The planned rectangles, ordered bottom to top, then left to right.
+    ///
+    /// # Panics
+    ///
+    /// If either component of `max_rect_size` is zero.
+    #[must_use]
+    pub fn plan_uploads(&self, max_rect_size: UVec2) -> Vec<UploadRect> {
+        assert!(
+            max_rect_size.x > 0 && max_rect_size.y > 0,
+            "max_rect_size must be non-zero on each axis"
+        );
+
+        let mut rects: Vec<URect> = Vec::new();
+        self.visit_dirty(|_, sub_rect| rects.push(*sub_rect));
+
+        let rects = merge_rects(rects);
+
+        let mut uploads: Vec<UploadRect> = Vec::with_capacity(rects.len());
+        for rect in rects {
+            for y in (rect.min.y..rect.max.y).step_by(max_rect_size.y as usize) {
+                for x in (rect.min.x..rect.max.x).step_by(max_rect_size.x as usize) {
+                    let min = UVec2::new(x, y);
+                    let max = (min + max_rect_size).min(rect.max);
+                    uploads.push(UploadRect {
+                        rect: URect::from_corners(min, max),
+                    });
+                }
+            }
+        }
+        uploads.sort_unstable_by_key(|u| (u.rect.min.y, u.rect.min.x));
+        uploads
+    }
+}
+
+// Merge non-overlapping rectangles that share a full edge, alternating between rows and
+// columns until no more can be merged.
+pub(crate) fn merge_rects(mut rects: Vec<URect>) -> Vec<URect> {
+    loop {
+        let count = rects.len();
+        rects = merge_rows(rects);
+        rects = merge_columns(rects);
+        if rects.len() == count {
+            return rects;
+        }
+    }
+}
+
+// Merge horizontally adjacent rectangles that span the same rows.
+fn merge_rows(mut rects: Vec<URect>) -> Vec<URect> {
+    rects.sort_unstable_by_key(|r| (r.min.y, r.max.y, r.min.x));
+    let mut merged: Vec<URect> = Vec::with_capacity(rects.len());
+    for rect in rects {
+        match merged.last_mut() {
+            Some(last)
+                if last.min.y == rect.min.y
+                    && last.max.y == rect.max.y
+                    && last.max.x == rect.min.x =>
+            {
+                last.max.x = rect.max.x;
+            }
+            _ => merged.push(rect),
+        }
+    }
+    merged
+}
+
+// Merge vertically adjacent rectangles that span the same columns.
+fn merge_columns(mut rects: Vec<URect>) -> Vec<URect> {
+    rects.sort_unstable_by_key(|r| (r.min.x, r.max.x, r.min.y));
+    let mut merged: Vec<URect> = Vec::with_capacity(rects.len());
+    for rect in rects {
+        match merged.last_mut() {
+            Some(last)
+                if last.min.x == rect.min.x
+                    && last.max.x == rect.max.x
+                    && last.max.y == rect.min.y =>
+            {
+                last.max.y = rect.max.y;
+            }
+            _ => merged.push(rect),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_plan_uploads_split() {
+        // A new map is entirely dirty
+        let pm = PixelMap::<bool, u32>::new(&UVec2::new(16, 12), false, 1);
+        let uploads = pm.plan_uploads(UVec2::splat(8));
+        let rects: Vec<URect> = uploads.iter().map(|u| u.rect).collect();
+        assert_eq!(
+            rects,
+            vec![
+                URect::new(0, 0, 8, 8),
+                URect::new(8, 0, 16, 8),
+                URect::new(0, 8, 8, 12),
+                URect::new(8, 8, 16, 12),
+            ]
+        );
+        assert_eq!(uploads.iter().map(|u| u.area()).sum::<u32>(), 16 * 12);
+    }
+
+    #[test]
+    fn test_plan_uploads_merge() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((15, 15), true);
+        pm.clear_dirty(true);
+        assert!(pm.plan_uploads(UVec2::splat(8)).is_empty());
+
+        // Leaves of differing sizes, forming a single rectangle
+        pm.draw_rect(&URect::new(0, 0, 5, 4), true);
+        pm.draw_rect(&URect::new(8, 8, 10, 10), true);
+        let rects: Vec<URect> = pm
+            .plan_uploads(UVec2::splat(8))
+            .iter()
+            .map(|u| u.rect)
+            .collect();
+        assert_eq!(
+            rects,
+            vec![URect::new(0, 0, 5, 4), URect::new(8, 8, 10, 10)]
+        );
+    }
+}
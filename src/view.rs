@@ -0,0 +1,273 @@
+use crate::{InclusiveRect, PNode, PixelMap};
+use bevy_math::{IRect, IVec2, URect, UVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A read-only view of a [PixelMap] through a coordinate transformation, translating
+/// coordinates in both directions, such as to query a map in the screen space of a library
+/// having a top-left origin, like `image`. View coordinates are signed, and relate to map
+/// coordinates by, in order:
+///
+/// 1. A vertical flip about the height of the map, if enabled, such that the bottom row of
+///    pixels of the map becomes the top row of the view, at `y = 0`.
+/// 2. A scale, such that each pixel of the map covers `scale` pixels of the view on each axis.
+/// 3. An offset, such that the `(0,0)` pixel of the transformed map is at `offset` in the view.
+///
+/// Methods of the wrapped [PixelMap] that aren't provided by [MapView] may be called by way of
+/// [MapView::map], converting coordinates with [MapView::to_map_point] and
+/// [MapView::to_map_rect].
+#[derive(Debug, Clone, Copy)]
+pub struct MapView<'a, T: Copy + PartialEq = bool, U: Unsigned + NumCast + Copy + Debug = u16> {
+    map: &'a PixelMap<T, U>,
+    offset: IVec2,
+    scale: u32,
+    flip_y: bool,
+}
+
+impl<'a, T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> MapView<'a, T, U> {
+    /// Create a [MapView] of the given [PixelMap], having no offset, a scale of `1`, and no
+    /// flip, such that view coordinates equal map coordinates.
+    #[inline]
+    #[must_use]
+    pub fn new(map: &'a PixelMap<T, U>) -> Self {
+        Self {
+            map,
+            offset: IVec2::ZERO,
+            scale: 1,
+            flip_y: false,
+        }
+    }
+
+    /// Set the position, in the view, of the `(0,0)` pixel of the transformed map.
+    #[inline]
+    #[must_use]
+    pub fn with_offset(mut self, offset: IVec2) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the number of view pixels covered by each map pixel, on each axis.
+    ///
+    /// # Panics
+    ///
+    /// If `scale` is zero.
+    #[inline]
+    #[must_use]
+    pub fn with_scale(mut self, scale: u32) -> Self {
+        assert!(scale > 0, "scale must be > 0");
+        self.scale = scale;
+        self
+    }
+
+    /// Flip the map vertically, such that the view has a top-left origin.
+    #[inline]
+    #[must_use]
+    pub fn with_flip_y(mut self, flip_y: bool) -> Self {
+        self.flip_y = flip_y;
+        self
+    }
+
+    /// Obtain the wrapped [PixelMap].
+    #[inline]
+    #[must_use]
+    pub fn map(&self) -> &'a PixelMap<T, U> {
+        self.map
+    }
+
+    /// Obtain the rectangle covered by the map, in view coordinates.
+    #[inline]
+    #[must_use]
+    pub fn view_rect(&self) -> IRect {
+        self.to_view_rect(&self.map.map_rect())
+    }
+
+    /// Convert a point in view coordinates to the map pixel that covers it.
+    ///
+    /// # Returns
+    ///
+    /// If the point is outside the map, `None` is returned. Otherwise, `Some` of the map
+    /// coordinates is returned.
+    #[must_use]
+    pub fn to_map_point(&self, point: IVec2) -> Option<UVec2> {
+        let p = point - self.offset;
+        if p.x < 0 || p.y < 0 {
+            return None;
+        }
+        let mut p = p.as_uvec2() / self.scale;
+        if self.flip_y {
+            p.y = self.map.map_size().y.checked_sub(p.y + 1)?;
+        }
+        self.map.map_rect().contains_exclusive(p).then_some(p)
+    }
+
+    /// Convert a map pixel to the point, in view coordinates, of the corner of its view
+    /// rectangle nearest the view origin.
+    #[inline]
+    #[must_use]
+    pub fn to_view_point(&self, point: UVec2) -> IVec2 {
+        self.to_view_rect(&URect::from_corners(point, point + UVec2::ONE))
+            .min
+    }
+
+    /// Convert a rectangle in view coordinates to the rectangle of map pixels that it
+    /// overlaps, clipped to the [PixelMap::map_rect]. The result may be empty.
+    #[must_use]
+    pub fn to_map_rect(&self, rect: &IRect) -> URect {
+        let scale = self.scale as i32;
+        let min = (rect.min - self.offset).max(IVec2::ZERO);
+        let max = (rect.max - self.offset).max(IVec2::ZERO);
+        let min = (min / scale).as_uvec2();
+        let max = ((max + scale - 1) / scale).as_uvec2();
+        let rect = if self.flip_y {
+            let height = self.map.map_size().y;
+            URect::from_corners(
+                UVec2::new(min.x, height.saturating_sub(max.y)),
+                UVec2::new(max.x, height.saturating_sub(min.y)),
+            )
+        } else {
+            URect::from_corners(min, max)
+        };
+        rect.intersect(self.map.map_rect())
+    }
+
+    /// Convert a rectangle in map coordinates to the rectangle that it covers, in view
+    /// coordinates.
+    #[must_use]
+    pub fn to_view_rect(&self, rect: &URect) -> IRect {
+        let rect = rect.as_irect();
+        let (min_y, max_y) = if self.flip_y {
+            let height = self.map.map_size().y as i32;
+            (height - rect.max.y, height - rect.min.y)
+        } else {
+            (rect.min.y, rect.max.y)
+        };
+        let scale = self.scale as i32;
+        IRect::from_corners(
+            IVec2::new(rect.min.x, min_y) * scale + self.offset,
+            IVec2::new(rect.max.x, max_y) * scale + self.offset,
+        )
+    }
+
+    /// Determine if the given point, in view coordinates, is covered by the map.
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, point: IVec2) -> bool {
+        self.to_map_point(point).is_some()
+    }
+
+    /// Obtain the value of the map pixel covering the given point, in view coordinates,
+    /// as per [PixelMap::get_pixel].
+    #[inline]
+    #[must_use]
+    pub fn get_pixel(&self, point: IVec2) -> Option<&'a T> {
+        self.map.get_pixel(self.to_map_point(point)?)
+    }
+
+    /// Obtain the leaf node covering the given point, in view coordinates, as per
+    /// [PixelMap::find_node].
+    #[inline]
+    #[must_use]
+    pub fn find_node(&self, point: IVec2) -> Option<&'a PNode<T, U>> {
+        self.map.find_node(self.to_map_point(point)?)
+    }
+
+    /// Visit all leaf nodes of the map, as per [PixelMap::visit], providing the rectangle,
+    /// in view coordinates, of each node's region within the map.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit<F>(&self, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &IRect),
+    {
+        self.map
+            .visit(|node, sub_rect| visitor(node, &self.to_view_rect(sub_rect)))
+    }
+
+    /// Visit all leaf nodes of the map overlapping the given rectangle, in view coordinates,
+    /// as per [PixelMap::visit_in_rect], providing the rectangle, in view coordinates, of the
+    /// intersection of each node's region and the map pixels overlapping `rect`.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit_in_rect<F>(&self, rect: &IRect, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &IRect),
+    {
+        let rect = self.to_map_rect(rect);
+        if rect.is_empty() {
+            return 0;
+        }
+        self.map.visit_in_rect(&rect, |node, sub_rect| {
+            visitor(node, &self.to_view_rect(sub_rect))
+        })
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Obtain a [MapView] of this [PixelMap], to which an offset, scale and vertical flip may
+    /// be applied.
+    #[inline]
+    #[must_use]
+    pub fn view(&self) -> MapView<'_, T, U> {
+        MapView::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{IRect, IVec2, URect, UVec2};
+
+    #[test]
+    fn test_map_view() {
+        let mut pm: PixelMap<u8, u32> = PixelMap::new(&UVec2::new(8, 4), 0, 1);
+        pm.set_pixel((1, 0), 1);
+        pm.set_pixel((2, 3), 2);
+
+        // Top-left origin, as per an image
+        let view = pm.view().with_flip_y(true);
+        assert_eq!(view.get_pixel(IVec2::new(1, 3)), Some(&1));
+        assert_eq!(view.get_pixel(IVec2::new(2, 0)), Some(&2));
+        assert_eq!(view.get_pixel(IVec2::new(2, 4)), None);
+        assert_eq!(view.to_view_point(UVec2::new(2, 3)), IVec2::new(2, 0));
+        assert_eq!(view.view_rect(), IRect::new(0, 0, 8, 4));
+
+        // Scaled and offset
+        let view = view.with_scale(4).with_offset(IVec2::new(-10, 5));
+        assert_eq!(view.view_rect(), IRect::new(-10, 5, 22, 21));
+        assert_eq!(view.get_pixel(IVec2::new(-6, 17)), Some(&1));
+        assert_eq!(view.get_pixel(IVec2::new(-3, 20)), Some(&1));
+        assert_eq!(view.get_pixel(IVec2::new(-2, 20)), Some(&0));
+        assert_eq!(view.get_pixel(IVec2::new(-11, 5)), None);
+        assert_eq!(
+            view.to_view_rect(&URect::new(1, 0, 2, 1)),
+            IRect::new(-6, 17, -2, 21)
+        );
+
+        // Round trip of rectangles
+        for rect in [
+            URect::new(1, 0, 2, 1),
+            URect::new(0, 1, 5, 4),
+            pm.map_rect(),
+        ] {
+            assert_eq!(view.to_map_rect(&view.to_view_rect(&rect)), rect);
+        }
+        assert_eq!(
+            view.to_map_rect(&IRect::new(-7, 16, -5, 18)),
+            URect::new(0, 0, 2, 2)
+        );
+        assert!(view.to_map_rect(&IRect::new(-40, 0, -20, 4)).is_empty());
+
+        let mut visited = Vec::new();
+        view.visit_in_rect(&IRect::new(-6, 17, -2, 21), |n, r| {
+            visited.push((*n.value(), *r))
+        });
+        assert_eq!(visited, vec![(1, IRect::new(-6, 17, -2, 21))]);
+        let mut area = 0;
+        view.visit(|_, r| area += r.width() * r.height());
+        assert_eq!(area, 32 * 16);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{CellFill, PNode, PixelMap};
+use bevy_math::URect;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A stateful visitor of the leaf nodes of a [PixelMap], as an alternative to the closures
+/// accepted by [PixelMap::visit] and [PixelMap::visit_in_rect]. Implemented by structs, a
+/// visitor can hold its own state with plain borrows, be reused across traversals, and be
+/// boxed into heterogeneous pipelines, such as a `Vec<Box<dyn LeafVisitor<T, U>>>`, which is
+/// itself a visitor that forwards each leaf to every visitor in turn.
+pub trait LeafVisitor<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> {
+    /// Visit a leaf node.
+    ///
+    /// # Parameters
+    ///
+    /// - `node`: The leaf node.
+    /// - `rect`: The intersection of the node's region and the visited rectangle.
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect);
+}
+
+/// A stateful visitor of all nodes of a [PixelMap], as an alternative to the closure accepted
+/// by [PixelMap::visit_nodes_in_rect]. Branch nodes are entered before, and left after, their
+/// children are visited, such as to maintain a stack of per-subtree state.
+pub trait NodeVisitor<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> {
+    /// Enter a branch node, before its children are visited. By default, all children are
+    /// visited.
+    ///
+    /// # Parameters
+    ///
+    /// - `node`: The branch node.
+    /// - `rect`: The intersection of the node's region and the visited rectangle.
+    ///
+    /// # Returns
+    ///
+    /// A [CellFill] that denotes which child nodes should be visited.
+    fn enter_branch(&mut self, node: &PNode<T, U>, rect: &URect) -> CellFill {
+        let _ = (node, rect);
+        CellFill::Full
+    }
+
+    /// Leave a branch node, after those of its children selected by
+    /// [NodeVisitor::enter_branch] have been visited. Does nothing by default.
+    ///
+    /// # Parameters
+    ///
+    /// - `node`: The branch node.
+    /// - `rect`: The intersection of the node's region and the visited rectangle.
+    fn leave_branch(&mut self, node: &PNode<T, U>, rect: &URect) {
+        let _ = (node, rect);
+    }
+
+    /// Visit a leaf node.
+    ///
+    /// # Parameters
+    ///
+    /// - `node`: The leaf node.
+    /// - `rect`: The intersection of the node's region and the visited rectangle.
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect);
+}
+
+impl<T, U, V> LeafVisitor<T, U> for &mut V
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    V: LeafVisitor<T, U> + ?Sized,
+{
+    #[inline]
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect) {
+        (**self).visit_leaf(node, rect);
+    }
+}
+
+impl<T, U, V> LeafVisitor<T, U> for Box<V>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    V: LeafVisitor<T, U> + ?Sized,
+{
+    #[inline]
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect) {
+        (**self).visit_leaf(node, rect);
+    }
+}
+
+impl<T, U, V> LeafVisitor<T, U> for [V]
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    V: LeafVisitor<T, U>,
+{
+    #[inline]
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect) {
+        for visitor in self {
+            visitor.visit_leaf(node, rect);
+        }
+    }
+}
+
+impl<T, U, V> LeafVisitor<T, U> for Vec<V>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    V: LeafVisitor<T, U>,
+{
+    #[inline]
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect) {
+        self.as_mut_slice().visit_leaf(node, rect);
+    }
+}
+
+impl<T, U, V> NodeVisitor<T, U> for &mut V
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    V: NodeVisitor<T, U> + ?Sized,
+{
+    #[inline]
+    fn enter_branch(&mut self, node: &PNode<T, U>, rect: &URect) -> CellFill {
+        (**self).enter_branch(node, rect)
+    }
+
+    #[inline]
+    fn leave_branch(&mut self, node: &PNode<T, U>, rect: &URect) {
+        (**self).leave_branch(node, rect);
+    }
+
+    #[inline]
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect) {
+        (**self).visit_leaf(node, rect);
+    }
+}
+
+impl<T, U, V> NodeVisitor<T, U> for Box<V>
+where
+    T: Copy + PartialEq,
+    U: Unsigned + NumCast + Copy + Debug,
+    V: NodeVisitor<T, U> + ?Sized,
+{
+    #[inline]
+    fn enter_branch(&mut self, node: &PNode<T, U>, rect: &URect) -> CellFill {
+        (**self).enter_branch(node, rect)
+    }
+
+    #[inline]
+    fn leave_branch(&mut self, node: &PNode<T, U>, rect: &URect) {
+        (**self).leave_branch(node, rect);
+    }
+
+    #[inline]
+    fn visit_leaf(&mut self, node: &PNode<T, U>, rect: &URect) {
+        (**self).visit_leaf(node, rect);
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Visit all leaf nodes in this [PixelMap] with a [LeafVisitor], as per [PixelMap::visit].
+    ///
+    /// # Parameters
+    ///
+    /// - `visitor`: The visitor of each leaf node.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    #[inline]
+    pub fn visit_with<V>(&self, visitor: &mut V) -> u32
+    where
+        V: LeafVisitor<T, U> + ?Sized,
+    {
+        self.visit_in_rect_with(&self.map_rect(), visitor)
+    }
+
+    /// Visit all leaf nodes in this [PixelMap] that overlap with the given rectangle, with a
+    /// [LeafVisitor], as per [PixelMap::visit_in_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `visitor`: The visitor of each leaf node.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit_in_rect_with<V>(&self, rect: &URect, visitor: &mut V) -> u32
+    where
+        V: LeafVisitor<T, U> + ?Sized,
+    {
+        self.visit_in_rect(rect, |node, sub_rect| visitor.visit_leaf(node, sub_rect))
+    }
+
+    /// Visit all nodes in this [PixelMap] that overlap with the given rectangle, with a
+    /// [NodeVisitor], which controls navigation as per [PixelMap::visit_nodes_in_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle in which contained or overlapping nodes will be visited.
+    /// - `visitor`: The visitor of each node.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit_nodes_in_rect_with<V>(&self, rect: &URect, visitor: &mut V) -> u32
+    where
+        V: NodeVisitor<T, U> + ?Sized,
+    {
+        let rect = rect.intersect(self.map_rect());
+        if rect.is_empty() {
+            return 0;
+        }
+        let mut traversed = 0u32;
+        self.root
+            .visit_nodes_in_rect_with(&rect, visitor, &mut traversed);
+        traversed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[derive(Default)]
+    struct CountValue {
+        value: u8,
+        count: u32,
+    }
+
+    impl LeafVisitor<u8, u16> for CountValue {
+        fn visit_leaf(&mut self, node: &PNode<u8, u16>, rect: &URect) {
+            if *node.value() == self.value {
+                self.count += rect.width() * rect.height();
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct MaxDepth {
+        depth: u32,
+        max: u32,
+        skipped: u32,
+    }
+
+    impl NodeVisitor<u8, u16> for MaxDepth {
+        fn enter_branch(&mut self, node: &PNode<u8, u16>, _: &URect) -> CellFill {
+            if node.region().size() <= 2 {
+                self.skipped += 1;
+                return CellFill::Empty;
+            }
+            self.depth += 1;
+            CellFill::Full
+        }
+
+        fn leave_branch(&mut self, node: &PNode<u8, u16>, _: &URect) {
+            if node.region().size() > 2 {
+                self.depth -= 1;
+            }
+        }
+
+        fn visit_leaf(&mut self, _: &PNode<u8, u16>, _: &URect) {
+            self.max = self.max.max(self.depth);
+        }
+    }
+
+    #[test]
+    fn test_leaf_visitor() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(8), 0, 1);
+        pm.draw_rect(&URect::new(0, 0, 3, 2), 1);
+        pm.set_pixel((6, 6), 2);
+
+        let mut ones = CountValue { value: 1, count: 0 };
+        let traversed = pm.visit_with(&mut ones);
+        assert_eq!(ones.count, 6);
+        assert_eq!(traversed, pm.visit(|_, _| {}));
+
+        // Reused, within a rectangle
+        ones.count = 0;
+        pm.visit_in_rect_with(&URect::new(1, 1, 8, 8), &mut ones);
+        assert_eq!(ones.count, 2);
+
+        // A pipeline of borrowed visitors
+        let mut zeros = CountValue::default();
+        let mut twos = CountValue { value: 2, count: 0 };
+        let mut pipeline: Vec<&mut dyn LeafVisitor<u8, u16>> = vec![&mut zeros, &mut twos];
+        pm.visit_with(&mut pipeline);
+        assert_eq!((zeros.count, twos.count), (57, 1));
+    }
+
+    #[test]
+    fn test_node_visitor() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(8), 0, 1);
+        pm.set_pixel((1, 1), 1);
+
+        let mut visitor = MaxDepth::default();
+        pm.visit_nodes_in_rect_with(&pm.map_rect(), &mut visitor);
+        assert_eq!(visitor.depth, 0);
+        assert_eq!(visitor.max, 2);
+        assert_eq!(visitor.skipped, 1);
+
+        let rect = URect::new(4, 4, 8, 8);
+        let mut boxed: Box<dyn NodeVisitor<u8, u16>> = Box::new(MaxDepth::default());
+        assert_eq!(
+            pm.visit_nodes_in_rect_with(&rect, &mut boxed),
+            pm.visit_nodes_in_rect(&rect, |_, _| CellFill::Full)
+        );
+    }
+}
@@ -0,0 +1,177 @@
+use crate::upload_plan::merge_rects;
+use crate::PixelMap;
+use bevy_math::URect;
+use fxhash::FxHashMap;
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+// The number of modified rectangles retained by a subscription between polls, beyond which
+// they are collapsed into their bounding rectangle, bounding the cost of each modification.
+const MAX_PENDING_CHANGES: usize = 64;
+
+/// A handle to a region of interest of a [PixelMap], as obtained from [PixelMap::watch].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WatchHandle(u32);
+
+// The regions of interest of a map, and the modified rectangles within each, awaiting a poll.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Watches {
+    next_id: u32,
+    watches: FxHashMap<u32, Watch>,
+}
+
+#[derive(Debug, Clone)]
+struct Watch {
+    rect: URect,
+    changes: Vec<URect>,
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Subscribe to modifications of pixel data within the given rectangle, such that each
+    /// system interested in an area of the map, such as a camera view or an AI sector, may
+    /// obtain the changes within its area with [PixelMap::poll_changes], independently of
+    /// the dirty state of nodes and of other subscriptions.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The region of interest. It is cropped to the [PixelMap::map_rect].
+    ///
+    /// # Returns
+    ///
+    /// A handle to the subscription, valid until passed to [PixelMap::unwatch].
+    pub fn watch(&mut self, rect: &URect) -> WatchHandle {
+        let rect = rect.intersect(self.map_rect);
+        let watches = self.watches.get_or_insert_with(Default::default);
+        let id = watches.next_id;
+        watches.next_id += 1;
+        watches.watches.insert(
+            id,
+            Watch {
+                rect,
+                changes: Vec::new(),
+            },
+        );
+        WatchHandle(id)
+    }
+
+    /// Cancel a subscription made by [PixelMap::watch], discarding its pending changes.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the subscription existed, or `false` otherwise.
+    pub fn unwatch(&mut self, handle: WatchHandle) -> bool {
+        let Some(watches) = &mut self.watches else {
+            return false;
+        };
+        let removed = watches.watches.remove(&handle.0).is_some();
+        if watches.watches.is_empty() {
+            self.watches = None;
+        }
+        removed
+    }
+
+    /// Obtain the rectangles modified within the region of interest of the given
+    /// subscription since it was made, or last polled, cropped to the region of interest.
+    /// Rectangles that share a full edge are merged, but rectangles of overlapping
+    /// modifications may overlap. When many modifications accumulate between polls, those
+    /// pending are collapsed into their bounding rectangle, which may include unmodified
+    /// pixels.
+    ///
+    /// # Parameters
+    ///
+    /// - `handle`: The subscription, as obtained from [PixelMap::watch].
+    ///
+    /// # Returns
+    ///
+    /// The modified rectangles, ordered bottom to top, then left to right. Empty if there
+    /// were no modifications, or the subscription does not exist.
+    pub fn poll_changes(&mut self, handle: WatchHandle) -> Vec<URect> {
+        let Some(watch) = self
+            .watches
+            .as_mut()
+            .and_then(|w| w.watches.get_mut(&handle.0))
+        else {
+            return Vec::new();
+        };
+        let mut changes = merge_rects(std::mem::take(&mut watch.changes));
+        changes.sort_unstable_by_key(|r| (r.min.y, r.min.x));
+        changes
+    }
+
+    // Record a modification of the given rectangle with each subscription it overlaps.
+    pub(crate) fn notify_watches(&mut self, rect: &URect) {
+        let Some(watches) = &mut self.watches else {
+            return;
+        };
+        for watch in watches.watches.values_mut() {
+            let rect = rect.intersect(watch.rect);
+            if rect.is_empty() || watch.changes.iter().any(|c| contains_rect(c, &rect)) {
+                continue;
+            }
+            watch.changes.retain(|c| !contains_rect(&rect, c));
+            if watch.changes.len() >= MAX_PENDING_CHANGES {
+                let bounds = watch.changes.drain(..).fold(rect, |a, c| a.union(c));
+                watch.changes.push(bounds);
+            } else {
+                watch.changes.push(rect);
+            }
+        }
+    }
+}
+
+// Determine if rectangle `a` contains all of rectangle `b`.
+#[inline]
+fn contains_rect(a: &URect, b: &URect) -> bool {
+    a.min.cmple(b.min).all() && b.max.cmple(a.max).all()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{URect, UVec2};
+
+    #[test]
+    fn test_watch() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(32), 0, 1);
+        let camera = pm.watch(&URect::new(0, 0, 16, 16));
+        let sector = pm.watch(&URect::new(16, 16, 32, 32));
+
+        pm.draw_rect(&URect::new(10, 10, 20, 12), 1);
+        pm.set_pixel((2, 3), 2);
+        pm.set_pixel((3, 3), 2);
+        assert_eq!(
+            pm.poll_changes(camera),
+            vec![URect::new(2, 3, 4, 4), URect::new(10, 10, 16, 12)]
+        );
+        assert!(pm.poll_changes(camera).is_empty());
+        assert!(pm.poll_changes(sector).is_empty());
+
+        // Contained modifications are absorbed
+        pm.draw_rect(&URect::new(18, 18, 20, 20), 1);
+        pm.draw_rect(&URect::new(16, 16, 24, 24), 1);
+        pm.set_pixel((17, 17), 2);
+        assert_eq!(pm.poll_changes(sector), vec![URect::new(16, 16, 24, 24)]);
+        assert!(pm.poll_changes(camera).is_empty());
+
+        assert!(pm.unwatch(camera));
+        assert!(!pm.unwatch(camera));
+        pm.set_pixel((2, 3), 3);
+        assert!(pm.poll_changes(camera).is_empty());
+    }
+
+    #[test]
+    fn test_watch_collapses_changes() {
+        let mut pm = PixelMap::<u8, u16>::new(&UVec2::splat(256), 0, 1);
+        let watch = pm.watch(&pm.map_rect());
+        let points: Vec<UVec2> = (0..100).map(|i| UVec2::new(i * 2, 5)).collect();
+        for p in &points {
+            pm.set_pixel(*p, 1);
+        }
+        let changes = pm.poll_changes(watch);
+        assert!(changes.len() <= 64);
+        for p in &points {
+            assert!(changes.iter().any(|c| c.contains_exclusive(*p)), "{p}");
+        }
+        assert_eq!(changes[0], URect::new(0, 5, 129, 6));
+    }
+}
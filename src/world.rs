@@ -0,0 +1,394 @@
+#[cfg(feature = "serialize")]
+use serde::{Deserialize, Serialize};
+
+use crate::{ICircle, PNode, PixelMap};
+use bevy_math::{Rect, URect, UVec2, Vec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// A point in the pixel coordinates of a [PixelMap], distinguished by type from a
+/// [WorldPoint] to prevent mixing up the two coordinate spaces. Converts into a [UVec2], so
+/// may be given to any method of a [PixelMap] that takes pixel coordinates.
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MapPoint(pub UVec2);
+
+/// A point in world units, as mapped to the pixel coordinates of a [PixelMap] by its
+/// [MapTransform]. Deliberately does not convert into pixel coordinates, other than by a
+/// [MapTransform], or the `*_world` methods of a [PixelMap].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WorldPoint(pub Vec2);
+
+impl From<UVec2> for MapPoint {
+    #[inline]
+    fn from(point: UVec2) -> Self {
+        Self(point)
+    }
+}
+
+impl From<MapPoint> for UVec2 {
+    #[inline]
+    fn from(point: MapPoint) -> Self {
+        point.0
+    }
+}
+
+impl From<Vec2> for WorldPoint {
+    #[inline]
+    fn from(point: Vec2) -> Self {
+        Self(point)
+    }
+}
+
+impl From<WorldPoint> for Vec2 {
+    #[inline]
+    fn from(point: WorldPoint) -> Self {
+        point.0
+    }
+}
+
+/// The placement of a [PixelMap] in world space, as a uniform scale followed by a
+/// translation, such that the bottom-left corner of pixel `p` is at world position
+/// `p * scale + translation`. See [PixelMap::set_transform].
+#[cfg_attr(feature = "serialize", derive(Serialize, Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapTransform {
+    /// The world position of the `(0,0)` point of the map.
+    pub translation: Vec2,
+
+    /// The size of a pixel, in world units.
+    pub scale: f32,
+}
+
+impl Default for MapTransform {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl MapTransform {
+    /// The transform under which world units are pixels, with coincident origins.
+    pub const IDENTITY: MapTransform = MapTransform {
+        translation: Vec2::ZERO,
+        scale: 1.0,
+    };
+
+    /// Create a new [MapTransform].
+    ///
+    /// # Parameters
+    ///
+    /// - `translation`: The world position of the `(0,0)` point of the map.
+    /// - `scale`: The size of a pixel, in world units.
+    ///
+    /// # Panics
+    ///
+    /// If `scale` is not finite and positive.
+    #[inline]
+    #[must_use]
+    pub fn new(translation: Vec2, scale: f32) -> Self {
+        assert!(
+            scale.is_finite() && scale > 0.0,
+            "scale must be finite and > 0"
+        );
+        Self { translation, scale }
+    }
+
+    /// Convert a point in pixel coordinates to world space, being the bottom-left corner of
+    /// the pixel.
+    #[inline]
+    #[must_use]
+    pub fn to_world(&self, point: MapPoint) -> WorldPoint {
+        WorldPoint(point.0.as_vec2() * self.scale + self.translation)
+    }
+
+    /// Convert a point in world space to the pixel coordinates of the pixel containing it.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the point lies left of, or below, the `(0,0)` point of the map, or beyond
+    /// the range of pixel coordinates.
+    #[inline]
+    #[must_use]
+    pub fn to_map(&self, point: WorldPoint) -> Option<MapPoint> {
+        let p = ((point.0 - self.translation) / self.scale).floor();
+        if p.cmplt(Vec2::ZERO).any() || p.cmpge(Vec2::splat(u32::MAX as f32)).any() {
+            return None;
+        }
+        Some(MapPoint(p.as_uvec2()))
+    }
+
+    /// Convert a rectangle in pixel coordinates to world space.
+    #[inline]
+    #[must_use]
+    pub fn to_world_rect(&self, rect: &URect) -> Rect {
+        Rect::from_corners(
+            self.to_world(MapPoint(rect.min)).0,
+            self.to_world(MapPoint(rect.max)).0,
+        )
+    }
+
+    /// Convert a rectangle in world space to the smallest rectangle of pixels covering it,
+    /// cropped to non-negative pixel coordinates.
+    #[must_use]
+    pub fn to_map_rect(&self, rect: &Rect) -> URect {
+        let min = ((rect.min - self.translation) / self.scale).floor();
+        let max = ((rect.max - self.translation) / self.scale).ceil();
+        let limit = Vec2::splat(u32::MAX as f32);
+        URect::from_corners(
+            min.clamp(Vec2::ZERO, limit).as_uvec2(),
+            max.clamp(Vec2::ZERO, limit).as_uvec2(),
+        )
+    }
+
+    /// Convert a length in world units to pixels.
+    #[inline]
+    #[must_use]
+    pub fn to_map_length(&self, length: f32) -> f32 {
+        length / self.scale
+    }
+}
+
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Place this [PixelMap] in world space, for the `*_world` methods, such as
+    /// [PixelMap::get_pixel_world], which take world coordinates. Without a transform, world
+    /// units are pixels, as per [MapTransform::IDENTITY].
+    ///
+    /// # Parameters
+    ///
+    /// - `transform`: The placement of this map in world space, or `None` to remove it.
+    #[inline]
+    pub fn set_transform(&mut self, transform: Option<MapTransform>) {
+        self.transform = transform;
+    }
+
+    /// Obtain the placement of this [PixelMap] in world space, if set.
+    /// See [PixelMap::set_transform].
+    #[inline]
+    #[must_use]
+    pub fn transform(&self) -> Option<MapTransform> {
+        self.transform
+    }
+
+    // The placement of this map in world space, defaulting to the identity.
+    #[inline]
+    fn world_transform(&self) -> MapTransform {
+        self.transform.unwrap_or_default()
+    }
+
+    /// Convert a point in world space to the pixel containing it, by the transform of this
+    /// [PixelMap]. See [PixelMap::set_transform].
+    ///
+    /// # Returns
+    ///
+    /// `None` if the point is outside the [PixelMap::map_rect].
+    #[inline]
+    #[must_use]
+    pub fn map_point(&self, point: WorldPoint) -> Option<MapPoint> {
+        self.world_transform()
+            .to_map(point)
+            .filter(|p| self.contains(p.0))
+    }
+
+    /// Convert a point in pixel coordinates to world space, being the bottom-left corner of
+    /// the pixel, by the transform of this [PixelMap]. See [PixelMap::set_transform].
+    #[inline]
+    #[must_use]
+    pub fn world_point(&self, point: MapPoint) -> WorldPoint {
+        self.world_transform().to_world(point)
+    }
+
+    /// Get the value of the pixel at the given world position, as per [PixelMap::get_pixel].
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The world position of the pixel for which to retrieve the associated value.
+    #[inline]
+    #[must_use]
+    pub fn get_pixel_world(&self, point: WorldPoint) -> Option<&T> {
+        self.map_point(point).and_then(|p| self.get_pixel(p))
+    }
+
+    /// Set the value of the pixel at the given world position, as per [PixelMap::set_pixel].
+    ///
+    /// # Parameters
+    ///
+    /// - `point`: The world position of the pixel to set.
+    /// - `value`: The value to assign to the pixel.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the point is within the [PixelMap::map_rect], and the pixel was set.
+    #[inline]
+    pub fn set_pixel_world(&mut self, point: WorldPoint, value: T) -> bool {
+        match self.map_point(point) {
+            Some(p) => self.set_pixel(p, value),
+            None => false,
+        }
+    }
+
+    /// Set the value of the pixels overlapping the given world rectangle, as per
+    /// [PixelMap::draw_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle, in world space, of the pixels to set.
+    /// - `value`: The value to assign to the pixels.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the rectangle overlaps the [PixelMap::map_rect], and pixels were set.
+    #[inline]
+    pub fn draw_rect_world(&mut self, rect: &Rect, value: T) -> bool {
+        let rect = self.world_transform().to_map_rect(rect);
+        self.draw_rect(&rect, value)
+    }
+
+    /// Set the value of the pixels within the given world circle, as per
+    /// [PixelMap::draw_circle]. The circle is centered upon the pixel containing `center`,
+    /// with its radius rounded to the nearest pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `center`: The world position of the center of the circle.
+    /// - `radius`: The radius of the circle, in world units.
+    /// - `value`: The value to assign to the pixels.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the circle overlaps the [PixelMap::map_rect], and pixels were set.
+    pub fn draw_circle_world(&mut self, center: WorldPoint, radius: f32, value: T) -> bool {
+        let transform = self.world_transform();
+        let center = ((center.0 - transform.translation) / transform.scale)
+            .floor()
+            .as_ivec2();
+        let radius = transform.to_map_length(radius).round().max(0.0) as u32;
+        self.draw_circle(&ICircle::new(center, radius), value)
+    }
+
+    /// Determine if any of the leaf nodes overlapping the given world rectangle match the
+    /// predicate, as per [PixelMap::any_in_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle, in world space, in which contained or overlapping nodes will
+    ///   be visited.
+    /// - `predicate`: A closure that takes a reference to a leaf node, and a reference to the
+    ///   world rectangle of the intersection of the node's region and the pixels overlapping
+    ///   `rect`. It returns `true` if the node matches the predicate, or `false` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// `None` if `rect` does not overlap the [PixelMap::map_rect].
+    #[must_use]
+    pub fn any_in_rect_world<F>(&self, rect: &Rect, mut predicate: F) -> Option<bool>
+    where
+        F: FnMut(&PNode<T, U>, &Rect) -> bool,
+    {
+        let transform = self.world_transform();
+        self.any_in_rect(&transform.to_map_rect(rect), |node, sub_rect| {
+            predicate(node, &transform.to_world_rect(sub_rect))
+        })
+    }
+
+    /// Visit all leaf nodes overlapping the given world rectangle, as per
+    /// [PixelMap::visit_in_rect].
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle, in world space, in which contained or overlapping nodes will
+    ///   be visited.
+    /// - `visitor`: A closure that takes a reference to a leaf node, and a reference to the
+    ///   world rectangle of the intersection of the node's region and the pixels overlapping
+    ///   `rect`.
+    ///
+    /// # Returns
+    ///
+    /// The number of nodes traversed.
+    pub fn visit_in_rect_world<F>(&self, rect: &Rect, mut visitor: F) -> u32
+    where
+        F: FnMut(&PNode<T, U>, &Rect),
+    {
+        let transform = self.world_transform();
+        self.visit_in_rect(&transform.to_map_rect(rect), |node, sub_rect| {
+            visitor(node, &transform.to_world_rect(sub_rect))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{Rect, URect, UVec2, Vec2};
+
+    #[test]
+    fn test_map_transform() {
+        let t = MapTransform::new(Vec2::new(-8.0, 4.0), 0.5);
+        assert_eq!(
+            t.to_world(MapPoint(UVec2::new(4, 2))),
+            WorldPoint(Vec2::new(-6.0, 5.0))
+        );
+        assert_eq!(
+            t.to_map(WorldPoint(Vec2::new(-5.8, 5.4))),
+            Some(MapPoint(UVec2::new(4, 2)))
+        );
+        assert_eq!(t.to_map(WorldPoint(Vec2::new(-8.1, 5.0))), None);
+        assert_eq!(
+            t.to_map_rect(&Rect::new(-9.0, 4.2, -6.9, 5.0)),
+            URect::new(0, 0, 3, 2)
+        );
+        assert_eq!(
+            t.to_world_rect(&URect::new(0, 0, 4, 2)),
+            Rect::new(-8.0, 4.0, -6.0, 5.0)
+        );
+        assert_eq!(MapTransform::default(), MapTransform::IDENTITY);
+    }
+
+    #[test]
+    fn test_world_methods() {
+        let mut pm: PixelMap<u8, u16> = PixelMap::new(&UVec2::splat(16), 0, 1);
+
+        // Without a transform, world units are pixels
+        assert_eq!(pm.transform(), None);
+        assert!(pm.set_pixel_world(WorldPoint(Vec2::new(3.5, 2.0)), 1));
+        assert_eq!(pm.get_pixel(MapPoint(UVec2::new(3, 2))), Some(&1));
+
+        pm.set_transform(Some(MapTransform::new(Vec2::new(100.0, 200.0), 2.0)));
+        assert_eq!(
+            pm.get_pixel_world(WorldPoint(Vec2::new(107.0, 204.5))),
+            Some(&1)
+        );
+        assert_eq!(pm.get_pixel_world(WorldPoint(Vec2::new(99.0, 204.5))), None);
+        assert_eq!(
+            pm.get_pixel_world(WorldPoint(Vec2::new(132.0, 204.5))),
+            None
+        );
+        assert!(!pm.set_pixel_world(WorldPoint(Vec2::new(0.0, 0.0)), 2));
+        assert_eq!(
+            pm.world_point(MapPoint(UVec2::new(3, 2))),
+            WorldPoint(Vec2::new(106.0, 204.0))
+        );
+
+        assert!(pm.draw_rect_world(&Rect::new(120.0, 220.0, 200.0, 300.0), 2));
+        assert_eq!(pm.get_pixel((10, 10)), Some(&2));
+        assert_eq!(pm.get_pixel((15, 15)), Some(&2));
+        assert_eq!(pm.get_pixel((9, 10)), Some(&0));
+
+        assert!(pm.draw_circle_world(WorldPoint(Vec2::new(101.0, 201.0)), 2.0, 3));
+        assert_eq!(pm.get_pixel((0, 0)), Some(&3));
+        assert_eq!(pm.get_pixel((1, 0)), Some(&3));
+        assert_eq!(pm.get_pixel((2, 0)), Some(&0));
+
+        assert_eq!(
+            pm.any_in_rect_world(&Rect::new(119.0, 219.0, 121.0, 221.0), |n, _| *n.value()
+                == 2),
+            Some(true)
+        );
+        let mut area = 0.0;
+        pm.visit_in_rect_world(&Rect::new(100.0, 200.0, 104.0, 204.0), |_, r| {
+            area += r.width() * r.height();
+        });
+        assert_eq!(area, 16.0);
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{iline, to_cropped_urect, ICircle, ILine, PixelMap, RasterShape};
+use bevy_math::{ivec2, IRect, IVec2};
+use num_traits::{NumCast, Unsigned};
+use std::fmt::Debug;
+
+/// Drawing operations that treat a [PixelMap] as a torus, such as for wrap-around worlds.
+/// Pixels beyond one edge of the [PixelMap::map_rect] wrap around to the opposite edge.
+impl<T: Copy + PartialEq, U: Unsigned + NumCast + Copy + Debug> PixelMap<T, U> {
+    /// Set the value of the pixels within the given rectangle, wrapping the portions that
+    /// extend beyond the edges of the map to the opposite edges.
+    ///
+    /// # Parameters
+    ///
+    /// - `rect`: The rectangle, exclusive of its `max` point, in which pixels will be set to
+    ///   the associated value. It may have negative coordinates.
+    /// - `value`: The value to assign to the pixels within the given rectangle.
+    ///
+    /// # Returns
+    ///
+    /// If the rectangle is not empty, `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_rect_wrapped(&mut self, rect: &IRect, value: T) -> bool {
+        let mut changed = false;
+        for offset in self.wrap_offsets(rect) {
+            let shifted = IRect::from_corners(rect.min + offset, rect.max + offset);
+            changed |= self.draw_rect(&to_cropped_urect(&shifted), value);
+        }
+        changed
+    }
+
+    /// Set the value of the pixels within the given circle, wrapping the portions that
+    /// extend beyond the edges of the map to the opposite edges.
+    ///
+    /// # Parameters
+    ///
+    /// - `circle`: The circle in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given circle.
+    ///
+    /// # Returns
+    ///
+    /// If any pixels of the circle are drawn, `true` is returned. Otherwise, `false` is returned.
+    #[inline]
+    pub fn draw_circle_wrapped(&mut self, circle: &ICircle, value: T) -> bool {
+        self.draw_shape_wrapped(circle, value)
+    }
+
+    /// Set the value of the pixels within the given shape, wrapping the portions that
+    /// extend beyond the edges of the map to the opposite edges.
+    ///
+    /// # Parameters
+    ///
+    /// - `shape`: The [RasterShape] in which pixels will be set to associated value.
+    /// - `value`: The value to assign to the pixels within the given shape.
+    ///
+    /// # Returns
+    ///
+    /// If any pixels of the shape are drawn, `true` is returned. Otherwise, `false` is returned.
+    pub fn draw_shape_wrapped<S>(&mut self, shape: &S, value: T) -> bool
+    where
+        S: RasterShape,
+    {
+        let aabb = shape.aabb();
+        let spans = shape.spans();
+        let mut changed = false;
+        // The aabb is inclusive of its max point, unlike the rectangles of wrap_offsets
+        for offset in self.wrap_offsets(&IRect::from_corners(aabb.min, aabb.max + IVec2::ONE)) {
+            let shifted: Vec<ILine> = spans
+                .iter()
+                .map(|span| iline(span.start() + offset, span.end() + offset))
+                .collect();
+            changed |= self.draw_spans(&shifted, value);
+        }
+        changed
+    }
+
+    /// Move all pixels of this [PixelMap] by the given offset, wrapping pixels that move
+    /// beyond the edges of the map to the opposite edges. Leaf nodes are moved as a whole,
+    /// rather than pixel by pixel.
+    ///
+    /// # Parameters
+    ///
+    /// - `offset`: The distance to move the pixels on each axis. It may be negative.
+    pub fn translate_wrapped(&mut self, offset: IVec2) {
+        let size = self.map_size().as_ivec2();
+        if size.cmple(IVec2::ZERO).any() || offset.rem_euclid(size) == IVec2::ZERO {
+            return;
+        }
+        let mut leaves: Vec<(IRect, T)> = Vec::new();
+        self.visit(|node, sub_rect| leaves.push((sub_rect.as_irect(), *node.value())));
+        // The leaves cover the map, so every pixel is overwritten
+        for (rect, value) in leaves {
+            let moved = IRect::from_corners(rect.min + offset, rect.max + offset);
+            self.draw_rect_wrapped(&moved, value);
+        }
+    }
+
+    // The offsets that move each wrapped portion of the given rectangle, exclusive of its
+    // max point, into the map.
+    fn wrap_offsets(&self, rect: &IRect) -> Vec<IVec2> {
+        let size = self.map_size().as_ivec2();
+        if rect.is_empty() || size.cmple(IVec2::ZERO).any() {
+            return Vec::new();
+        }
+        let min = rect.min.div_euclid(size);
+        let max = (rect.max - IVec2::ONE).div_euclid(size);
+        let mut offsets = Vec::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                offsets.push(-ivec2(x, y) * size);
+            }
+        }
+        offsets
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use bevy_math::{IRect, IVec2, URect, UVec2};
+
+    fn true_pixels(pm: &PixelMap<bool, u32>) -> Vec<UVec2> {
+        pm.matching_points(&pm.map_rect(), |n, _| *n.value())
+    }
+
+    #[test]
+    fn test_draw_rect_wrapped() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        assert!(pm.draw_rect_wrapped(&IRect::new(-2, -2, 2, 2), true));
+        assert_eq!(true_pixels(&pm).len(), 16);
+        for p in [(15, 15), (0, 0), (1, 15), (15, 1), (14, 14)] {
+            assert_eq!(pm.get_pixel(p), Some(&true), "{:?}", p);
+        }
+        assert_eq!(pm.get_pixel((2, 2)), Some(&false));
+
+        // Beyond a full map width away
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.draw_rect_wrapped(&IRect::new(33, 0, 34, 1), true);
+        assert_eq!(true_pixels(&pm), vec![UVec2::new(1, 0)]);
+    }
+
+    #[test]
+    fn test_draw_circle_wrapped() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        assert!(pm.draw_circle_wrapped(&ICircle::new((0, 8), 2), true));
+
+        let mut expected = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        expected.draw_circle(&ICircle::new((0, 8), 2), true);
+        expected.draw_circle(&ICircle::new((16, 8), 2), true);
+        assert_eq!(true_pixels(&pm), true_pixels(&expected));
+        assert_eq!(pm.get_pixel((14, 8)), Some(&true));
+    }
+
+    #[test]
+    fn test_translate_wrapped() {
+        let mut pm = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        pm.set_pixel((15, 0), true);
+        pm.draw_rect(&URect::new(4, 4, 8, 8), true);
+        pm.translate_wrapped(IVec2::new(1, -1));
+
+        let mut expected = PixelMap::<bool, u32>::new(&UVec2::splat(16), false, 1);
+        expected.set_pixel((0, 15), true);
+        expected.draw_rect(&URect::new(5, 3, 9, 7), true);
+        assert_eq!(true_pixels(&pm), true_pixels(&expected));
+    }
+}